@@ -4,11 +4,20 @@
 // This module provides Tauri commands for video export operations,
 // acting as a bridge between the frontend and the FFmpeg export module.
 
-use crate::commands::{CommandError, CommandResult};
+use crate::commands::project::read_project_record;
+use crate::commands::{CommandError, CommandResult, Project};
+use crate::ffmpeg::capabilities::get_ffmpeg_capabilities;
 use crate::ffmpeg::export::{
     estimate_export_size, estimate_export_time, export_video, export_video_with_progress,
-    validate_export_settings, ExportClip, ExportSettings, ExportVideoRequest, ExportVideoResponse,
+    get_timeline_duration, slice_chapters_to_range, slice_clips_to_range,
+    validate_export_settings, ExportChapter, ExportClip, ExportMetadataTags, ExportSettings,
+    ExportVideoRequest, ExportVideoResponse,
 };
+use crate::ffmpeg::packaging::{
+    get_abr_ladder, package_streaming_export, validate_package_name, AbrRendition,
+    StreamingPackageRequest, StreamingPackageResponse,
+};
+use crate::ffmpeg::render_cache::{RenderCache, RenderCacheStats};
 use serde::{Deserialize, Serialize};
 use tauri::Emitter;
 
@@ -23,6 +32,31 @@ pub struct ExportTimelineRequest {
     pub output_path: String,
     pub filename: String,
     pub settings: ExportSettings,
+    #[serde(default)]
+    pub in_point: Option<f64>, // Range start in timeline seconds (None = timeline start)
+    #[serde(default)]
+    pub out_point: Option<f64>, // Range end in timeline seconds (None = timeline end)
+    #[serde(default)]
+    pub markers: Vec<ExportChapter>, // Timeline markers exported as chapters
+    #[serde(default)]
+    pub project_id: Option<String>, // Project providing container tags and the render cache
+}
+
+/// Request to export timeline as an adaptive streaming package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportStreamingPackageRequest {
+    pub timeline_clips: Vec<TimelineExportClip>,
+    pub output_path: String,  // Directory the package folder is created in
+    pub package_name: String, // Package folder name
+    pub preset: String,       // "standard", "mobile", "low_bandwidth"
+    #[serde(default)]
+    pub renditions: Option<Vec<AbrRendition>>, // Overrides the preset ladder
+    #[serde(default)]
+    pub include_dash: bool,
+    #[serde(default)]
+    pub in_point: Option<f64>,
+    #[serde(default)]
+    pub out_point: Option<f64>,
 }
 
 /// Timeline clip for export
@@ -77,8 +111,12 @@ pub async fn export_timeline(
     app_handle: tauri::AppHandle,
     request: ExportTimelineRequest,
 ) -> CommandResult<ExportVideoResponse> {
-    // Validate export settings
-    validate_export_settings(&request.settings)?;
+    // Validate export settings against what the FFmpeg build supports
+    let capabilities = get_ffmpeg_capabilities(&app_handle).await.ok();
+    validate_export_settings(&request.settings, capabilities.as_ref())?;
+
+    // Resolve chapters and container tags
+    let (chapters, tags) = get_timeline_export_metadata(&request)?;
 
     // Convert timeline clips to export clips
     let export_clips: Vec<ExportClip> = request
@@ -96,11 +134,17 @@ pub async fn export_timeline(
         })
         .collect();
 
+    // Restrict to the selected in/out range
+    let export_clips = apply_export_range(export_clips, request.in_point, request.out_point)?;
+
     // Create export request
     let export_request = ExportVideoRequest {
         clips: export_clips,
         output_path: request.output_path,
         settings: request.settings,
+        chapters,
+        tags,
+        project_id: request.project_id,
     };
 
     // Execute export
@@ -113,8 +157,12 @@ pub async fn export_timeline_with_progress(
     app_handle: tauri::AppHandle,
     request: ExportTimelineRequest,
 ) -> CommandResult<ExportVideoResponse> {
-    // Validate export settings
-    validate_export_settings(&request.settings)?;
+    // Validate export settings against what the FFmpeg build supports
+    let capabilities = get_ffmpeg_capabilities(&app_handle).await.ok();
+    validate_export_settings(&request.settings, capabilities.as_ref())?;
+
+    // Resolve chapters and container tags
+    let (chapters, tags) = get_timeline_export_metadata(&request)?;
 
     // Convert timeline clips to export clips
     let export_clips: Vec<ExportClip> = request
@@ -132,6 +180,9 @@ pub async fn export_timeline_with_progress(
         })
         .collect();
 
+    // Restrict to the selected in/out range
+    let export_clips = apply_export_range(export_clips, request.in_point, request.out_point)?;
+
     // Create full output path by combining directory and filename
     let filename = if request.filename.ends_with(".mp4") {
         request.filename
@@ -148,12 +199,63 @@ pub async fn export_timeline_with_progress(
         clips: export_clips,
         output_path: full_output_path,
         settings: request.settings,
+        chapters,
+        tags,
+        project_id: request.project_id,
     };
 
     // Execute export with progress tracking
     export_video_with_progress(app_handle, export_request).await
 }
 
+/// Export timeline as an HLS (and optionally DASH) package
+#[tauri::command]
+pub async fn export_timeline_streaming_package(
+    app_handle: tauri::AppHandle,
+    request: ExportStreamingPackageRequest,
+) -> CommandResult<StreamingPackageResponse> {
+    // Resolve the ABR ladder from the preset unless one is given
+    let renditions = match request.renditions {
+        Some(renditions) => renditions,
+        None => get_abr_ladder(&request.preset)?,
+    };
+
+    // Convert timeline clips to export clips
+    let export_clips: Vec<ExportClip> = request
+        .timeline_clips
+        .into_iter()
+        .map(|clip| ExportClip {
+            file_path: clip.file_path,
+            start_time: clip.start_time,
+            duration: clip.duration,
+            trim_start: clip.trim_start,
+            trim_end: clip.trim_end,
+            track_id: clip.track_id,
+            trimmed_file_path: None, // Will be set during trimming process
+            original_duration: clip.original_duration,
+        })
+        .collect();
+
+    // Restrict to the selected in/out range
+    let export_clips = apply_export_range(export_clips, request.in_point, request.out_point)?;
+
+    // Package into a folder inside the output directory
+    validate_package_name(&request.package_name)?;
+    let output_dir = std::path::Path::new(&request.output_path)
+        .join(&request.package_name)
+        .to_string_lossy()
+        .to_string();
+
+    let package_request = StreamingPackageRequest {
+        clips: export_clips,
+        output_dir,
+        renditions,
+        include_dash: request.include_dash,
+    };
+
+    package_streaming_export(app_handle, package_request).await
+}
+
 /// Estimate export time and file size
 #[tauri::command]
 pub async fn estimate_export_info(request: ExportTimelineRequest) -> CommandResult<ExportEstimate> {
@@ -173,6 +275,9 @@ pub async fn estimate_export_info(request: ExportTimelineRequest) -> CommandResu
         })
         .collect();
 
+    // Restrict to the selected in/out range
+    let export_clips = apply_export_range(export_clips, request.in_point, request.out_point)?;
+
     // Calculate estimates
     let estimated_time = estimate_export_time(&export_clips, &request.settings);
     let estimated_size = estimate_export_size(&export_clips, &request.settings);
@@ -189,9 +294,11 @@ pub async fn estimate_export_info(request: ExportTimelineRequest) -> CommandResu
 /// Validate export settings
 #[tauri::command]
 pub async fn validate_export_settings_command(
+    app_handle: tauri::AppHandle,
     settings: ExportSettings,
 ) -> CommandResult<ValidationResult> {
-    match validate_export_settings(&settings) {
+    let capabilities = get_ffmpeg_capabilities(&app_handle).await.ok();
+    match validate_export_settings(&settings, capabilities.as_ref()) {
         Ok(_) => Ok(ValidationResult {
             is_valid: true,
             errors: vec![],
@@ -265,15 +372,30 @@ pub async fn cleanup_export_files() -> CommandResult<()> {
     crate::ffmpeg::export::cleanup_export_temp_files().await
 }
 
+/// Get the contents and size of a project's render cache
+#[tauri::command]
+pub async fn get_render_cache_info(project_id: String) -> CommandResult<RenderCacheStats> {
+    RenderCache::for_project(&project_id)?.stats()
+}
+
+/// Purge a project's render cache down to a size limit (everything by default)
+#[tauri::command]
+pub async fn purge_render_cache(
+    project_id: String,
+    max_size_bytes: Option<u64>,
+) -> CommandResult<RenderCacheStats> {
+    RenderCache::for_project(&project_id)?.purge(max_size_bytes.unwrap_or(0))
+}
+
 /// Cancel an ongoing export
 #[tauri::command]
 pub async fn cancel_export(
     app_handle: tauri::AppHandle,
     export_id: String,
 ) -> CommandResult<bool> {
-    // For MVP, we'll implement a simple cancellation mechanism
-    // In a full implementation, this would track active processes and kill them
-    
+    // Kill the running encode; the export itself then reports the failure
+    let cancelled = crate::ffmpeg::export::cancel_active_export();
+
     // Emit cancellation progress
     let cancel_progress = crate::ffmpeg::export::ExportProgress {
         progress: 0.0,
@@ -290,9 +412,7 @@ pub async fn cancel_export(
     app_handle.emit("export-progress", &cancel_progress)
         .map_err(|e| CommandError::ffmpeg_error(format!("Failed to emit cancellation progress: {}", e)))?;
 
-    // TODO: Implement actual process termination
-    // For now, just return success
-    Ok(true)
+    Ok(cancelled)
 }
 
 /// Get export status
@@ -390,6 +510,57 @@ pub fn format_estimated_time(seconds: f64) -> String {
     }
 }
 
+/// Restrict export clips to an in/out range when either point is set
+pub fn apply_export_range(
+    clips: Vec<ExportClip>,
+    in_point: Option<f64>,
+    out_point: Option<f64>,
+) -> CommandResult<Vec<ExportClip>> {
+    if in_point.is_none() && out_point.is_none() {
+        return Ok(clips);
+    }
+
+    let in_point = in_point.unwrap_or(0.0);
+    let out_point = out_point.unwrap_or_else(|| get_timeline_duration(&clips));
+    slice_clips_to_range(&clips, in_point, out_point)
+}
+
+/// Resolve the chapters and container tags for a timeline export
+///
+/// Markers are restricted to the export range and tags come from the
+/// project record when a project ID is given.
+pub fn get_timeline_export_metadata(
+    request: &ExportTimelineRequest,
+) -> CommandResult<(Vec<ExportChapter>, ExportMetadataTags)> {
+    let chapters = if request.in_point.is_some() || request.out_point.is_some() {
+        slice_chapters_to_range(
+            &request.markers,
+            request.in_point.unwrap_or(0.0),
+            request.out_point.unwrap_or(f64::INFINITY),
+        )
+    } else {
+        request.markers.clone()
+    };
+
+    let tags = match &request.project_id {
+        Some(project_id) => get_project_export_tags(&read_project_record(project_id)?),
+        None => ExportMetadataTags::default(),
+    };
+
+    Ok((chapters, tags))
+}
+
+/// Build container tags from a project record
+pub fn get_project_export_tags(project: &Project) -> ExportMetadataTags {
+    ExportMetadataTags {
+        title: Some(project.name.clone()),
+        artist: project.author.clone(),
+        comment: project.description.clone().filter(|d| !d.is_empty()),
+        creation_time: Some(project.created_at.clone()),
+        project_id: Some(project.id.clone()),
+    }
+}
+
 /// Get default export settings
 pub fn get_default_export_settings() -> ExportSettings {
     ExportSettings {
@@ -397,6 +568,10 @@ pub fn get_default_export_settings() -> ExportSettings {
         quality: "medium".to_string(),
         format: "mp4".to_string(),
         codec: "h264".to_string(),
+        rate_control: "quality".to_string(),
+        crf: None,
+        video_bitrate_kbps: None,
+        target_size_mb: None,
     }
 }
 
@@ -134,6 +134,9 @@ pub async fn import_video_file(
             timestamp: Some(1.0), // Extract frame at 1 second
             width: Some(320),
             height: Some(180),
+            best_frame_window: None,
+            format: None,
+            fit: None,
         },
     )
     .await?;
@@ -369,6 +372,7 @@ mod tests {
                     has_audio: true,
                     audio_codec: Some("aac".to_string()),
                     audio_bitrate: Some(128000),
+                    is_vfr: false,
                 },
             },
             metadata: VideoMetadata {
@@ -383,6 +387,7 @@ mod tests {
                 has_audio: true,
                 audio_codec: Some("aac".to_string()),
                 audio_bitrate: Some(128000),
+                is_vfr: false,
             },
             thumbnail_path: "/path/to/thumbnail.jpg".to_string(),
         };
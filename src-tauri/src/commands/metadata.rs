@@ -6,13 +6,42 @@
 // and the FFmpeg modules.
 
 use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::capabilities::FfmpegCapabilitiesResponse;
+use crate::ffmpeg::hover_preview::{
+    request_hover_preview, GenerateHoverPreviewRequest, GenerateHoverPreviewResponse,
+    HoverPreviewFormat, HoverPreviewSettings, DEFAULT_HOVER_PREVIEW_WIDTH,
+    DEFAULT_HOVER_SEGMENT_COUNT, DEFAULT_HOVER_SEGMENT_DURATION,
+};
+use crate::ffmpeg::integrity::{
+    get_repaired_path, repair_media_internal, scan_media_integrity_internal, MediaHealthReport,
+    RepairMediaRequest, RepairMediaResponse, ScanMediaIntegrityRequest,
+};
 use crate::ffmpeg::probe::{
     extract_video_metadata, ExtractMetadataRequest, ExtractMetadataResponse,
 };
+use crate::ffmpeg::scene::{
+    build_scene_sub_clips, detect_scenes_internal, DetectScenesRequest, DetectScenesResponse,
+    SplitAtScenesRequest, DEFAULT_MIN_SCENE_DURATION, DEFAULT_SCENE_THRESHOLD,
+};
+use crate::ffmpeg::scrub::{
+    generate_scrub_strip_internal, get_default_scrub_interval, get_scrub_index_paths,
+    GenerateScrubStripRequest, GenerateScrubStripResponse, DEFAULT_SCRUB_TILE_HEIGHT,
+    DEFAULT_SCRUB_TILE_WIDTH,
+};
+use crate::ffmpeg::silence::{
+    detect_silence_internal, remove_silence_from_clips, DetectSilenceRequest,
+    DetectSilenceResponse, RemoveSilenceRequest, DEFAULT_MIN_SILENCE_DURATION,
+    DEFAULT_SILENCE_PADDING, DEFAULT_SILENCE_THRESHOLD_DB,
+};
 use crate::ffmpeg::thumbnail::{
     delete_thumbnail, generate_multiple_thumbnails, generate_thumbnail, thumbnail_exists,
     GenerateMultipleThumbnailsRequest, GenerateMultipleThumbnailsResponse,
-    GenerateThumbnailRequest, GenerateThumbnailResponse,
+    GenerateThumbnailRequest, GenerateThumbnailResponse, ThumbnailCache, ThumbnailCacheStats,
+    ThumbnailFit, ThumbnailFormat,
+};
+use crate::ffmpeg::waveform::{
+    get_waveform_peaks_internal, GetWaveformPeaksRequest, WaveformPeaksResponse,
+    DEFAULT_PEAK_COUNT,
 };
 use serde::{Deserialize, Serialize};
 
@@ -52,6 +81,12 @@ pub struct GenerateThumbnailOnlyRequest {
     pub timestamp: Option<f64>,
     pub width: Option<u32>,
     pub height: Option<u32>,
+    #[serde(default)]
+    pub best_frame_window: Option<f64>,
+    #[serde(default)]
+    pub format: Option<ThumbnailFormat>,
+    #[serde(default)]
+    pub fit: Option<ThumbnailFit>,
 }
 
 /// Request to check thumbnail existence
@@ -112,6 +147,9 @@ pub async fn import_video_with_metadata(
                 timestamp: request.thumbnail_timestamp,
                 width: None,
                 height: None,
+                best_frame_window: None,
+                format: None,
+                fit: None,
             },
         )
         .await
@@ -142,7 +180,7 @@ pub async fn import_video_with_metadata(
     })
 }
 
-/// Get video metadata only
+/// Get video metadata only, including per-stream details when ffprobe is available
 #[tauri::command]
 pub async fn get_video_metadata(
     app_handle: tauri::AppHandle,
@@ -170,6 +208,9 @@ pub async fn generate_video_thumbnail(
             timestamp: request.timestamp,
             width: request.width,
             height: request.height,
+            best_frame_window: request.best_frame_window,
+            format: request.format,
+            fit: request.fit,
         },
     )
     .await
@@ -192,6 +233,9 @@ pub async fn check_thumbnail_exists(request: CheckThumbnailRequest) -> CommandRe
         timestamp: request.timestamp,
         width: None,
         height: None,
+        best_frame_window: None,
+        format: None,
+        fit: None,
     })
     .await
 }
@@ -204,10 +248,52 @@ pub async fn delete_video_thumbnail(request: GenerateThumbnailOnlyRequest) -> Co
         timestamp: request.timestamp,
         width: request.width,
         height: request.height,
+        best_frame_window: None,
+        format: request.format,
+        fit: request.fit,
     })
     .await
 }
 
+/// Get a looping hover preview for the media bin, generating it in the background if needed
+#[tauri::command]
+pub async fn generate_hover_preview(
+    app_handle: tauri::AppHandle,
+    request: GenerateHoverPreviewRequest,
+) -> CommandResult<GenerateHoverPreviewResponse> {
+    // Validate file path first
+    crate::commands::validate_file_path(&request.file_path)?;
+
+    let settings = HoverPreviewSettings {
+        format: request.format.unwrap_or(HoverPreviewFormat::Webp),
+        segment_count: request.segment_count.unwrap_or(DEFAULT_HOVER_SEGMENT_COUNT),
+        segment_duration: request.segment_duration.unwrap_or(DEFAULT_HOVER_SEGMENT_DURATION),
+        width: request.width.unwrap_or(DEFAULT_HOVER_PREVIEW_WIDTH),
+    };
+
+    match request_hover_preview(&app_handle, &request.file_path, settings) {
+        Ok(response) => Ok(response),
+        Err(error) => Ok(GenerateHoverPreviewResponse {
+            success: false,
+            preview_path: None,
+            ready: false,
+            error_message: Some(error.message),
+        }),
+    }
+}
+
+/// Get the size and entry count of the thumbnail cache
+#[tauri::command]
+pub async fn get_thumbnail_cache_info() -> CommandResult<ThumbnailCacheStats> {
+    ThumbnailCache::open()?.stats()
+}
+
+/// Purge the thumbnail cache down to a size limit (everything by default)
+#[tauri::command]
+pub async fn purge_thumbnail_cache(max_size_bytes: Option<u64>) -> CommandResult<ThumbnailCacheStats> {
+    ThumbnailCache::open()?.purge(max_size_bytes.unwrap_or(0))
+}
+
 /// Batch import multiple video files
 #[tauri::command]
 pub async fn batch_import_videos(
@@ -241,6 +327,258 @@ pub async fn batch_import_videos(
     Ok(results)
 }
 
+/// Scan a media file for damage (missing index, decoding errors, truncation, audio gaps)
+#[tauri::command]
+pub async fn scan_media_integrity(
+    app_handle: tauri::AppHandle,
+    request: ScanMediaIntegrityRequest,
+) -> CommandResult<MediaHealthReport> {
+    crate::commands::validate_file_path(&request.file_path)?;
+    scan_media_integrity_internal(&app_handle, &request.file_path, request.full_decode).await
+}
+
+/// Repair a damaged media file by remuxing it into a new file
+#[tauri::command]
+pub async fn repair_media_file(
+    app_handle: tauri::AppHandle,
+    request: RepairMediaRequest,
+) -> CommandResult<RepairMediaResponse> {
+    crate::commands::validate_file_path(&request.file_path)?;
+
+    let output_path = match request.output_path {
+        Some(output_path) => std::path::PathBuf::from(output_path),
+        None => get_repaired_path(std::path::Path::new(&request.file_path)),
+    };
+
+    match repair_media_internal(&app_handle, &request.file_path, &output_path).await {
+        Ok(report) => Ok(RepairMediaResponse {
+            success: true,
+            output_path: Some(output_path.to_string_lossy().to_string()),
+            error_message: None,
+            report: Some(report),
+        }),
+        Err(error) => Ok(RepairMediaResponse {
+            success: false,
+            output_path: None,
+            error_message: Some(error.message),
+            report: None,
+        }),
+    }
+}
+
+/// Detect scene changes in a video
+#[tauri::command]
+pub async fn detect_scenes(
+    app_handle: tauri::AppHandle,
+    request: DetectScenesRequest,
+) -> CommandResult<DetectScenesResponse> {
+    // Probe the duration so cuts near the end can be dropped
+    let metadata_response = extract_video_metadata(
+        app_handle.clone(),
+        ExtractMetadataRequest {
+            file_path: request.file_path.clone(),
+        },
+    )
+    .await?;
+    let duration = match metadata_response.metadata {
+        Some(metadata) => metadata.duration,
+        None => {
+            return Ok(DetectScenesResponse {
+                success: false,
+                cuts: vec![],
+                duration: 0.0,
+                error_message: metadata_response.error_message,
+            })
+        }
+    };
+
+    match detect_scenes_internal(
+        &app_handle,
+        &request.file_path,
+        request.threshold.unwrap_or(DEFAULT_SCENE_THRESHOLD),
+        request.min_scene_duration.unwrap_or(DEFAULT_MIN_SCENE_DURATION),
+        duration,
+    )
+    .await
+    {
+        Ok(cuts) => Ok(DetectScenesResponse {
+            success: true,
+            cuts,
+            duration,
+            error_message: None,
+        }),
+        Err(error) => Ok(DetectScenesResponse {
+            success: false,
+            cuts: vec![],
+            duration,
+            error_message: Some(error.message),
+        }),
+    }
+}
+
+/// Split a source into timeline sub-clips at scene cuts
+#[tauri::command]
+pub async fn split_clip_at_scenes(
+    request: SplitAtScenesRequest,
+) -> CommandResult<Vec<crate::commands::export::TimelineExportClip>> {
+    build_scene_sub_clips(&request)
+}
+
+/// Detect silent intervals in a clip's audio
+#[tauri::command]
+pub async fn detect_silence(
+    app_handle: tauri::AppHandle,
+    request: DetectSilenceRequest,
+) -> CommandResult<DetectSilenceResponse> {
+    // Probe the duration so trailing silence can be closed
+    let metadata_response = extract_video_metadata(
+        app_handle.clone(),
+        ExtractMetadataRequest {
+            file_path: request.file_path.clone(),
+        },
+    )
+    .await?;
+    let duration = match metadata_response.metadata {
+        Some(metadata) => metadata.duration,
+        None => {
+            return Ok(DetectSilenceResponse {
+                success: false,
+                intervals: vec![],
+                duration: 0.0,
+                error_message: metadata_response.error_message,
+            })
+        }
+    };
+
+    match detect_silence_internal(
+        &app_handle,
+        &request.file_path,
+        request.threshold_db.unwrap_or(DEFAULT_SILENCE_THRESHOLD_DB),
+        request.min_silence_duration.unwrap_or(DEFAULT_MIN_SILENCE_DURATION),
+        duration,
+    )
+    .await
+    {
+        Ok(intervals) => Ok(DetectSilenceResponse {
+            success: true,
+            intervals,
+            duration,
+            error_message: None,
+        }),
+        Err(error) => Ok(DetectSilenceResponse {
+            success: false,
+            intervals: vec![],
+            duration,
+            error_message: Some(error.message),
+        }),
+    }
+}
+
+/// Remove silent intervals from a clip list, producing jump cuts ready for export
+#[tauri::command]
+pub async fn remove_silence(
+    request: RemoveSilenceRequest,
+) -> CommandResult<Vec<crate::commands::export::TimelineExportClip>> {
+    remove_silence_from_clips(
+        &request.clips,
+        &request.silences,
+        request.padding.unwrap_or(DEFAULT_SILENCE_PADDING),
+    )
+}
+
+/// Generate a sprite-sheet scrub strip for the timeline
+#[tauri::command]
+pub async fn generate_scrub_strip(
+    app_handle: tauri::AppHandle,
+    request: GenerateScrubStripRequest,
+) -> CommandResult<GenerateScrubStripResponse> {
+    // Validate file path first
+    crate::commands::validate_file_path(&request.file_path)?;
+
+    // Probe the duration to lay out the tiles
+    let metadata_response = extract_video_metadata(
+        app_handle.clone(),
+        ExtractMetadataRequest {
+            file_path: request.file_path.clone(),
+        },
+    )
+    .await?;
+    let duration = match metadata_response.metadata {
+        Some(metadata) => metadata.duration,
+        None => {
+            return Ok(GenerateScrubStripResponse {
+                success: false,
+                index: None,
+                index_path: None,
+                vtt_path: None,
+                error_message: metadata_response.error_message,
+            })
+        }
+    };
+
+    match generate_scrub_strip_internal(
+        &app_handle,
+        &request.file_path,
+        duration,
+        request.interval.unwrap_or_else(|| get_default_scrub_interval(duration)),
+        request.tile_width.unwrap_or(DEFAULT_SCRUB_TILE_WIDTH),
+        request.tile_height.unwrap_or(DEFAULT_SCRUB_TILE_HEIGHT),
+    )
+    .await
+    {
+        Ok(index) => {
+            let (index_path, vtt_path) = match get_scrub_index_paths(&index) {
+                Some((index_path, vtt_path)) => (
+                    Some(index_path.to_string_lossy().to_string()),
+                    Some(vtt_path.to_string_lossy().to_string()),
+                ),
+                None => (None, None),
+            };
+            Ok(GenerateScrubStripResponse {
+                success: true,
+                index: Some(index),
+                index_path,
+                vtt_path,
+                error_message: None,
+            })
+        }
+        Err(error) => Ok(GenerateScrubStripResponse {
+            success: false,
+            index: None,
+            index_path: None,
+            vtt_path: None,
+            error_message: Some(error.message),
+        }),
+    }
+}
+
+/// Get waveform peaks for a time range of a clip's audio
+#[tauri::command]
+pub async fn get_waveform_peaks(
+    app_handle: tauri::AppHandle,
+    request: GetWaveformPeaksRequest,
+) -> CommandResult<WaveformPeaksResponse> {
+    match get_waveform_peaks_internal(
+        &app_handle,
+        &request.file_path,
+        request.start_time,
+        request.end_time,
+        request.peak_count.unwrap_or(DEFAULT_PEAK_COUNT),
+    )
+    .await
+    {
+        Ok(response) => Ok(response),
+        Err(error) => Ok(WaveformPeaksResponse {
+            success: false,
+            peaks_per_second: 0.0,
+            start_time: request.start_time,
+            min_peaks: vec![],
+            max_peaks: vec![],
+            error_message: Some(error.message),
+        }),
+    }
+}
+
 /// Get FFmpeg version information
 #[tauri::command]
 pub async fn get_ffmpeg_version(app_handle: tauri::AppHandle) -> CommandResult<String> {
@@ -253,6 +591,25 @@ pub async fn check_ffmpeg_availability(app_handle: tauri::AppHandle) -> CommandR
     crate::ffmpeg::probe::check_ffmpeg_probe_availability(&app_handle).await
 }
 
+/// Get the encoders, decoders, filters, formats and hardware accelerators FFmpeg supports
+#[tauri::command]
+pub async fn get_ffmpeg_capabilities(
+    app_handle: tauri::AppHandle,
+) -> CommandResult<FfmpegCapabilitiesResponse> {
+    match crate::ffmpeg::capabilities::get_ffmpeg_capabilities(&app_handle).await {
+        Ok(capabilities) => Ok(FfmpegCapabilitiesResponse {
+            success: true,
+            capabilities: Some(capabilities),
+            error_message: None,
+        }),
+        Err(error) => Ok(FfmpegCapabilitiesResponse {
+            success: false,
+            capabilities: None,
+            error_message: Some(error.message),
+        }),
+    }
+}
+
 // ============================================================================
 // UTILITY FUNCTIONS
 // ============================================================================
@@ -11,6 +11,7 @@ pub mod metadata;
 pub mod project;
 pub mod project_import;
 pub mod recording;
+pub mod timeline;
 
 // ============================================================================
 // COMMON ERROR TYPES
@@ -60,6 +61,10 @@ impl CommandError {
     pub fn recording_error(message: String) -> Self {
         Self::new(message, "recording_error".to_string())
     }
+
+    pub fn binary_not_found(message: String) -> Self {
+        Self::new(message, "binary_not_found".to_string())
+    }
 }
 
 impl From<std::io::Error> for CommandError {
@@ -97,6 +102,8 @@ pub struct Project {
     pub created_at: String,
     pub updated_at: String,
     pub is_active: bool,
+    #[serde(default)]
+    pub author: Option<String>,
 }
 
 /// ProjectSettings - Configuration for a project
@@ -170,12 +177,26 @@ pub fn get_temp_dir() -> CommandResult<std::path::PathBuf> {
 
 /// Generate a hash for a file path (for thumbnail caching)
 pub fn hash_file_path(file_path: &str) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
+    format!("{:016x}", stable_hash(&[file_path.as_bytes()]))
+}
 
-    let mut hasher = DefaultHasher::new();
-    file_path.hash(&mut hasher);
-    format!("{:x}", hasher.finish())
+/// Hash byte strings with 64-bit FNV-1a
+///
+/// Unlike `DefaultHasher`, the result is the same across Rust versions and
+/// runs, so it is safe to use in file names that persist on disk. Parts are
+/// separated so ("ab", "c") and ("a", "bc") hash differently.
+pub fn stable_hash(parts: &[&[u8]]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for part in parts {
+        for byte in part.iter().chain(std::iter::once(&0xffu8)) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
 }
 
 /// Validate that a file exists and is readable
@@ -319,6 +340,15 @@ mod tests {
         assert!(!hash1.is_empty());
     }
 
+    #[test]
+    fn test_stable_hash() {
+        // Known FNV-1a values pin the hash so cached file names never change
+        assert_eq!(stable_hash(&[]), 0xcbf29ce484222325);
+        assert_eq!(stable_hash(&[b"a"]), 0x089bc907b544c769);
+        assert_eq!(hash_file_path("a"), "089bc907b544c769");
+        assert_ne!(stable_hash(&[b"ab", b"c"]), stable_hash(&[b"a", b"bc"]));
+    }
+
     #[test]
     fn test_is_supported_video_format() {
         // Supported formats
@@ -418,6 +448,10 @@ mod tests {
         let unsupported_error = CommandError::unsupported_format("Unsupported format".to_string());
         assert_eq!(unsupported_error.error_type, "unsupported_format");
         assert_eq!(unsupported_error.message, "Unsupported format");
+
+        let binary_error = CommandError::binary_not_found("ffprobe binary not found".to_string());
+        assert_eq!(binary_error.error_type, "binary_not_found");
+        assert_eq!(binary_error.message, "ffprobe binary not found");
     }
 
     #[test]
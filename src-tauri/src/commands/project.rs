@@ -15,6 +15,8 @@ use crate::commands::{CommandError, CommandResult, Project, ProjectMetadata, Pro
 pub struct CreateProjectRequest {
     pub name: String,
     pub description: String,
+    #[serde(default)]
+    pub author: Option<String>,
 }
 
 /// Request to open a project
@@ -75,11 +77,44 @@ fn get_projects_dir() -> Result<PathBuf, CommandError> {
 }
 
 /// Get project directory path
-fn get_project_dir(project_id: &str) -> Result<PathBuf, CommandError> {
+pub fn get_project_dir(project_id: &str) -> Result<PathBuf, CommandError> {
     let projects_dir = get_projects_dir()?;
     Ok(projects_dir.join(project_id))
 }
 
+/// Read a project record from its project.json
+pub fn read_project_record(project_id: &str) -> Result<Project, CommandError> {
+    let project_file = get_project_dir(project_id)?.join("project.json");
+    if !project_file.exists() {
+        return Err(CommandError::validation_error(format!(
+            "Project not found: {}",
+            project_id
+        )));
+    }
+
+    let project_json = fs::read_to_string(&project_file)
+        .map_err(|e| CommandError::file_error(format!("Failed to read project file: {}", e)))?;
+
+    serde_json::from_str(&project_json).map_err(|e| {
+        CommandError::serialization_error(format!("Failed to parse project file: {}", e))
+    })
+}
+
+/// Read project settings, falling back to defaults if none are saved
+pub fn read_project_settings(project_id: &str) -> Result<ProjectSettings, CommandError> {
+    let settings_file = get_project_dir(project_id)?.join("settings.json");
+    if !settings_file.exists() {
+        return Ok(create_default_settings(project_id));
+    }
+
+    let settings_json = fs::read_to_string(&settings_file)
+        .map_err(|e| CommandError::file_error(format!("Failed to read settings file: {}", e)))?;
+
+    serde_json::from_str(&settings_json).map_err(|e| {
+        CommandError::serialization_error(format!("Failed to parse settings file: {}", e))
+    })
+}
+
 /// Create project directory structure
 fn create_project_structure(project_dir: &Path) -> Result<(), CommandError> {
     // Create main project directory
@@ -152,6 +187,7 @@ pub async fn create_project(
         created_at: chrono::Utc::now().to_rfc3339(),
         updated_at: chrono::Utc::now().to_rfc3339(),
         is_active: false,
+        author: request.author,
     };
 
     // Save project metadata
@@ -300,22 +336,7 @@ pub async fn load_project_settings(
     app_handle: tauri::AppHandle,
     request: LoadProjectSettingsRequest,
 ) -> CommandResult<ProjectSettings> {
-    let project_dir = get_project_dir(&request.project_id)?;
-    let settings_file = project_dir.join("settings.json");
-
-    if !settings_file.exists() {
-        // Return default settings if file doesn't exist
-        return Ok(create_default_settings(&request.project_id));
-    }
-
-    let settings_json = fs::read_to_string(&settings_file)
-        .map_err(|e| CommandError::file_error(format!("Failed to read settings file: {}", e)))?;
-
-    let settings: ProjectSettings = serde_json::from_str(&settings_json).map_err(|e| {
-        CommandError::serialization_error(format!("Failed to parse settings file: {}", e))
-    })?;
-
-    Ok(settings)
+    read_project_settings(&request.project_id)
 }
 
 /// Load project metadata
@@ -8,8 +8,17 @@ use crate::commands::{
     get_file_size, get_filename_with_extension, is_supported_video_format, validate_file_path,
     CommandError, CommandResult, Project,
 };
+use crate::commands::project::read_project_settings;
+use crate::ffmpeg::conform::{conform_to_constant_frame_rate, get_conformed_path};
+use crate::ffmpeg::integrity::{
+    has_blocking_issues, remux_media_file, scan_media_integrity_internal, MediaHealthReport,
+};
 use crate::ffmpeg::probe::{extract_video_metadata, ExtractMetadataRequest, VideoMetadata};
-use crate::ffmpeg::thumbnail::{generate_thumbnail, GenerateThumbnailRequest};
+use crate::ffmpeg::proxy::{
+    get_asset_proxy_record, get_proxy_path_for_asset, needs_proxy, resolve_preview_source,
+    start_proxy_generation, update_asset_proxy_record, AssetProxyRecord,
+};
+use crate::ffmpeg::thumbnail::{generate_thumbnail, GenerateThumbnailRequest, DEFAULT_BEST_FRAME_WINDOW};
 
 // ============================================================================
 // REQUEST/RESPONSE TYPES
@@ -20,6 +29,10 @@ use crate::ffmpeg::thumbnail::{generate_thumbnail, GenerateThumbnailRequest};
 pub struct ImportVideoToProjectRequest {
     pub project_id: String,
     pub source_file_path: String,
+    #[serde(default)]
+    pub conform_vfr: bool, // Transcode variable frame rate sources to the project framerate
+    #[serde(default)]
+    pub repair_damaged: bool, // Remux damaged sources into the project instead of rejecting them
 }
 
 /// Response from importing a video file into a project
@@ -29,6 +42,21 @@ pub struct ImportVideoToProjectResponse {
     pub project_file_path: String,
     pub metadata: VideoMetadata,
     pub thumbnail_path: String,
+    pub proxy_path: Option<String>, // Where the proxy appears once the background job finishes
+    #[serde(default)]
+    pub conformed: bool, // Whether the asset was conformed to a constant frame rate
+    #[serde(default)]
+    pub health: Option<MediaHealthReport>, // Integrity scan of the source before import
+    #[serde(default)]
+    pub repaired: bool, // Whether the asset was repaired on import
+}
+
+/// Asset in a project's assets directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectAsset {
+    pub filename: String,
+    pub file_path: String,
+    pub proxy: Option<AssetProxyRecord>, // Recorded proxy and its state, if any
 }
 
 // ============================================================================
@@ -97,6 +125,24 @@ fn generate_project_asset_filename(original_filename: &str, project_id: &str) ->
     format!("{}_{}_{}.{}", base_name, project_id, timestamp, extension)
 }
 
+/// Extract metadata for a project asset
+async fn extract_asset_metadata(
+    app_handle: &tauri::AppHandle,
+    asset_path: &Path,
+) -> CommandResult<VideoMetadata> {
+    let metadata_response = extract_video_metadata(
+        app_handle.clone(),
+        ExtractMetadataRequest {
+            file_path: asset_path.to_string_lossy().to_string(),
+        },
+    )
+    .await?;
+
+    metadata_response.metadata.ok_or_else(|| {
+        CommandError::ffmpeg_error("Failed to extract video metadata".to_string())
+    })
+}
+
 /// Copy file from source to destination
 fn copy_file(source: &Path, destination: &Path) -> CommandResult<()> {
     // Create parent directories if they don't exist
@@ -155,7 +201,7 @@ pub async fn import_video_to_project(
     let original_filename = get_filename_with_extension(&request.source_file_path)
         .unwrap_or_else(|| "video.mp4".to_string());
     let project_filename = generate_project_asset_filename(&original_filename, &request.project_id);
-    let project_file_path = assets_dir.join(&project_filename);
+    let mut project_file_path = assets_dir.join(&project_filename);
 
     // Check if file already exists in project
     if project_file_path.exists() {
@@ -165,21 +211,75 @@ pub async fn import_video_to_project(
         )));
     }
 
-    // Copy file to project assets directory
-    copy_file(source_path, &project_file_path)?;
+    // Scan the source for damage before it enters the project
+    let health = scan_media_integrity_internal(&app_handle, &request.source_file_path, false).await?;
+    let repaired = !health.healthy && health.repairable && request.repair_damaged;
+    if has_blocking_issues(&health) && !repaired {
+        let reasons: Vec<String> = health.issues.iter().map(|issue| issue.message.clone()).collect();
+        return Err(CommandError::validation_error(format!(
+            "Source file is damaged: {}",
+            reasons.join("; ")
+        )));
+    }
+
+    // Copy (or repair) file into project assets directory
+    if repaired {
+        remux_media_file(
+            &app_handle,
+            &request.source_file_path,
+            &project_file_path,
+            health.issues.iter().any(|issue| issue.kind == "audio_gap"),
+        )
+        .await?;
+    } else {
+        copy_file(source_path, &project_file_path)?;
+    }
 
     // Extract video metadata using FFmpeg (from the copied file)
-    let metadata_response = extract_video_metadata(
-        app_handle.clone(),
-        ExtractMetadataRequest {
-            file_path: project_file_path.to_string_lossy().to_string(),
-        },
-    )
-    .await?;
+    let mut metadata = extract_asset_metadata(&app_handle, &project_file_path).await?;
+
+    // Conform variable frame rate sources to the project framerate if requested
+    let conformed = request.conform_vfr && metadata.is_vfr;
+    if conformed {
+        let conformed_path = get_conformed_path(&project_file_path);
+
+        // Never conform over another asset that shares the file stem
+        if conformed_path != project_file_path && conformed_path.exists() {
+            let _ = fs::remove_file(&project_file_path);
+            return Err(CommandError::validation_error(format!(
+                "File already exists in project: {}",
+                conformed_path.display()
+            )));
+        }
 
-    let metadata = metadata_response.metadata.ok_or_else(|| {
-        CommandError::ffmpeg_error("Failed to extract video metadata".to_string())
-    })?;
+        let conform_result = match read_project_settings(&request.project_id) {
+            Ok(settings) => {
+                conform_to_constant_frame_rate(
+                    &app_handle,
+                    &project_file_path.to_string_lossy(),
+                    &conformed_path,
+                    settings.default_framerate,
+                )
+                .await
+            }
+            Err(error) => Err(error),
+        };
+
+        // Don't leave the unconformed copy behind in the project
+        if let Err(error) = conform_result {
+            let _ = fs::remove_file(&project_file_path);
+            return Err(error);
+        }
+
+        // The conformed file replaces the copied original
+        if conformed_path != project_file_path {
+            fs::remove_file(&project_file_path).map_err(|e| {
+                CommandError::file_error(format!("Failed to remove unconformed asset: {}", e))
+            })?;
+        }
+        project_file_path = conformed_path;
+        metadata = extract_asset_metadata(&app_handle, &project_file_path).await?;
+    }
 
     // Generate thumbnail (save to project thumbnails directory)
     let thumbnails_dir = get_project_thumbnails_directory(&request.project_id)?;
@@ -190,26 +290,49 @@ pub async fn import_video_to_project(
         app_handle.clone(),
         GenerateThumbnailRequest {
             file_path: project_file_path.to_string_lossy().to_string(),
-            timestamp: Some(1.0), // Extract frame at 1 second
+            timestamp: Some(1.0), // Used if no better frame is found
             width: Some(320),
             height: Some(180),
+            best_frame_window: Some(DEFAULT_BEST_FRAME_WINDOW),
+            format: None,
+            fit: None,
         },
     )
     .await?;
 
     // Extract thumbnail path from response
-    let thumbnail_path_str = thumbnail_response
+    let cached_thumbnail_path = thumbnail_response
         .thumbnail_path
         .ok_or_else(|| CommandError::ffmpeg_error("Failed to generate thumbnail".to_string()))?;
 
+    // Keep a copy in the project, since the shared thumbnail cache evicts old entries
+    fs::copy(&cached_thumbnail_path, &thumbnail_path)
+        .map_err(|e| CommandError::file_error(format!("Failed to copy thumbnail into project: {}", e)))?;
+    let thumbnail_path_str = thumbnail_path.to_string_lossy().to_string();
+
+    // Generate a low-resolution proxy for large sources in the background
+    let project_file_path_str = project_file_path.to_string_lossy().to_string();
+    let proxy_path = if needs_proxy(&metadata) {
+        get_proxy_path_for_asset(&project_file_path_str).map(|proxy_path| {
+            start_proxy_generation(app_handle.clone(), project_file_path_str.clone(), proxy_path.clone());
+            proxy_path.to_string_lossy().to_string()
+        })
+    } else {
+        None
+    };
+
     // Generate unique clip ID
     let clip_id = Uuid::new_v4().to_string();
 
     Ok(ImportVideoToProjectResponse {
         clip_id,
-        project_file_path: project_file_path.to_string_lossy().to_string(),
+        project_file_path: project_file_path_str,
         metadata,
         thumbnail_path: thumbnail_path_str,
+        proxy_path,
+        conformed,
+        health: Some(health),
+        repaired,
     })
 }
 
@@ -233,12 +356,12 @@ pub async fn get_project_asset_path(
     Ok(asset_path.to_string_lossy().to_string())
 }
 
-/// List all assets in a project
+/// List all assets in a project with their proxy state
 #[tauri::command]
 pub async fn list_project_assets(
     app_handle: tauri::AppHandle,
     project_id: String,
-) -> CommandResult<Vec<String>> {
+) -> CommandResult<Vec<ProjectAsset>> {
     let assets_dir = get_project_assets_directory(&project_id)?;
     let mut assets = Vec::new();
 
@@ -255,7 +378,12 @@ pub async fn list_project_assets(
             let path = entry.path();
             if path.is_file() {
                 if let Some(filename) = path.file_name().and_then(|name| name.to_str()) {
-                    assets.push(filename.to_string());
+                    let file_path = path.to_string_lossy().to_string();
+                    assets.push(ProjectAsset {
+                        filename: filename.to_string(),
+                        proxy: get_asset_proxy_record(&file_path),
+                        file_path,
+                    });
                 }
             }
         }
@@ -284,5 +412,20 @@ pub async fn delete_project_asset(
     fs::remove_file(&asset_path)
         .map_err(|e| CommandError::file_error(format!("Failed to delete asset: {}", e)))?;
 
+    // Remove the asset's proxy as well
+    if let Some(proxy_path) = get_proxy_path_for_asset(&asset_path.to_string_lossy()) {
+        if proxy_path.exists() {
+            fs::remove_file(&proxy_path)
+                .map_err(|e| CommandError::file_error(format!("Failed to delete proxy: {}", e)))?;
+        }
+    }
+    update_asset_proxy_record(&asset_path.to_string_lossy(), None)?;
+
     Ok(())
 }
+
+/// Get the file to use for previewing a video (its proxy when ready)
+#[tauri::command]
+pub async fn get_preview_source(file_path: String) -> CommandResult<String> {
+    Ok(resolve_preview_source(&file_path))
+}
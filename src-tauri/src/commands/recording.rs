@@ -9,7 +9,9 @@ use crate::recording::screen::{get_available_screens as get_screens, ScreenInfo}
 use crate::recording::camera::{get_available_cameras as get_cameras, CameraInfo, start_camera_preview, stop_camera_preview, get_camera_preview_data};
 use crate::recording::session::RECORDING_MANAGER;
 use crate::recording::permissions;
-use crate::ffmpeg::thumbnail::generate_default_thumbnail;
+use crate::ffmpeg::capabilities::get_ffmpeg_capabilities;
+use crate::ffmpeg::runner::{resolve_ffmpeg_binary, FfmpegTool};
+use crate::ffmpeg::thumbnail::generate_best_frame_thumbnail;
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Manager, AppHandle};
 use std::path::PathBuf;
@@ -178,7 +180,7 @@ pub async fn stop_recording(
     
     // Generate thumbnail if we have a file path
     let thumbnail_path = if let Some(ref path) = file_path {
-        match generate_default_thumbnail(&app_handle, path).await {
+        match generate_best_frame_thumbnail(&app_handle, path).await {
             Ok(thumb_path) => Some(thumb_path),
             Err(e) => {
                 eprintln!("Failed to generate thumbnail: {}", e.message);
@@ -276,6 +278,15 @@ pub async fn get_recording_session(
 // HELPER FUNCTIONS
 // ============================================================================
 
+/// Resolve the FFmpeg binary long-running recordings are started with
+///
+/// Recordings are stopped with SIGINT by the process manager, so they need a
+/// path rather than going through the FFmpeg runner.
+fn resolve_recording_ffmpeg_path(app_handle: &AppHandle) -> Result<String, CommandError> {
+    let binary = resolve_ffmpeg_binary(FfmpegTool::Ffmpeg, Some(app_handle))?;
+    Ok(binary.path.to_string_lossy().to_string())
+}
+
 /// Validate recording settings
 fn validate_recording_settings(settings: &RecordingSettingsRequest) -> Result<(), CommandError> {
     if settings.frame_rate == 0 || settings.frame_rate > 60 {
@@ -342,29 +353,11 @@ async fn start_screen_recording_impl(
             CommandError::validation_error(format!("Failed to create session: {}", e))
         })?;
     
-    // Get FFmpeg sidecar path
-    // In dev mode, use the binary from src-tauri/bin/
-    // In production, use the bundled binary
-    let ffmpeg_path = if cfg!(debug_assertions) {
-        // Development mode - use source tree path
-        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
-            .map_err(|e| CommandError::validation_error(format!("Failed to get manifest dir: {}", e)))?;
-        let dev_path = std::path::PathBuf::from(manifest_dir).join("bin/ffmpeg");
-        dev_path.to_string_lossy().to_string()
-    } else {
-        // Production mode - use bundled sidecar
-        app_handle.path().resolve("bin/ffmpeg", tauri::path::BaseDirectory::Resource)
-            .map_err(|e| {
-                CommandError::validation_error(format!("Failed to find FFmpeg: {}", e))
-            })?
-            .to_string_lossy()
-            .to_string()
-    };
+    // Resolve the FFmpeg binary the recording process is started with
+    let ffmpeg_path = resolve_recording_ffmpeg_path(&app_handle)?;
     
-    // Verify FFmpeg binary exists and is executable
-    if !std::path::Path::new(&ffmpeg_path).exists() {
-        return Err(CommandError::validation_error(format!("FFmpeg binary not found at: {}", ffmpeg_path)));
-    }
+    // Probe FFmpeg so the platform backend can pick inputs this build supports
+    let _ = get_ffmpeg_capabilities(&app_handle).await;
     
     // Start actual screen recording with the manager's session ID
     let recording_session = crate::recording::screen::start_screen_recording_with_id(
@@ -452,21 +445,10 @@ async fn start_pip_recording_impl(
     settings: RecordingSettingsRequest,
 ) -> Result<String, CommandError> {
     // Resolve FFmpeg path
-    let ffmpeg_path = if cfg!(debug_assertions) {
-        // Development mode: use direct path
-        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
-            .map_err(|e| CommandError::validation_error(format!("Failed to get manifest dir: {}", e)))?;
-        format!("{}/bin/ffmpeg", manifest_dir)
-    } else {
-        // Production mode: resolve from resources
-        use tauri::Manager;
-        app_handle
-            .path()
-            .resolve("bin/ffmpeg", tauri::path::BaseDirectory::Resource)
-            .map_err(|e| CommandError::validation_error(format!("Failed to resolve FFmpeg path: {}", e)))?
-            .to_string_lossy()
-            .to_string()
-    };
+    let ffmpeg_path = resolve_recording_ffmpeg_path(&app_handle)?;
+    
+    // Probe FFmpeg so the platform backend can pick inputs this build supports
+    let _ = get_ffmpeg_capabilities(&app_handle).await;
     
     // Create session
     let session_id = RECORDING_MANAGER
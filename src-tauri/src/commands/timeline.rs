@@ -0,0 +1,479 @@
+// ============================================================================
+// TIMELINE COMMANDS
+// ============================================================================
+// This module provides Tauri commands for saving and loading a project's
+// timeline (timeline.json) and exporting a project straight from it.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::export::{
+    export_timeline_with_progress, validate_timeline_clips, ExportTimelineRequest,
+    TimelineExportClip,
+};
+use crate::commands::project::get_project_dir;
+use crate::commands::{validate_file_path, CommandError, CommandResult};
+use crate::ffmpeg::export::{ExportChapter, ExportSettings, ExportVideoResponse};
+
+// ============================================================================
+// TIMELINE TYPES
+// ============================================================================
+
+/// Project timeline persisted as timeline.json in the project directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTimeline {
+    #[serde(default)]
+    pub version: u32, // Timeline format version, see TIMELINE_FORMAT_VERSION
+    pub tracks: Vec<TimelineTrack>,
+    #[serde(default)]
+    pub markers: Vec<ExportChapter>, // Timeline markers exported as chapters
+    #[serde(default)]
+    pub in_point: Option<f64>, // Default export range start
+    #[serde(default)]
+    pub out_point: Option<f64>, // Default export range end
+    #[serde(default)]
+    pub updated_at: Option<String>, // Set when the timeline is saved
+}
+
+/// Track on a project timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineTrack {
+    pub id: String,
+    pub name: String,
+    pub clips: Vec<TimelineClip>,
+}
+
+/// Clip on a timeline track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineClip {
+    pub id: String,
+    pub file_path: String, // Absolute, or relative to the project directory
+    pub start_time: f64,   // Start time in timeline
+    pub duration: f64,     // Duration in timeline
+    pub trim_start: f64,   // Trim start in source video
+    pub trim_end: f64,     // Trim end in source video
+    pub original_duration: f64, // Original duration of the source video
+    #[serde(default)]
+    pub effects: Vec<TimelineEffect>,
+}
+
+/// Effect applied to a timeline clip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEffect {
+    pub effect_type: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+// ============================================================================
+// REQUEST/RESPONSE TYPES
+// ============================================================================
+
+/// Request to save a project timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveProjectTimelineRequest {
+    pub project_id: String,
+    pub timeline: ProjectTimeline,
+}
+
+/// Request to export a project from its saved timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProjectRequest {
+    pub project_id: String,
+    pub output_path: String,
+    pub filename: String,
+    pub settings: ExportSettings,
+    #[serde(default = "default_use_saved_range")]
+    pub use_saved_range: bool, // false exports only the range given here (None = whole timeline)
+    #[serde(default)]
+    pub in_point: Option<f64>, // Overrides the timeline's saved range start
+    #[serde(default)]
+    pub out_point: Option<f64>, // Overrides the timeline's saved range end
+}
+
+/// Exports use the timeline's saved range unless told otherwise
+fn default_use_saved_range() -> bool {
+    true
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Current timeline format version
+pub const TIMELINE_FORMAT_VERSION: u32 = 1;
+
+/// Timeline file name inside a project directory
+const TIMELINE_FILE_NAME: &str = "timeline.json";
+
+/// Effects the export pipeline can render (none yet)
+const SUPPORTED_EXPORT_EFFECTS: &[&str] = &[];
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Create an empty timeline at the current format version
+pub fn create_empty_timeline() -> ProjectTimeline {
+    ProjectTimeline {
+        version: TIMELINE_FORMAT_VERSION,
+        tracks: vec![],
+        markers: vec![],
+        in_point: None,
+        out_point: None,
+        updated_at: None,
+    }
+}
+
+/// Parse timeline JSON, upgrading older format versions
+pub fn parse_project_timeline(timeline_json: &str) -> CommandResult<ProjectTimeline> {
+    let mut timeline: ProjectTimeline = serde_json::from_str(timeline_json).map_err(|e| {
+        CommandError::serialization_error(format!("Failed to parse timeline file: {}", e))
+    })?;
+
+    if timeline.version > TIMELINE_FORMAT_VERSION {
+        return Err(CommandError::validation_error(format!(
+            "Timeline format version {} is newer than supported version {}",
+            timeline.version, TIMELINE_FORMAT_VERSION
+        )));
+    }
+
+    // Version 0 (unversioned) timelines share the version 1 layout
+    timeline.version = TIMELINE_FORMAT_VERSION;
+    Ok(timeline)
+}
+
+/// Read a project's timeline, returning an empty timeline if none is saved
+pub fn read_project_timeline(project_dir: &Path) -> CommandResult<ProjectTimeline> {
+    let timeline_file = project_dir.join(TIMELINE_FILE_NAME);
+    if !timeline_file.exists() {
+        return Ok(create_empty_timeline());
+    }
+
+    let timeline_json = fs::read_to_string(&timeline_file)
+        .map_err(|e| CommandError::file_error(format!("Failed to read timeline file: {}", e)))?;
+    parse_project_timeline(&timeline_json)
+}
+
+/// Write a project's timeline, replacing the previous file atomically
+pub fn write_project_timeline(project_dir: &Path, timeline: &ProjectTimeline) -> CommandResult<()> {
+    let timeline_json = serde_json::to_string_pretty(timeline).map_err(|e| {
+        CommandError::serialization_error(format!("Failed to serialize timeline: {}", e))
+    })?;
+
+    // Write next to the timeline and rename so a crash never leaves a torn file
+    let timeline_file = project_dir.join(TIMELINE_FILE_NAME);
+    let partial_file = project_dir.join(format!("{}.partial", TIMELINE_FILE_NAME));
+    fs::write(&partial_file, timeline_json)
+        .map_err(|e| CommandError::file_error(format!("Failed to write timeline file: {}", e)))?;
+    fs::rename(&partial_file, &timeline_file).map_err(|e| {
+        let _ = fs::remove_file(&partial_file);
+        CommandError::file_error(format!("Failed to replace timeline file: {}", e))
+    })
+}
+
+/// Resolve a clip path relative to the project directory
+pub fn resolve_timeline_clip_path(project_dir: &Path, file_path: &str) -> PathBuf {
+    let path = Path::new(file_path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        project_dir.join(path)
+    }
+}
+
+/// Validate a timeline for export
+pub fn validate_project_timeline(timeline: &ProjectTimeline) -> CommandResult<()> {
+    let mut track_ids = std::collections::HashSet::new();
+    for track in &timeline.tracks {
+        if !track_ids.insert(track.id.as_str()) {
+            return Err(CommandError::validation_error(format!(
+                "Duplicate track id: {}",
+                track.id
+            )));
+        }
+
+        // Clips on the same track must not overlap
+        let mut clips: Vec<&TimelineClip> = track.clips.iter().collect();
+        clips.sort_by(|a, b| {
+            a.start_time
+                .partial_cmp(&b.start_time)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        for pair in clips.windows(2) {
+            if pair[0].start_time + pair[0].duration > pair[1].start_time + 0.001 {
+                return Err(CommandError::validation_error(format!(
+                    "Clips {} and {} overlap on track {}",
+                    pair[0].id, pair[1].id, track.id
+                )));
+            }
+        }
+
+        for clip in &track.clips {
+            if clip.start_time < 0.0 {
+                return Err(CommandError::validation_error(format!(
+                    "Clip {} starts before the timeline: {}",
+                    clip.id, clip.start_time
+                )));
+            }
+
+            if clip.original_duration > 0.0 && clip.trim_end > clip.original_duration + 0.001 {
+                return Err(CommandError::validation_error(format!(
+                    "Clip {} trims past the end of its source: {} > {}",
+                    clip.id, clip.trim_end, clip.original_duration
+                )));
+            }
+
+            if let Some(effect) = clip
+                .effects
+                .iter()
+                .find(|effect| !SUPPORTED_EXPORT_EFFECTS.contains(&effect.effect_type.as_str()))
+            {
+                return Err(CommandError::validation_error(format!(
+                    "Effect '{}' on clip {} is not supported by export",
+                    effect.effect_type, clip.id
+                )));
+            }
+        }
+    }
+
+    validate_timeline_clips(&timeline_to_export_clips(timeline, Path::new("")))
+}
+
+/// Convert a timeline to export clips, resolving relative clip paths
+pub fn timeline_to_export_clips(timeline: &ProjectTimeline, project_dir: &Path) -> Vec<TimelineExportClip> {
+    timeline
+        .tracks
+        .iter()
+        .flat_map(|track| {
+            track.clips.iter().map(move |clip| TimelineExportClip {
+                file_path: resolve_timeline_clip_path(project_dir, &clip.file_path)
+                    .to_string_lossy()
+                    .to_string(),
+                start_time: clip.start_time,
+                duration: clip.duration,
+                trim_start: clip.trim_start,
+                trim_end: clip.trim_end,
+                track_id: track.id.clone(),
+                original_duration: clip.original_duration,
+            })
+        })
+        .collect()
+}
+
+/// Get the (in, out) range a project export covers
+///
+/// With `use_saved_range` the request's points override the saved ones one by
+/// one; without it only the request's points apply, so a saved range can be
+/// bypassed to export the whole timeline.
+pub fn resolve_project_export_range(
+    request: &ExportProjectRequest,
+    timeline: &ProjectTimeline,
+) -> (Option<f64>, Option<f64>) {
+    if request.use_saved_range {
+        (
+            request.in_point.or(timeline.in_point),
+            request.out_point.or(timeline.out_point),
+        )
+    } else {
+        (request.in_point, request.out_point)
+    }
+}
+
+/// Get an existing project directory
+fn get_existing_project_dir(project_id: &str) -> CommandResult<PathBuf> {
+    let project_dir = get_project_dir(project_id)?;
+    if !project_dir.exists() {
+        return Err(CommandError::validation_error(format!(
+            "Project not found: {}",
+            project_id
+        )));
+    }
+
+    Ok(project_dir)
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Save a project's timeline
+#[tauri::command]
+pub async fn save_project_timeline(request: SaveProjectTimelineRequest) -> CommandResult<ProjectTimeline> {
+    let project_dir = get_existing_project_dir(&request.project_id)?;
+
+    let mut timeline = request.timeline;
+    if timeline.version > TIMELINE_FORMAT_VERSION {
+        return Err(CommandError::validation_error(format!(
+            "Timeline format version {} is newer than supported version {}",
+            timeline.version, TIMELINE_FORMAT_VERSION
+        )));
+    }
+    timeline.version = TIMELINE_FORMAT_VERSION;
+    timeline.updated_at = Some(chrono::Utc::now().to_rfc3339());
+
+    write_project_timeline(&project_dir, &timeline)?;
+    Ok(timeline)
+}
+
+/// Load a project's timeline
+#[tauri::command]
+pub async fn load_project_timeline(project_id: String) -> CommandResult<ProjectTimeline> {
+    let project_dir = get_existing_project_dir(&project_id)?;
+    read_project_timeline(&project_dir)
+}
+
+/// Export a project from its saved timeline
+#[tauri::command]
+pub async fn export_project(
+    app_handle: tauri::AppHandle,
+    request: ExportProjectRequest,
+) -> CommandResult<ExportVideoResponse> {
+    let project_dir = get_existing_project_dir(&request.project_id)?;
+
+    // Load and validate the saved timeline
+    let timeline = read_project_timeline(&project_dir)?;
+    validate_project_timeline(&timeline)?;
+
+    // Every source must still be on disk
+    let timeline_clips = timeline_to_export_clips(&timeline, &project_dir);
+    for clip in &timeline_clips {
+        validate_file_path(&clip.file_path)?;
+    }
+
+    let (in_point, out_point) = resolve_project_export_range(&request, &timeline);
+    let export_request = ExportTimelineRequest {
+        timeline_clips,
+        output_path: request.output_path,
+        filename: request.filename,
+        settings: request.settings,
+        in_point,
+        out_point,
+        markers: timeline.markers,
+        project_id: Some(request.project_id),
+    };
+
+    export_timeline_with_progress(app_handle, export_request).await
+}
+
+// ============================================================================
+// UNIT TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(id: &str, start_time: f64, duration: f64) -> TimelineClip {
+        TimelineClip {
+            id: id.to_string(),
+            file_path: format!("assets/{}.mp4", id),
+            start_time,
+            duration,
+            trim_start: 0.0,
+            trim_end: duration,
+            original_duration: duration,
+            effects: vec![],
+        }
+    }
+
+    fn timeline(clips: Vec<TimelineClip>) -> ProjectTimeline {
+        let mut timeline = create_empty_timeline();
+        timeline.tracks.push(TimelineTrack {
+            id: "track-1".to_string(),
+            name: "Video 1".to_string(),
+            clips,
+        });
+        timeline
+    }
+
+    #[test]
+    fn test_parse_project_timeline_versions() {
+        // Unversioned timelines are upgraded
+        let parsed = parse_project_timeline(r#"{"tracks": []}"#).unwrap();
+        assert_eq!(parsed.version, TIMELINE_FORMAT_VERSION);
+
+        let newer = format!(r#"{{"version": {}, "tracks": []}}"#, TIMELINE_FORMAT_VERSION + 1);
+        assert!(parse_project_timeline(&newer).is_err());
+    }
+
+    #[test]
+    fn test_timeline_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // No timeline saved yet
+        assert!(read_project_timeline(temp_dir.path()).unwrap().tracks.is_empty());
+
+        let saved = timeline(vec![clip("a", 0.0, 5.0)]);
+        write_project_timeline(temp_dir.path(), &saved).unwrap();
+
+        let loaded = read_project_timeline(temp_dir.path()).unwrap();
+        assert_eq!(loaded.tracks.len(), 1);
+        assert_eq!(loaded.tracks[0].clips[0].id, "a");
+        assert!(!temp_dir.path().join("timeline.json.partial").exists());
+    }
+
+    #[test]
+    fn test_validate_project_timeline() {
+        assert!(validate_project_timeline(&timeline(vec![clip("a", 0.0, 5.0), clip("b", 5.0, 3.0)])).is_ok());
+
+        // Empty timeline
+        assert!(validate_project_timeline(&create_empty_timeline()).is_err());
+
+        // Overlapping clips
+        assert!(validate_project_timeline(&timeline(vec![clip("a", 0.0, 5.0), clip("b", 4.0, 3.0)])).is_err());
+
+        // Trim past the source end
+        let mut long_trim = clip("a", 0.0, 5.0);
+        long_trim.trim_end = 6.0;
+        assert!(validate_project_timeline(&timeline(vec![long_trim])).is_err());
+
+        // Unsupported effect
+        let mut with_effect = clip("a", 0.0, 5.0);
+        with_effect.effects.push(TimelineEffect {
+            effect_type: "blur".to_string(),
+            params: serde_json::Value::Null,
+        });
+        assert!(validate_project_timeline(&timeline(vec![with_effect])).is_err());
+    }
+
+    #[test]
+    fn test_timeline_to_export_clips() {
+        let mut absolute = clip("b", 5.0, 3.0);
+        absolute.file_path = "/media/b.mp4".to_string();
+
+        let clips = timeline_to_export_clips(
+            &timeline(vec![clip("a", 0.0, 5.0), absolute]),
+            Path::new("/projects/p1"),
+        );
+
+        assert_eq!(clips[0].file_path, "/projects/p1/assets/a.mp4");
+        assert_eq!(clips[1].file_path, "/media/b.mp4");
+        assert_eq!(clips[1].track_id, "track-1");
+    }
+
+    #[test]
+    fn test_resolve_project_export_range() {
+        let mut saved = timeline(vec![clip("a", 0.0, 30.0)]);
+        saved.in_point = Some(5.0);
+        saved.out_point = Some(20.0);
+
+        // Older frontends omit the flag and get the saved range
+        let mut request: ExportProjectRequest = serde_json::from_str(
+            r#"{"project_id":"p1","output_path":"/out","filename":"a.mp4","settings":{"resolution":"source","quality":"high","format":"mp4","codec":"h264"}}"#,
+        )
+        .unwrap();
+        assert!(request.use_saved_range);
+        assert_eq!(resolve_project_export_range(&request, &saved), (Some(5.0), Some(20.0)));
+
+        // Request points override the saved ones individually
+        request.out_point = Some(25.0);
+        assert_eq!(resolve_project_export_range(&request, &saved), (Some(5.0), Some(25.0)));
+
+        // Without the saved range the whole timeline can be exported
+        request.use_saved_range = false;
+        request.out_point = None;
+        assert_eq!(resolve_project_export_range(&request, &saved), (None, None));
+    }
+}
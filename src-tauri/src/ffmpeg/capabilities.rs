@@ -0,0 +1,318 @@
+// ============================================================================
+// FFMPEG CAPABILITIES MODULE
+// ============================================================================
+// This module discovers what the FFmpeg build in use supports.
+// It runs `-encoders`, `-decoders`, `-filters`, `-formats` and `-hwaccels`
+// once, parses the lists and caches the result for the rest of the session,
+// so export settings can be checked before an encode fails halfway.
+
+use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Components compiled into the FFmpeg build
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FfmpegCapabilities {
+    pub encoders: BTreeSet<String>,
+    pub decoders: BTreeSet<String>,
+    pub filters: BTreeSet<String>,
+    pub muxers: BTreeSet<String>,   // Output formats
+    pub demuxers: BTreeSet<String>, // Input formats
+    pub hwaccels: BTreeSet<String>, // Hardware acceleration methods (e.g. "videotoolbox")
+}
+
+/// Response from capability discovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FfmpegCapabilitiesResponse {
+    pub success: bool,
+    pub capabilities: Option<FfmpegCapabilities>,
+    pub error_message: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    /// Capabilities of the FFmpeg binary, probed on first use
+    static ref FFMPEG_CAPABILITIES: Mutex<Option<FfmpegCapabilities>> = Mutex::new(None);
+}
+
+// ============================================================================
+// CAPABILITY DISCOVERY
+// ============================================================================
+
+/// Get the capabilities of the FFmpeg build, probing it on first use
+pub async fn get_ffmpeg_capabilities(app_handle: &tauri::AppHandle) -> CommandResult<FfmpegCapabilities> {
+    if let Some(capabilities) = get_cached_ffmpeg_capabilities() {
+        return Ok(capabilities);
+    }
+
+    let capabilities = probe_ffmpeg_capabilities(&ProcessRunner::new(app_handle)).await?;
+    if let Ok(mut cached) = FFMPEG_CAPABILITIES.lock() {
+        *cached = Some(capabilities.clone());
+    }
+    Ok(capabilities)
+}
+
+/// Get the capabilities if they have already been probed
+pub fn get_cached_ffmpeg_capabilities() -> Option<FfmpegCapabilities> {
+    FFMPEG_CAPABILITIES.lock().ok().and_then(|cached| cached.clone())
+}
+
+/// Run every listing option through `runner` and parse the results
+pub async fn probe_ffmpeg_capabilities(runner: &dyn FfmpegRunner) -> CommandResult<FfmpegCapabilities> {
+    let encoders = run_listing(runner, "-encoders").await?;
+    let decoders = run_listing(runner, "-decoders").await?;
+    let filters = run_listing(runner, "-filters").await?;
+    let formats = run_listing(runner, "-formats").await?;
+    let hwaccels = run_listing(runner, "-hwaccels").await?;
+
+    let (demuxers, muxers) = parse_format_list(&formats);
+    Ok(FfmpegCapabilities {
+        encoders: parse_codec_list(&encoders),
+        decoders: parse_codec_list(&decoders),
+        filters: parse_filter_list(&filters),
+        muxers,
+        demuxers,
+        hwaccels: parse_hwaccel_list(&hwaccels),
+    })
+}
+
+/// Run FFmpeg with a single listing option and return what it printed
+async fn run_listing(runner: &dyn FfmpegRunner, option: &str) -> CommandResult<String> {
+    let output = runner
+        .run(FfmpegInvocation::ffmpeg(["-hide_banner", option]))
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg {} failed: {}",
+            option, stderr
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// ============================================================================
+// PARSING FUNCTIONS
+// ============================================================================
+
+/// Lines after the dashed separator that ends a listing's legend
+fn listing_entries(output: &str) -> impl Iterator<Item = &str> {
+    output
+        .lines()
+        .skip_while(|line| {
+            let line = line.trim();
+            line.is_empty() || !line.chars().all(|c| c == '-')
+        })
+        .skip(1)
+}
+
+/// Parse `-encoders` or `-decoders` output into codec implementation names
+///
+/// Entries look like ` V....D libx264   libx264 H.264 / AVC ...`.
+pub fn parse_codec_list(output: &str) -> BTreeSet<String> {
+    listing_entries(output)
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Parse `-filters` output into filter names
+///
+/// Entries look like ` TSC scale   V->V   Scale the input video ...`; the
+/// legend has no separator, so only lines with an input->output column count.
+pub fn parse_filter_list(output: &str) -> BTreeSet<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.as_slice() {
+                [_, name, io, ..] if io.contains("->") => Some(name.to_string()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Parse `-formats` output into (demuxers, muxers)
+///
+/// Entries start with D (demuxing), E (muxing) and d (device) flags, followed
+/// by a comma-separated list of names, e.g. ` DE mov,mp4,m4a QuickTime / MOV`.
+pub fn parse_format_list(output: &str) -> (BTreeSet<String>, BTreeSet<String>) {
+    let mut demuxers = BTreeSet::new();
+    let mut muxers = BTreeSet::new();
+
+    for line in listing_entries(output) {
+        let mut tokens = line.split_whitespace().peekable();
+        let mut flags = String::new();
+        while let Some(token) = tokens.peek() {
+            if !token.chars().all(|c| matches!(c, 'D' | 'E' | 'd' | '.')) {
+                break;
+            }
+            flags.push_str(token);
+            tokens.next();
+        }
+
+        let Some(names) = tokens.next() else {
+            continue;
+        };
+        for name in names.split(',') {
+            if flags.contains('D') {
+                demuxers.insert(name.to_string());
+            }
+            if flags.contains('E') {
+                muxers.insert(name.to_string());
+            }
+        }
+    }
+
+    (demuxers, muxers)
+}
+
+/// Parse `-hwaccels` output into method names
+pub fn parse_hwaccel_list(output: &str) -> BTreeSet<String> {
+    output
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.ends_with(':'))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+// ============================================================================
+// QUERY FUNCTIONS
+// ============================================================================
+
+impl FfmpegCapabilities {
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoders.contains(name)
+    }
+
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.contains(name)
+    }
+
+    pub fn has_muxer(&self, name: &str) -> bool {
+        self.muxers.contains(name)
+    }
+
+    pub fn has_demuxer(&self, name: &str) -> bool {
+        self.demuxers.contains(name)
+    }
+
+    /// First of `candidates` that this build can encode with
+    pub fn first_encoder<'a>(&self, candidates: &[&'a str]) -> Option<&'a str> {
+        candidates.iter().copied().find(|name| self.has_encoder(name))
+    }
+}
+
+// ============================================================================
+// UNIT TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODERS: &str = "Encoders:\n \
+        V..... = Video\n \
+        A..... = Audio\n \
+        .....D = Supports direct rendering method 1\n \
+        ------\n \
+        V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC / MPEG-4 part 10 (codec h264)\n \
+        V....D h264_videotoolbox    VideoToolbox H.264 Encoder (codec h264)\n \
+        A....D aac                  AAC (Advanced Audio Coding)\n";
+
+    const FILTERS: &str = "Filters:\n  \
+        T.. = Timeline support\n  \
+        A = Audio input/output\n  \
+        | = Source or sink filter\n \
+        ... concat            N->N       Concatenate audio and video streams.\n \
+        TSC scale             V->V       Scale the input video size and/or convert the image format.\n \
+        ... color             |->V       Provide an uniformly colored input.\n";
+
+    const FORMATS: &str = "File formats:\n \
+        D. = Demuxing supported\n \
+        .E = Muxing supported\n \
+        --\n \
+        D  concat          Virtual concatenation script\n \
+        DE mov,mp4,m4a,3gp,3g2,mj2 QuickTime / MOV\n  \
+        E mp4             MP4 (MPEG-4 Part 14)\n \
+        D d x11grab       X11 screen capture\n";
+
+    #[test]
+    fn test_parse_codec_list() {
+        let encoders = parse_codec_list(ENCODERS);
+        assert_eq!(encoders.len(), 3);
+        assert!(encoders.contains("libx264"));
+        assert!(encoders.contains("h264_videotoolbox"));
+        assert!(!encoders.contains("Video"));
+    }
+
+    #[test]
+    fn test_parse_filter_list() {
+        let filters = parse_filter_list(FILTERS);
+        assert_eq!(filters.into_iter().collect::<Vec<_>>(), vec!["color", "concat", "scale"]);
+    }
+
+    #[test]
+    fn test_parse_format_list() {
+        let (demuxers, muxers) = parse_format_list(FORMATS);
+        assert!(demuxers.contains("concat"));
+        assert!(demuxers.contains("mov"));
+        assert!(demuxers.contains("x11grab"));
+        assert!(!muxers.contains("concat"));
+        assert!(muxers.contains("mp4"));
+        assert!(muxers.contains("3gp"));
+    }
+
+    #[test]
+    fn test_parse_hwaccel_list() {
+        let hwaccels = parse_hwaccel_list("Hardware acceleration methods:\nvideotoolbox\nvaapi\n\n");
+        assert_eq!(hwaccels.into_iter().collect::<Vec<_>>(), vec!["vaapi", "videotoolbox"]);
+    }
+
+    #[test]
+    fn test_probe_ffmpeg_capabilities_with_runner() {
+        let runner = crate::ffmpeg::runner::MockFfmpegRunner::new()
+            .with_output(0, ENCODERS, "")
+            .with_output(0, ENCODERS, "")
+            .with_output(0, FILTERS, "")
+            .with_output(0, FORMATS, "")
+            .with_output(0, "Hardware acceleration methods:\nvideotoolbox\n", "");
+
+        let capabilities = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(probe_ffmpeg_capabilities(&runner))
+            .unwrap();
+
+        assert!(capabilities.has_encoder("libx264"));
+        assert!(capabilities.decoders.contains("aac"));
+        assert!(capabilities.has_filter("scale"));
+        assert!(capabilities.has_muxer("mp4"));
+        assert!(capabilities.has_demuxer("concat"));
+        assert!(capabilities.hwaccels.contains("videotoolbox"));
+        assert_eq!(capabilities.first_encoder(&["libopenh264", "h264_videotoolbox"]), Some("h264_videotoolbox"));
+
+        let options: Vec<String> = runner.invocations().into_iter().map(|(_, args)| args[1].clone()).collect();
+        assert_eq!(options, vec!["-encoders", "-decoders", "-filters", "-formats", "-hwaccels"]);
+    }
+
+    #[test]
+    fn test_probe_ffmpeg_capabilities_reports_failure() {
+        let runner = crate::ffmpeg::runner::MockFfmpegRunner::new()
+            .with_output(1, "", "Unrecognized option 'encoders'");
+
+        let error = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(probe_ffmpeg_capabilities(&runner))
+            .unwrap_err();
+        assert!(error.message.contains("-encoders"));
+    }
+}
@@ -0,0 +1,156 @@
+// ============================================================================
+// FFMPEG CONFORM MODULE
+// ============================================================================
+// This module conforms variable frame rate sources (typically screen
+// recordings) to a constant frame rate so trims land on exact frames and
+// audio stays in sync on export.
+
+use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::export::{get_export_encoders, ExportEncoders};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Conform video bitrate (kbps) for encoders without CRF support
+const CONFORM_FALLBACK_VIDEO_BITRATE_KBPS: u32 = 20000;
+
+// ============================================================================
+// CONFORM FUNCTIONS
+// ============================================================================
+
+/// Get the output path for a conformed copy of a source
+///
+/// Conformed files are always H.264/AAC in MP4, whatever the source container.
+pub fn get_conformed_path(source_path: &Path) -> PathBuf {
+    source_path.with_extension("mp4")
+}
+
+/// Build FFmpeg arguments to conform a source to a constant frame rate
+///
+/// The fps filter duplicates or drops frames onto a fixed grid, and audio is
+/// resampled against its timestamps so it stays aligned with the new video.
+pub fn build_conform_args(
+    input_path: &str,
+    output_path: &str,
+    framerate: u32,
+    encoders: &ExportEncoders,
+) -> Vec<String> {
+    let mut args = vec![
+        "-i".to_string(),
+        input_path.to_string(),
+        "-vf".to_string(),
+        format!("fps={}", framerate),
+        "-r".to_string(),
+        framerate.to_string(),
+        "-c:v".to_string(),
+        encoders.video.clone(),
+    ];
+    if encoders.video == "libx264" {
+        args.extend(vec![
+            "-preset".to_string(),
+            "veryfast".to_string(),
+            "-crf".to_string(),
+            "18".to_string(), // Near-lossless, this replaces the original
+        ]);
+    } else {
+        args.extend(vec!["-b:v".to_string(), format!("{}k", CONFORM_FALLBACK_VIDEO_BITRATE_KBPS)]);
+    }
+    args.extend(vec![
+        "-pix_fmt".to_string(),
+        "yuv420p".to_string(),
+        "-af".to_string(),
+        "aresample=async=1".to_string(),
+        "-c:a".to_string(),
+        encoders.audio.clone(),
+        "-b:a".to_string(),
+        "192k".to_string(),
+        "-movflags".to_string(),
+        "+faststart".to_string(),
+        "-y".to_string(), // Overwrite output file
+        output_path.to_string(),
+    ]);
+    args
+}
+
+/// Conform a source to a constant frame rate
+///
+/// Renders into a partial file and renames it, so the output path only ever
+/// holds a complete file.
+pub async fn conform_to_constant_frame_rate(
+    app_handle: &tauri::AppHandle,
+    input_path: &str,
+    output_path: &Path,
+    framerate: u32,
+) -> CommandResult<()> {
+    if framerate == 0 {
+        return Err(CommandError::validation_error(
+            "Conform framerate must be greater than 0".to_string(),
+        ));
+    }
+
+    let partial_path = output_path.with_extension("conform.partial.mp4");
+    let encoders = get_export_encoders(app_handle).await;
+    let args = build_conform_args(input_path, &partial_path.to_string_lossy(), framerate, &encoders);
+
+    // Execute FFmpeg
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&partial_path);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg conform failed: {}",
+            stderr
+        )));
+    }
+
+    std::fs::rename(&partial_path, output_path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial_path);
+        CommandError::io_error(format!("Failed to move conformed file into place: {}", e))
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_conformed_path() {
+        assert_eq!(
+            get_conformed_path(Path::new("/p/assets/screen_1.mov")),
+            PathBuf::from("/p/assets/screen_1.mp4")
+        );
+        assert_eq!(
+            get_conformed_path(Path::new("/p/assets/clip.mp4")),
+            PathBuf::from("/p/assets/clip.mp4")
+        );
+    }
+
+    #[test]
+    fn test_build_conform_args() {
+        let args = build_conform_args("in.mov", "out.partial.mp4", 30, &ExportEncoders::default());
+
+        assert!(args.windows(2).any(|w| w[0] == "-vf" && w[1] == "fps=30"));
+        assert!(args.windows(2).any(|w| w[0] == "-r" && w[1] == "30"));
+        assert!(args.windows(2).any(|w| w[0] == "-crf" && w[1] == "18"));
+        assert!(args.windows(2).any(|w| w[0] == "-af" && w[1] == "aresample=async=1"));
+        assert_eq!(args.last().unwrap(), "out.partial.mp4");
+
+        let encoders = ExportEncoders {
+            video: "h264_videotoolbox".to_string(),
+            audio: "aac_at".to_string(),
+        };
+        let args = build_conform_args("in.mov", "out.partial.mp4", 30, &encoders);
+        assert!(args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "h264_videotoolbox"));
+        assert!(args.windows(2).any(|w| w[0] == "-c:a" && w[1] == "aac_at"));
+        assert!(args.windows(2).any(|w| w[0] == "-b:v" && w[1] == "20000k"));
+        assert!(!args.contains(&"-crf".to_string()));
+    }
+}
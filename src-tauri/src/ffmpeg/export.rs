@@ -5,8 +5,11 @@
 // It supports concatenation and basic export operations for the MVP.
 
 use crate::commands::{get_temp_dir, CommandError, CommandResult};
+use crate::ffmpeg::capabilities::{get_ffmpeg_capabilities, FfmpegCapabilities};
+use crate::ffmpeg::probe::{MediaDetails, VideoMetadata};
+use crate::ffmpeg::render_cache::{build_segment_cache_key, get_source_identity, RenderCache, RenderCachePins, SourceIdentity, NO_TRANSFORM};
+use crate::ffmpeg::runner::{CancellationToken, FfmpegInvocation, FfmpegRunner, ProcessRunner};
 use serde::{Deserialize, Serialize};
-use tauri_plugin_shell::ShellExt;
 use tauri::Emitter;
 use regex::Regex;
 
@@ -20,6 +23,40 @@ pub struct ExportVideoRequest {
     pub clips: Vec<ExportClip>,
     pub output_path: String,
     pub settings: ExportSettings,
+    #[serde(default)]
+    pub chapters: Vec<ExportChapter>, // Timeline markers written as chapters
+    #[serde(default)]
+    pub tags: ExportMetadataTags, // Container metadata tags
+    #[serde(default)]
+    pub project_id: Option<String>, // Project whose render cache trimmed segments are reused from
+}
+
+/// Validated inputs of one export run
+#[derive(Clone, Copy)]
+struct ExportJob<'a> {
+    clips: &'a [ExportClip],
+    output_path: &'a str,
+    settings: &'a ExportSettings,
+    chapters: &'a [ExportChapter],
+    tags: &'a ExportMetadataTags,
+    render_cache: Option<&'a RenderCache>, // Cache trimmed segments are reused from
+}
+
+/// Chapter marker on the export timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportChapter {
+    pub start_time: f64, // Start time in timeline seconds
+    pub title: String,
+}
+
+/// Container metadata tags written into the export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportMetadataTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub comment: Option<String>,
+    pub creation_time: Option<String>, // RFC 3339 timestamp
+    pub project_id: Option<String>,
 }
 
 /// Individual clip for export
@@ -42,6 +79,19 @@ pub struct ExportSettings {
     pub quality: String,    // "high", "medium", "low"
     pub format: String,     // "mp4"
     pub codec: String,      // "h264"
+    #[serde(default = "default_rate_control")]
+    pub rate_control: String, // "quality", "crf", "cbr", "abr", "target_size"
+    #[serde(default)]
+    pub crf: Option<u8>, // Custom CRF (0-51) for "crf"
+    #[serde(default)]
+    pub video_bitrate_kbps: Option<u32>, // Video bitrate for "cbr" and "abr"
+    #[serde(default)]
+    pub target_size_mb: Option<f64>, // Output size limit for "target_size" (two-pass)
+}
+
+/// Default rate control mode: CRF derived from the quality preset
+fn default_rate_control() -> String {
+    "quality".to_string()
 }
 
 /// Export progress information
@@ -64,6 +114,61 @@ pub struct ExportVideoResponse {
     pub success: bool,
     pub output_path: Option<String>,
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub verification: Option<ExportVerificationReport>, // Probe of the finished file
+    #[serde(default)]
+    pub chapter_file_path: Option<String>, // YouTube chapter list written next to the export
+}
+
+/// Post-export verification of the rendered file against the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportVerificationReport {
+    pub passed: bool, // True when there are no mismatches
+    pub expected_duration: f64,
+    pub actual_duration: f64,
+    pub width: u32,
+    pub height: u32,
+    pub fps: f64,
+    pub has_audio: bool,
+    pub mismatches: Vec<ExportVerificationMismatch>,
+    pub warnings: Vec<String>,
+}
+
+/// A property of the exported file that differs from what was requested
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportVerificationMismatch {
+    pub field: String, // "duration", "resolution", "fps", "audio"
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Final encoding statistics reported by FFmpeg
+#[derive(Debug, Clone, PartialEq)]
+pub struct FfmpegFinalStats {
+    pub frames: u64,
+    pub dropped_frames: u64,
+    pub duplicated_frames: u64,
+    pub time: Option<f64>, // Output timestamp of the last packet in seconds
+}
+
+/// Encoders used for an export
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportEncoders {
+    pub video: String, // e.g. "libx264", "h264_videotoolbox"
+    pub audio: String, // e.g. "aac"
+}
+
+/// Export settings adjusted to what the FFmpeg build supports
+#[derive(Debug, Clone)]
+pub struct ExportEncoding {
+    pub settings: ExportSettings,
+    pub encoders: ExportEncoders,
+    pub warnings: Vec<String>, // Options that were downgraded
+}
+
+lazy_static::lazy_static! {
+    /// Cancellation token of the export currently encoding, if any
+    static ref ACTIVE_EXPORT_CANCELLATION: std::sync::Mutex<Option<CancellationToken>> = std::sync::Mutex::new(None);
 }
 
 // ============================================================================
@@ -76,9 +181,12 @@ pub async fn export_video(
     app_handle: tauri::AppHandle,
     request: ExportVideoRequest,
 ) -> CommandResult<ExportVideoResponse> {
-    let clips = request.clips;
+    let clips = use_original_sources(request.clips);
     let output_path = request.output_path;
     let settings = request.settings;
+    let chapters = request.chapters;
+    let tags = request.tags;
+    let render_cache = open_export_render_cache(request.project_id.as_deref());
 
     // Validate input
     if clips.is_empty() {
@@ -105,16 +213,33 @@ pub async fn export_video(
     }
 
     // Export video
-    match export_video_internal(&app_handle, &clips, &output_path, &settings).await {
-        Ok(_) => Ok(ExportVideoResponse {
-            success: true,
-            output_path: Some(output_path),
-            error_message: None,
-        }),
+    let job = ExportJob {
+        clips: &clips,
+        output_path: &output_path,
+        settings: &settings,
+        chapters: &chapters,
+        tags: &tags,
+        render_cache: render_cache.as_ref(),
+    };
+    match export_video_internal(&app_handle, job).await {
+        Ok(mut report) => {
+            // Write the chapter list next to the export
+            let chapter_file_path = write_chapter_list_for_export(&output_path, &clips, &chapters, &mut report);
+
+            Ok(ExportVideoResponse {
+                success: true,
+                output_path: Some(output_path),
+                error_message: None,
+                verification: Some(report),
+                chapter_file_path,
+            })
+        },
         Err(error) => Ok(ExportVideoResponse {
             success: false,
             output_path: None,
             error_message: Some(error.message),
+            verification: None,
+            chapter_file_path: None,
         }),
     }
 }
@@ -125,9 +250,12 @@ pub async fn export_video_with_progress(
     app_handle: tauri::AppHandle,
     request: ExportVideoRequest,
 ) -> CommandResult<ExportVideoResponse> {
-    let clips = request.clips;
+    let clips = use_original_sources(request.clips);
     let output_path = request.output_path;
     let settings = request.settings;
+    let chapters = request.chapters;
+    let tags = request.tags;
+    let render_cache = open_export_render_cache(request.project_id.as_deref());
 
     // Validate input
     if clips.is_empty() {
@@ -162,8 +290,23 @@ pub async fn export_video_with_progress(
         .map_err(|e| CommandError::ffmpeg_error(format!("Failed to emit start progress: {}", e)))?;
 
     // Export video with real-time progress tracking
-    match export_video_with_progress_internal(&app_handle, &clips, &output_path, &settings, total_duration).await {
-        Ok(_) => {
+    let cancellation = begin_active_export();
+    let job = ExportJob {
+        clips: &clips,
+        output_path: &output_path,
+        settings: &settings,
+        chapters: &chapters,
+        tags: &tags,
+        render_cache: render_cache.as_ref(),
+    };
+    let result = export_video_with_progress_internal(&app_handle, job, total_duration, &cancellation).await;
+    end_active_export();
+
+    match result {
+        Ok(mut report) => {
+            // Write the chapter list next to the export
+            let chapter_file_path = write_chapter_list_for_export(&output_path, &clips, &chapters, &mut report);
+
             // Emit completion progress
             let complete_progress = create_export_complete_progress();
             app_handle.emit("export-progress", &complete_progress)
@@ -173,6 +316,8 @@ pub async fn export_video_with_progress(
                 success: true,
                 output_path: Some(output_path),
                 error_message: None,
+                verification: Some(report),
+                chapter_file_path,
             })
         },
         Err(error) => {
@@ -185,6 +330,8 @@ pub async fn export_video_with_progress(
                 success: false,
                 output_path: None,
                 error_message: Some(error.message),
+                verification: None,
+                chapter_file_path: None,
             })
         },
     }
@@ -198,56 +345,96 @@ pub async fn export_video_with_progress(
 /// Internal function to export video using FFmpeg
 async fn export_video_internal(
     app_handle: &tauri::AppHandle,
-    clips: &[ExportClip],
-    output_path: &str,
-    settings: &ExportSettings,
-) -> CommandResult<()> {
+    job: ExportJob<'_>,
+) -> CommandResult<ExportVerificationReport> {
+    let ExportJob { clips, output_path, settings, chapters, tags, render_cache } = job;
+
+    // Resolve encoders and rate control before any temporary files are created;
+    // bitrates are spread over the concatenated output, not the timeline span
+    let duration = get_concatenated_duration(clips);
+    let capabilities = get_ffmpeg_capabilities(app_handle).await.ok();
+    let encoding = resolve_export_encoding(settings, duration, capabilities.as_ref())?;
+    let settings = &encoding.settings;
+    let rate_control_args = get_rate_control_args(settings, duration)?;
+
     // Create temporary file tracker
     let mut tracker = TempFileTracker::new();
 
-    // Trim clips that need trimming with tracking
-    let trimmed_clips = trim_clips_for_export_with_tracking(app_handle, clips, &mut tracker).await?;
+    // Trim clips that need trimming, reusing cached segments where possible
+    let trimmed_clips = trim_clips_for_export_with_cache(app_handle, clips, &encoding.encoders, &mut tracker, render_cache).await?;
 
     // Generate concat file using trimmed clips and track it
     let concat_file_path = generate_concat_file_with_tracking(&trimmed_clips, &mut tracker).await?;
 
-    // Get FFmpeg sidecar
-    let sidecar = app_handle.shell().sidecar("ffmpeg").map_err(|e| {
-        CommandError::ffmpeg_error(format!("Failed to create FFmpeg sidecar: {}", e))
-    })?;
+    // Render into a staging file next to the destination so a failed run never
+    // clobbers a previous good export
+    let staging_path = create_staging_output_path(output_path)?;
+    tracker.add_file(staging_path.clone());
+
+    // Write chapters and container tags for FFmpeg to mux into the output
+    let output_chapters = map_chapters_to_output_time(chapters, clips);
+    let metadata_file_path = match write_ffmetadata_file_with_tracking(&output_chapters, tags, duration, &mut tracker).await {
+        Ok(path) => path,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+
+    // Concatenate without re-encoding when every clip already matches
+    let mut stream_copy_fallback = None;
+    let stream_copy = StreamCopyRequest {
+        prepared_clips: &trimmed_clips,
+        concat_file_path: &concat_file_path,
+        metadata_file_path: metadata_file_path.as_deref(),
+        staging_path: &staging_path,
+        output_path,
+        clips,
+        settings,
+        cancellation: None,
+    };
+    match try_stream_copy_export(app_handle, &stream_copy).await {
+        StreamCopyOutcome::Completed { metadata, stderr } => {
+            tracker.cleanup_all().await?;
+            return Ok(verify_exported_video(app_handle, clips, settings, &metadata, &stderr).await);
+        }
+        StreamCopyOutcome::Fallback(reason) => stream_copy_fallback = Some(reason),
+        StreamCopyOutcome::Skipped => {}
+        StreamCopyOutcome::Cancelled(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    }
+
+    // Run the analysis pass first when encoding to a target size
+    let passlog_prefix = match run_first_pass_if_needed(app_handle, &concat_file_path, settings, duration, &mut tracker).await {
+        Ok(prefix) => prefix,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
 
     // Build FFmpeg command
-    let mut args = vec![
-        "-f",
-        "concat",
-        "-safe",
-        "0",
-        "-i",
+    let args = build_export_encode_args(
         &concat_file_path,
-        "-c:v",
-        "libx264", // Re-encode video to avoid stream copy issues
-        "-crf",
-        "18",      // High quality
-        "-preset",
-        "medium",
-        "-c:a",
-        "aac",     // Re-encode audio
-        "-b:a",
-        "192k",
-        "-y",      // Overwrite output file
-        output_path,
-    ];
+        metadata_file_path.as_deref(),
+        &staging_path,
+        &encoding.encoders,
+        &rate_control_args,
+        passlog_prefix.as_deref(),
+    );
 
     // Execute FFmpeg
-    let output = sidecar
-        .args(&args)
-        .output()
-        .await
-        .map_err(|e| CommandError::ffmpeg_error(format!("Failed to execute ffmpeg: {}", e)))?;
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    // Get FFmpeg output for error handling and encoding statistics
+    let stderr = String::from_utf8_lossy(&output.stderr);
 
     // Check if command succeeded
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
         // Clean up temporary files even on failure
         let _ = tracker.cleanup_all().await;
         return Err(CommandError::ffmpeg_error(format!(
@@ -256,10 +443,25 @@ async fn export_video_internal(
         )));
     }
 
+    // Verify the staged render and move it into place
+    let output_metadata = match finalize_staged_export(app_handle, &staging_path, output_path, clips).await {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+
     // Clean up all temporary files
     tracker.cleanup_all().await?;
 
-    Ok(())
+    // Compare the finished file with what was requested
+    let mut report = verify_exported_video(app_handle, clips, settings, &output_metadata, &stderr).await;
+    if let Some(reason) = stream_copy_fallback {
+        report.warnings.push(format!("Stream copy not possible, timeline was re-encoded: {}", reason));
+    }
+    report.warnings.extend(encoding.warnings.iter().cloned());
+    Ok(report)
 }
 
 /// Generate FFmpeg concat file
@@ -286,7 +488,7 @@ async fn generate_concat_file(clips: &[ExportClip]) -> CommandResult<String> {
 }
 
 /// Generate FFmpeg concat file with temporary file tracking
-async fn generate_concat_file_with_tracking(
+pub async fn generate_concat_file_with_tracking(
     clips: &[ExportClip],
     tracker: &mut TempFileTracker,
 ) -> CommandResult<String> {
@@ -399,6 +601,14 @@ pub fn get_timeline_duration(clips: &[ExportClip]) -> f64 {
     last_clip.start_time + last_clip.duration
 }
 
+/// Get the length of the concatenated output (sum of clip durations)
+///
+/// Clips are joined back to back in track order, so timeline gaps are dropped
+/// and clips on additional tracks are appended rather than overlapped.
+pub fn get_concatenated_duration(clips: &[ExportClip]) -> f64 {
+    clips.iter().map(|clip| clip.duration).sum()
+}
+
 /// Validate track ordering for clips
 pub fn validate_track_ordering(clips: &[ExportClip]) -> CommandResult<()> {
     // Group clips by track without sorting
@@ -495,61 +705,111 @@ pub fn validate_timeline_clips_for_export(clips: &[ExportClip]) -> CommandResult
 /// Export video with progress tracking (real implementation)
 async fn export_video_with_progress_internal(
     app_handle: &tauri::AppHandle,
-    clips: &[ExportClip],
-    output_path: &str,
-    settings: &ExportSettings,
+    job: ExportJob<'_>,
     total_duration: f64,
-) -> CommandResult<()> {
+    cancellation: &CancellationToken,
+) -> CommandResult<ExportVerificationReport> {
+    let ExportJob { clips, output_path, settings, chapters, tags, render_cache } = job;
+
+    // Resolve encoders and rate control before any temporary files are created;
+    // bitrates are spread over the concatenated output, not the timeline span
+    let duration = get_concatenated_duration(clips);
+    let capabilities = get_ffmpeg_capabilities(app_handle).await.ok();
+    let encoding = resolve_export_encoding(settings, duration, capabilities.as_ref())?;
+    let settings = &encoding.settings;
+    let rate_control_args = get_rate_control_args(settings, duration)?;
+
     // Create temporary file tracker
     let mut tracker = TempFileTracker::new();
 
-    // Trim clips that need trimming with tracking
-    let trimmed_clips = trim_clips_for_export_with_tracking(app_handle, clips, &mut tracker).await?;
+    // Trim clips that need trimming, reusing cached segments where possible
+    let trimmed_clips = trim_clips_for_export_with_cache(app_handle, clips, &encoding.encoders, &mut tracker, render_cache).await?;
 
     // Generate concat file using trimmed clips and track it
     let concat_file_path = generate_concat_file_with_tracking(&trimmed_clips, &mut tracker).await?;
 
-    // Get FFmpeg sidecar
-    let sidecar = app_handle.shell().sidecar("ffmpeg").map_err(|e| {
-        CommandError::ffmpeg_error(format!("Failed to create FFmpeg sidecar: {}", e))
-    })?;
+    // Render into a staging file next to the destination so a failed run never
+    // clobbers a previous good export
+    let staging_path = create_staging_output_path(output_path)?;
+    tracker.add_file(staging_path.clone());
 
-    // Build FFmpeg command
-    let args = vec![
-        "-f",
-        "concat",
-        "-safe",
-        "0",
-        "-i",
-        &concat_file_path,
-        "-c:v",
-        "libx264", // Re-encode video to avoid stream copy issues
-        "-crf",
-        "18",      // High quality
-        "-preset",
-        "medium",
-        "-c:a",
-        "aac",     // Re-encode audio
-        "-b:a",
-        "192k",
-        "-y",      // Overwrite output file
+    // Write chapters and container tags for FFmpeg to mux into the output
+    let output_chapters = map_chapters_to_output_time(chapters, clips);
+    let metadata_file_path = match write_ffmetadata_file_with_tracking(&output_chapters, tags, duration, &mut tracker).await {
+        Ok(path) => path,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+
+    // Concatenate without re-encoding when every clip already matches
+    let mut stream_copy_fallback = None;
+    let stream_copy = StreamCopyRequest {
+        prepared_clips: &trimmed_clips,
+        concat_file_path: &concat_file_path,
+        metadata_file_path: metadata_file_path.as_deref(),
+        staging_path: &staging_path,
         output_path,
-    ];
+        clips,
+        settings,
+        cancellation: Some(cancellation),
+    };
+    match try_stream_copy_export(app_handle, &stream_copy).await {
+        StreamCopyOutcome::Completed { metadata, stderr } => {
+            // The caller emits completion once the chapter list is written
+            tracker.cleanup_all().await?;
+            return Ok(verify_exported_video(app_handle, clips, settings, &metadata, &stderr).await);
+        }
+        StreamCopyOutcome::Fallback(reason) => stream_copy_fallback = Some(reason),
+        StreamCopyOutcome::Skipped => {}
+        StreamCopyOutcome::Cancelled(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    }
 
-    // Log the command for debugging
+    // Run the analysis pass first when encoding to a target size
+    let passlog_prefix = match run_first_pass_if_needed(app_handle, &concat_file_path, settings, duration, &mut tracker).await {
+        Ok(prefix) => prefix,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+
+    // Build FFmpeg command
+    let args = build_export_encode_args(
+        &concat_file_path,
+        metadata_file_path.as_deref(),
+        &staging_path,
+        &encoding.encoders,
+        &rate_control_args,
+        passlog_prefix.as_deref(),
+    );
 
-    // For MVP, we'll use a simple approach with simulated progress
     // Emit start progress
     let start_progress = create_export_start_progress();
     app_handle.emit("export-progress", &start_progress)
         .map_err(|e| CommandError::ffmpeg_error(format!("Failed to emit start progress: {}", e)))?;
 
-    // Execute FFmpeg and wait for completion
-    let output = sidecar
-        .args(&args)
-        .output()
-        .await
-        .map_err(|e| CommandError::ffmpeg_error(format!("Failed to execute ffmpeg: {}", e)))?;
+    // Execute FFmpeg, forwarding its progress lines as they are printed
+    let progress_handle = app_handle.clone();
+    let invocation = FfmpegInvocation::ffmpeg(&args)
+        .with_cancellation(cancellation.clone())
+        .on_stderr_line(move |line| {
+            if let Some(progress) = parse_ffmpeg_progress(line, total_duration) {
+                let _ = progress_handle.emit("export-progress", &progress);
+            }
+        });
+    let output = match ProcessRunner::new(app_handle).run(invocation).await {
+        Ok(output) => output,
+        Err(error) => {
+            // Cancelled or failed to start; don't leave the partial render behind
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
 
     // Get FFmpeg output for error handling
     let stderr = String::from_utf8_lossy(&output.stderr);
@@ -568,6 +828,15 @@ async fn export_video_with_progress_internal(
         )));
     }
 
+    // Verify the staged render and move it into place
+    let output_metadata = match finalize_staged_export(app_handle, &staging_path, output_path, clips).await {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+
     // Emit completion progress
     let complete_progress = create_export_complete_progress();
     app_handle.emit("export-progress", &complete_progress)
@@ -576,9 +845,403 @@ async fn export_video_with_progress_internal(
     // Clean up all temporary files
     tracker.cleanup_all().await?;
 
+    // Compare the finished file with what was requested
+    let mut report = verify_exported_video(app_handle, clips, settings, &output_metadata, &stderr).await;
+    if let Some(reason) = stream_copy_fallback {
+        report.warnings.push(format!("Stream copy not possible, timeline was re-encoded: {}", reason));
+    }
+    report.warnings.extend(encoding.warnings.iter().cloned());
+    Ok(report)
+}
+
+// ============================================================================
+// CANCELLATION FUNCTIONS
+// ============================================================================
+
+/// Register the cancellation token of an export that is starting
+fn begin_active_export() -> CancellationToken {
+    let token = CancellationToken::new();
+    if let Ok(mut active) = ACTIVE_EXPORT_CANCELLATION.lock() {
+        *active = Some(token.clone());
+    }
+    token
+}
+
+/// Forget the token once the export has finished
+fn end_active_export() {
+    if let Ok(mut active) = ACTIVE_EXPORT_CANCELLATION.lock() {
+        *active = None;
+    }
+}
+
+/// Stop the export that is currently encoding
+///
+/// Returns false when no export is running.
+pub fn cancel_active_export() -> bool {
+    match ACTIVE_EXPORT_CANCELLATION.lock() {
+        Ok(active) => match active.as_ref() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        },
+        Err(_) => false,
+    }
+}
+
+// ============================================================================
+// ATOMIC OUTPUT FUNCTIONS
+// ============================================================================
+
+/// Smallest duration tolerance used when verifying exports (seconds)
+///
+/// This is the largest shortfall always accepted between the expected and the
+/// probed duration; long exports are allowed 2% instead.
+const EXPORT_DURATION_TOLERANCE: f64 = 0.5;
+
+/// Create a hidden staging path next to the final output file
+///
+/// The staging file lives in the same directory so the final rename never
+/// crosses filesystems, and keeps the output extension so FFmpeg picks the
+/// same container.
+pub fn create_staging_output_path(output_path: &str) -> CommandResult<String> {
+    let path = std::path::Path::new(output_path);
+    let output_dir = path
+        .parent()
+        .ok_or_else(|| CommandError::validation_error("Invalid output path".to_string()))?;
+    let filename = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| CommandError::validation_error("Invalid output filename".to_string()))?;
+
+    let extension = get_file_extension(output_path);
+    let nanoseconds = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0);
+    let staging_filename = format!(".{}.{}.partial.{}", filename, nanoseconds, extension);
+
+    Ok(output_dir.join(staging_filename).to_string_lossy().to_string())
+}
+
+/// Get the minimum duration a complete export must have
+///
+/// Clips are concatenated back to back, so timeline gaps are dropped and clips
+/// on additional tracks are appended. A complete render is therefore at least
+/// as long as the shorter of the timeline span and the summed clip durations.
+pub fn get_expected_export_duration(clips: &[ExportClip]) -> f64 {
+    get_timeline_duration(clips).min(get_concatenated_duration(clips))
+}
+
+/// Verify that a probed export duration is not truncated
+pub fn verify_export_duration(actual_duration: f64, expected_duration: f64) -> CommandResult<()> {
+    // Allow 2% drift on long exports (keyframe alignment, audio padding)
+    let tolerance = EXPORT_DURATION_TOLERANCE.max(expected_duration * 0.02);
+
+    if actual_duration + tolerance < expected_duration {
+        return Err(CommandError::ffmpeg_error(format!(
+            "Exported file is truncated: rendered {:.2}s but expected {:.2}s",
+            actual_duration, expected_duration
+        )));
+    }
+
     Ok(())
 }
 
+/// Atomically move a verified staging file onto the final output path
+pub fn promote_staged_export(staging_path: &str, output_path: &str) -> CommandResult<()> {
+    std::fs::rename(staging_path, output_path).map_err(|e| {
+        CommandError::io_error(format!("Failed to move export into place: {}", e))
+    })
+}
+
+/// Verify a staged export by probing it, then move it onto the output path
+///
+/// On any failure the staging file is left for the caller's tracker to remove
+/// and the previous file at `output_path` is untouched. Returns the probed
+/// metadata of the promoted file.
+async fn finalize_staged_export(
+    app_handle: &tauri::AppHandle,
+    staging_path: &str,
+    output_path: &str,
+    clips: &[ExportClip],
+) -> CommandResult<VideoMetadata> {
+    // Make sure FFmpeg actually produced a file
+    let staged_size = get_temp_file_size(staging_path).map_err(|_| {
+        CommandError::ffmpeg_error("FFmpeg did not produce an output file".to_string())
+    })?;
+
+    if staged_size == 0 {
+        return Err(CommandError::ffmpeg_error(
+            "FFmpeg produced an empty output file".to_string(),
+        ));
+    }
+
+    // Probe the staged render
+    let probe_response = crate::ffmpeg::probe::extract_video_metadata(
+        app_handle.clone(),
+        crate::ffmpeg::probe::ExtractMetadataRequest {
+            file_path: staging_path.to_string(),
+        },
+    )
+    .await?;
+
+    let metadata = probe_response.metadata.ok_or_else(|| {
+        CommandError::ffmpeg_error(format!(
+            "Failed to verify exported file: {}",
+            probe_response
+                .error_message
+                .unwrap_or_else(|| "unreadable output".to_string())
+        ))
+    })?;
+
+    // Reject truncated renders before they replace anything
+    verify_export_duration(metadata.duration, get_expected_export_duration(clips))?;
+
+    promote_staged_export(staging_path, output_path)?;
+
+    Ok(metadata)
+}
+
+// ============================================================================
+// EXPORT VERIFICATION FUNCTIONS
+// ============================================================================
+
+/// Frame rate difference treated as a mismatch (fps)
+const EXPORT_FPS_TOLERANCE: f64 = 0.1;
+
+/// Audio/video duration difference reported as drift (seconds)
+const EXPORT_AV_DRIFT_TOLERANCE: f64 = 0.1;
+
+/// Probe the finished export and its sources and build a verification report
+async fn verify_exported_video(
+    app_handle: &tauri::AppHandle,
+    clips: &[ExportClip],
+    settings: &ExportSettings,
+    output_metadata: &VideoMetadata,
+    ffmpeg_stderr: &str,
+) -> ExportVerificationReport {
+    let (source_metadata, probe_warnings) = probe_source_clips(app_handle, clips).await;
+
+    let mut report = build_export_verification_report(
+        output_metadata,
+        &source_metadata,
+        get_concatenated_duration(clips),
+        settings,
+        ffmpeg_stderr,
+    );
+    report.warnings.extend(probe_warnings);
+    report
+}
+
+/// Probe each distinct source file in timeline order
+///
+/// Probe failures are returned as warnings; verification never fails an
+/// export that has already been written.
+async fn probe_source_clips(
+    app_handle: &tauri::AppHandle,
+    clips: &[ExportClip],
+) -> (Vec<VideoMetadata>, Vec<String>) {
+    let (probes, warnings) = probe_source_clips_with_details(app_handle, clips).await;
+    let source_metadata = probes.into_iter().map(|(metadata, _)| metadata).collect();
+    (source_metadata, warnings)
+}
+
+/// Probe each distinct source file in timeline order, keeping stream details
+async fn probe_source_clips_with_details(
+    app_handle: &tauri::AppHandle,
+    clips: &[ExportClip],
+) -> (Vec<(VideoMetadata, Option<MediaDetails>)>, Vec<String>) {
+    let mut source_probes = Vec::new();
+    let mut warnings = Vec::new();
+    let mut probed_paths: Vec<&str> = Vec::new();
+
+    let sorted_clips = sort_clips_by_track_and_timeline_position(clips);
+    for clip in &sorted_clips {
+        if probed_paths.contains(&clip.file_path.as_str()) {
+            continue;
+        }
+        probed_paths.push(&clip.file_path);
+
+        let response = crate::ffmpeg::probe::extract_video_metadata(
+            app_handle.clone(),
+            crate::ffmpeg::probe::ExtractMetadataRequest {
+                file_path: clip.file_path.clone(),
+            },
+        )
+        .await;
+
+        match response {
+            Ok(response) if response.metadata.is_some() => {
+                source_probes.extend(response.metadata.map(|metadata| (metadata, response.details)));
+            }
+            Ok(response) => warnings.push(format!(
+                "Could not probe source {}: {}",
+                clip.file_path,
+                response.error_message.unwrap_or_default()
+            )),
+            Err(error) => warnings.push(format!(
+                "Could not probe source {}: {}",
+                clip.file_path, error.message
+            )),
+        }
+    }
+
+    (source_probes, warnings)
+}
+
+/// Get the fixed output dimensions for a resolution setting
+pub fn get_target_dimensions(resolution: &str) -> Option<(u32, u32)> {
+    match resolution {
+        "1080p" => Some((1920, 1080)),
+        "720p" => Some((1280, 720)),
+        _ => None, // "source" keeps the input dimensions
+    }
+}
+
+/// Build a verification report comparing the exported file with the request
+///
+/// `source_metadata` holds the probed sources in timeline order; the first
+/// source defines the expected dimensions for "source" resolution exports.
+/// `expected_duration` is the concatenated length of the exported clips.
+pub fn build_export_verification_report(
+    output: &VideoMetadata,
+    source_metadata: &[VideoMetadata],
+    expected_duration: f64,
+    settings: &ExportSettings,
+    ffmpeg_stderr: &str,
+) -> ExportVerificationReport {
+    let mut mismatches = Vec::new();
+    let mut warnings = Vec::new();
+
+    // Duration
+    let duration_tolerance = EXPORT_DURATION_TOLERANCE.max(expected_duration * 0.02);
+    if (output.duration - expected_duration).abs() > duration_tolerance {
+        mismatches.push(ExportVerificationMismatch {
+            field: "duration".to_string(),
+            expected: format!("{:.2}s", expected_duration),
+            actual: format!("{:.2}s", output.duration),
+        });
+    }
+
+    // Resolution
+    let expected_dimensions = get_target_dimensions(&settings.resolution).or_else(|| {
+        source_metadata
+            .first()
+            .map(|source| (source.width, source.height))
+    });
+    if let Some((expected_width, expected_height)) = expected_dimensions {
+        if output.width != expected_width || output.height != expected_height {
+            mismatches.push(ExportVerificationMismatch {
+                field: "resolution".to_string(),
+                expected: format!("{}x{}", expected_width, expected_height),
+                actual: format!("{}x{}", output.width, output.height),
+            });
+        }
+    }
+
+    // Frame rate (concat keeps the rate of the first source)
+    if let Some(source) = source_metadata.first() {
+        if source.fps > 0.0 && (output.fps - source.fps).abs() > EXPORT_FPS_TOLERANCE {
+            mismatches.push(ExportVerificationMismatch {
+                field: "fps".to_string(),
+                expected: format!("{:.2}", source.fps),
+                actual: format!("{:.2}", output.fps),
+            });
+        }
+        if source_metadata
+            .iter()
+            .any(|other| (other.fps - source.fps).abs() > EXPORT_FPS_TOLERANCE)
+        {
+            warnings.push("Sources have different frame rates; frames may have been dropped or duplicated".to_string());
+        }
+    } else if output.fps <= 0.0 {
+        mismatches.push(ExportVerificationMismatch {
+            field: "fps".to_string(),
+            expected: "> 0".to_string(),
+            actual: format!("{:.2}", output.fps),
+        });
+    }
+
+    // Audio presence
+    let expects_audio = source_metadata.iter().any(|source| source.has_audio);
+    if !source_metadata.is_empty() && expects_audio != output.has_audio {
+        mismatches.push(ExportVerificationMismatch {
+            field: "audio".to_string(),
+            expected: if expects_audio { "present" } else { "absent" }.to_string(),
+            actual: if output.has_audio { "present" } else { "absent" }.to_string(),
+        });
+    }
+    if expects_audio && source_metadata.iter().any(|source| !source.has_audio) {
+        warnings.push("Some sources have no audio track; their sections may be silent or out of sync".to_string());
+    }
+
+    // Encoder statistics
+    if let Some(stats) = parse_ffmpeg_final_stats(ffmpeg_stderr) {
+        if stats.dropped_frames > 0 {
+            warnings.push(format!("FFmpeg dropped {} frames during export", stats.dropped_frames));
+        }
+        if stats.duplicated_frames > 0 {
+            warnings.push(format!("FFmpeg duplicated {} frames during export", stats.duplicated_frames));
+        }
+
+        // The muxed time follows the longest stream, so a gap between it and the
+        // video frame count means audio ran longer or shorter than video
+        if let Some(time) = stats.time {
+            if output.has_audio && output.fps > 0.0 && stats.frames > 0 {
+                let video_duration = stats.frames as f64 / output.fps;
+                let drift = time - video_duration;
+                if drift.abs() > EXPORT_AV_DRIFT_TOLERANCE {
+                    warnings.push(format!(
+                        "Audio and video durations differ by {:.2}s (possible A/V drift)",
+                        drift
+                    ));
+                }
+            }
+        }
+    }
+
+    ExportVerificationReport {
+        passed: mismatches.is_empty(),
+        expected_duration,
+        actual_duration: output.duration,
+        width: output.width,
+        height: output.height,
+        fps: output.fps,
+        has_audio: output.has_audio,
+        mismatches,
+        warnings,
+    }
+}
+
+/// Parse the last statistics line FFmpeg prints when encoding finishes
+pub fn parse_ffmpeg_final_stats(stderr: &str) -> Option<FfmpegFinalStats> {
+    let frame_regex = Regex::new(r"frame=\s*(\d+)").unwrap();
+    let drop_regex = Regex::new(r"drop=\s*(\d+)").unwrap();
+    let dup_regex = Regex::new(r"dup=\s*(\d+)").unwrap();
+    let time_regex = Regex::new(r"time=(\d{2}:\d{2}:\d{2}\.\d{2})").unwrap();
+
+    // Progress updates are separated by carriage returns
+    let last_line = stderr
+        .split(['\r', '\n'])
+        .rfind(|line| frame_regex.is_match(line))?;
+
+    let capture_u64 = |regex: &Regex| {
+        regex
+            .captures(last_line)
+            .and_then(|cap| cap.get(1))
+            .and_then(|m| m.as_str().parse::<u64>().ok())
+    };
+
+    Some(FfmpegFinalStats {
+        frames: capture_u64(&frame_regex)?,
+        dropped_frames: capture_u64(&drop_regex).unwrap_or(0),
+        duplicated_frames: capture_u64(&dup_regex).unwrap_or(0),
+        time: time_regex
+            .captures(last_line)
+            .and_then(|cap| cap.get(1))
+            .and_then(|m| parse_time_to_seconds(m.as_str()).ok()),
+    })
+}
+
 // ============================================================================
 // TRIM DATA VALIDATION FUNCTIONS
 // ============================================================================
@@ -703,6 +1366,89 @@ pub fn get_clips_not_needing_trimming(clips: &[ExportClip]) -> Vec<usize> {
         .collect()
 }
 
+/// Point clips at full-quality originals in case a proxy path was passed in
+pub fn use_original_sources(clips: Vec<ExportClip>) -> Vec<ExportClip> {
+    clips
+        .into_iter()
+        .map(|mut clip| {
+            clip.file_path = crate::ffmpeg::proxy::resolve_original_source(&clip.file_path);
+            clip
+        })
+        .collect()
+}
+
+// ============================================================================
+// RANGE EXPORT FUNCTIONS
+// ============================================================================
+
+/// Clips overlapping the range by less than this are dropped (seconds)
+const MIN_RANGE_CLIP_DURATION: f64 = 0.1;
+
+/// Restrict clips to the timeline region between `in_point` and `out_point`
+///
+/// Clips outside the range are dropped and clips straddling a boundary get
+/// their trim points moved so the trimming pipeline cuts them. Timeline
+/// positions are rebased so the range starts at 0.
+pub fn slice_clips_to_range(
+    clips: &[ExportClip],
+    in_point: f64,
+    out_point: f64,
+) -> CommandResult<Vec<ExportClip>> {
+    if in_point < 0.0 {
+        return Err(CommandError::validation_error(format!(
+            "Export range in point cannot be negative: {}",
+            in_point
+        )));
+    }
+
+    if out_point <= in_point {
+        return Err(CommandError::validation_error(format!(
+            "Invalid export range: {} to {} (out point must be after in point)",
+            in_point, out_point
+        )));
+    }
+
+    let mut sliced_clips = Vec::new();
+    for clip in clips {
+        let clip_end = clip.start_time + clip.duration;
+
+        // Amount of the clip that falls before the in point and after the out point
+        let head_cut = (in_point - clip.start_time).max(0.0);
+        let tail_cut = (clip_end - out_point).max(0.0);
+        let remaining_duration = clip.duration - head_cut - tail_cut;
+        if remaining_duration < MIN_RANGE_CLIP_DURATION {
+            continue;
+        }
+
+        let sliced_clip = ExportClip {
+            file_path: clip.file_path.clone(),
+            start_time: clip.start_time.max(in_point) - in_point,
+            duration: remaining_duration,
+            trim_start: clip.trim_start + head_cut,
+            trim_end: clip.trim_end - tail_cut,
+            track_id: clip.track_id.clone(),
+            trimmed_file_path: None,
+            original_duration: clip.original_duration,
+        };
+        validate_trim_data(
+            sliced_clip.trim_start,
+            sliced_clip.trim_end,
+            sliced_clip.original_duration,
+            sliced_clips.len(),
+        )?;
+        sliced_clips.push(sliced_clip);
+    }
+
+    if sliced_clips.is_empty() {
+        return Err(CommandError::validation_error(format!(
+            "No clips in export range {} to {}",
+            in_point, out_point
+        )));
+    }
+
+    Ok(sliced_clips)
+}
+
 // ============================================================================
 // FFMPEG TRIM IMPLEMENTATION FUNCTIONS
 // ============================================================================
@@ -714,6 +1460,7 @@ pub fn generate_trim_command_args(
     trim_start: f64,
     trim_duration: f64,
     use_copy_codec: bool,
+    encoders: &ExportEncoders,
 ) -> Vec<String> {
     let mut args = vec![
         "-i".to_string(),
@@ -731,12 +1478,12 @@ pub fn generate_trim_command_args(
             "copy".to_string(),
         ]);
     } else {
-        // Use default encoding (will be slower but more compatible)
+        // Re-encode with the export encoders (slower but more compatible)
         args.extend(vec![
             "-c:v".to_string(),
-            "libx264".to_string(),
+            encoders.video.clone(),
             "-c:a".to_string(),
-            "aac".to_string(),
+            encoders.audio.clone(),
         ]);
     }
 
@@ -755,21 +1502,27 @@ pub async fn trim_video_clip(
     trim_start: f64,
     trim_duration: f64,
     use_copy_codec: bool,
+    encoders: &ExportEncoders,
+) -> CommandResult<()> {
+    let runner = ProcessRunner::new(app_handle);
+    trim_video_clip_with_runner(&runner, input_path, output_path, trim_start, trim_duration, use_copy_codec, encoders).await
+}
+
+/// Trim a single video clip through `runner`
+pub async fn trim_video_clip_with_runner(
+    runner: &dyn FfmpegRunner,
+    input_path: &str,
+    output_path: &str,
+    trim_start: f64,
+    trim_duration: f64,
+    use_copy_codec: bool,
+    encoders: &ExportEncoders,
 ) -> CommandResult<()> {
     // Generate FFmpeg command arguments
-    let args = generate_trim_command_args(input_path, output_path, trim_start, trim_duration, use_copy_codec);
-
-    // Get FFmpeg sidecar
-    let sidecar = app_handle.shell().sidecar("ffmpeg").map_err(|e| {
-        CommandError::ffmpeg_error(format!("Failed to create FFmpeg sidecar: {}", e))
-    })?;
+    let args = generate_trim_command_args(input_path, output_path, trim_start, trim_duration, use_copy_codec, encoders);
 
     // Execute FFmpeg trim command
-    let output = sidecar
-        .args(&args)
-        .output()
-        .await
-        .map_err(|e| CommandError::ffmpeg_error(format!("Failed to execute ffmpeg trim: {}", e)))?;
+    let output = runner.run(FfmpegInvocation::ffmpeg(&args)).await?;
 
     // Check if command succeeded
     if !output.status.success() {
@@ -836,6 +1589,7 @@ pub fn supports_copy_codec(file_extension: &str) -> bool {
 pub async fn trim_clips_for_export(
     app_handle: &tauri::AppHandle,
     clips: &[ExportClip],
+    encoders: &ExportEncoders,
 ) -> CommandResult<Vec<ExportClip>> {
     let mut trimmed_clips = Vec::new();
     let clips_needing_trim = get_clips_needing_trimming(clips);
@@ -856,6 +1610,7 @@ pub async fn trim_clips_for_export(
                 clip.trim_start,
                 trim_duration,
                 use_copy_codec,
+                encoders,
             ).await?;
 
             // Create new clip with trimmed file path
@@ -879,11 +1634,41 @@ pub async fn trim_clips_for_export(
     Ok(trimmed_clips)
 }
 
+/// Open the render cache for an export, if it belongs to a project
+///
+/// Exports still run without a cache when the project directory is missing.
+pub fn open_export_render_cache(project_id: Option<&str>) -> Option<RenderCache> {
+    let project_id = project_id?;
+    match RenderCache::for_project(project_id) {
+        Ok(cache) => Some(cache),
+        Err(error) => {
+            eprintln!("Warning: Render cache unavailable for project {}: {}", project_id, error.message);
+            None
+        }
+    }
+}
+
 /// Trim all clips that need trimming with temporary file tracking
 pub async fn trim_clips_for_export_with_tracking(
     app_handle: &tauri::AppHandle,
     clips: &[ExportClip],
+    encoders: &ExportEncoders,
+    tracker: &mut TempFileTracker,
+) -> CommandResult<Vec<ExportClip>> {
+    trim_clips_for_export_with_cache(app_handle, clips, encoders, tracker, None).await
+}
+
+/// Trim all clips that need trimming, reusing segments from a render cache
+///
+/// Cached segments are owned by the cache, so only uncached trims are tracked
+/// as temporary files; cached ones are pinned so eviction cannot remove them
+/// before the export has used them.
+pub async fn trim_clips_for_export_with_cache(
+    app_handle: &tauri::AppHandle,
+    clips: &[ExportClip],
+    encoders: &ExportEncoders,
     tracker: &mut TempFileTracker,
+    render_cache: Option<&RenderCache>,
 ) -> CommandResult<Vec<ExportClip>> {
     let mut trimmed_clips = Vec::new();
     let clips_needing_trim = get_clips_needing_trimming(clips);
@@ -891,30 +1676,42 @@ pub async fn trim_clips_for_export_with_tracking(
     for (index, clip) in clips.iter().enumerate() {
         if clips_needing_trim.contains(&index) {
             // This clip needs trimming
-            let file_extension = get_file_extension(&clip.file_path);
-            let temp_path = create_and_track_temp_file(tracker, "trimmed_clip", &file_extension).await?;
             let trim_duration = calculate_trimmed_duration(clip.trim_start, clip.trim_end);
-            let use_copy_codec = supports_copy_codec(&file_extension);
-
-            // Trim the clip
-            trim_video_clip(
-                app_handle,
-                &clip.file_path,
-                &temp_path,
-                clip.trim_start,
-                trim_duration,
-                use_copy_codec,
-            ).await?;
+            let segment_path = match render_cache {
+                Some(cache) => trim_video_clip_cached(app_handle, clip, trim_duration, encoders, cache, &mut tracker.segment_pins).await?,
+                None => None,
+            };
+            let segment_path = match segment_path {
+                Some(path) => path,
+                None => {
+                    let file_extension = get_file_extension(&clip.file_path);
+                    let temp_path = create_and_track_temp_file(tracker, "trimmed_clip", &file_extension).await?;
+                    let use_copy_codec = supports_copy_codec(&file_extension);
+
+                    // Trim the clip
+                    trim_video_clip(
+                        app_handle,
+                        &clip.file_path,
+                        &temp_path,
+                        clip.trim_start,
+                        trim_duration,
+                        use_copy_codec,
+                        encoders,
+                    ).await?;
+
+                    temp_path
+                }
+            };
 
             // Create new clip with trimmed file path
             let trimmed_clip = ExportClip {
-                file_path: temp_path.clone(),
+                file_path: segment_path.clone(),
                 start_time: clip.start_time,
                 duration: trim_duration, // Update duration to trimmed duration
                 trim_start: 0.0, // Reset trim points since we've already trimmed
                 trim_end: trim_duration,
                 track_id: clip.track_id.clone(),
-                trimmed_file_path: Some(temp_path),
+                trimmed_file_path: Some(segment_path),
                 original_duration: clip.original_duration, // Preserve original duration
             };
             trimmed_clips.push(trimmed_clip);
@@ -927,12 +1724,106 @@ pub async fn trim_clips_for_export_with_tracking(
     Ok(trimmed_clips)
 }
 
+/// Get the render cache key for a trimmed clip
+///
+/// The encode settings mirror `generate_trim_command_args`, so a change to how
+/// a source is trimmed never reuses an old segment.
+pub fn get_trim_segment_cache_key(clip: &ExportClip, source: &SourceIdentity, encoders: &ExportEncoders) -> String {
+    let file_extension = get_file_extension(&clip.file_path);
+    let encode_settings = if supports_copy_codec(&file_extension) {
+        format!("trim:copy:{}", file_extension)
+    } else {
+        format!("trim:{}+{}:{}", encoders.video, encoders.audio, file_extension)
+    };
+
+    build_segment_cache_key(source, clip.trim_start, clip.trim_end, NO_TRANSFORM, &encode_settings)
+}
+
+/// Trim a clip into the render cache, or reuse its cached segment
+///
+/// Returns `None` when the source cannot be identified or the cache index
+/// cannot be read, so the caller falls back to an uncached trim. The segment
+/// is pinned in `pins` before it is looked up or rendered.
+async fn trim_video_clip_cached(
+    app_handle: &tauri::AppHandle,
+    clip: &ExportClip,
+    trim_duration: f64,
+    encoders: &ExportEncoders,
+    cache: &RenderCache,
+    pins: &mut RenderCachePins,
+) -> CommandResult<Option<String>> {
+    let source = match get_source_identity(&clip.file_path) {
+        Ok(source) => source,
+        Err(_) => return Ok(None),
+    };
+    let key = get_trim_segment_cache_key(clip, &source, encoders);
+    pins.pin(&key);
+
+    // Reuse the segment from a previous export; an unreadable index falls
+    // back to an uncached trim rather than failing the export
+    match cache.lookup(&key) {
+        Ok(Some(cached_path)) => return Ok(Some(cached_path.to_string_lossy().to_string())),
+        Ok(None) => {}
+        Err(error) => {
+            eprintln!("Warning: Render cache lookup failed, trimming without cache: {}", error.message);
+            return Ok(None);
+        }
+    }
+
+    // Trim into a partial file so an interrupted trim is never picked up. The
+    // name is unique per job so concurrent exports of the same clip don't
+    // write into each other's partial file.
+    std::fs::create_dir_all(&cache.cache_dir).map_err(|e| {
+        CommandError::file_error(format!("Failed to create render cache directory: {}", e))
+    })?;
+    let file_extension = get_file_extension(&clip.file_path);
+    let segment_path = cache.get_segment_path(&key, &file_extension);
+    let nanoseconds = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0);
+    let partial_path = cache.get_segment_path(
+        &key,
+        &format!("{}.{}.partial.{}", std::process::id(), nanoseconds, file_extension),
+    );
+
+    if let Err(error) = trim_video_clip(
+        app_handle,
+        &clip.file_path,
+        &partial_path.to_string_lossy(),
+        clip.trim_start,
+        trim_duration,
+        supports_copy_codec(&file_extension),
+        encoders,
+    ).await {
+        let _ = std::fs::remove_file(&partial_path);
+        return Err(error);
+    }
+
+    if let Err(e) = std::fs::rename(&partial_path, &segment_path) {
+        let _ = std::fs::remove_file(&partial_path);
+        // Another export may have finished the same segment first
+        if !segment_path.exists() {
+            return Err(CommandError::io_error(format!(
+                "Failed to move cached segment into place: {}",
+                e
+            )));
+        }
+    }
+
+    cache.insert(&key, &segment_path, &clip.file_path, clip.trim_start, clip.trim_end)?;
+    Ok(Some(segment_path.to_string_lossy().to_string()))
+}
+
 // ============================================================================
 // UTILITY FUNCTIONS
 // ============================================================================
 
 /// Validate export settings
-pub fn validate_export_settings(settings: &ExportSettings) -> CommandResult<()> {
+///
+/// When the FFmpeg capabilities are known, settings the build cannot encode
+/// at all are rejected too.
+pub fn validate_export_settings(
+    settings: &ExportSettings,
+    capabilities: Option<&FfmpegCapabilities>,
+) -> CommandResult<()> {
     // Validate resolution
     if !matches!(settings.resolution.as_str(), "source" | "1080p" | "720p") {
         return Err(CommandError::validation_error(
@@ -961,6 +1852,45 @@ pub fn validate_export_settings(settings: &ExportSettings) -> CommandResult<()>
         ));
     }
 
+    // Validate rate control and its parameter
+    match settings.rate_control.as_str() {
+        "quality" => {}
+        "crf" => match settings.crf {
+            Some(crf) if crf <= 51 => {}
+            _ => {
+                return Err(CommandError::validation_error(
+                    "CRF rate control requires a CRF value between 0 and 51".to_string(),
+                ))
+            }
+        },
+        "cbr" | "abr" => match settings.video_bitrate_kbps {
+            Some(bitrate) if bitrate > 0 && bitrate <= MAX_VIDEO_BITRATE_KBPS => {}
+            _ => {
+                return Err(CommandError::validation_error(format!(
+                    "Bitrate rate control requires a video bitrate between 1 and {} kbps",
+                    MAX_VIDEO_BITRATE_KBPS
+                )))
+            }
+        },
+        "target_size" => match settings.target_size_mb {
+            Some(size) if size > 0.0 && size.is_finite() => {}
+            _ => {
+                return Err(CommandError::validation_error(
+                    "Target size rate control requires a size greater than 0 MB".to_string(),
+                ))
+            }
+        },
+        _ => {
+            return Err(CommandError::validation_error(
+                "Invalid rate control. Must be 'quality', 'crf', 'cbr', 'abr', or 'target_size'".to_string(),
+            ))
+        }
+    }
+
+    if let Some(capabilities) = capabilities {
+        check_export_capabilities(settings, capabilities)?;
+    }
+
     Ok(())
 }
 
@@ -988,1284 +1918,3076 @@ pub fn get_quality_args(quality: &str) -> Vec<String> {
     }
 }
 
-/// Estimate export time based on clips and settings
-pub fn estimate_export_time(clips: &[ExportClip], settings: &ExportSettings) -> f64 {
-    let total_duration: f64 = clips.iter().map(|clip| clip.duration).sum();
-
-    // Rough estimation based on settings
-    let mut multiplier = 1.0;
+// ============================================================================
+// CAPABILITY FUNCTIONS
+// ============================================================================
 
-    match settings.resolution.as_str() {
-        "1080p" => multiplier *= 1.2,
-        "720p" => multiplier *= 0.8,
-        _ => multiplier *= 1.0,
+/// H.264 encoders in order of preference; only libx264 supports CRF and two-pass
+const H264_ENCODERS: &[&str] = &[
+    "libx264",
+    "h264_videotoolbox",
+    "h264_nvenc",
+    "h264_qsv",
+    "h264_amf",
+    "h264_mf",
+    "libopenh264",
+];
+
+/// AAC encoders in order of preference
+const AAC_ENCODERS: &[&str] = &["aac", "libfdk_aac"];
+
+impl Default for ExportEncoders {
+    fn default() -> Self {
+        Self {
+            video: "libx264".to_string(),
+            audio: "aac".to_string(),
+        }
     }
+}
 
-    match settings.quality.as_str() {
-        "high" => multiplier *= 1.5,
-        "medium" => multiplier *= 1.0,
-        "low" => multiplier *= 0.7,
-        _ => multiplier *= 1.0,
+/// Video bitrate (kbps) standing in for a quality preset when CRF is unavailable
+pub fn get_fallback_quality_bitrate(quality: &str) -> u32 {
+    match quality {
+        "high" => 8000,
+        "low" => 2500,
+        _ => 5000,
     }
-
-    // Base estimation: 0.1x real-time for simple concatenation
-    total_duration * 0.1 * multiplier
 }
 
-/// Check if clips are compatible for concatenation
-pub fn check_clip_compatibility(clips: &[ExportClip]) -> CommandResult<()> {
-    if clips.is_empty() {
+/// Reject settings the FFmpeg build cannot encode at all
+///
+/// Options with a fallback are left to `resolve_export_encoding`.
+pub fn check_export_capabilities(settings: &ExportSettings, capabilities: &FfmpegCapabilities) -> CommandResult<()> {
+    if !capabilities.has_demuxer("concat") {
         return Err(CommandError::validation_error(
-            "No clips provided".to_string(),
+            "This FFmpeg build cannot join clips (missing 'concat' demuxer)".to_string(),
         ));
     }
 
-    // For MVP, we'll assume all clips are compatible
-    // In a full implementation, we'd check codec, resolution, etc.
-    Ok(())
-}
-
-/// Get export file size estimate
-pub fn estimate_export_size(clips: &[ExportClip], settings: &ExportSettings) -> u64 {
-    let total_duration: f64 = clips.iter().map(|clip| clip.duration).sum();
+    if !capabilities.has_muxer(&settings.format) {
+        return Err(CommandError::validation_error(format!(
+            "This FFmpeg build cannot write {} files (missing '{}' muxer)",
+            settings.format, settings.format
+        )));
+    }
 
-    // Rough estimation based on settings
-    let base_bitrate = match settings.resolution.as_str() {
-        "1080p" => 8000000, // 8 Mbps
-        "720p" => 3000000,  // 3 Mbps
-        _ => 5000000,       // 5 Mbps
-    };
+    if settings.resolution != "source" && !capabilities.has_filter("scale") {
+        return Err(CommandError::validation_error(format!(
+            "This FFmpeg build cannot resize to {} (missing 'scale' filter). Export at source resolution instead",
+            settings.resolution
+        )));
+    }
 
-    let bitrate = match settings.quality.as_str() {
-        "high" => (base_bitrate as f64 * 1.5) as u64,
-        "medium" => base_bitrate, // Keep current bitrate
-        "low" => (base_bitrate as f64 * 0.7) as u64,
-        _ => base_bitrate, // Keep current bitrate
-    };
+    let video_encoder = capabilities.first_encoder(H264_ENCODERS).ok_or_else(|| {
+        CommandError::validation_error(format!(
+            "This FFmpeg build has no H.264 encoder (looked for {})",
+            H264_ENCODERS.join(", ")
+        ))
+    })?;
 
-    // Calculate estimated size in bytes
-    (total_duration * bitrate as f64 / 8.0) as u64
-}
+    if video_encoder != "libx264" && settings.rate_control == "crf" {
+        return Err(CommandError::validation_error(format!(
+            "CRF rate control needs libx264, which this FFmpeg build lacks ({} would be used). Use 'quality', 'cbr', 'abr' or 'target_size' instead",
+            video_encoder
+        )));
+    }
 
-/// Clean up export temporary files
-pub async fn cleanup_export_temp_files() -> CommandResult<()> {
-    let temp_dir = get_temp_dir()?;
+    if capabilities.first_encoder(AAC_ENCODERS).is_none() {
+        return Err(CommandError::validation_error(format!(
+            "This FFmpeg build has no AAC encoder (looked for {})",
+            AAC_ENCODERS.join(", ")
+        )));
+    }
 
-    if temp_dir.exists() {
-        let entries = std::fs::read_dir(&temp_dir)
-            .map_err(|e| CommandError::io_error(format!("Failed to read temp directory: {}", e)))?;
+    Ok(())
+}
 
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() {
-                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    // Clean up both concat files (.txt) and trimmed clip files
-                    if filename.ends_with(".txt") || filename.starts_with("trimmed_clip_") {
-                        std::fs::remove_file(&path).map_err(|e| {
-                            CommandError::io_error(format!("Failed to delete temp file: {}", e))
-                        })?;
-                    }
-                }
+/// Pick encoders and downgrade options the FFmpeg build cannot honour
+///
+/// Without known capabilities libx264 and aac are assumed. Without libx264 the
+/// quality presets become fixed bitrates and target-size encodes run in a
+/// single pass, since CRF and two-pass logs are libx264 features.
+pub fn resolve_export_encoding(
+    settings: &ExportSettings,
+    duration: f64,
+    capabilities: Option<&FfmpegCapabilities>,
+) -> CommandResult<ExportEncoding> {
+    let mut encoding = ExportEncoding {
+        settings: settings.clone(),
+        encoders: ExportEncoders::default(),
+        warnings: Vec::new(),
+    };
+    let Some(capabilities) = capabilities else {
+        return Ok(encoding);
+    };
+    check_export_capabilities(settings, capabilities)?;
+
+    // Both are present, check_export_capabilities rejects builds without them
+    let encoders = select_export_encoders(Some(capabilities));
+    let video_encoder = encoders.video.as_str();
+    let audio_encoder = encoders.audio.as_str();
+
+    if video_encoder != "libx264" {
+        encoding.warnings.push(format!("libx264 is not available, video was encoded with {}", video_encoder));
+        match settings.rate_control.as_str() {
+            "quality" => {
+                let bitrate = get_fallback_quality_bitrate(&settings.quality);
+                encoding.settings.rate_control = "abr".to_string();
+                encoding.settings.video_bitrate_kbps = Some(bitrate);
+                encoding.warnings.push(format!(
+                    "'{}' quality was encoded at {} kbps instead of a CRF",
+                    settings.quality, bitrate
+                ));
+            }
+            "target_size" => {
+                encoding.settings.rate_control = "abr".to_string();
+                encoding.settings.video_bitrate_kbps = get_constrained_video_bitrate(settings, duration)?;
+                encoding.warnings.push(
+                    "Target size was encoded in a single pass, the output size may be less accurate".to_string(),
+                );
             }
+            _ => {}
         }
     }
 
-    Ok(())
+    if audio_encoder != "aac" {
+        encoding.warnings.push(format!("Native AAC encoder is not available, audio was encoded with {}", audio_encoder));
+    }
+
+    encoding.encoders = encoders;
+    Ok(encoding)
 }
 
-/// Clean up specific trimmed clip files
-pub async fn cleanup_trimmed_clip_files(trimmed_clips: &[ExportClip]) -> CommandResult<()> {
-    for clip in trimmed_clips {
-        if clip.file_path.contains("trimmed_clip_") {
-            if std::path::Path::new(&clip.file_path).exists() {
-                std::fs::remove_file(&clip.file_path).map_err(|e| {
-                    CommandError::io_error(format!("Failed to delete trimmed clip file: {}", e))
-                })?;
-            }
-        }
+/// Pick the H.264 and AAC encoders the FFmpeg build provides
+///
+/// Falls back to libx264 and aac when capabilities are unknown.
+pub fn select_export_encoders(capabilities: Option<&FfmpegCapabilities>) -> ExportEncoders {
+    let defaults = ExportEncoders::default();
+    let Some(capabilities) = capabilities else {
+        return defaults;
+    };
+
+    ExportEncoders {
+        video: capabilities.first_encoder(H264_ENCODERS).map(str::to_string).unwrap_or(defaults.video),
+        audio: capabilities.first_encoder(AAC_ENCODERS).map(str::to_string).unwrap_or(defaults.audio),
     }
-    Ok(())
+}
+
+/// Get the encoders for re-encoding work outside of a full export
+///
+/// Used by trims, packaging and proxies, which have no export settings to
+/// validate against the FFmpeg build.
+pub async fn get_export_encoders(app_handle: &tauri::AppHandle) -> ExportEncoders {
+    let capabilities = get_ffmpeg_capabilities(app_handle).await.ok();
+    select_export_encoders(capabilities.as_ref())
 }
 
 // ============================================================================
-// TEMPORARY FILE MANAGEMENT SYSTEM
+// RATE CONTROL FUNCTIONS
 // ============================================================================
 
-/// Track temporary files for cleanup
-#[derive(Debug, Clone)]
-pub struct TempFileTracker {
-    pub files: Vec<String>,
-    pub directories: Vec<String>,
+/// Audio bitrate used for every export (kbps)
+pub const EXPORT_AUDIO_BITRATE_KBPS: u32 = 192;
+
+/// Share of a target size reserved for container overhead
+const TARGET_SIZE_CONTAINER_OVERHEAD: f64 = 0.02;
+
+/// Lowest video bitrate a target size may resolve to (kbps)
+const MIN_TARGET_VIDEO_BITRATE_KBPS: u32 = 100;
+
+/// Highest video bitrate an export may request (kbps)
+pub const MAX_VIDEO_BITRATE_KBPS: u32 = 500_000;
+
+/// Whether the settings need a two-pass encode
+pub fn uses_two_pass(settings: &ExportSettings) -> bool {
+    settings.rate_control == "target_size"
 }
 
-impl TempFileTracker {
-    /// Create a new temporary file tracker
-    pub fn new() -> Self {
-        Self {
-            files: Vec::new(),
-            directories: Vec::new(),
-        }
+/// Compute the video bitrate (kbps) that fits a duration into a target size
+///
+/// Reserves room for the audio track and container overhead. Sizes are in
+/// MiB so a "25 MB" upload limit is never exceeded.
+pub fn calculate_target_video_bitrate(target_size_mb: f64, duration: f64) -> CommandResult<u32> {
+    if duration <= 0.0 {
+        return Err(CommandError::validation_error(
+            "Cannot encode to a target size with an empty timeline".to_string(),
+        ));
     }
 
-    /// Add a temporary file to track
-    pub fn add_file(&mut self, file_path: String) {
-        self.files.push(file_path);
-    }
+    let total_kbits = target_size_mb * 1024.0 * 1024.0 * 8.0 / 1000.0;
+    let available_kbps = total_kbits * (1.0 - TARGET_SIZE_CONTAINER_OVERHEAD) / duration;
+    let video_kbps = available_kbps - EXPORT_AUDIO_BITRATE_KBPS as f64;
 
-    /// Add a temporary directory to track
-    pub fn add_directory(&mut self, dir_path: String) {
-        self.directories.push(dir_path);
+    if video_kbps < MIN_TARGET_VIDEO_BITRATE_KBPS as f64 {
+        return Err(CommandError::validation_error(format!(
+            "Target size of {:.1} MB is too small for {:.1}s of video",
+            target_size_mb, duration
+        )));
     }
 
-    /// Clean up all tracked files and directories
-    pub async fn cleanup_all(&self) -> CommandResult<()> {
-        // Clean up files first
-        for file_path in &self.files {
-            if std::path::Path::new(file_path).exists() {
-                if let Err(e) = std::fs::remove_file(file_path) {
-                    eprintln!("Warning: Failed to delete temporary file {}: {}", file_path, e);
-                    // Don't fail the entire cleanup for individual file errors
-                }
-            }
-        }
+    Ok(video_kbps.floor() as u32)
+}
 
-        // Clean up directories (in reverse order to handle nested dirs)
-        for dir_path in self.directories.iter().rev() {
-            if std::path::Path::new(dir_path).exists() {
-                if let Err(e) = std::fs::remove_dir_all(dir_path) {
-                    eprintln!("Warning: Failed to delete temporary directory {}: {}", dir_path, e);
-                    // Don't fail the entire cleanup for individual directory errors
-                }
-            }
+/// Get the video bitrate (kbps) an export is constrained to, if any
+pub fn get_constrained_video_bitrate(settings: &ExportSettings, duration: f64) -> CommandResult<Option<u32>> {
+    match settings.rate_control.as_str() {
+        "cbr" | "abr" => Ok(settings.video_bitrate_kbps),
+        "target_size" => {
+            let target_size_mb = settings.target_size_mb.ok_or_else(|| {
+                CommandError::validation_error("Target size rate control requires a size".to_string())
+            })?;
+            calculate_target_video_bitrate(target_size_mb, duration).map(Some)
         }
+        _ => Ok(None),
+    }
+}
 
-        Ok(())
+/// Get FFmpeg video rate control arguments for the export settings
+///
+/// `duration` is the timeline duration used to derive a target-size bitrate.
+pub fn get_rate_control_args(settings: &ExportSettings, duration: f64) -> CommandResult<Vec<String>> {
+    match settings.rate_control.as_str() {
+        "crf" => {
+            let crf = settings.crf.ok_or_else(|| {
+                CommandError::validation_error("CRF rate control requires a CRF value".to_string())
+            })?;
+            Ok(vec!["-crf".to_string(), crf.to_string()])
+        }
+        "cbr" => {
+            let bitrate = get_constrained_video_bitrate(settings, duration)?.ok_or_else(|| {
+                CommandError::validation_error("CBR rate control requires a video bitrate".to_string())
+            })?;
+            Ok(vec![
+                "-b:v".to_string(),
+                format!("{}k", bitrate),
+                "-minrate".to_string(),
+                format!("{}k", bitrate),
+                "-maxrate".to_string(),
+                format!("{}k", bitrate),
+                "-bufsize".to_string(),
+                format!("{}k", bitrate.saturating_mul(2)),
+            ])
+        }
+        "abr" | "target_size" => {
+            let bitrate = get_constrained_video_bitrate(settings, duration)?.ok_or_else(|| {
+                CommandError::validation_error("ABR rate control requires a video bitrate".to_string())
+            })?;
+            Ok(vec!["-b:v".to_string(), format!("{}k", bitrate)])
+        }
+        _ => Ok(get_quality_args(&settings.quality)),
     }
+}
 
-    /// Get count of tracked files
-    pub fn file_count(&self) -> usize {
-        self.files.len()
+/// Build the FFmpeg arguments for the final encode of a concat file
+///
+/// `passlog_prefix` is set for the second pass of a two-pass encode.
+pub fn build_export_encode_args(
+    concat_file_path: &str,
+    metadata_file_path: Option<&str>,
+    output_path: &str,
+    encoders: &ExportEncoders,
+    rate_control_args: &[String],
+    passlog_prefix: Option<&str>,
+) -> Vec<String> {
+    let mut args = vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        concat_file_path.to_string(),
+    ];
+    args.extend(get_ffmetadata_input_args(metadata_file_path));
+    args.extend(vec![
+        "-c:v".to_string(),
+        encoders.video.clone(), // Re-encode video to avoid stream copy issues
+    ]);
+    args.extend(rate_control_args.iter().cloned());
+    if encoders.video == "libx264" {
+        args.extend(vec!["-preset".to_string(), "medium".to_string()]);
     }
 
-    /// Get count of tracked directories
-    pub fn directory_count(&self) -> usize {
-        self.directories.len()
+    if let Some(prefix) = passlog_prefix {
+        args.extend(vec![
+            "-pass".to_string(),
+            "2".to_string(),
+            "-passlogfile".to_string(),
+            prefix.to_string(),
+        ]);
     }
 
-    /// Check if tracker is empty
-    pub fn is_empty(&self) -> bool {
-        self.files.is_empty() && self.directories.is_empty()
+    args.extend(vec![
+        "-c:a".to_string(),
+        encoders.audio.clone(), // Re-encode audio
+        "-b:a".to_string(),
+        format!("{}k", EXPORT_AUDIO_BITRATE_KBPS),
+    ]);
+
+    // Custom tags such as project_id are only written to MP4 with this flag
+    if metadata_file_path.is_some() {
+        args.extend(vec!["-movflags".to_string(), "+use_metadata_tags".to_string()]);
     }
-}
 
-/// Create a temporary file and track it
-pub async fn create_and_track_temp_file(
-    tracker: &mut TempFileTracker,
-    prefix: &str,
-    extension: &str,
-) -> CommandResult<String> {
-    let file_path = create_temp_file_path(prefix, extension)?;
-    tracker.add_file(file_path.clone());
-    Ok(file_path)
+    args.extend(vec![
+        "-y".to_string(), // Overwrite output file
+        output_path.to_string(),
+    ]);
+
+    args
 }
 
-/// Create a temporary directory and track it
-pub async fn create_and_track_temp_dir(
-    tracker: &mut TempFileTracker,
-    dir_name: &str,
-) -> CommandResult<String> {
-    let dir_path = create_temp_dir_path(dir_name)?;
-    std::fs::create_dir_all(&dir_path).map_err(|e| {
-        CommandError::io_error(format!("Failed to create temporary directory: {}", e))
-    })?;
-    tracker.add_directory(dir_path.clone());
-    Ok(dir_path)
+/// Build the FFmpeg arguments for the analysis pass of a two-pass encode
+pub fn build_first_pass_args(
+    concat_file_path: &str,
+    rate_control_args: &[String],
+    passlog_prefix: &str,
+) -> Vec<String> {
+    let mut args = vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        concat_file_path.to_string(),
+        "-c:v".to_string(),
+        "libx264".to_string(),
+    ];
+    args.extend(rate_control_args.iter().cloned());
+    args.extend(vec![
+        "-preset".to_string(),
+        "medium".to_string(),
+        "-pass".to_string(),
+        "1".to_string(),
+        "-passlogfile".to_string(),
+        passlog_prefix.to_string(),
+        "-an".to_string(), // Audio is not needed for analysis
+        "-f".to_string(),
+        "null".to_string(),
+        "-y".to_string(),
+        "-".to_string(),
+    ]);
+    args
 }
 
-/// Clean up all temporary files in the temp directory (emergency cleanup)
-pub async fn cleanup_all_temp_files() -> CommandResult<()> {
-    let temp_dir = get_temp_dir()?;
+/// Run the first pass of a two-pass encode when the settings require it
+///
+/// Returns the pass log prefix for the second pass. The log files are added
+/// to the tracker so they are removed with the other temporary files.
+async fn run_first_pass_if_needed(
+    app_handle: &tauri::AppHandle,
+    concat_file_path: &str,
+    settings: &ExportSettings,
+    duration: f64,
+    tracker: &mut TempFileTracker,
+) -> CommandResult<Option<String>> {
+    if !uses_two_pass(settings) {
+        return Ok(None);
+    }
 
-    if temp_dir.exists() {
-        let entries = std::fs::read_dir(&temp_dir)
-            .map_err(|e| CommandError::io_error(format!("Failed to read temp directory: {}", e)))?;
+    let rate_control_args = get_rate_control_args(settings, duration)?;
 
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() {
-                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    // Clean up all temporary files (concat, trimmed clips, etc.)
-                    if filename.ends_with(".txt") || 
-                       filename.starts_with("trimmed_clip_") ||
-                       filename.starts_with("temp_") ||
-                       filename.starts_with("export_") {
-                        if let Err(e) = std::fs::remove_file(&path) {
-                            eprintln!("Warning: Failed to delete temp file {}: {}", filename, e);
-                        }
-                    }
-                } else if path.is_dir() {
-                    let dirname = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    // Clean up temporary directories
-                    if dirname.starts_with("temp_") || dirname.starts_with("export_") {
-                        if let Err(e) = std::fs::remove_dir_all(&path) {
-                            eprintln!("Warning: Failed to delete temp directory {}: {}", dirname, e);
-                        }
-                    }
-                }
-            }
-        }
+    // Create pass log prefix in the temp directory
+    let temp_dir = get_temp_dir()?;
+    std::fs::create_dir_all(&temp_dir)
+        .map_err(|e| CommandError::io_error(format!("Failed to create temp directory: {}", e)))?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let passlog_prefix = temp_dir
+        .join(format!("export_pass_{}", timestamp))
+        .to_string_lossy()
+        .to_string();
+
+    // libx264 writes "<prefix>-0.log" and "<prefix>-0.log.mbtree"
+    tracker.add_file(format!("{}-0.log", passlog_prefix));
+    tracker.add_file(format!("{}-0.log.mbtree", passlog_prefix));
+
+    let args = build_first_pass_args(concat_file_path, &rate_control_args, &passlog_prefix);
+
+    // Execute the analysis pass
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg first pass failed: {}",
+            stderr
+        )));
     }
 
-    Ok(())
+    Ok(Some(passlog_prefix))
 }
 
-/// Clean up temporary files older than specified hours
-pub async fn cleanup_old_temp_files(max_age_hours: u64) -> CommandResult<()> {
-    let temp_dir = get_temp_dir()?;
-    let cutoff_time = chrono::Utc::now() - chrono::Duration::hours(max_age_hours as i64);
+// ============================================================================
+// STREAM COPY FUNCTIONS
+// ============================================================================
 
-    if temp_dir.exists() {
-        let entries = std::fs::read_dir(&temp_dir)
-            .map_err(|e| CommandError::io_error(format!("Failed to read temp directory: {}", e)))?;
+/// Frame rate difference still considered the same stream parameters (fps)
+const STREAM_COPY_FPS_TOLERANCE: f64 = 0.01;
+
+/// Inputs of a concat stream copy attempt
+struct StreamCopyRequest<'a> {
+    prepared_clips: &'a [ExportClip], // Clips as written to the concat file
+    concat_file_path: &'a str,
+    metadata_file_path: Option<&'a str>,
+    staging_path: &'a str,
+    output_path: &'a str,
+    clips: &'a [ExportClip], // Clips as requested, for verification
+    settings: &'a ExportSettings,
+    cancellation: Option<&'a CancellationToken>,
+}
 
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if let Ok(metadata) = path.metadata() {
-                    if let Ok(modified) = metadata.modified() {
-                        let modified_time = chrono::DateTime::<chrono::Utc>::from(modified);
-                        if modified_time < cutoff_time {
-                            if path.is_file() {
-                                let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                                if filename.ends_with(".txt") || 
-                                   filename.starts_with("trimmed_clip_") ||
-                                   filename.starts_with("temp_") ||
-                                   filename.starts_with("export_") {
-                                    if let Err(e) = std::fs::remove_file(&path) {
-                                        eprintln!("Warning: Failed to delete old temp file {}: {}", filename, e);
-                                    }
-                                }
-                            } else if path.is_dir() {
-                                let dirname = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                                if dirname.starts_with("temp_") || dirname.starts_with("export_") {
-                                    if let Err(e) = std::fs::remove_dir_all(&path) {
-                                        eprintln!("Warning: Failed to delete old temp directory {}: {}", dirname, e);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+/// Result of attempting to export with concat stream copy
+enum StreamCopyOutcome {
+    Completed { metadata: VideoMetadata, stderr: String }, // Output promoted
+    Fallback(String),         // Reason the timeline must be re-encoded
+    Skipped,                  // Settings require re-encoding
+    Cancelled(CommandError),  // Export was cancelled; don't re-encode
+}
+
+/// Whether the export settings allow copying streams without re-encoding
+pub fn is_stream_copy_eligible(settings: &ExportSettings) -> bool {
+    settings.resolution == "source"
+        && settings.rate_control == "quality"
+        && settings.format == "mp4"
+        && settings.codec == "h264"
+}
+
+/// Check that all clips share codec parameters so concat can copy streams
+///
+/// Beyond codecs, resolution and frame rate, the H.264 profile, level, pixel
+/// format and timebase and the audio sample rate and channel layout must
+/// match, so clips without stream details are never copied.
+pub fn check_stream_copy_compatibility(
+    metadata: &[VideoMetadata],
+    details: &[Option<MediaDetails>],
+) -> CommandResult<()> {
+    let first = metadata.first().ok_or_else(|| {
+        CommandError::validation_error("No clip metadata available".to_string())
+    })?;
+
+    let details: Vec<&MediaDetails> = details.iter().flatten().collect();
+    if details.len() != metadata.len() {
+        return Err(CommandError::validation_error(
+            "Stream details are unavailable for some clips".to_string(),
+        ));
+    }
+
+    if first.codec != "h264" {
+        return Err(CommandError::validation_error(format!(
+            "Video codec {} is not h264",
+            first.codec
+        )));
+    }
+
+    if first.has_audio && first.audio_codec.as_deref() != Some("aac") {
+        return Err(CommandError::validation_error(format!(
+            "Audio codec {} is not aac",
+            first.audio_codec.as_deref().unwrap_or("unknown")
+        )));
+    }
+
+    for other in &metadata[1..] {
+        if other.codec != first.codec {
+            return Err(CommandError::validation_error(format!(
+                "Video codecs differ ({} vs {})",
+                first.codec, other.codec
+            )));
+        }
+
+        if other.width != first.width || other.height != first.height {
+            return Err(CommandError::validation_error(format!(
+                "Resolutions differ ({}x{} vs {}x{})",
+                first.width, first.height, other.width, other.height
+            )));
+        }
+
+        if (other.fps - first.fps).abs() > STREAM_COPY_FPS_TOLERANCE {
+            return Err(CommandError::validation_error(format!(
+                "Frame rates differ ({:.2} vs {:.2})",
+                first.fps, other.fps
+            )));
+        }
+
+        if other.has_audio != first.has_audio || other.audio_codec != first.audio_codec {
+            return Err(CommandError::validation_error(
+                "Audio tracks differ between clips".to_string(),
+            ));
         }
     }
 
+    for other in &details[1..] {
+        check_stream_details_match(details[0], other)?;
+    }
+
     Ok(())
 }
 
-/// Get temporary file size
-pub fn get_temp_file_size(file_path: &str) -> CommandResult<u64> {
-    let metadata = std::fs::metadata(file_path)
-        .map_err(|e| CommandError::io_error(format!("Failed to get file metadata: {}", e)))?;
-    Ok(metadata.len())
+/// Compare the stream parameters concat needs to match beyond the codec
+fn check_stream_details_match(first: &MediaDetails, other: &MediaDetails) -> CommandResult<()> {
+    let (first_video, other_video) = (first.video_streams.first(), other.video_streams.first());
+    check_same_stream_parameter(
+        "H.264 profiles",
+        first_video.and_then(|stream| stream.profile.as_ref()),
+        other_video.and_then(|stream| stream.profile.as_ref()),
+    )?;
+    check_same_stream_parameter(
+        "H.264 levels",
+        first_video.and_then(|stream| stream.level),
+        other_video.and_then(|stream| stream.level),
+    )?;
+    check_same_stream_parameter(
+        "Pixel formats",
+        first_video.and_then(|stream| stream.pix_fmt.as_ref()),
+        other_video.and_then(|stream| stream.pix_fmt.as_ref()),
+    )?;
+    check_same_stream_parameter(
+        "Video timebases",
+        first_video.and_then(|stream| stream.time_base.as_ref()),
+        other_video.and_then(|stream| stream.time_base.as_ref()),
+    )?;
+
+    let (first_audio, other_audio) = (first.audio_streams.first(), other.audio_streams.first());
+    check_same_stream_parameter(
+        "Audio sample rates",
+        first_audio.and_then(|stream| stream.sample_rate),
+        other_audio.and_then(|stream| stream.sample_rate),
+    )?;
+    check_same_stream_parameter(
+        "Audio channel counts",
+        first_audio.and_then(|stream| stream.channels),
+        other_audio.and_then(|stream| stream.channels),
+    )?;
+    check_same_stream_parameter(
+        "Audio channel layouts",
+        first_audio.and_then(|stream| stream.channel_layout.as_ref()),
+        other_audio.and_then(|stream| stream.channel_layout.as_ref()),
+    )
 }
 
-/// Get total size of all temporary files
-pub async fn get_total_temp_files_size() -> CommandResult<u64> {
-    let temp_dir = get_temp_dir()?;
-    let mut total_size = 0u64;
-
-    if temp_dir.exists() {
-        let entries = std::fs::read_dir(&temp_dir)
-            .map_err(|e| CommandError::io_error(format!("Failed to read temp directory: {}", e)))?;
-
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() {
-                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    if filename.ends_with(".txt") || 
-                       filename.starts_with("trimmed_clip_") ||
-                       filename.starts_with("temp_") ||
-                       filename.starts_with("export_") {
-                        if let Ok(metadata) = path.metadata() {
-                            total_size += metadata.len();
-                        }
-                    }
-                }
-            }
-        }
+/// Fail with a readable message when a stream parameter differs
+fn check_same_stream_parameter<T: PartialEq + std::fmt::Display>(
+    name: &str,
+    first: Option<T>,
+    other: Option<T>,
+) -> CommandResult<()> {
+    if first == other {
+        return Ok(());
     }
 
-    Ok(total_size)
+    let describe = |value: &Option<T>| {
+        value
+            .as_ref()
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    Err(CommandError::validation_error(format!(
+        "{} differ ({} vs {})",
+        name,
+        describe(&first),
+        describe(&other)
+    )))
 }
 
-// ============================================================================
-// PROGRESS PARSING FUNCTIONS
-// ============================================================================
+/// Build the FFmpeg arguments to concatenate clips by copying streams
+pub fn build_stream_copy_args(
+    concat_file_path: &str,
+    metadata_file_path: Option<&str>,
+    output_path: &str,
+) -> Vec<String> {
+    let mut args = vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        concat_file_path.to_string(),
+    ];
+    args.extend(get_ffmetadata_input_args(metadata_file_path));
 
-/// Parse FFmpeg progress from stderr output
-pub fn parse_ffmpeg_progress(stderr_line: &str, total_duration: f64) -> Option<ExportProgress> {
-    // FFmpeg progress patterns to match
-    let frame_regex = Regex::new(r"frame=\s*(\d+)").unwrap();
-    let fps_regex = Regex::new(r"fps=\s*([\d.]+)").unwrap();
-    let time_regex = Regex::new(r"time=(\d{2}:\d{2}:\d{2}\.\d{2})").unwrap();
-    let bitrate_regex = Regex::new(r"bitrate=\s*([\d.]+)\s*kbits/s").unwrap();
-    let speed_regex = Regex::new(r"speed=\s*([\d.]+)x").unwrap();
+    let movflags = if metadata_file_path.is_some() {
+        "+faststart+use_metadata_tags"
+    } else {
+        "+faststart"
+    };
+    args.extend(vec![
+        "-c".to_string(),
+        "copy".to_string(), // No re-encode
+        "-movflags".to_string(),
+        movflags.to_string(),
+        "-y".to_string(), // Overwrite output file
+        output_path.to_string(),
+    ]);
+    args
+}
 
-    // Extract frame number
-    let current_frame = frame_regex
-        .captures(stderr_line)
-        .and_then(|cap| cap.get(1))
-        .and_then(|m| m.as_str().parse::<u64>().ok());
+/// Try to export by concatenating the prepared clips with stream copy
+///
+/// Probes the files that will actually be concatenated (trimmed copies where
+/// present). Any failure is returned as a fallback reason so the caller can
+/// re-encode into the same staging file.
+async fn try_stream_copy_export(
+    app_handle: &tauri::AppHandle,
+    request: &StreamCopyRequest<'_>,
+) -> StreamCopyOutcome {
+    if !is_stream_copy_eligible(request.settings) {
+        return StreamCopyOutcome::Skipped;
+    }
 
-    // Extract FPS
-    let fps = fps_regex
-        .captures(stderr_line)
-        .and_then(|cap| cap.get(1))
-        .and_then(|m| m.as_str().parse::<f64>().ok());
+    // Probe every clip that goes into the concat file
+    let (probes, probe_warnings) = probe_source_clips_with_details(app_handle, request.prepared_clips).await;
+    if let Some(warning) = probe_warnings.into_iter().next() {
+        return StreamCopyOutcome::Fallback(warning);
+    }
+    let (metadata, details): (Vec<VideoMetadata>, Vec<Option<MediaDetails>>) = probes.into_iter().unzip();
+    if let Err(error) = check_stream_copy_compatibility(&metadata, &details) {
+        return StreamCopyOutcome::Fallback(error.message);
+    }
 
-    // Extract time
-    let time = time_regex
-        .captures(stderr_line)
-        .and_then(|cap| cap.get(1))
-        .map(|m| m.as_str().to_string());
+    // Execute FFmpeg stream copy
+    let args = build_stream_copy_args(request.concat_file_path, request.metadata_file_path, request.staging_path);
+    let mut invocation = FfmpegInvocation::ffmpeg(&args);
+    if let Some(cancellation) = request.cancellation {
+        invocation = invocation.with_cancellation(cancellation.clone());
+    }
+    let output = match ProcessRunner::new(app_handle).run(invocation).await {
+        Ok(output) => output,
+        Err(error) if request.cancellation.is_some_and(|token| token.is_cancelled()) => {
+            return StreamCopyOutcome::Cancelled(error);
+        }
+        Err(error) => return StreamCopyOutcome::Fallback(error.message),
+    };
 
-    // Extract bitrate
-    let bitrate = bitrate_regex
-        .captures(stderr_line)
-        .and_then(|cap| cap.get(1))
-        .and_then(|m| m.as_str().parse::<f64>().ok());
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if !output.status.success() {
+        return StreamCopyOutcome::Fallback(format!("FFmpeg stream copy failed: {}", stderr));
+    }
 
-    // Extract speed
-    let speed = speed_regex
-        .captures(stderr_line)
-        .and_then(|cap| cap.get(1))
-        .and_then(|m| m.as_str().parse::<f64>().ok());
+    // Verify the copied output like any other render before promoting it
+    match finalize_staged_export(app_handle, request.staging_path, request.output_path, request.clips).await {
+        Ok(metadata) => StreamCopyOutcome::Completed { metadata, stderr },
+        Err(error) => StreamCopyOutcome::Fallback(error.message),
+    }
+}
 
-    // Calculate progress based on time
-    if let Some(time_str) = &time {
-        if let Ok(current_time) = parse_time_to_seconds(time_str) {
-            let progress = if total_duration > 0.0 {
-                (current_time / total_duration * 100.0).min(100.0)
-            } else {
-                0.0
-            };
+// ============================================================================
+// CHAPTER AND METADATA FUNCTIONS
+// ============================================================================
 
-            // Calculate estimated time remaining
-            let estimated_remaining = if let Some(speed) = speed {
-                if speed > 0.0 {
-                    (total_duration - current_time) / speed
-                } else {
-                    0.0
-                }
-            } else {
-                0.0
-            };
+/// Title given to the chapter inserted at 0:00 when markers start later
+const INTRO_CHAPTER_TITLE: &str = "Intro";
 
-            return Some(ExportProgress {
-                progress,
-                current_step: "Exporting".to_string(),
-                estimated_time_remaining: estimated_remaining,
-                error: None,
-                current_frame,
-                total_frames: None, // We don't have total frames from FFmpeg output
-                fps,
-                bitrate,
-                time: Some(time_str.clone()),
+/// Sort chapters and keep those inside the export
+///
+/// Chapters past the end are dropped, duplicate start times keep the first
+/// title, and an intro chapter is added so the list starts at 0:00 as
+/// YouTube requires.
+pub fn normalize_chapters(chapters: &[ExportChapter], total_duration: f64) -> Vec<ExportChapter> {
+    let mut sorted: Vec<ExportChapter> = chapters
+        .iter()
+        .filter(|chapter| chapter.start_time.is_finite())
+        .filter(|chapter| chapter.start_time >= 0.0 && chapter.start_time < total_duration)
+        .cloned()
+        .collect();
+    sorted.sort_by(|a, b| {
+        a.start_time
+            .partial_cmp(&b.start_time)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    sorted.dedup_by(|later, earlier| (later.start_time - earlier.start_time).abs() < 0.001);
+
+    let mut normalized = Vec::new();
+    if let Some(first) = sorted.first() {
+        if first.start_time >= 0.001 {
+            normalized.push(ExportChapter {
+                start_time: 0.0,
+                title: INTRO_CHAPTER_TITLE.to_string(),
             });
         }
     }
 
-    None
+    for chapter in sorted {
+        let title = chapter.title.trim();
+        let title = if title.is_empty() {
+            format!("Chapter {}", normalized.len() + 1)
+        } else {
+            title.to_string()
+        };
+        normalized.push(ExportChapter {
+            start_time: chapter.start_time,
+            title,
+        });
+    }
+
+    normalized
 }
 
-/// Parse time string (HH:MM:SS.mm) to seconds
-fn parse_time_to_seconds(time_str: &str) -> Result<f64, Box<dyn std::error::Error>> {
-    let parts: Vec<&str> = time_str.split(':').collect();
-    if parts.len() != 3 {
-        return Err("Invalid time format".into());
-    }
-
-    let hours: f64 = parts[0].parse()?;
-    let minutes: f64 = parts[1].parse()?;
-    let seconds: f64 = parts[2].parse()?;
-
-    Ok(hours * 3600.0 + minutes * 60.0 + seconds)
+/// Restrict chapters to the region between `in_point` and `out_point`
+///
+/// The chapter already running at the in point is kept and moved to 0 so the
+/// exported range starts inside a named chapter.
+pub fn slice_chapters_to_range(
+    chapters: &[ExportChapter],
+    in_point: f64,
+    out_point: f64,
+) -> Vec<ExportChapter> {
+    let running_chapter = chapters
+        .iter()
+        .filter(|chapter| chapter.start_time <= in_point)
+        .max_by(|a, b| {
+            a.start_time
+                .partial_cmp(&b.start_time)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+    let mut sliced: Vec<ExportChapter> = running_chapter
+        .map(|chapter| ExportChapter {
+            start_time: 0.0,
+            title: chapter.title.clone(),
+        })
+        .into_iter()
+        .collect();
+
+    sliced.extend(
+        chapters
+            .iter()
+            .filter(|chapter| chapter.start_time > in_point && chapter.start_time < out_point)
+            .map(|chapter| ExportChapter {
+                start_time: chapter.start_time - in_point,
+                title: chapter.title.clone(),
+            }),
+    );
+
+    sliced
 }
 
-/// Create a progress update for export start
-pub fn create_export_start_progress() -> ExportProgress {
-    ExportProgress {
-        progress: 0.0,
-        current_step: "Preparing".to_string(),
-        estimated_time_remaining: 0.0,
-        error: None,
-        current_frame: None,
-        total_frames: None,
-        fps: None,
-        bitrate: None,
-        time: None,
-    }
+/// Move chapters from timeline time into the time of the concatenated output
+///
+/// Clips are joined in track order with gaps dropped, so a chapter is placed at
+/// its offset inside the clip that covers it. A chapter in a gap moves to the
+/// start of the next clip, and chapters after the last clip are dropped.
+pub fn map_chapters_to_output_time(chapters: &[ExportChapter], clips: &[ExportClip]) -> Vec<ExportChapter> {
+    // Output start time of each clip in concat order
+    let mut output_start = 0.0;
+    let mut layout = Vec::with_capacity(clips.len());
+    for clip in sort_clips_by_track_and_timeline_position(clips) {
+        let clip_output_start = output_start;
+        output_start += clip.duration;
+        layout.push((clip, clip_output_start));
+    }
+
+    chapters
+        .iter()
+        .filter_map(|chapter| {
+            let time = chapter.start_time;
+            let covering_clip = layout
+                .iter()
+                .find(|(clip, _)| time >= clip.start_time && time < clip.start_time + clip.duration);
+            let output_time = match covering_clip {
+                Some((clip, clip_output_start)) => clip_output_start + (time - clip.start_time),
+                None => layout
+                    .iter()
+                    .find(|(clip, _)| clip.start_time > time)
+                    .map(|(_, clip_output_start)| *clip_output_start)?,
+            };
+
+            Some(ExportChapter {
+                start_time: output_time,
+                title: chapter.title.clone(),
+            })
+        })
+        .collect()
 }
 
-/// Create a progress update for export completion
-pub fn create_export_complete_progress() -> ExportProgress {
-    ExportProgress {
-        progress: 100.0,
-        current_step: "Completed".to_string(),
-        estimated_time_remaining: 0.0,
-        error: None,
-        current_frame: None,
-        total_frames: None,
-        fps: None,
-        bitrate: None,
-        time: None,
+/// Escape a value for an FFMETADATA file
+pub fn escape_ffmetadata_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
     }
+    escaped
 }
 
-/// Create a progress update for export error
-pub fn create_export_error_progress(error_message: String) -> ExportProgress {
-    ExportProgress {
-        progress: 0.0,
-        current_step: "Failed".to_string(),
-        estimated_time_remaining: 0.0,
-        error: Some(error_message),
-        current_frame: None,
-        total_frames: None,
-        fps: None,
-        bitrate: None,
-        time: None,
+/// Generate FFMETADATA content with container tags and chapters
+pub fn generate_ffmetadata_content(
+    chapters: &[ExportChapter],
+    tags: &ExportMetadataTags,
+    total_duration: f64,
+) -> String {
+    let mut content = String::from(";FFMETADATA1\n");
+
+    // Global container tags
+    let tag_values = [
+        ("title", &tags.title),
+        ("artist", &tags.artist),
+        ("comment", &tags.comment),
+        ("creation_time", &tags.creation_time),
+        ("project_id", &tags.project_id),
+    ];
+    for (key, value) in tag_values {
+        if let Some(value) = value {
+            content.push_str(&format!("{}={}\n", key, escape_ffmetadata_value(value)));
+        }
     }
-}
 
-// ============================================================================
-// UNIT TESTS
-// ============================================================================
+    // Chapters in milliseconds, each ending where the next starts
+    let chapters = normalize_chapters(chapters, total_duration);
+    for (index, chapter) in chapters.iter().enumerate() {
+        let end_time = chapters
+            .get(index + 1)
+            .map(|next| next.start_time)
+            .unwrap_or(total_duration);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::commands::export::{ExportTimelineRequest, TimelineExportClip, ExportProgressUpdate, ExportStatus, ExportJob};
+        content.push_str("\n[CHAPTER]\nTIMEBASE=1/1000\n");
+        content.push_str(&format!("START={}\n", (chapter.start_time * 1000.0).round() as u64));
+        content.push_str(&format!("END={}\n", (end_time * 1000.0).round() as u64));
+        content.push_str(&format!("title={}\n", escape_ffmetadata_value(&chapter.title)));
+    }
 
-    #[test]
-    fn test_parse_ffmpeg_progress() {
-        let stderr_line = "frame= 1234 fps=30.0 q=23.0 size=   10240kB time=00:00:41.13 bitrate=2048.0kbits/s speed=1.0x";
-        let total_duration = 60.0; // 60 seconds
+    content
+}
 
-        let progress = parse_ffmpeg_progress(stderr_line, total_duration);
+/// Get FFmpeg arguments that add an FFMETADATA file as the metadata source
+pub fn get_ffmetadata_input_args(metadata_file_path: Option<&str>) -> Vec<String> {
+    match metadata_file_path {
+        Some(path) => vec![
+            "-f".to_string(),
+            "ffmetadata".to_string(),
+            "-i".to_string(),
+            path.to_string(),
+            "-map_metadata".to_string(),
+            "1".to_string(),
+            "-map_chapters".to_string(),
+            "1".to_string(),
+        ],
+        None => vec![],
+    }
+}
 
-        assert!(progress.is_some());
-        let progress = progress.unwrap();
+/// Write an FFMETADATA file for the export and track it
+///
+/// Returns `None` when there are no chapters or tags to write.
+pub async fn write_ffmetadata_file_with_tracking(
+    chapters: &[ExportChapter],
+    tags: &ExportMetadataTags,
+    total_duration: f64,
+    tracker: &mut TempFileTracker,
+) -> CommandResult<Option<String>> {
+    let has_tags = tags.title.is_some()
+        || tags.artist.is_some()
+        || tags.comment.is_some()
+        || tags.creation_time.is_some()
+        || tags.project_id.is_some();
+    if chapters.is_empty() && !has_tags {
+        return Ok(None);
+    }
+
+    let metadata_file_path = create_and_track_temp_file(tracker, "export_metadata", "txt").await?;
+    let content = generate_ffmetadata_content(chapters, tags, total_duration);
+    std::fs::write(&metadata_file_path, content)
+        .map_err(|e| CommandError::io_error(format!("Failed to write metadata file: {}", e)))?;
+
+    Ok(Some(metadata_file_path))
+}
 
-        assert_eq!(progress.current_frame, Some(1234));
-        assert_eq!(progress.fps, Some(30.0));
-        assert_eq!(progress.bitrate, Some(2048.0));
-        assert_eq!(progress.time, Some("00:00:41.13".to_string()));
-        assert!(progress.progress > 0.0);
-        assert!(progress.progress < 100.0);
-        assert_eq!(progress.current_step, "Exporting");
+/// Format a chapter start time the way YouTube descriptions expect
+///
+/// Uses H:MM:SS for videos an hour or longer and M:SS otherwise.
+pub fn format_youtube_timestamp(seconds: f64, total_duration: f64) -> String {
+    let total_seconds = seconds.max(0.0).floor() as u64;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let secs = total_seconds % 60;
+
+    if total_duration >= 3600.0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
     }
+}
 
-    #[test]
-    fn test_parse_ffmpeg_progress_invalid_line() {
-        let stderr_line = "This is not a progress line";
-        let total_duration = 60.0;
+/// Generate a YouTube-style chapter list, one "timestamp title" per line
+pub fn generate_youtube_chapter_list(chapters: &[ExportChapter], total_duration: f64) -> String {
+    normalize_chapters(chapters, total_duration)
+        .iter()
+        .map(|chapter| {
+            format!(
+                "{} {}\n",
+                format_youtube_timestamp(chapter.start_time, total_duration),
+                chapter.title
+            )
+        })
+        .collect()
+}
 
-        let progress = parse_ffmpeg_progress(stderr_line, total_duration);
-        assert!(progress.is_none());
-    }
+/// Get the chapter list path next to an export ("video.mp4" -> "video.chapters.txt")
+pub fn get_chapter_list_path(output_path: &str) -> String {
+    std::path::Path::new(output_path)
+        .with_extension("chapters.txt")
+        .to_string_lossy()
+        .to_string()
+}
 
-    #[test]
-    fn test_parse_ffmpeg_progress_complete() {
-        let stderr_line = "frame= 1800 fps=30.0 q=23.0 size=   15360kB time=00:01:00.00 bitrate=2048.0kbits/s speed=1.0x";
-        let total_duration = 60.0; // 60 seconds
+/// Write the YouTube chapter list next to a finished export
+///
+/// Failures are added to the verification warnings; the export itself has
+/// already succeeded.
+fn write_chapter_list_for_export(
+    output_path: &str,
+    clips: &[ExportClip],
+    chapters: &[ExportChapter],
+    report: &mut ExportVerificationReport,
+) -> Option<String> {
+    if chapters.is_empty() {
+        return None;
+    }
+
+    let chapters = map_chapters_to_output_time(chapters, clips);
+    let chapter_list = generate_youtube_chapter_list(&chapters, get_concatenated_duration(clips));
+    let chapter_list_path = get_chapter_list_path(output_path);
+    match std::fs::write(&chapter_list_path, chapter_list) {
+        Ok(_) => Some(chapter_list_path),
+        Err(e) => {
+            report.warnings.push(format!("Failed to write chapter list: {}", e));
+            None
+        }
+    }
+}
 
-        let progress = parse_ffmpeg_progress(stderr_line, total_duration);
+/// Estimate export time based on clips and settings
+pub fn estimate_export_time(clips: &[ExportClip], settings: &ExportSettings) -> f64 {
+    let total_duration: f64 = clips.iter().map(|clip| clip.duration).sum();
 
-        assert!(progress.is_some());
-        let progress = progress.unwrap();
+    // Rough estimation based on settings
+    let mut multiplier = 1.0;
 
-        assert_eq!(progress.progress, 100.0);
-        assert_eq!(progress.current_step, "Exporting");
+    match settings.resolution.as_str() {
+        "1080p" => multiplier *= 1.2,
+        "720p" => multiplier *= 0.8,
+        _ => multiplier *= 1.0,
     }
 
-    #[test]
-    fn test_parse_time_to_seconds() {
-        assert_eq!(parse_time_to_seconds("00:00:30.50").unwrap(), 30.5);
-        assert_eq!(parse_time_to_seconds("00:01:30.00").unwrap(), 90.0);
-        assert_eq!(parse_time_to_seconds("01:00:00.00").unwrap(), 3600.0);
-        assert_eq!(parse_time_to_seconds("01:30:45.25").unwrap(), 5445.25);
+    match settings.quality.as_str() {
+        "high" => multiplier *= 1.5,
+        "medium" => multiplier *= 1.0,
+        "low" => multiplier *= 0.7,
+        _ => multiplier *= 1.0,
     }
 
-    #[test]
-    fn test_parse_time_to_seconds_invalid() {
-        assert!(parse_time_to_seconds("invalid").is_err());
-        assert!(parse_time_to_seconds("00:00").is_err());
-        assert!(parse_time_to_seconds("").is_err());
+    // Two-pass encodes analyse the timeline before encoding it
+    if uses_two_pass(settings) {
+        multiplier *= 1.8;
     }
 
-    #[test]
-    fn test_create_export_start_progress() {
-        let progress = create_export_start_progress();
+    // Base estimation: 0.1x real-time for simple concatenation
+    total_duration * 0.1 * multiplier
+}
 
-        assert_eq!(progress.progress, 0.0);
-        assert_eq!(progress.current_step, "Preparing");
-        assert_eq!(progress.estimated_time_remaining, 0.0);
-        assert!(progress.error.is_none());
+/// Check if clips are compatible for concatenation
+pub fn check_clip_compatibility(clips: &[ExportClip]) -> CommandResult<()> {
+    if clips.is_empty() {
+        return Err(CommandError::validation_error(
+            "No clips provided".to_string(),
+        ));
     }
 
-    #[test]
-    fn test_create_export_complete_progress() {
-        let progress = create_export_complete_progress();
+    // For MVP, we'll assume all clips are compatible
+    // In a full implementation, we'd check codec, resolution, etc.
+    Ok(())
+}
 
-        assert_eq!(progress.progress, 100.0);
-        assert_eq!(progress.current_step, "Completed");
-        assert_eq!(progress.estimated_time_remaining, 0.0);
-        assert!(progress.error.is_none());
+/// Get export file size estimate
+///
+/// Exact for bitrate-constrained rate control; a rough guess for CRF modes.
+pub fn estimate_export_size(clips: &[ExportClip], settings: &ExportSettings) -> u64 {
+    // Target size encodes are sized to the requested limit
+    if uses_two_pass(settings) {
+        if let Some(target_size_mb) = settings.target_size_mb {
+            return (target_size_mb * 1024.0 * 1024.0) as u64;
+        }
     }
 
-    #[test]
-    fn test_create_export_error_progress() {
-        let error_message = "Export failed".to_string();
-        let progress = create_export_error_progress(error_message.clone());
-
-        assert_eq!(progress.progress, 0.0);
-        assert_eq!(progress.current_step, "Failed");
-        assert_eq!(progress.estimated_time_remaining, 0.0);
-        assert_eq!(progress.error, Some(error_message));
+    // Bitrate-constrained encodes follow the requested bitrate
+    let total_duration = get_concatenated_duration(clips);
+    if let Ok(Some(video_kbps)) = get_constrained_video_bitrate(settings, total_duration) {
+        let total_kbps = video_kbps as f64 + EXPORT_AUDIO_BITRATE_KBPS as f64;
+        return (total_duration * total_kbps * 1000.0 / 8.0) as u64;
     }
 
-    #[test]
-    fn test_validate_export_settings() {
-        let valid_settings = ExportSettings {
-            resolution: "1080p".to_string(),
-            quality: "high".to_string(),
-            format: "mp4".to_string(),
-            codec: "h264".to_string(),
-        };
-
-        assert!(validate_export_settings(&valid_settings).is_ok());
-    }
 
-    #[test]
-    fn test_validate_export_settings_invalid_resolution() {
-        let invalid_settings = ExportSettings {
-            resolution: "invalid".to_string(),
-            quality: "high".to_string(),
-            format: "mp4".to_string(),
-            codec: "h264".to_string(),
-        };
+    // Rough estimation based on settings
+    let base_bitrate = match settings.resolution.as_str() {
+        "1080p" => 8000000, // 8 Mbps
+        "720p" => 3000000,  // 3 Mbps
+        _ => 5000000,       // 5 Mbps
+    };
 
-        assert!(validate_export_settings(&invalid_settings).is_err());
-    }
+    let bitrate = match settings.quality.as_str() {
+        "high" => (base_bitrate as f64 * 1.5) as u64,
+        "medium" => base_bitrate, // Keep current bitrate
+        "low" => (base_bitrate as f64 * 0.7) as u64,
+        _ => base_bitrate, // Keep current bitrate
+    };
 
-    #[test]
-    fn test_validate_export_settings_invalid_quality() {
-        let invalid_settings = ExportSettings {
-            resolution: "1080p".to_string(),
-            quality: "invalid".to_string(),
-            format: "mp4".to_string(),
-            codec: "h264".to_string(),
-        };
+    // Calculate estimated size in bytes
+    (total_duration * bitrate as f64 / 8.0) as u64
+}
 
-        assert!(validate_export_settings(&invalid_settings).is_err());
-    }
+/// Clean up export temporary files
+pub async fn cleanup_export_temp_files() -> CommandResult<()> {
+    let temp_dir = get_temp_dir()?;
 
-    #[test]
-    fn test_validate_export_settings_invalid_format() {
-        let invalid_settings = ExportSettings {
-            resolution: "1080p".to_string(),
-            quality: "high".to_string(),
-            format: "avi".to_string(),
-            codec: "h264".to_string(),
-        };
+    if temp_dir.exists() {
+        let entries = std::fs::read_dir(&temp_dir)
+            .map_err(|e| CommandError::io_error(format!("Failed to read temp directory: {}", e)))?;
 
-        assert!(validate_export_settings(&invalid_settings).is_err());
+        for entry in entries {
+            if let Ok(entry) = entry {
+                let path = entry.path();
+                if path.is_file() {
+                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    // Clean up both concat files (.txt) and trimmed clip files
+                    if filename.ends_with(".txt") || filename.starts_with("trimmed_clip_") {
+                        std::fs::remove_file(&path).map_err(|e| {
+                            CommandError::io_error(format!("Failed to delete temp file: {}", e))
+                        })?;
+                    }
+                }
+            }
+        }
     }
 
-    #[test]
-    fn test_validate_export_settings_invalid_codec() {
-        let invalid_settings = ExportSettings {
+    Ok(())
+}
+
+/// Clean up specific trimmed clip files
+pub async fn cleanup_trimmed_clip_files(trimmed_clips: &[ExportClip]) -> CommandResult<()> {
+    for clip in trimmed_clips {
+        if clip.file_path.contains("trimmed_clip_") {
+            if std::path::Path::new(&clip.file_path).exists() {
+                std::fs::remove_file(&clip.file_path).map_err(|e| {
+                    CommandError::io_error(format!("Failed to delete trimmed clip file: {}", e))
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// TEMPORARY FILE MANAGEMENT SYSTEM
+// ============================================================================
+
+/// Track temporary files for cleanup
+#[derive(Debug, Clone)]
+pub struct TempFileTracker {
+    pub files: Vec<String>,
+    pub directories: Vec<String>,
+    pub segment_pins: RenderCachePins, // Cached segments in use, kept until the tracker is dropped
+}
+
+impl TempFileTracker {
+    /// Create a new temporary file tracker
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            directories: Vec::new(),
+            segment_pins: RenderCachePins::new(),
+        }
+    }
+
+    /// Add a temporary file to track
+    pub fn add_file(&mut self, file_path: String) {
+        self.files.push(file_path);
+    }
+
+    /// Add a temporary directory to track
+    pub fn add_directory(&mut self, dir_path: String) {
+        self.directories.push(dir_path);
+    }
+
+    /// Clean up all tracked files and directories
+    pub async fn cleanup_all(&self) -> CommandResult<()> {
+        // Clean up files first
+        for file_path in &self.files {
+            if std::path::Path::new(file_path).exists() {
+                if let Err(e) = std::fs::remove_file(file_path) {
+                    eprintln!("Warning: Failed to delete temporary file {}: {}", file_path, e);
+                    // Don't fail the entire cleanup for individual file errors
+                }
+            }
+        }
+
+        // Clean up directories (in reverse order to handle nested dirs)
+        for dir_path in self.directories.iter().rev() {
+            if std::path::Path::new(dir_path).exists() {
+                if let Err(e) = std::fs::remove_dir_all(dir_path) {
+                    eprintln!("Warning: Failed to delete temporary directory {}: {}", dir_path, e);
+                    // Don't fail the entire cleanup for individual directory errors
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get count of tracked files
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Get count of tracked directories
+    pub fn directory_count(&self) -> usize {
+        self.directories.len()
+    }
+
+    /// Check if tracker is empty
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.directories.is_empty()
+    }
+}
+
+/// Create a temporary file and track it
+pub async fn create_and_track_temp_file(
+    tracker: &mut TempFileTracker,
+    prefix: &str,
+    extension: &str,
+) -> CommandResult<String> {
+    let file_path = create_temp_file_path(prefix, extension)?;
+    tracker.add_file(file_path.clone());
+    Ok(file_path)
+}
+
+/// Create a temporary directory and track it
+pub async fn create_and_track_temp_dir(
+    tracker: &mut TempFileTracker,
+    dir_name: &str,
+) -> CommandResult<String> {
+    let dir_path = create_temp_dir_path(dir_name)?;
+    std::fs::create_dir_all(&dir_path).map_err(|e| {
+        CommandError::io_error(format!("Failed to create temporary directory: {}", e))
+    })?;
+    tracker.add_directory(dir_path.clone());
+    Ok(dir_path)
+}
+
+/// Clean up all temporary files in the temp directory (emergency cleanup)
+pub async fn cleanup_all_temp_files() -> CommandResult<()> {
+    let temp_dir = get_temp_dir()?;
+
+    if temp_dir.exists() {
+        let entries = std::fs::read_dir(&temp_dir)
+            .map_err(|e| CommandError::io_error(format!("Failed to read temp directory: {}", e)))?;
+
+        for entry in entries {
+            if let Ok(entry) = entry {
+                let path = entry.path();
+                if path.is_file() {
+                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    // Clean up all temporary files (concat, trimmed clips, etc.)
+                    if filename.ends_with(".txt") || 
+                       filename.starts_with("trimmed_clip_") ||
+                       filename.starts_with("temp_") ||
+                       filename.starts_with("export_") {
+                        if let Err(e) = std::fs::remove_file(&path) {
+                            eprintln!("Warning: Failed to delete temp file {}: {}", filename, e);
+                        }
+                    }
+                } else if path.is_dir() {
+                    let dirname = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    // Clean up temporary directories
+                    if dirname.starts_with("temp_") || dirname.starts_with("export_") {
+                        if let Err(e) = std::fs::remove_dir_all(&path) {
+                            eprintln!("Warning: Failed to delete temp directory {}: {}", dirname, e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Clean up temporary files older than specified hours
+pub async fn cleanup_old_temp_files(max_age_hours: u64) -> CommandResult<()> {
+    let temp_dir = get_temp_dir()?;
+    let cutoff_time = chrono::Utc::now() - chrono::Duration::hours(max_age_hours as i64);
+
+    if temp_dir.exists() {
+        let entries = std::fs::read_dir(&temp_dir)
+            .map_err(|e| CommandError::io_error(format!("Failed to read temp directory: {}", e)))?;
+
+        for entry in entries {
+            if let Ok(entry) = entry {
+                let path = entry.path();
+                if let Ok(metadata) = path.metadata() {
+                    if let Ok(modified) = metadata.modified() {
+                        let modified_time = chrono::DateTime::<chrono::Utc>::from(modified);
+                        if modified_time < cutoff_time {
+                            if path.is_file() {
+                                let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                                if filename.ends_with(".txt") || 
+                                   filename.starts_with("trimmed_clip_") ||
+                                   filename.starts_with("temp_") ||
+                                   filename.starts_with("export_") {
+                                    if let Err(e) = std::fs::remove_file(&path) {
+                                        eprintln!("Warning: Failed to delete old temp file {}: {}", filename, e);
+                                    }
+                                }
+                            } else if path.is_dir() {
+                                let dirname = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                                if dirname.starts_with("temp_") || dirname.starts_with("export_") {
+                                    if let Err(e) = std::fs::remove_dir_all(&path) {
+                                        eprintln!("Warning: Failed to delete old temp directory {}: {}", dirname, e);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Get temporary file size
+pub fn get_temp_file_size(file_path: &str) -> CommandResult<u64> {
+    let metadata = std::fs::metadata(file_path)
+        .map_err(|e| CommandError::io_error(format!("Failed to get file metadata: {}", e)))?;
+    Ok(metadata.len())
+}
+
+/// Get total size of all temporary files
+pub async fn get_total_temp_files_size() -> CommandResult<u64> {
+    let temp_dir = get_temp_dir()?;
+    let mut total_size = 0u64;
+
+    if temp_dir.exists() {
+        let entries = std::fs::read_dir(&temp_dir)
+            .map_err(|e| CommandError::io_error(format!("Failed to read temp directory: {}", e)))?;
+
+        for entry in entries {
+            if let Ok(entry) = entry {
+                let path = entry.path();
+                if path.is_file() {
+                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    if filename.ends_with(".txt") || 
+                       filename.starts_with("trimmed_clip_") ||
+                       filename.starts_with("temp_") ||
+                       filename.starts_with("export_") {
+                        if let Ok(metadata) = path.metadata() {
+                            total_size += metadata.len();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(total_size)
+}
+
+// ============================================================================
+// PROGRESS PARSING FUNCTIONS
+// ============================================================================
+
+/// Parse FFmpeg progress from stderr output
+pub fn parse_ffmpeg_progress(stderr_line: &str, total_duration: f64) -> Option<ExportProgress> {
+    // FFmpeg progress patterns to match
+    let frame_regex = Regex::new(r"frame=\s*(\d+)").unwrap();
+    let fps_regex = Regex::new(r"fps=\s*([\d.]+)").unwrap();
+    let time_regex = Regex::new(r"time=(\d{2}:\d{2}:\d{2}\.\d{2})").unwrap();
+    let bitrate_regex = Regex::new(r"bitrate=\s*([\d.]+)\s*kbits/s").unwrap();
+    let speed_regex = Regex::new(r"speed=\s*([\d.]+)x").unwrap();
+
+    // Extract frame number
+    let current_frame = frame_regex
+        .captures(stderr_line)
+        .and_then(|cap| cap.get(1))
+        .and_then(|m| m.as_str().parse::<u64>().ok());
+
+    // Extract FPS
+    let fps = fps_regex
+        .captures(stderr_line)
+        .and_then(|cap| cap.get(1))
+        .and_then(|m| m.as_str().parse::<f64>().ok());
+
+    // Extract time
+    let time = time_regex
+        .captures(stderr_line)
+        .and_then(|cap| cap.get(1))
+        .map(|m| m.as_str().to_string());
+
+    // Extract bitrate
+    let bitrate = bitrate_regex
+        .captures(stderr_line)
+        .and_then(|cap| cap.get(1))
+        .and_then(|m| m.as_str().parse::<f64>().ok());
+
+    // Extract speed
+    let speed = speed_regex
+        .captures(stderr_line)
+        .and_then(|cap| cap.get(1))
+        .and_then(|m| m.as_str().parse::<f64>().ok());
+
+    // Calculate progress based on time
+    if let Some(time_str) = &time {
+        if let Ok(current_time) = parse_time_to_seconds(time_str) {
+            let progress = if total_duration > 0.0 {
+                (current_time / total_duration * 100.0).min(100.0)
+            } else {
+                0.0
+            };
+
+            // Calculate estimated time remaining
+            let estimated_remaining = if let Some(speed) = speed {
+                if speed > 0.0 {
+                    (total_duration - current_time) / speed
+                } else {
+                    0.0
+                }
+            } else {
+                0.0
+            };
+
+            return Some(ExportProgress {
+                progress,
+                current_step: "Exporting".to_string(),
+                estimated_time_remaining: estimated_remaining,
+                error: None,
+                current_frame,
+                total_frames: None, // We don't have total frames from FFmpeg output
+                fps,
+                bitrate,
+                time: Some(time_str.clone()),
+            });
+        }
+    }
+
+    None
+}
+
+/// Parse time string (HH:MM:SS.mm) to seconds
+fn parse_time_to_seconds(time_str: &str) -> Result<f64, Box<dyn std::error::Error>> {
+    let parts: Vec<&str> = time_str.split(':').collect();
+    if parts.len() != 3 {
+        return Err("Invalid time format".into());
+    }
+
+    let hours: f64 = parts[0].parse()?;
+    let minutes: f64 = parts[1].parse()?;
+    let seconds: f64 = parts[2].parse()?;
+
+    Ok(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Create a progress update for export start
+pub fn create_export_start_progress() -> ExportProgress {
+    ExportProgress {
+        progress: 0.0,
+        current_step: "Preparing".to_string(),
+        estimated_time_remaining: 0.0,
+        error: None,
+        current_frame: None,
+        total_frames: None,
+        fps: None,
+        bitrate: None,
+        time: None,
+    }
+}
+
+/// Create a progress update for export completion
+pub fn create_export_complete_progress() -> ExportProgress {
+    ExportProgress {
+        progress: 100.0,
+        current_step: "Completed".to_string(),
+        estimated_time_remaining: 0.0,
+        error: None,
+        current_frame: None,
+        total_frames: None,
+        fps: None,
+        bitrate: None,
+        time: None,
+    }
+}
+
+/// Create a progress update for export error
+pub fn create_export_error_progress(error_message: String) -> ExportProgress {
+    ExportProgress {
+        progress: 0.0,
+        current_step: "Failed".to_string(),
+        estimated_time_remaining: 0.0,
+        error: Some(error_message),
+        current_frame: None,
+        total_frames: None,
+        fps: None,
+        bitrate: None,
+        time: None,
+    }
+}
+
+// ============================================================================
+// UNIT TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::export::{ExportTimelineRequest, TimelineExportClip, ExportProgressUpdate, ExportStatus, ExportJob};
+
+    #[test]
+    fn test_parse_ffmpeg_progress() {
+        let stderr_line = "frame= 1234 fps=30.0 q=23.0 size=   10240kB time=00:00:41.13 bitrate=2048.0kbits/s speed=1.0x";
+        let total_duration = 60.0; // 60 seconds
+
+        let progress = parse_ffmpeg_progress(stderr_line, total_duration);
+
+        assert!(progress.is_some());
+        let progress = progress.unwrap();
+
+        assert_eq!(progress.current_frame, Some(1234));
+        assert_eq!(progress.fps, Some(30.0));
+        assert_eq!(progress.bitrate, Some(2048.0));
+        assert_eq!(progress.time, Some("00:00:41.13".to_string()));
+        assert!(progress.progress > 0.0);
+        assert!(progress.progress < 100.0);
+        assert_eq!(progress.current_step, "Exporting");
+    }
+
+    #[test]
+    fn test_parse_ffmpeg_progress_invalid_line() {
+        let stderr_line = "This is not a progress line";
+        let total_duration = 60.0;
+
+        let progress = parse_ffmpeg_progress(stderr_line, total_duration);
+        assert!(progress.is_none());
+    }
+
+    #[test]
+    fn test_parse_ffmpeg_progress_complete() {
+        let stderr_line = "frame= 1800 fps=30.0 q=23.0 size=   15360kB time=00:01:00.00 bitrate=2048.0kbits/s speed=1.0x";
+        let total_duration = 60.0; // 60 seconds
+
+        let progress = parse_ffmpeg_progress(stderr_line, total_duration);
+
+        assert!(progress.is_some());
+        let progress = progress.unwrap();
+
+        assert_eq!(progress.progress, 100.0);
+        assert_eq!(progress.current_step, "Exporting");
+    }
+
+    #[test]
+    fn test_parse_time_to_seconds() {
+        assert_eq!(parse_time_to_seconds("00:00:30.50").unwrap(), 30.5);
+        assert_eq!(parse_time_to_seconds("00:01:30.00").unwrap(), 90.0);
+        assert_eq!(parse_time_to_seconds("01:00:00.00").unwrap(), 3600.0);
+        assert_eq!(parse_time_to_seconds("01:30:45.25").unwrap(), 5445.25);
+    }
+
+    #[test]
+    fn test_parse_time_to_seconds_invalid() {
+        assert!(parse_time_to_seconds("invalid").is_err());
+        assert!(parse_time_to_seconds("00:00").is_err());
+        assert!(parse_time_to_seconds("").is_err());
+    }
+
+    #[test]
+    fn test_create_export_start_progress() {
+        let progress = create_export_start_progress();
+
+        assert_eq!(progress.progress, 0.0);
+        assert_eq!(progress.current_step, "Preparing");
+        assert_eq!(progress.estimated_time_remaining, 0.0);
+        assert!(progress.error.is_none());
+    }
+
+    #[test]
+    fn test_create_export_complete_progress() {
+        let progress = create_export_complete_progress();
+
+        assert_eq!(progress.progress, 100.0);
+        assert_eq!(progress.current_step, "Completed");
+        assert_eq!(progress.estimated_time_remaining, 0.0);
+        assert!(progress.error.is_none());
+    }
+
+    #[test]
+    fn test_create_export_error_progress() {
+        let error_message = "Export failed".to_string();
+        let progress = create_export_error_progress(error_message.clone());
+
+        assert_eq!(progress.progress, 0.0);
+        assert_eq!(progress.current_step, "Failed");
+        assert_eq!(progress.estimated_time_remaining, 0.0);
+        assert_eq!(progress.error, Some(error_message));
+    }
+
+    #[test]
+    fn test_validate_export_settings() {
+        let valid_settings = ExportSettings {
+            resolution: "1080p".to_string(),
+            quality: "high".to_string(),
+            format: "mp4".to_string(),
+            codec: "h264".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        };
+
+        assert!(validate_export_settings(&valid_settings, None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_export_settings_invalid_resolution() {
+        let invalid_settings = ExportSettings {
+            resolution: "invalid".to_string(),
+            quality: "high".to_string(),
+            format: "mp4".to_string(),
+            codec: "h264".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        };
+
+        assert!(validate_export_settings(&invalid_settings, None).is_err());
+    }
+
+    #[test]
+    fn test_validate_export_settings_invalid_quality() {
+        let invalid_settings = ExportSettings {
+            resolution: "1080p".to_string(),
+            quality: "invalid".to_string(),
+            format: "mp4".to_string(),
+            codec: "h264".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        };
+
+        assert!(validate_export_settings(&invalid_settings, None).is_err());
+    }
+
+    #[test]
+    fn test_validate_export_settings_invalid_format() {
+        let invalid_settings = ExportSettings {
+            resolution: "1080p".to_string(),
+            quality: "high".to_string(),
+            format: "avi".to_string(),
+            codec: "h264".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        };
+
+        assert!(validate_export_settings(&invalid_settings, None).is_err());
+    }
+
+    #[test]
+    fn test_validate_export_settings_invalid_codec() {
+        let invalid_settings = ExportSettings {
+            resolution: "1080p".to_string(),
+            quality: "high".to_string(),
+            format: "mp4".to_string(),
+            codec: "h265".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        };
+
+        assert!(validate_export_settings(&invalid_settings, None).is_err());
+    }
+
+    #[test]
+    fn test_get_resolution_args() {
+        assert_eq!(get_resolution_args("1080p", 1920, 1080), vec!["-vf", "scale=1920:1080"]);
+        assert_eq!(get_resolution_args("720p", 1920, 1080), vec!["-vf", "scale=1280:720"]);
+        assert_eq!(get_resolution_args("source", 1920, 1080), Vec::<String>::new());
+        assert_eq!(get_resolution_args("invalid", 1920, 1080), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_get_quality_args() {
+        assert_eq!(get_quality_args("high"), vec!["-crf", "18"]);
+        assert_eq!(get_quality_args("medium"), vec!["-crf", "23"]);
+        assert_eq!(get_quality_args("low"), vec!["-crf", "28"]);
+        assert_eq!(get_quality_args("invalid"), vec!["-crf", "23"]);
+    }
+
+    #[test]
+    fn test_estimate_export_time() {
+        let clips = vec![
+        ExportClip {
+            file_path: "clip1.mp4".to_string(),
+            start_time: 0.0,
+            duration: 10.0,
+            trim_start: 0.0,
+            trim_end: 10.0,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+            ExportClip {
+                file_path: "clip2.mp4".to_string(),
+                start_time: 10.0,
+                duration: 20.0,
+                trim_start: 0.0,
+                trim_end: 20.0,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 20.0,
+        },
+        ];
+
+        let settings = ExportSettings {
             resolution: "1080p".to_string(),
             quality: "high".to_string(),
             format: "mp4".to_string(),
-            codec: "h265".to_string(),
+            codec: "h264".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        };
+
+        let estimated_time = estimate_export_time(&clips, &settings);
+        assert!(estimated_time > 0.0);
+        assert!(estimated_time < 30.0); // Should be reasonable
+    }
+
+    #[test]
+    fn test_check_clip_compatibility() {
+        let clips = vec![
+        ExportClip {
+            file_path: "clip1.mp4".to_string(),
+            start_time: 0.0,
+            duration: 10.0,
+            trim_start: 0.0,
+            trim_end: 10.0,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+        ];
+
+        assert!(check_clip_compatibility(&clips).is_ok());
+    }
+
+    #[test]
+    fn test_check_clip_compatibility_empty() {
+        let clips = vec![];
+        assert!(check_clip_compatibility(&clips).is_err());
+    }
+
+    #[test]
+    fn test_estimate_export_size() {
+        let clips = vec![
+        ExportClip {
+            file_path: "clip1.mp4".to_string(),
+            start_time: 0.0,
+            duration: 10.0,
+            trim_start: 0.0,
+            trim_end: 10.0,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+        ];
+
+        let settings = ExportSettings {
+            resolution: "1080p".to_string(),
+            quality: "high".to_string(),
+            format: "mp4".to_string(),
+            codec: "h264".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        };
+
+        let estimated_size = estimate_export_size(&clips, &settings);
+        assert!(estimated_size > 0);
+    }
+
+    #[test]
+    fn test_export_clip_creation() {
+        let clip = ExportClip {
+            file_path: "test.mp4".to_string(),
+            start_time: 0.0,
+            duration: 10.0,
+            trim_start: 0.0,
+            trim_end: 10.0,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+        };
+
+        assert_eq!(clip.file_path, "test.mp4");
+        assert_eq!(clip.start_time, 0.0);
+        assert_eq!(clip.duration, 10.0);
+        assert_eq!(clip.trim_start, 0.0);
+        assert_eq!(clip.trim_end, 10.0);
+    }
+
+    #[test]
+    fn test_export_settings_creation() {
+        let settings = ExportSettings {
+            resolution: "1080p".to_string(),
+            quality: "high".to_string(),
+            format: "mp4".to_string(),
+            codec: "h264".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        };
+
+        assert_eq!(settings.resolution, "1080p");
+        assert_eq!(settings.quality, "high");
+        assert_eq!(settings.format, "mp4");
+        assert_eq!(settings.codec, "h264");
+    }
+
+    #[test]
+    fn test_export_progress_creation() {
+        let progress = ExportProgress {
+            progress: 50.0,
+            current_step: "Exporting".to_string(),
+            estimated_time_remaining: 30.0,
+            error: None,
+            current_frame: Some(1000),
+            total_frames: Some(2000),
+            fps: Some(30.0),
+            bitrate: Some(2048.0),
+            time: Some("00:00:30.00".to_string()),
+        };
+
+        assert_eq!(progress.progress, 50.0);
+        assert_eq!(progress.current_step, "Exporting");
+        assert_eq!(progress.estimated_time_remaining, 30.0);
+        assert!(progress.error.is_none());
+        assert_eq!(progress.current_frame, Some(1000));
+        assert_eq!(progress.total_frames, Some(2000));
+        assert_eq!(progress.fps, Some(30.0));
+        assert_eq!(progress.bitrate, Some(2048.0));
+        assert_eq!(progress.time, Some("00:00:30.00".to_string()));
+    }
+
+    #[test]
+    fn test_export_timeline_request_creation() {
+        let request = ExportTimelineRequest {
+            timeline_clips: vec![
+                TimelineExportClip {
+                    file_path: "clip1.mp4".to_string(),
+                    start_time: 0.0,
+                    duration: 10.0,
+                    trim_start: 0.0,
+                    trim_end: 10.0,
+                    track_id: "track1".to_string(),
+                }
+            ],
+            output_path: "/path/to/output.mp4".to_string(),
+            filename: "output.mp4".to_string(),
+            in_point: None,
+            out_point: None,
+            markers: vec![],
+            project_id: None,
+            settings: ExportSettings {
+                resolution: "1080p".to_string(),
+                quality: "high".to_string(),
+                format: "mp4".to_string(),
+                codec: "h264".to_string(),
+                rate_control: "quality".to_string(),
+                crf: None,
+                video_bitrate_kbps: None,
+                target_size_mb: None,
+            },
+        };
+
+        assert_eq!(request.timeline_clips.len(), 1);
+        assert_eq!(request.output_path, "/path/to/output.mp4");
+        assert_eq!(request.filename, "output.mp4");
+        assert_eq!(request.settings.resolution, "1080p");
+    }
+
+    #[test]
+    fn test_export_progress_update_creation() {
+        let progress = ExportProgressUpdate {
+            progress: 50.0,
+            current_step: "Exporting".to_string(),
+            estimated_time_remaining: 30.0,
+            error: None,
+        };
+
+        assert_eq!(progress.progress, 50.0);
+        assert_eq!(progress.current_step, "Exporting");
+        assert_eq!(progress.estimated_time_remaining, 30.0);
+        assert!(progress.error.is_none());
+    }
+
+    #[test]
+    fn test_export_status_enum() {
+        let status = ExportStatus::Preparing;
+        assert!(matches!(status, ExportStatus::Preparing));
+
+        let status = ExportStatus::Exporting;
+        assert!(matches!(status, ExportStatus::Exporting));
+
+        let status = ExportStatus::Completed;
+        assert!(matches!(status, ExportStatus::Completed));
+
+        let status = ExportStatus::Failed;
+        assert!(matches!(status, ExportStatus::Failed));
+
+        let status = ExportStatus::Cancelled;
+        assert!(matches!(status, ExportStatus::Cancelled));
+    }
+
+    #[test]
+    fn test_export_job_creation() {
+        let job = ExportJob {
+            id: "export_123".to_string(),
+            status: ExportStatus::Exporting,
+            progress: 75.0,
+            output_path: Some("/path/to/output.mp4".to_string()),
+            error_message: None,
+            created_at: chrono::Utc::now(),
+        };
+
+        assert_eq!(job.id, "export_123");
+        assert!(matches!(job.status, ExportStatus::Exporting));
+        assert_eq!(job.progress, 75.0);
+        assert_eq!(job.output_path, Some("/path/to/output.mp4".to_string()));
+        assert!(job.error_message.is_none());
+    }
+
+    // ============================================================================
+    // TRIM VALIDATION TESTS
+    // ============================================================================
+
+    #[test]
+    fn test_validate_trim_data_valid() {
+        let result = validate_trim_data(5.0, 10.0, 15.0, 0);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_trim_data_negative_start() {
+        let result = validate_trim_data(-1.0, 10.0, 15.0, 0);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("negative trim start"));
+    }
+
+    #[test]
+    fn test_validate_trim_data_invalid_range() {
+        let result = validate_trim_data(10.0, 5.0, 15.0, 0);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("invalid trim range"));
+    }
+
+    #[test]
+    fn test_validate_trim_data_end_equals_start() {
+        let result = validate_trim_data(5.0, 5.0, 15.0, 0);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("invalid trim range"));
+    }
+
+    #[test]
+    fn test_validate_trim_data_exceeds_duration() {
+        let result = validate_trim_data(5.0, 20.0, 15.0, 0);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("exceeds original video duration"));
+    }
+
+    #[test]
+    fn test_validate_trim_data_too_short() {
+        let result = validate_trim_data(5.0, 5.05, 15.0, 0);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("duration too short"));
+    }
+
+    #[test]
+    fn test_validate_trim_data_minimum_duration() {
+        let result = validate_trim_data(5.0, 5.11, 15.0, 0);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_needs_trimming_true() {
+        assert!(needs_trimming(5.0, 10.0, 15.0));
+        assert!(needs_trimming(0.0, 10.0, 15.0));
+        assert!(needs_trimming(5.0, 15.0, 15.0));
+    }
+
+    #[test]
+    fn test_needs_trimming_false() {
+        assert!(!needs_trimming(0.0, 15.0, 15.0));
+        assert!(!needs_trimming(0.01, 14.99, 15.0)); // Within tolerance
+    }
+
+    #[test]
+    fn test_calculate_trimmed_duration() {
+        assert_eq!(calculate_trimmed_duration(5.0, 10.0), 5.0);
+        assert_eq!(calculate_trimmed_duration(0.0, 15.0), 15.0);
+        assert_eq!(calculate_trimmed_duration(2.5, 7.5), 5.0);
+    }
+
+    #[test]
+    fn test_validate_export_clips_trim_data_valid() {
+        let clips = vec![
+            ExportClip {
+                file_path: "clip1.mp4".to_string(),
+                start_time: 0.0,
+                duration: 5.0,
+                trim_start: 0.0,
+                trim_end: 5.0,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+            ExportClip {
+                file_path: "clip2.mp4".to_string(),
+                start_time: 5.0,
+                duration: 3.0,
+                trim_start: 2.0,
+                trim_end: 5.0,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+        ];
+
+        let result = validate_export_clips_trim_data(&clips);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_export_clips_trim_data_empty() {
+        let clips = vec![];
+        let result = validate_export_clips_trim_data(&clips);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("No clips to export"));
+    }
+
+    #[test]
+    fn test_validate_export_clips_trim_data_invalid() {
+        let clips = vec![
+            ExportClip {
+                file_path: "clip1.mp4".to_string(),
+                start_time: 0.0,
+                duration: 5.0,
+                trim_start: -1.0, // Invalid
+                trim_end: 5.0,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+        ];
+
+        let result = validate_export_clips_trim_data(&clips);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("negative trim start"));
+    }
+
+    #[test]
+    fn test_get_clips_needing_trimming() {
+        let clips = vec![
+            ExportClip {
+                file_path: "clip1.mp4".to_string(),
+                start_time: 0.0,
+                duration: 5.0,
+                trim_start: 0.0,
+                trim_end: 5.0, // No trimming needed
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+            ExportClip {
+                file_path: "clip2.mp4".to_string(),
+                start_time: 5.0,
+                duration: 3.0,
+                trim_start: 2.0,
+                trim_end: 5.0, // Trimming needed
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+            ExportClip {
+                file_path: "clip3.mp4".to_string(),
+                start_time: 8.0,
+                duration: 2.0,
+                trim_start: 0.0,
+                trim_end: 2.0, // No trimming needed
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+        ];
+
+        let indices = get_clips_needing_trimming(&clips);
+        assert_eq!(indices, vec![1]); // Only clip 2 needs trimming
+    }
+
+    #[test]
+    fn test_get_clips_not_needing_trimming() {
+        let clips = vec![
+            ExportClip {
+                file_path: "clip1.mp4".to_string(),
+                start_time: 0.0,
+                duration: 5.0,
+                trim_start: 0.0,
+                trim_end: 5.0, // No trimming needed
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+            ExportClip {
+                file_path: "clip2.mp4".to_string(),
+                start_time: 5.0,
+                duration: 3.0,
+                trim_start: 2.0,
+                trim_end: 5.0, // Trimming needed
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+            ExportClip {
+                file_path: "clip3.mp4".to_string(),
+                start_time: 8.0,
+                duration: 2.0,
+                trim_start: 0.0,
+                trim_end: 2.0, // No trimming needed
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 10.0,
+        },
+        ];
+
+        let indices = get_clips_not_needing_trimming(&clips);
+        assert_eq!(indices, vec![0, 2]); // Clips 1 and 3 don't need trimming
+    }
+
+    // ============================================================================
+    // FFMPEG TRIM FUNCTIONALITY TESTS
+    // ============================================================================
+
+    #[test]
+    fn test_generate_trim_command_args_with_copy_codec() {
+        let args = generate_trim_command_args(
+            "input.mp4",
+            "output.mp4",
+            5.0,
+            10.0,
+            true, // use_copy_codec
+            &ExportEncoders::default(),
+        );
+
+        assert!(args.contains(&"-i".to_string()));
+        assert!(args.contains(&"input.mp4".to_string()));
+        assert!(args.contains(&"-ss".to_string()));
+        assert!(args.contains(&"5.000".to_string()));
+        assert!(args.contains(&"-t".to_string()));
+        assert!(args.contains(&"10.000".to_string()));
+        assert!(args.contains(&"-c".to_string()));
+        assert!(args.contains(&"copy".to_string()));
+        assert!(args.contains(&"-y".to_string()));
+        assert!(args.contains(&"output.mp4".to_string()));
+    }
+
+    #[test]
+    fn test_generate_trim_command_args_without_copy_codec() {
+        let args = generate_trim_command_args(
+            "input.mp4",
+            "output.mp4",
+            5.0,
+            10.0,
+            false, // use_copy_codec
+            &ExportEncoders::default(),
+        );
+
+        assert!(args.contains(&"-i".to_string()));
+        assert!(args.contains(&"input.mp4".to_string()));
+        assert!(args.contains(&"-ss".to_string()));
+        assert!(args.contains(&"5.000".to_string()));
+        assert!(args.contains(&"-t".to_string()));
+        assert!(args.contains(&"10.000".to_string()));
+        assert!(args.contains(&"-c:v".to_string()));
+        assert!(args.contains(&"libx264".to_string()));
+        assert!(args.contains(&"-c:a".to_string()));
+        assert!(args.contains(&"aac".to_string()));
+        assert!(args.contains(&"-y".to_string()));
+        assert!(args.contains(&"output.mp4".to_string()));
+
+        let encoders = ExportEncoders {
+            video: "h264_videotoolbox".to_string(),
+            audio: "libfdk_aac".to_string(),
         };
+        let args = generate_trim_command_args("input.wmv", "output.wmv", 5.0, 10.0, false, &encoders);
+        assert!(args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "h264_videotoolbox"));
+        assert!(args.windows(2).any(|w| w[0] == "-c:a" && w[1] == "libfdk_aac"));
+        assert!(!args.contains(&"libx264".to_string()));
+    }
+
+    #[test]
+    fn test_trim_video_clip_with_runner() {
+        let runner = crate::ffmpeg::runner::MockFfmpegRunner::new()
+            .with_output(0, "", "")
+            .with_output(1, "", "input.mp4: Invalid data found when processing input");
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        runtime
+            .block_on(trim_video_clip_with_runner(&runner, "input.mp4", "output.mp4", 5.0, 10.0, true, &ExportEncoders::default()))
+            .unwrap();
+        assert_eq!(
+            runner.invocations()[0].1,
+            generate_trim_command_args("input.mp4", "output.mp4", 5.0, 10.0, true, &ExportEncoders::default())
+        );
+
+        let error = runtime
+            .block_on(trim_video_clip_with_runner(&runner, "input.mp4", "output.mp4", 5.0, 10.0, true, &ExportEncoders::default()))
+            .unwrap_err();
+        assert!(error.message.contains("Invalid data found"));
+    }
+
+    #[test]
+    fn test_create_temp_trim_file_path() {
+        let result = create_temp_trim_file_path(0, "mp4");
+        assert!(result.is_ok());
+        let path = result.unwrap();
+        assert!(path.contains("trimmed_clip_0_"));
+        assert!(path.ends_with(".mp4"));
+    }
+
+    #[test]
+    fn test_get_file_extension() {
+        assert_eq!(get_file_extension("video.mp4"), "mp4");
+        assert_eq!(get_file_extension("video.MOV"), "mov");
+        assert_eq!(get_file_extension("video.avi"), "avi");
+        assert_eq!(get_file_extension("video"), "mp4"); // default
+        assert_eq!(get_file_extension("video.mkv"), "mkv");
+    }
+
+    #[test]
+    fn test_supports_copy_codec() {
+        assert!(supports_copy_codec("mp4"));
+        assert!(supports_copy_codec("mov"));
+        assert!(supports_copy_codec("avi"));
+        assert!(supports_copy_codec("mkv"));
+        assert!(supports_copy_codec("webm"));
+        assert!(!supports_copy_codec("wmv"));
+        assert!(!supports_copy_codec("flv"));
+        assert!(!supports_copy_codec("unknown"));
+    }
+
+    #[test]
+    fn test_trim_command_precision() {
+        let args = generate_trim_command_args(
+            "input.mp4",
+            "output.mp4",
+            1.234567,
+            5.678901,
+            true,
+            &ExportEncoders::default(),
+        );
 
-        assert!(validate_export_settings(&invalid_settings).is_err());
+        // Check that precision is limited to 3 decimal places
+        assert!(args.contains(&"1.235".to_string()));
+        assert!(args.contains(&"5.679".to_string()));
     }
 
+    // ============================================================================
+    // TEMPORARY FILE MANAGEMENT TESTS
+    // ============================================================================
+
     #[test]
-    fn test_get_resolution_args() {
-        assert_eq!(get_resolution_args("1080p", 1920, 1080), vec!["-vf", "scale=1920:1080"]);
-        assert_eq!(get_resolution_args("720p", 1920, 1080), vec!["-vf", "scale=1280:720"]);
-        assert_eq!(get_resolution_args("source", 1920, 1080), Vec::<String>::new());
-        assert_eq!(get_resolution_args("invalid", 1920, 1080), Vec::<String>::new());
+    fn test_temp_file_tracker_creation() {
+        let tracker = TempFileTracker::new();
+        assert!(tracker.is_empty());
+        assert_eq!(tracker.file_count(), 0);
+        assert_eq!(tracker.directory_count(), 0);
     }
 
     #[test]
-    fn test_get_quality_args() {
-        assert_eq!(get_quality_args("high"), vec!["-crf", "18"]);
-        assert_eq!(get_quality_args("medium"), vec!["-crf", "23"]);
-        assert_eq!(get_quality_args("low"), vec!["-crf", "28"]);
-        assert_eq!(get_quality_args("invalid"), vec!["-crf", "23"]);
+    fn test_temp_file_tracker_add_file() {
+        let mut tracker = TempFileTracker::new();
+        tracker.add_file("test_file.txt".to_string());
+        assert!(!tracker.is_empty());
+        assert_eq!(tracker.file_count(), 1);
+        assert_eq!(tracker.directory_count(), 0);
     }
 
     #[test]
-    fn test_estimate_export_time() {
-        let clips = vec![
-        ExportClip {
-            file_path: "clip1.mp4".to_string(),
-            start_time: 0.0,
-            duration: 10.0,
-            trim_start: 0.0,
-            trim_end: 10.0,
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-            ExportClip {
-                file_path: "clip2.mp4".to_string(),
-                start_time: 10.0,
-                duration: 20.0,
-                trim_start: 0.0,
-                trim_end: 20.0,
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 20.0,
-        },
-        ];
-
-        let settings = ExportSettings {
-            resolution: "1080p".to_string(),
-            quality: "high".to_string(),
-            format: "mp4".to_string(),
-            codec: "h264".to_string(),
-        };
-
-        let estimated_time = estimate_export_time(&clips, &settings);
-        assert!(estimated_time > 0.0);
-        assert!(estimated_time < 30.0); // Should be reasonable
+    fn test_temp_file_tracker_add_directory() {
+        let mut tracker = TempFileTracker::new();
+        tracker.add_directory("test_dir".to_string());
+        assert!(!tracker.is_empty());
+        assert_eq!(tracker.file_count(), 0);
+        assert_eq!(tracker.directory_count(), 1);
     }
 
     #[test]
-    fn test_check_clip_compatibility() {
-        let clips = vec![
-        ExportClip {
-            file_path: "clip1.mp4".to_string(),
-            start_time: 0.0,
-            duration: 10.0,
-            trim_start: 0.0,
-            trim_end: 10.0,
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-        ];
-
-        assert!(check_clip_compatibility(&clips).is_ok());
+    fn test_temp_file_tracker_add_multiple() {
+        let mut tracker = TempFileTracker::new();
+        tracker.add_file("file1.txt".to_string());
+        tracker.add_file("file2.txt".to_string());
+        tracker.add_directory("dir1".to_string());
+        tracker.add_directory("dir2".to_string());
+        
+        assert!(!tracker.is_empty());
+        assert_eq!(tracker.file_count(), 2);
+        assert_eq!(tracker.directory_count(), 2);
     }
 
     #[test]
-    fn test_check_clip_compatibility_empty() {
-        let clips = vec![];
-        assert!(check_clip_compatibility(&clips).is_err());
+    fn test_generate_unique_temp_filename() {
+        let result1 = generate_unique_temp_filename("test", "txt");
+        let result2 = generate_unique_temp_filename("test", "txt");
+        
+        assert!(result1.is_ok());
+        assert!(result2.is_ok());
+        
+        let filename1 = result1.unwrap();
+        let filename2 = result2.unwrap();
+        
+        // Should be different due to nanosecond precision
+        assert_ne!(filename1, filename2);
+        
+        // Should contain the prefix and extension
+        assert!(filename1.contains("test_"));
+        assert!(filename1.ends_with(".txt"));
+        assert!(filename2.contains("test_"));
+        assert!(filename2.ends_with(".txt"));
     }
 
     #[test]
-    fn test_estimate_export_size() {
-        let clips = vec![
-        ExportClip {
-            file_path: "clip1.mp4".to_string(),
-            start_time: 0.0,
-            duration: 10.0,
-            trim_start: 0.0,
-            trim_end: 10.0,
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-        ];
-
-        let settings = ExportSettings {
-            resolution: "1080p".to_string(),
-            quality: "high".to_string(),
-            format: "mp4".to_string(),
-            codec: "h264".to_string(),
-        };
-
-        let estimated_size = estimate_export_size(&clips, &settings);
-        assert!(estimated_size > 0);
+    fn test_create_temp_file_path() {
+        let result = create_temp_file_path("export", "mp4");
+        assert!(result.is_ok());
+        
+        let file_path = result.unwrap();
+        assert!(file_path.contains("export_"));
+        assert!(file_path.ends_with(".mp4"));
     }
 
     #[test]
-    fn test_export_clip_creation() {
-        let clip = ExportClip {
-            file_path: "test.mp4".to_string(),
-            start_time: 0.0,
-            duration: 10.0,
-            trim_start: 0.0,
-            trim_end: 10.0,
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-        };
-
-        assert_eq!(clip.file_path, "test.mp4");
-        assert_eq!(clip.start_time, 0.0);
-        assert_eq!(clip.duration, 10.0);
-        assert_eq!(clip.trim_start, 0.0);
-        assert_eq!(clip.trim_end, 10.0);
+    fn test_create_temp_dir_path() {
+        let result = create_temp_dir_path("export");
+        assert!(result.is_ok());
+        
+        let dir_path = result.unwrap();
+        assert!(dir_path.contains("export_"));
     }
 
     #[test]
-    fn test_export_settings_creation() {
-        let settings = ExportSettings {
-            resolution: "1080p".to_string(),
-            quality: "high".to_string(),
-            format: "mp4".to_string(),
-            codec: "h264".to_string(),
-        };
-
-        assert_eq!(settings.resolution, "1080p");
-        assert_eq!(settings.quality, "high");
-        assert_eq!(settings.format, "mp4");
-        assert_eq!(settings.codec, "h264");
+    fn test_get_temp_file_size() {
+        // Create a temporary file for testing
+        let temp_dir = std::env::temp_dir();
+        let test_file = temp_dir.join("test_size.txt");
+        let test_content = "Hello, World!";
+        
+        std::fs::write(&test_file, test_content).unwrap();
+        
+        let result = get_temp_file_size(test_file.to_str().unwrap());
+        assert!(result.is_ok());
+        
+        let size = result.unwrap();
+        assert_eq!(size, test_content.len() as u64);
+        
+        // Clean up
+        std::fs::remove_file(&test_file).unwrap();
     }
 
     #[test]
-    fn test_export_progress_creation() {
-        let progress = ExportProgress {
-            progress: 50.0,
-            current_step: "Exporting".to_string(),
-            estimated_time_remaining: 30.0,
-            error: None,
-            current_frame: Some(1000),
-            total_frames: Some(2000),
-            fps: Some(30.0),
-            bitrate: Some(2048.0),
-            time: Some("00:00:30.00".to_string()),
-        };
-
-        assert_eq!(progress.progress, 50.0);
-        assert_eq!(progress.current_step, "Exporting");
-        assert_eq!(progress.estimated_time_remaining, 30.0);
-        assert!(progress.error.is_none());
-        assert_eq!(progress.current_frame, Some(1000));
-        assert_eq!(progress.total_frames, Some(2000));
-        assert_eq!(progress.fps, Some(30.0));
-        assert_eq!(progress.bitrate, Some(2048.0));
-        assert_eq!(progress.time, Some("00:00:30.00".to_string()));
+    fn test_get_temp_file_size_nonexistent() {
+        let result = get_temp_file_size("/nonexistent/file.txt");
+        assert!(result.is_err());
     }
 
     #[test]
-    fn test_export_timeline_request_creation() {
-        let request = ExportTimelineRequest {
-            timeline_clips: vec![
-                TimelineExportClip {
-                    file_path: "clip1.mp4".to_string(),
-                    start_time: 0.0,
-                    duration: 10.0,
-                    trim_start: 0.0,
-                    trim_end: 10.0,
-                    track_id: "track1".to_string(),
-                }
-            ],
-            output_path: "/path/to/output.mp4".to_string(),
-            filename: "output.mp4".to_string(),
-            settings: ExportSettings {
-                resolution: "1080p".to_string(),
-                quality: "high".to_string(),
-                format: "mp4".to_string(),
-                codec: "h264".to_string(),
-            },
-        };
+    fn test_create_temp_trim_file_path_different_index() {
+        let result1 = create_temp_trim_file_path(0, "mp4");
+        let result2 = create_temp_trim_file_path(1, "mp4");
+        
+        assert!(result1.is_ok());
+        assert!(result2.is_ok());
+        
+        let path1 = result1.unwrap();
+        let path2 = result2.unwrap();
+        
+        // Should be different due to different indices
+        assert_ne!(path1, path2);
+        
+        // Should contain correct indices
+        assert!(path1.contains("trimmed_clip_0_"));
+        assert!(path2.contains("trimmed_clip_1_"));
+    }
 
-        assert_eq!(request.timeline_clips.len(), 1);
-        assert_eq!(request.output_path, "/path/to/output.mp4");
-        assert_eq!(request.filename, "output.mp4");
-        assert_eq!(request.settings.resolution, "1080p");
+    // ============================================================================
+    // ATOMIC OUTPUT TESTS
+    // ============================================================================
+
+    #[test]
+    fn test_create_staging_output_path() {
+        let staging_path = create_staging_output_path("/exports/final.mp4").unwrap();
+        let staging = std::path::Path::new(&staging_path);
+
+        // Sibling of the output, hidden, and keeps the container extension
+        assert_eq!(staging.parent(), Some(std::path::Path::new("/exports")));
+        assert!(staging_path.contains("/.final.mp4."));
+        assert!(staging_path.ends_with(".partial.mp4"));
+        assert_ne!(staging_path, "/exports/final.mp4");
     }
 
     #[test]
-    fn test_export_progress_update_creation() {
-        let progress = ExportProgressUpdate {
-            progress: 50.0,
-            current_step: "Exporting".to_string(),
-            estimated_time_remaining: 30.0,
-            error: None,
-        };
+    fn test_get_expected_export_duration_with_gap() {
+        let clips = vec![
+            ExportClip {
+                file_path: "clip1.mp4".to_string(),
+                start_time: 0.0,
+                duration: 10.0,
+                trim_start: 0.0,
+                trim_end: 10.0,
+                track_id: "track1".to_string(),
+                trimmed_file_path: None,
+                original_duration: 10.0,
+            },
+            ExportClip {
+                file_path: "clip2.mp4".to_string(),
+                start_time: 15.0, // 5s gap is dropped by concat
+                duration: 5.0,
+                trim_start: 0.0,
+                trim_end: 5.0,
+                track_id: "track1".to_string(),
+                trimmed_file_path: None,
+                original_duration: 5.0,
+            },
+        ];
 
-        assert_eq!(progress.progress, 50.0);
-        assert_eq!(progress.current_step, "Exporting");
-        assert_eq!(progress.estimated_time_remaining, 30.0);
-        assert!(progress.error.is_none());
+        assert_eq!(get_timeline_duration(&clips), 20.0);
+        assert_eq!(get_expected_export_duration(&clips), 15.0);
     }
 
     #[test]
-    fn test_export_status_enum() {
-        let status = ExportStatus::Preparing;
-        assert!(matches!(status, ExportStatus::Preparing));
+    fn test_verify_export_duration() {
+        assert!(verify_export_duration(15.0, 15.0).is_ok());
+        assert!(verify_export_duration(14.8, 15.0).is_ok()); // Within tolerance
+        assert!(verify_export_duration(16.0, 15.0).is_ok()); // Longer is fine
 
-        let status = ExportStatus::Exporting;
-        assert!(matches!(status, ExportStatus::Exporting));
+        let result = verify_export_duration(7.3, 15.0);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().message.contains("truncated"));
+    }
 
-        let status = ExportStatus::Completed;
-        assert!(matches!(status, ExportStatus::Completed));
+    #[test]
+    fn test_promote_staged_export_replaces_previous_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_path = temp_dir.path().join("final.mp4");
+        let output_str = output_path.to_str().unwrap();
+        std::fs::write(&output_path, b"previous export").unwrap();
 
-        let status = ExportStatus::Failed;
-        assert!(matches!(status, ExportStatus::Failed));
+        let staging_path = create_staging_output_path(output_str).unwrap();
+        std::fs::write(&staging_path, b"new export").unwrap();
 
-        let status = ExportStatus::Cancelled;
-        assert!(matches!(status, ExportStatus::Cancelled));
+        assert!(promote_staged_export(&staging_path, output_str).is_ok());
+        assert_eq!(std::fs::read(&output_path).unwrap(), b"new export");
+        assert!(!std::path::Path::new(&staging_path).exists());
     }
 
     #[test]
-    fn test_export_job_creation() {
-        let job = ExportJob {
-            id: "export_123".to_string(),
-            status: ExportStatus::Exporting,
-            progress: 75.0,
-            output_path: Some("/path/to/output.mp4".to_string()),
-            error_message: None,
-            created_at: chrono::Utc::now(),
-        };
+    fn test_promote_staged_export_missing_staging_keeps_previous_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_path = temp_dir.path().join("final.mp4");
+        let output_str = output_path.to_str().unwrap();
+        std::fs::write(&output_path, b"previous export").unwrap();
 
-        assert_eq!(job.id, "export_123");
-        assert!(matches!(job.status, ExportStatus::Exporting));
-        assert_eq!(job.progress, 75.0);
-        assert_eq!(job.output_path, Some("/path/to/output.mp4".to_string()));
-        assert!(job.error_message.is_none());
+        let staging_path = create_staging_output_path(output_str).unwrap();
+
+        assert!(promote_staged_export(&staging_path, output_str).is_err());
+        assert_eq!(std::fs::read(&output_path).unwrap(), b"previous export");
     }
 
     // ============================================================================
-    // TRIM VALIDATION TESTS
+    // RANGE EXPORT TESTS
     // ============================================================================
 
-    #[test]
-    fn test_validate_trim_data_valid() {
-        let result = validate_trim_data(5.0, 10.0, 15.0, 0);
-        assert!(result.is_ok());
+    fn create_range_clip(file_path: &str, start_time: f64, trim_start: f64, trim_end: f64) -> ExportClip {
+        ExportClip {
+            file_path: file_path.to_string(),
+            start_time,
+            duration: trim_end - trim_start,
+            trim_start,
+            trim_end,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: 30.0,
+        }
     }
 
     #[test]
-    fn test_validate_trim_data_negative_start() {
-        let result = validate_trim_data(-1.0, 10.0, 15.0, 0);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("negative trim start"));
-    }
+    fn test_slice_clips_to_range_straddling_clips() {
+        // Timeline: a.mp4 [0, 10), b.mp4 [10, 20), c.mp4 [20, 30)
+        let clips = vec![
+            create_range_clip("a.mp4", 0.0, 5.0, 15.0),
+            create_range_clip("b.mp4", 10.0, 0.0, 10.0),
+            create_range_clip("c.mp4", 20.0, 0.0, 10.0),
+        ];
 
-    #[test]
-    fn test_validate_trim_data_invalid_range() {
-        let result = validate_trim_data(10.0, 5.0, 15.0, 0);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("invalid trim range"));
-    }
+        let sliced = slice_clips_to_range(&clips, 7.0, 14.0).unwrap();
 
-    #[test]
-    fn test_validate_trim_data_end_equals_start() {
-        let result = validate_trim_data(5.0, 5.0, 15.0, 0);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("invalid trim range"));
+        assert_eq!(sliced.len(), 2);
+        assert_eq!(sliced[0].file_path, "a.mp4");
+        assert_eq!(sliced[0].start_time, 0.0);
+        assert_eq!(sliced[0].trim_start, 12.0);
+        assert_eq!(sliced[0].trim_end, 15.0);
+        assert_eq!(sliced[0].duration, 3.0);
+
+        assert_eq!(sliced[1].file_path, "b.mp4");
+        assert_eq!(sliced[1].start_time, 3.0);
+        assert_eq!(sliced[1].trim_start, 0.0);
+        assert_eq!(sliced[1].trim_end, 4.0);
+        assert_eq!(sliced[1].duration, 4.0);
+        assert!(needs_trimming(sliced[1].trim_start, sliced[1].trim_end, sliced[1].original_duration));
     }
 
     #[test]
-    fn test_validate_trim_data_exceeds_duration() {
-        let result = validate_trim_data(5.0, 20.0, 15.0, 0);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("exceeds original video duration"));
+    fn test_slice_clips_to_range_drops_clips_outside() {
+        let clips = vec![
+            create_range_clip("a.mp4", 0.0, 0.0, 10.0),
+            create_range_clip("b.mp4", 10.0, 0.0, 10.0),
+            create_range_clip("c.mp4", 20.0, 0.0, 10.0),
+        ];
+
+        let sliced = slice_clips_to_range(&clips, 10.0, 20.0).unwrap();
+        assert_eq!(sliced.len(), 1);
+        assert_eq!(sliced[0].file_path, "b.mp4");
+        assert_eq!(get_timeline_duration(&sliced), 10.0);
+
+        // A sliver shorter than the minimum clip duration is dropped
+        let sliced = slice_clips_to_range(&clips, 9.95, 20.0).unwrap();
+        assert_eq!(sliced.len(), 1);
     }
 
     #[test]
-    fn test_validate_trim_data_too_short() {
-        let result = validate_trim_data(5.0, 5.05, 15.0, 0);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("duration too short"));
+    fn test_slice_clips_to_range_invalid_range() {
+        let clips = vec![create_range_clip("a.mp4", 0.0, 0.0, 10.0)];
+
+        assert!(slice_clips_to_range(&clips, -1.0, 5.0).is_err());
+        assert!(slice_clips_to_range(&clips, 5.0, 5.0).is_err());
+        assert!(slice_clips_to_range(&clips, 12.0, 20.0).is_err()); // Nothing in range
     }
 
     #[test]
-    fn test_validate_trim_data_minimum_duration() {
-        let result = validate_trim_data(5.0, 5.11, 15.0, 0);
-        assert!(result.is_ok());
+    fn test_apply_export_range_defaults() {
+        use crate::commands::export::apply_export_range;
+
+        let clips = vec![
+            create_range_clip("a.mp4", 0.0, 0.0, 10.0),
+            create_range_clip("b.mp4", 10.0, 0.0, 10.0),
+        ];
+
+        // No range keeps the timeline untouched
+        assert_eq!(apply_export_range(clips.clone(), None, None).unwrap().len(), 2);
+
+        // Only an in point runs to the end of the timeline
+        let sliced = apply_export_range(clips.clone(), Some(15.0), None).unwrap();
+        assert_eq!(sliced.len(), 1);
+        assert_eq!(sliced[0].trim_start, 5.0);
+        assert_eq!(sliced[0].trim_end, 10.0);
+
+        // Only an out point starts at the beginning of the timeline
+        let sliced = apply_export_range(clips, None, Some(5.0)).unwrap();
+        assert_eq!(sliced.len(), 1);
+        assert_eq!(sliced[0].trim_end, 5.0);
     }
 
-    #[test]
-    fn test_needs_trimming_true() {
-        assert!(needs_trimming(5.0, 10.0, 15.0));
-        assert!(needs_trimming(0.0, 10.0, 15.0));
-        assert!(needs_trimming(5.0, 15.0, 15.0));
+    // ============================================================================
+    // RATE CONTROL TESTS
+    // ============================================================================
+
+    fn create_rate_control_clip(start_time: f64, duration: f64) -> ExportClip {
+        ExportClip {
+            file_path: "clip.mp4".to_string(),
+            start_time,
+            duration,
+            trim_start: 0.0,
+            trim_end: duration,
+            track_id: "track1".to_string(),
+            trimmed_file_path: None,
+            original_duration: duration,
+        }
     }
 
     #[test]
-    fn test_needs_trimming_false() {
-        assert!(!needs_trimming(0.0, 15.0, 15.0));
-        assert!(!needs_trimming(0.01, 14.99, 15.0)); // Within tolerance
+    fn test_rate_control_defaults_when_missing_from_json() {
+        let json = r#"{"resolution":"source","quality":"high","format":"mp4","codec":"h264"}"#;
+        let settings: ExportSettings = serde_json::from_str(json).unwrap();
+
+        assert_eq!(settings.rate_control, "quality");
+        assert_eq!(get_rate_control_args(&settings, 10.0).unwrap(), vec!["-crf", "18"]);
     }
 
     #[test]
-    fn test_calculate_trimmed_duration() {
-        assert_eq!(calculate_trimmed_duration(5.0, 10.0), 5.0);
-        assert_eq!(calculate_trimmed_duration(0.0, 15.0), 15.0);
-        assert_eq!(calculate_trimmed_duration(2.5, 7.5), 5.0);
+    fn test_validate_rate_control_settings() {
+        let mut settings = create_test_export_settings("source");
+        assert!(validate_export_settings(&settings, None).is_ok());
+
+        settings.rate_control = "crf".to_string();
+        assert!(validate_export_settings(&settings, None).is_err());
+        settings.crf = Some(52);
+        assert!(validate_export_settings(&settings, None).is_err());
+        settings.crf = Some(20);
+        assert!(validate_export_settings(&settings, None).is_ok());
+
+        settings.rate_control = "cbr".to_string();
+        assert!(validate_export_settings(&settings, None).is_err());
+        settings.video_bitrate_kbps = Some(MAX_VIDEO_BITRATE_KBPS + 1);
+        assert!(validate_export_settings(&settings, None).is_err());
+        settings.video_bitrate_kbps = Some(4000);
+        assert!(validate_export_settings(&settings, None).is_ok());
+
+        settings.rate_control = "target_size".to_string();
+        assert!(validate_export_settings(&settings, None).is_err());
+        settings.target_size_mb = Some(25.0);
+        assert!(validate_export_settings(&settings, None).is_ok());
+
+        settings.rate_control = "vbr".to_string();
+        assert!(validate_export_settings(&settings, None).is_err());
     }
 
     #[test]
-    fn test_validate_export_clips_trim_data_valid() {
-        let clips = vec![
-            ExportClip {
-                file_path: "clip1.mp4".to_string(),
-                start_time: 0.0,
-                duration: 5.0,
-                trim_start: 0.0,
-                trim_end: 5.0,
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-            ExportClip {
-                file_path: "clip2.mp4".to_string(),
-                start_time: 5.0,
-                duration: 3.0,
-                trim_start: 2.0,
-                trim_end: 5.0,
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-        ];
+    fn test_get_rate_control_args() {
+        let mut settings = create_test_export_settings("source");
 
-        let result = validate_export_clips_trim_data(&clips);
-        assert!(result.is_ok());
+        settings.rate_control = "crf".to_string();
+        settings.crf = Some(21);
+        assert_eq!(get_rate_control_args(&settings, 10.0).unwrap(), vec!["-crf", "21"]);
+
+        settings.rate_control = "abr".to_string();
+        settings.video_bitrate_kbps = Some(3000);
+        assert_eq!(get_rate_control_args(&settings, 10.0).unwrap(), vec!["-b:v", "3000k"]);
+
+        settings.rate_control = "cbr".to_string();
+        assert_eq!(
+            get_rate_control_args(&settings, 10.0).unwrap(),
+            vec!["-b:v", "3000k", "-minrate", "3000k", "-maxrate", "3000k", "-bufsize", "6000k"]
+        );
     }
 
     #[test]
-    fn test_validate_export_clips_trim_data_empty() {
-        let clips = vec![];
-        let result = validate_export_clips_trim_data(&clips);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("No clips to export"));
+    fn test_calculate_target_video_bitrate() {
+        // 25 MiB over 60s: 209715.2 kbit * 0.98 / 60 = 3425.35 kbps, minus 192 audio
+        assert_eq!(calculate_target_video_bitrate(25.0, 60.0).unwrap(), 3233);
+
+        // Too small to hold the audio track alone
+        assert!(calculate_target_video_bitrate(1.0, 600.0).is_err());
+        assert!(calculate_target_video_bitrate(25.0, 0.0).is_err());
     }
 
     #[test]
-    fn test_validate_export_clips_trim_data_invalid() {
+    fn test_target_size_uses_concatenated_duration() {
+        // Gaps are dropped from the output, so only clip durations count
         let clips = vec![
-            ExportClip {
-                file_path: "clip1.mp4".to_string(),
-                start_time: 0.0,
-                duration: 5.0,
-                trim_start: -1.0, // Invalid
-                trim_end: 5.0,
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
+            create_rate_control_clip(0.0, 20.0),
+            create_rate_control_clip(40.0, 20.0),
         ];
+        let mut settings = create_test_export_settings("source");
+        settings.rate_control = "target_size".to_string();
+        settings.target_size_mb = Some(25.0);
 
-        let result = validate_export_clips_trim_data(&clips);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("negative trim start"));
+        let duration = get_concatenated_duration(&clips);
+        assert_eq!(duration, 40.0);
+        assert!(uses_two_pass(&settings));
+        assert_eq!(get_rate_control_args(&settings, duration).unwrap(), vec!["-b:v", "4946k"]);
+        assert_eq!(estimate_export_size(&clips, &settings), 25 * 1024 * 1024);
     }
 
     #[test]
-    fn test_get_clips_needing_trimming() {
-        let clips = vec![
-            ExportClip {
-                file_path: "clip1.mp4".to_string(),
-                start_time: 0.0,
-                duration: 5.0,
-                trim_start: 0.0,
-                trim_end: 5.0, // No trimming needed
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-            ExportClip {
-                file_path: "clip2.mp4".to_string(),
-                start_time: 5.0,
-                duration: 3.0,
-                trim_start: 2.0,
-                trim_end: 5.0, // Trimming needed
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-            ExportClip {
-                file_path: "clip3.mp4".to_string(),
-                start_time: 8.0,
-                duration: 2.0,
-                trim_start: 0.0,
-                trim_end: 2.0, // No trimming needed
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-        ];
+    fn test_estimate_export_size_for_bitrate_modes() {
+        let clips = vec![create_rate_control_clip(0.0, 10.0)];
+        let mut settings = create_test_export_settings("1080p");
+        settings.rate_control = "cbr".to_string();
+        settings.video_bitrate_kbps = Some(2808);
 
-        let indices = get_clips_needing_trimming(&clips);
-        assert_eq!(indices, vec![1]); // Only clip 2 needs trimming
+        // (2808 + 192) kbps * 10s = 30000 kbit = 3,750,000 bytes
+        assert_eq!(estimate_export_size(&clips, &settings), 3_750_000);
+
+        // Gaps between clips add nothing to the output
+        let clips = vec![create_rate_control_clip(0.0, 5.0), create_rate_control_clip(20.0, 5.0)];
+        assert_eq!(estimate_export_size(&clips, &settings), 3_750_000);
     }
 
     #[test]
-    fn test_get_clips_not_needing_trimming() {
-        let clips = vec![
-            ExportClip {
-                file_path: "clip1.mp4".to_string(),
-                start_time: 0.0,
-                duration: 5.0,
-                trim_start: 0.0,
-                trim_end: 5.0, // No trimming needed
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-            ExportClip {
-                file_path: "clip2.mp4".to_string(),
-                start_time: 5.0,
-                duration: 3.0,
-                trim_start: 2.0,
-                trim_end: 5.0, // Trimming needed
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-            ExportClip {
-                file_path: "clip3.mp4".to_string(),
-                start_time: 8.0,
-                duration: 2.0,
-                trim_start: 0.0,
-                trim_end: 2.0, // No trimming needed
-            track_id: "track1".to_string(),
-            trimmed_file_path: None,
-            original_duration: 10.0,
-        },
-        ];
+    fn test_build_two_pass_args() {
+        let rate_args = vec!["-b:v".to_string(), "3000k".to_string()];
 
-        let indices = get_clips_not_needing_trimming(&clips);
-        assert_eq!(indices, vec![0, 2]); // Clips 1 and 3 don't need trimming
+        let first_pass = build_first_pass_args("list.txt", &rate_args, "/tmp/passlog");
+        assert!(first_pass.windows(2).any(|w| w[0] == "-pass" && w[1] == "1"));
+        assert!(first_pass.contains(&"-an".to_string()));
+        assert_eq!(first_pass.last().unwrap(), "-");
+
+        let second_pass = build_export_encode_args("list.txt", None, "out.mp4", &ExportEncoders::default(), &rate_args, Some("/tmp/passlog"));
+        assert!(second_pass.windows(2).any(|w| w[0] == "-pass" && w[1] == "2"));
+        assert!(second_pass.windows(2).any(|w| w[0] == "-passlogfile" && w[1] == "/tmp/passlog"));
+        assert!(second_pass.windows(2).any(|w| w[0] == "-b:a" && w[1] == "192k"));
+        assert_eq!(second_pass.last().unwrap(), "out.mp4");
+
+        let single_pass = build_export_encode_args("list.txt", None, "out.mp4", &ExportEncoders::default(), &rate_args, None);
+        assert!(!single_pass.contains(&"-pass".to_string()));
     }
 
     // ============================================================================
-    // FFMPEG TRIM FUNCTIONALITY TESTS
+    // CAPABILITY TESTS
     // ============================================================================
 
+    fn create_test_capabilities(encoders: &[&str]) -> FfmpegCapabilities {
+        let to_set = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        FfmpegCapabilities {
+            encoders: to_set(encoders),
+            filters: to_set(&["scale", "concat"]),
+            muxers: to_set(&["mp4"]),
+            demuxers: to_set(&["concat", "mov"]),
+            ..Default::default()
+        }
+    }
+
     #[test]
-    fn test_generate_trim_command_args_with_copy_codec() {
-        let args = generate_trim_command_args(
-            "input.mp4",
-            "output.mp4",
-            5.0,
-            10.0,
-            true, // use_copy_codec
-        );
+    fn test_validate_export_settings_with_capabilities() {
+        let mut settings = create_test_export_settings("1080p");
+        assert!(validate_export_settings(&settings, Some(&create_test_capabilities(&["libx264", "aac"]))).is_ok());
 
-        assert!(args.contains(&"-i".to_string()));
-        assert!(args.contains(&"input.mp4".to_string()));
-        assert!(args.contains(&"-ss".to_string()));
-        assert!(args.contains(&"5.000".to_string()));
-        assert!(args.contains(&"-t".to_string()));
-        assert!(args.contains(&"10.000".to_string()));
-        assert!(args.contains(&"-c".to_string()));
-        assert!(args.contains(&"copy".to_string()));
-        assert!(args.contains(&"-y".to_string()));
-        assert!(args.contains(&"output.mp4".to_string()));
+        let error = validate_export_settings(&settings, Some(&create_test_capabilities(&["aac"]))).unwrap_err();
+        assert!(error.message.contains("no H.264 encoder"));
+
+        let error = validate_export_settings(&settings, Some(&create_test_capabilities(&["libx264"]))).unwrap_err();
+        assert!(error.message.contains("no AAC encoder"));
+
+        let mut without_scale = create_test_capabilities(&["libx264", "aac"]);
+        without_scale.filters.clear();
+        assert!(validate_export_settings(&settings, Some(&without_scale)).unwrap_err().message.contains("'scale' filter"));
+        settings.resolution = "source".to_string();
+        assert!(validate_export_settings(&settings, Some(&without_scale)).is_ok());
+
+        // CRF has no equivalent on the fallback encoders
+        settings.rate_control = "crf".to_string();
+        settings.crf = Some(20);
+        let fallback = create_test_capabilities(&["h264_videotoolbox", "aac"]);
+        assert!(validate_export_settings(&settings, Some(&fallback)).unwrap_err().message.contains("CRF rate control needs libx264"));
     }
 
     #[test]
-    fn test_generate_trim_command_args_without_copy_codec() {
-        let args = generate_trim_command_args(
-            "input.mp4",
-            "output.mp4",
-            5.0,
-            10.0,
-            false, // use_copy_codec
-        );
+    fn test_resolve_export_encoding_downgrades_without_libx264() {
+        let mut settings = create_test_export_settings("source");
+        let capabilities = create_test_capabilities(&["h264_videotoolbox", "libfdk_aac"]);
+
+        let encoding = resolve_export_encoding(&settings, 60.0, Some(&capabilities)).unwrap();
+        assert_eq!(encoding.encoders.video, "h264_videotoolbox");
+        assert_eq!(encoding.encoders.audio, "libfdk_aac");
+        assert_eq!(encoding.settings.rate_control, "abr");
+        assert_eq!(encoding.settings.video_bitrate_kbps, Some(get_fallback_quality_bitrate(&settings.quality)));
+        assert_eq!(encoding.warnings.len(), 3);
+
+        settings.rate_control = "target_size".to_string();
+        settings.target_size_mb = Some(25.0);
+        let encoding = resolve_export_encoding(&settings, 60.0, Some(&capabilities)).unwrap();
+        assert!(!uses_two_pass(&encoding.settings));
+        assert_eq!(get_rate_control_args(&encoding.settings, 60.0).unwrap(), vec!["-b:v", "3233k"]);
+
+        // Unknown capabilities keep the settings as requested
+        let encoding = resolve_export_encoding(&settings, 60.0, None).unwrap();
+        assert_eq!(encoding.encoders, ExportEncoders::default());
+        assert!(uses_two_pass(&encoding.settings));
+        assert!(encoding.warnings.is_empty());
+    }
 
-        assert!(args.contains(&"-i".to_string()));
-        assert!(args.contains(&"input.mp4".to_string()));
-        assert!(args.contains(&"-ss".to_string()));
-        assert!(args.contains(&"5.000".to_string()));
-        assert!(args.contains(&"-t".to_string()));
-        assert!(args.contains(&"10.000".to_string()));
-        assert!(args.contains(&"-c:v".to_string()));
-        assert!(args.contains(&"libx264".to_string()));
-        assert!(args.contains(&"-c:a".to_string()));
-        assert!(args.contains(&"aac".to_string()));
-        assert!(args.contains(&"-y".to_string()));
-        assert!(args.contains(&"output.mp4".to_string()));
+    #[test]
+    fn test_build_export_encode_args_with_fallback_encoders() {
+        let encoders = ExportEncoders {
+            video: "h264_nvenc".to_string(),
+            audio: "aac".to_string(),
+        };
+        let rate_args = vec!["-b:v".to_string(), "5000k".to_string()];
+
+        let args = build_export_encode_args("list.txt", None, "out.mp4", &encoders, &rate_args, None);
+        assert!(args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "h264_nvenc"));
+        assert!(!args.contains(&"-preset".to_string()));
     }
 
+    // ============================================================================
+    // STREAM COPY TESTS
+    // ============================================================================
+
     #[test]
-    fn test_create_temp_trim_file_path() {
-        let result = create_temp_trim_file_path(0, "mp4");
-        assert!(result.is_ok());
-        let path = result.unwrap();
-        assert!(path.contains("trimmed_clip_0_"));
-        assert!(path.ends_with(".mp4"));
+    fn test_is_stream_copy_eligible() {
+        let mut settings = create_test_export_settings("source");
+        assert!(is_stream_copy_eligible(&settings));
+
+        settings.resolution = "720p".to_string();
+        assert!(!is_stream_copy_eligible(&settings));
+
+        settings.resolution = "source".to_string();
+        settings.rate_control = "target_size".to_string();
+        settings.target_size_mb = Some(25.0);
+        assert!(!is_stream_copy_eligible(&settings));
     }
 
     #[test]
-    fn test_get_file_extension() {
-        assert_eq!(get_file_extension("video.mp4"), "mp4");
-        assert_eq!(get_file_extension("video.MOV"), "mov");
-        assert_eq!(get_file_extension("video.avi"), "avi");
-        assert_eq!(get_file_extension("video"), "mp4"); // default
-        assert_eq!(get_file_extension("video.mkv"), "mkv");
+    fn test_check_stream_copy_compatibility_matching_clips() {
+        let metadata = vec![
+            create_test_metadata(10.0, 1920, 1080, 30.0, true),
+            create_test_metadata(4.0, 1920, 1080, 30.0, true),
+        ];
+        let details = vec![Some(create_test_details()), Some(create_test_details())];
+        assert!(check_stream_copy_compatibility(&metadata, &details).is_ok());
     }
 
     #[test]
-    fn test_supports_copy_codec() {
-        assert!(supports_copy_codec("mp4"));
-        assert!(supports_copy_codec("mov"));
-        assert!(supports_copy_codec("avi"));
-        assert!(supports_copy_codec("mkv"));
-        assert!(supports_copy_codec("webm"));
-        assert!(!supports_copy_codec("wmv"));
-        assert!(!supports_copy_codec("flv"));
-        assert!(!supports_copy_codec("unknown"));
+    fn test_check_stream_copy_compatibility_mismatches() {
+        let base = create_test_metadata(10.0, 1920, 1080, 30.0, true);
+        let one = vec![Some(create_test_details())];
+        let two = vec![Some(create_test_details()), Some(create_test_details())];
+
+        let resolution = vec![base.clone(), create_test_metadata(10.0, 1280, 720, 30.0, true)];
+        assert!(check_stream_copy_compatibility(&resolution, &two).is_err());
+
+        let fps = vec![base.clone(), create_test_metadata(10.0, 1920, 1080, 29.97, true)];
+        assert!(check_stream_copy_compatibility(&fps, &two).is_err());
+
+        let audio = vec![base.clone(), create_test_metadata(10.0, 1920, 1080, 30.0, false)];
+        assert!(check_stream_copy_compatibility(&audio, &two).is_err());
+
+        let mut hevc = base.clone();
+        hevc.codec = "hevc".to_string();
+        assert!(check_stream_copy_compatibility(&[hevc], &one).is_err());
+
+        let mut opus = base;
+        opus.audio_codec = Some("opus".to_string());
+        assert!(check_stream_copy_compatibility(&[opus], &one).is_err());
+
+        assert!(check_stream_copy_compatibility(&[], &[]).is_err());
     }
 
     #[test]
-    fn test_trim_command_precision() {
-        let args = generate_trim_command_args(
-            "input.mp4",
-            "output.mp4",
-            1.234567,
-            5.678901,
-            true,
-        );
+    fn test_check_stream_copy_compatibility_stream_details() {
+        let metadata = vec![
+            create_test_metadata(10.0, 1920, 1080, 30.0, true),
+            create_test_metadata(4.0, 1920, 1080, 30.0, true),
+        ];
+        let check = |edit: fn(&mut MediaDetails)| {
+            let mut other = create_test_details();
+            edit(&mut other);
+            check_stream_copy_compatibility(&metadata, &[Some(create_test_details()), Some(other)])
+        };
 
-        // Check that precision is limited to 3 decimal places
-        assert!(args.contains(&"1.235".to_string()));
-        assert!(args.contains(&"5.679".to_string()));
+        let error = check(|d| d.video_streams[0].profile = Some("Main".to_string())).unwrap_err();
+        assert_eq!(error.message, "H.264 profiles differ (High vs Main)");
+        assert!(check(|d| d.video_streams[0].level = Some(51)).is_err());
+        assert!(check(|d| d.video_streams[0].pix_fmt = Some("yuv422p".to_string())).is_err());
+        assert!(check(|d| d.video_streams[0].time_base = Some("1/90000".to_string())).is_err());
+        assert!(check(|d| d.audio_streams[0].sample_rate = Some(44100)).is_err());
+        assert!(check(|d| d.audio_streams[0].channels = Some(1)).is_err());
+        assert!(check(|d| d.audio_streams[0].channel_layout = Some("mono".to_string())).is_err());
+
+        // Clips without stream details are never copied
+        assert!(check_stream_copy_compatibility(&metadata, &[Some(create_test_details()), None]).is_err());
+    }
+
+    #[test]
+    fn test_build_stream_copy_args() {
+        let args = build_stream_copy_args("list.txt", None, "out.mp4");
+        assert!(args.windows(2).any(|w| w[0] == "-c" && w[1] == "copy"));
+        assert!(!args.contains(&"libx264".to_string()));
+        assert_eq!(args.last().unwrap(), "out.mp4");
     }
 
     // ============================================================================
-    // TEMPORARY FILE MANAGEMENT TESTS
+    // CHAPTER AND METADATA TESTS
     // ============================================================================
 
+    fn chapter(start_time: f64, title: &str) -> ExportChapter {
+        ExportChapter {
+            start_time,
+            title: title.to_string(),
+        }
+    }
+
     #[test]
-    fn test_temp_file_tracker_creation() {
-        let tracker = TempFileTracker::new();
-        assert!(tracker.is_empty());
-        assert_eq!(tracker.file_count(), 0);
-        assert_eq!(tracker.directory_count(), 0);
+    fn test_normalize_chapters() {
+        let chapters = vec![
+            chapter(90.0, "Wrap up"),
+            chapter(15.0, "Setup"),
+            chapter(15.0, "Duplicate"),
+            chapter(45.0, "  "),
+            chapter(200.0, "Past the end"),
+        ];
+
+        let normalized = normalize_chapters(&chapters, 120.0);
+
+        assert_eq!(
+            normalized,
+            vec![
+                chapter(0.0, "Intro"),
+                chapter(15.0, "Setup"),
+                chapter(45.0, "Chapter 3"),
+                chapter(90.0, "Wrap up"),
+            ]
+        );
     }
 
     #[test]
-    fn test_temp_file_tracker_add_file() {
-        let mut tracker = TempFileTracker::new();
-        tracker.add_file("test_file.txt".to_string());
-        assert!(!tracker.is_empty());
-        assert_eq!(tracker.file_count(), 1);
-        assert_eq!(tracker.directory_count(), 0);
+    fn test_generate_ffmetadata_content() {
+        let tags = ExportMetadataTags {
+            title: Some("Rust; part 1".to_string()),
+            artist: None,
+            comment: None,
+            creation_time: Some("2025-01-02T03:04:05+00:00".to_string()),
+            project_id: Some("abc".to_string()),
+        };
+        let chapters = vec![chapter(0.0, "Start"), chapter(12.5, "a=b")];
+
+        let content = generate_ffmetadata_content(&chapters, &tags, 30.0);
+
+        assert!(content.starts_with(";FFMETADATA1\n"));
+        assert!(content.contains("title=Rust\\; part 1\n"));
+        assert!(!content.contains("artist="));
+        assert!(content.contains("project_id=abc\n"));
+        assert!(content.contains("[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=12500\ntitle=Start\n"));
+        assert!(content.contains("START=12500\nEND=30000\ntitle=a\\=b\n"));
     }
 
     #[test]
-    fn test_temp_file_tracker_add_directory() {
-        let mut tracker = TempFileTracker::new();
-        tracker.add_directory("test_dir".to_string());
-        assert!(!tracker.is_empty());
-        assert_eq!(tracker.file_count(), 0);
-        assert_eq!(tracker.directory_count(), 1);
+    fn test_generate_youtube_chapter_list() {
+        let chapters = vec![chapter(0.0, "Intro"), chapter(65.4, "Install"), chapter(600.0, "Build")];
+        assert_eq!(
+            generate_youtube_chapter_list(&chapters, 900.0),
+            "0:00 Intro\n1:05 Install\n10:00 Build\n"
+        );
+
+        // Long videos use hours for every line
+        assert_eq!(format_youtube_timestamp(65.0, 3700.0), "0:01:05");
+        assert_eq!(format_youtube_timestamp(3661.0, 3700.0), "1:01:01");
     }
 
     #[test]
-    fn test_temp_file_tracker_add_multiple() {
-        let mut tracker = TempFileTracker::new();
-        tracker.add_file("file1.txt".to_string());
-        tracker.add_file("file2.txt".to_string());
-        tracker.add_directory("dir1".to_string());
-        tracker.add_directory("dir2".to_string());
-        
-        assert!(!tracker.is_empty());
-        assert_eq!(tracker.file_count(), 2);
-        assert_eq!(tracker.directory_count(), 2);
+    fn test_slice_chapters_to_range() {
+        let chapters = vec![chapter(0.0, "Intro"), chapter(30.0, "Setup"), chapter(90.0, "Build"), chapter(150.0, "Outro")];
+
+        let sliced = slice_chapters_to_range(&chapters, 45.0, 120.0);
+
+        assert_eq!(sliced, vec![chapter(0.0, "Setup"), chapter(45.0, "Build")]);
     }
 
     #[test]
-    fn test_generate_unique_temp_filename() {
-        let result1 = generate_unique_temp_filename("test", "txt");
-        let result2 = generate_unique_temp_filename("test", "txt");
-        
-        assert!(result1.is_ok());
-        assert!(result2.is_ok());
-        
-        let filename1 = result1.unwrap();
-        let filename2 = result2.unwrap();
-        
-        // Should be different due to nanosecond precision
-        assert_ne!(filename1, filename2);
-        
-        // Should contain the prefix and extension
-        assert!(filename1.contains("test_"));
-        assert!(filename1.ends_with(".txt"));
-        assert!(filename2.contains("test_"));
-        assert!(filename2.ends_with(".txt"));
+    fn test_map_chapters_to_output_time() {
+        // Clips at 0-20 and 40-60 on one track, then 10-20 on a second track
+        let mut overlay = create_rate_control_clip(10.0, 10.0);
+        overlay.track_id = "track2".to_string();
+        let clips = vec![create_rate_control_clip(0.0, 20.0), create_rate_control_clip(40.0, 20.0), overlay];
+        let chapters = vec![
+            chapter(0.0, "Intro"),
+            chapter(30.0, "In the gap"),
+            chapter(50.0, "Second clip"),
+            chapter(90.0, "Past the end"),
+        ];
+
+        let mapped = map_chapters_to_output_time(&chapters, &clips);
+
+        assert_eq!(
+            mapped,
+            vec![chapter(0.0, "Intro"), chapter(20.0, "In the gap"), chapter(30.0, "Second clip")]
+        );
+
+        // The last chapter ends at the output duration, not the timeline span
+        let content = generate_ffmetadata_content(&mapped, &ExportMetadataTags::default(), get_concatenated_duration(&clips));
+        assert!(content.contains("START=30000\nEND=50000\ntitle=Second clip\n"));
     }
 
     #[test]
-    fn test_create_temp_file_path() {
-        let result = create_temp_file_path("export", "mp4");
-        assert!(result.is_ok());
-        
-        let file_path = result.unwrap();
-        assert!(file_path.contains("export_"));
-        assert!(file_path.ends_with(".mp4"));
+    fn test_ffmetadata_args() {
+        assert!(get_ffmetadata_input_args(None).is_empty());
+
+        let args = build_stream_copy_args("list.txt", Some("meta.txt"), "out.mp4");
+        assert!(args.windows(2).any(|w| w[0] == "-i" && w[1] == "meta.txt"));
+        assert!(args.windows(2).any(|w| w[0] == "-map_chapters" && w[1] == "1"));
+        assert!(args.contains(&"+faststart+use_metadata_tags".to_string()));
+
+        let args = build_export_encode_args("list.txt", Some("meta.txt"), "out.mp4", &ExportEncoders::default(), &[], None);
+        assert!(args.windows(2).any(|w| w[0] == "-map_metadata" && w[1] == "1"));
+        assert_eq!(args.last().unwrap(), "out.mp4");
     }
 
     #[test]
-    fn test_create_temp_dir_path() {
-        let result = create_temp_dir_path("export");
-        assert!(result.is_ok());
-        
-        let dir_path = result.unwrap();
-        assert!(dir_path.contains("export_"));
+    fn test_get_chapter_list_path() {
+        assert_eq!(get_chapter_list_path("/exports/tutorial.mp4"), "/exports/tutorial.chapters.txt");
+    }
+
+    // ============================================================================
+    // EXPORT VERIFICATION TESTS
+    // ============================================================================
+
+    fn create_test_metadata(duration: f64, width: u32, height: u32, fps: f64, has_audio: bool) -> VideoMetadata {
+        VideoMetadata {
+            duration,
+            width,
+            height,
+            fps,
+            codec: "h264".to_string(),
+            bitrate: 5_000_000,
+            file_size: 1024,
+            format: "mp4".to_string(),
+            has_audio,
+            audio_codec: if has_audio { Some("aac".to_string()) } else { None },
+            audio_bitrate: None,
+            is_vfr: false,
+        }
+    }
+
+    fn create_test_details() -> MediaDetails {
+        serde_json::from_value(serde_json::json!({
+            "container": { "tags": {} },
+            "video_streams": [{
+                "index": 0, "codec": "h264", "profile": "High", "level": 40, "pix_fmt": "yuv420p",
+                "time_base": "1/15360", "width": 1920, "height": 1080, "fps": 30.0, "rotation": 0
+            }],
+            "audio_streams": [{
+                "index": 1, "codec": "aac", "sample_rate": 48000, "channels": 2, "channel_layout": "stereo"
+            }],
+            "subtitle_streams": [],
+            "data_streams": []
+        }))
+        .unwrap()
+    }
+
+    fn create_test_export_settings(resolution: &str) -> ExportSettings {
+        ExportSettings {
+            resolution: resolution.to_string(),
+            quality: "high".to_string(),
+            format: "mp4".to_string(),
+            codec: "h264".to_string(),
+            rate_control: "quality".to_string(),
+            crf: None,
+            video_bitrate_kbps: None,
+            target_size_mb: None,
+        }
     }
 
     #[test]
-    fn test_get_temp_file_size() {
-        // Create a temporary file for testing
-        let temp_dir = std::env::temp_dir();
-        let test_file = temp_dir.join("test_size.txt");
-        let test_content = "Hello, World!";
-        
-        std::fs::write(&test_file, test_content).unwrap();
-        
-        let result = get_temp_file_size(test_file.to_str().unwrap());
-        assert!(result.is_ok());
-        
-        let size = result.unwrap();
-        assert_eq!(size, test_content.len() as u64);
-        
-        // Clean up
-        std::fs::remove_file(&test_file).unwrap();
+    fn test_verification_report_passes_matching_export() {
+        let source = create_test_metadata(10.0, 1920, 1080, 30.0, true);
+        let output = create_test_metadata(10.02, 1920, 1080, 30.0, true);
+
+        let report = build_export_verification_report(
+            &output,
+            &[source],
+            10.0,
+            &create_test_export_settings("source"),
+            "",
+        );
+
+        assert!(report.passed);
+        assert!(report.mismatches.is_empty());
+        assert!(report.warnings.is_empty());
+        assert_eq!(report.width, 1920);
     }
 
     #[test]
-    fn test_get_temp_file_size_nonexistent() {
-        let result = get_temp_file_size("/nonexistent/file.txt");
-        assert!(result.is_err());
+    fn test_verification_report_flags_mismatches() {
+        let source = create_test_metadata(10.0, 1920, 1080, 30.0, true);
+        let output = create_test_metadata(7.0, 1920, 1080, 25.0, false);
+
+        let report = build_export_verification_report(
+            &output,
+            &[source],
+            10.0,
+            &create_test_export_settings("720p"),
+            "",
+        );
+
+        assert!(!report.passed);
+        let fields: Vec<&str> = report.mismatches.iter().map(|m| m.field.as_str()).collect();
+        assert_eq!(fields, vec!["duration", "resolution", "fps", "audio"]);
+        assert_eq!(report.mismatches[1].expected, "1280x720");
+        assert_eq!(report.mismatches[1].actual, "1920x1080");
     }
 
     #[test]
-    fn test_create_temp_trim_file_path_different_index() {
-        let result1 = create_temp_trim_file_path(0, "mp4");
-        let result2 = create_temp_trim_file_path(1, "mp4");
-        
-        assert!(result1.is_ok());
-        assert!(result2.is_ok());
-        
-        let path1 = result1.unwrap();
-        let path2 = result2.unwrap();
-        
-        // Should be different due to different indices
-        assert_ne!(path1, path2);
-        
-        // Should contain correct indices
-        assert!(path1.contains("trimmed_clip_0_"));
-        assert!(path2.contains("trimmed_clip_1_"));
+    fn test_verification_report_with_overlapping_tracks() {
+        let mut overlay = create_range_clip("overlay.mp4", 2.0, 0.0, 5.0);
+        overlay.track_id = "track2".to_string();
+        let clips = vec![create_range_clip("main.mp4", 0.0, 0.0, 10.0), overlay];
+
+        // The second track is appended, so the output outlasts the timeline span
+        assert_eq!(get_timeline_duration(&clips), 10.0);
+        assert_eq!(get_concatenated_duration(&clips), 15.0);
+
+        let source = create_test_metadata(10.0, 1920, 1080, 30.0, true);
+        let output = create_test_metadata(15.0, 1920, 1080, 30.0, true);
+        let report = build_export_verification_report(
+            &output,
+            &[source],
+            get_concatenated_duration(&clips),
+            &create_test_export_settings("source"),
+            "",
+        );
+
+        assert!(report.passed, "{:?}", report.mismatches);
+        assert_eq!(report.expected_duration, 15.0);
+    }
+
+    #[test]
+    fn test_verification_report_warns_on_dropped_frames_and_drift() {
+        let source = create_test_metadata(10.0, 1280, 720, 30.0, true);
+        let output = create_test_metadata(10.0, 1280, 720, 30.0, true);
+        let stderr = "frame=  150 fps= 60 q=28.0 size=512kB time=00:00:05.00 bitrate=800kbits/s\r\
+                      frame=  270 fps= 60 q=-1.0 Lsize=1024kB time=00:00:10.00 bitrate=800kbits/s dup=3 drop=12 speed=2x\n";
+
+        let report = build_export_verification_report(
+            &output,
+            &[source],
+            10.0,
+            &create_test_export_settings("source"),
+            stderr,
+        );
+
+        assert!(report.passed);
+        assert!(report.warnings.iter().any(|w| w.contains("dropped 12 frames")));
+        assert!(report.warnings.iter().any(|w| w.contains("duplicated 3 frames")));
+        assert!(report.warnings.iter().any(|w| w.contains("A/V drift")));
+    }
+
+    #[test]
+    fn test_parse_ffmpeg_final_stats() {
+        let stderr = "Input #0, mov,mp4\n\
+                      frame=   30 fps=0.0 q=28.0 size=256kB time=00:00:01.00 bitrate=2000kbits/s\r\
+                      frame=  300 fps=120 q=-1.0 Lsize=2048kB time=00:00:10.00 bitrate=1600kbits/s drop=2 speed=4x\n\
+                      video:1900kB audio:140kB";
+
+        let stats = parse_ffmpeg_final_stats(stderr).unwrap();
+        assert_eq!(stats.frames, 300);
+        assert_eq!(stats.dropped_frames, 2);
+        assert_eq!(stats.duplicated_frames, 0);
+        assert_eq!(stats.time, Some(10.0));
+
+        assert_eq!(parse_ffmpeg_final_stats("no progress output"), None);
+    }
+
+    #[test]
+    fn test_get_target_dimensions() {
+        assert_eq!(get_target_dimensions("1080p"), Some((1920, 1080)));
+        assert_eq!(get_target_dimensions("720p"), Some((1280, 720)));
+        assert_eq!(get_target_dimensions("source"), None);
     }
 
     // ============================================================================
@@ -0,0 +1,488 @@
+// ============================================================================
+// FFMPEG HOVER PREVIEW MODULE
+// ============================================================================
+// This module generates short looping previews for the media bin: a few short
+// segments sampled across a clip, joined in one FFmpeg pass into an animated
+// WebP/GIF or a tiny MP4. Previews are stored in the thumbnail cache (so they
+// share its LRU eviction) and rendered in the background, with an event
+// emitted when each one is ready.
+
+use crate::commands::{stable_hash, CommandError, CommandResult};
+use crate::ffmpeg::render_cache::{get_source_identity, SourceIdentity};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use crate::ffmpeg::thumbnail::ThumbnailCache;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::Emitter;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Request to generate a hover preview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateHoverPreviewRequest {
+    pub file_path: String,
+    #[serde(default)]
+    pub format: Option<HoverPreviewFormat>, // Defaults to animated WebP
+    #[serde(default)]
+    pub segment_count: Option<u32>, // Segments sampled across the clip
+    #[serde(default)]
+    pub segment_duration: Option<f64>, // Seconds per segment
+    #[serde(default)]
+    pub width: Option<u32>, // Output width; height follows the aspect ratio
+}
+
+/// Response from a hover preview request
+///
+/// When `ready` is false the preview is being generated in the background and
+/// a `hover-preview-generated` event is emitted once `preview_path` exists.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateHoverPreviewResponse {
+    pub success: bool,
+    pub preview_path: Option<String>,
+    pub ready: bool,
+    pub error_message: Option<String>,
+}
+
+/// Event emitted when a background hover preview job finishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoverPreviewGeneratedEvent {
+    pub file_path: String,
+    pub preview_path: String,
+    pub success: bool,
+    pub error_message: Option<String>,
+}
+
+/// Hover preview output format
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HoverPreviewFormat {
+    Webp,
+    Gif,
+    Mp4,
+}
+
+/// Settings a hover preview is rendered with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoverPreviewSettings {
+    pub format: HoverPreviewFormat,
+    pub segment_count: u32,
+    pub segment_duration: f64,
+    pub width: u32,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default number of segments sampled across a clip
+pub const DEFAULT_HOVER_SEGMENT_COUNT: u32 = 5;
+
+/// Default segment length in seconds
+pub const DEFAULT_HOVER_SEGMENT_DURATION: f64 = 1.0;
+
+/// Default preview width in pixels
+pub const DEFAULT_HOVER_PREVIEW_WIDTH: u32 = 240;
+
+/// Most segments a preview may have
+const MAX_HOVER_SEGMENT_COUNT: u32 = 20;
+
+/// Preview frame rate
+const HOVER_PREVIEW_FPS: u32 = 12;
+
+/// Event name for finished hover preview jobs
+pub const HOVER_PREVIEW_GENERATED_EVENT: &str = "hover-preview-generated";
+
+lazy_static::lazy_static! {
+    /// Cache keys of previews currently being generated, so repeated hovers
+    /// do not start duplicate jobs
+    static ref HOVER_PREVIEW_JOBS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+// ============================================================================
+// INTERNAL FUNCTIONS
+// ============================================================================
+
+/// Whether a hover preview with this cache key is being generated
+pub fn is_hover_preview_running(key: &str) -> bool {
+    HOVER_PREVIEW_JOBS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(key)
+}
+
+/// Return a cached hover preview, or start generating it in the background
+pub fn request_hover_preview(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    settings: HoverPreviewSettings,
+) -> CommandResult<GenerateHoverPreviewResponse> {
+    validate_hover_preview_settings(&settings)?;
+
+    let source = get_source_identity(file_path)?;
+    let key = build_hover_preview_cache_key(&source, &settings);
+    let cache = ThumbnailCache::open()?;
+
+    if let Some(entry) = cache.lookup(&key)? {
+        return Ok(GenerateHoverPreviewResponse {
+            success: true,
+            preview_path: Some(cache.get_entry_path(&entry).to_string_lossy().to_string()),
+            ready: true,
+            error_message: None,
+        });
+    }
+
+    let preview_path = cache.get_thumbnail_path(&key, settings.format.extension());
+
+    // Only one job per preview; later requests just wait for the same event
+    let is_new_job = HOVER_PREVIEW_JOBS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key.clone());
+    if is_new_job {
+        start_hover_preview_generation(app_handle.clone(), file_path.to_string(), key, settings);
+    }
+
+    Ok(GenerateHoverPreviewResponse {
+        success: true,
+        preview_path: Some(preview_path.to_string_lossy().to_string()),
+        ready: false,
+        error_message: None,
+    })
+}
+
+/// Generate a hover preview in the background and emit an event when done
+fn start_hover_preview_generation(
+    app_handle: tauri::AppHandle,
+    file_path: String,
+    key: String,
+    settings: HoverPreviewSettings,
+) {
+    tauri::async_runtime::spawn(async move {
+        let cache = ThumbnailCache::open();
+        let result = match &cache {
+            Ok(cache) => generate_hover_preview(&app_handle, cache, &file_path, &key, &settings).await,
+            Err(error) => Err(error.clone()),
+        };
+        HOVER_PREVIEW_JOBS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&key);
+
+        let preview_path = cache
+            .map(|cache| cache.get_thumbnail_path(&key, settings.format.extension()))
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let event = HoverPreviewGeneratedEvent {
+            file_path,
+            preview_path,
+            success: result.is_ok(),
+            error_message: result.err().map(|error| error.message),
+        };
+        if let Err(e) = app_handle.emit(HOVER_PREVIEW_GENERATED_EVENT, &event) {
+            eprintln!("Warning: Failed to emit hover preview event: {}", e);
+        }
+    });
+}
+
+/// Render a hover preview into the thumbnail cache
+async fn generate_hover_preview(
+    app_handle: &tauri::AppHandle,
+    cache: &ThumbnailCache,
+    file_path: &str,
+    key: &str,
+    settings: &HoverPreviewSettings,
+) -> CommandResult<()> {
+    // Probe the duration to spread segments across the clip
+    let metadata_response = crate::ffmpeg::probe::extract_video_metadata(
+        app_handle.clone(),
+        crate::ffmpeg::probe::ExtractMetadataRequest {
+            file_path: file_path.to_string(),
+        },
+    )
+    .await?;
+    let duration = metadata_response
+        .metadata
+        .map(|metadata| metadata.duration)
+        .ok_or_else(|| {
+            CommandError::ffmpeg_error(
+                metadata_response
+                    .error_message
+                    .unwrap_or_else(|| "Failed to read clip duration".to_string()),
+            )
+        })?;
+
+    let segments = get_hover_preview_segments(duration, settings.segment_count, settings.segment_duration);
+    if segments.is_empty() {
+        return Err(CommandError::validation_error(format!(
+            "Cannot build a hover preview for a clip with duration {}",
+            duration
+        )));
+    }
+
+    // Render into a partial file and rename, so the cache never holds a torn preview.
+    // The ".partial" suffix keeps cache purges from taking it for a stale preview.
+    let preview_path = cache.get_thumbnail_path(key, settings.format.extension());
+    let partial_path = preview_path.with_extension(format!("{}.partial", settings.format.extension()));
+    let source = crate::ffmpeg::proxy::resolve_preview_source(file_path);
+    let args = build_hover_preview_args(&source, &partial_path.to_string_lossy(), &segments, settings);
+
+    // Execute FFmpeg
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&partial_path);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg hover preview generation failed: {}",
+            stderr
+        )));
+    }
+
+    std::fs::rename(&partial_path, &preview_path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial_path);
+        CommandError::io_error(format!("Failed to move hover preview into place: {}", e))
+    })?;
+
+    cache.insert(key, Path::new(&preview_path), file_path, 0.0, (settings.width, 0), None)
+}
+
+/// Validate hover preview settings
+fn validate_hover_preview_settings(settings: &HoverPreviewSettings) -> CommandResult<()> {
+    if settings.segment_count == 0 || settings.segment_count > MAX_HOVER_SEGMENT_COUNT {
+        return Err(CommandError::validation_error(format!(
+            "Segment count must be between 1 and {}, got {}",
+            MAX_HOVER_SEGMENT_COUNT, settings.segment_count
+        )));
+    }
+    if settings.segment_duration <= 0.0 {
+        return Err(CommandError::validation_error(format!(
+            "Segment duration must be greater than 0, got {}",
+            settings.segment_duration
+        )));
+    }
+    if settings.width < 16 {
+        return Err(CommandError::validation_error(format!(
+            "Preview width must be at least 16 pixels, got {}",
+            settings.width
+        )));
+    }
+    // H.264 with yuv420p needs even dimensions
+    if settings.format == HoverPreviewFormat::Mp4 && !settings.width.is_multiple_of(2) {
+        return Err(CommandError::validation_error(format!(
+            "MP4 preview width must be even, got {}",
+            settings.width
+        )));
+    }
+    Ok(())
+}
+
+/// Build the cache key for a hover preview
+pub fn build_hover_preview_cache_key(source: &SourceIdentity, settings: &HoverPreviewSettings) -> String {
+    let segment_ms = (settings.segment_duration * 1000.0).round() as i64;
+    let hash = stable_hash(&[
+        b"hover",
+        source.path.as_bytes(),
+        &source.size_bytes.to_le_bytes(),
+        &source.modified_secs.to_le_bytes(),
+        settings.format.extension().as_bytes(),
+        &settings.segment_count.to_le_bytes(),
+        &segment_ms.to_le_bytes(),
+        &settings.width.to_le_bytes(),
+    ]);
+    format!("{:016x}", hash)
+}
+
+/// Get the (start, duration) of each segment sampled across a clip
+///
+/// Segments sit in the middle of equal parts of the clip. Clips too short to
+/// hold every segment are previewed whole.
+pub fn get_hover_preview_segments(duration: f64, segment_count: u32, segment_duration: f64) -> Vec<(f64, f64)> {
+    if duration <= 0.0 || segment_count == 0 || segment_duration <= 0.0 {
+        return vec![];
+    }
+
+    let part_duration = duration / segment_count as f64;
+    if part_duration < segment_duration {
+        return vec![(0.0, duration.min(segment_count as f64 * segment_duration))];
+    }
+
+    (0..segment_count)
+        .map(|i| {
+            let start = part_duration * i as f64 + (part_duration - segment_duration) / 2.0;
+            (start, segment_duration)
+        })
+        .collect()
+}
+
+/// Build FFmpeg arguments that cut and join every segment in one pass
+///
+/// Each segment is a separate input seeked with `-ss`/`-t`, so only the
+/// sampled parts of the clip are decoded.
+pub fn build_hover_preview_args(
+    input_path: &str,
+    output_path: &str,
+    segments: &[(f64, f64)],
+    settings: &HoverPreviewSettings,
+) -> Vec<String> {
+    let mut args = vec!["-hide_banner".to_string()];
+    for (start, duration) in segments {
+        args.extend([
+            "-ss".to_string(),
+            start.to_string(),
+            "-t".to_string(),
+            duration.to_string(),
+            "-i".to_string(),
+            input_path.to_string(),
+        ]);
+    }
+
+    // Normalize every segment, then concatenate them
+    let mut filter = String::new();
+    for i in 0..segments.len() {
+        filter.push_str(&format!(
+            "[{i}:v]fps={fps},scale={w}:-2,setsar=1[v{i}];",
+            i = i,
+            fps = HOVER_PREVIEW_FPS,
+            w = settings.width
+        ));
+    }
+    for i in 0..segments.len() {
+        filter.push_str(&format!("[v{}]", i));
+    }
+    filter.push_str(&format!("concat=n={}:v=1:a=0", segments.len()));
+
+    // GIFs look far better with a palette built from the preview itself
+    if settings.format == HoverPreviewFormat::Gif {
+        filter.push_str(",split[a][b];[a]palettegen[p];[b][p]paletteuse");
+    }
+    filter.push_str("[out]");
+
+    args.extend([
+        "-filter_complex".to_string(),
+        filter,
+        "-map".to_string(),
+        "[out]".to_string(),
+        "-an".to_string(),
+    ]);
+
+    match settings.format {
+        HoverPreviewFormat::Webp => args.extend([
+            "-c:v".to_string(),
+            "libwebp".to_string(),
+            "-quality".to_string(),
+            "60".to_string(),
+            "-loop".to_string(),
+            "0".to_string(),
+        ]),
+        HoverPreviewFormat::Gif => args.extend(["-loop".to_string(), "0".to_string()]),
+        HoverPreviewFormat::Mp4 => args.extend([
+            "-c:v".to_string(),
+            "libx264".to_string(),
+            "-preset".to_string(),
+            "veryfast".to_string(),
+            "-crf".to_string(),
+            "28".to_string(),
+            "-pix_fmt".to_string(),
+            "yuv420p".to_string(),
+            "-movflags".to_string(),
+            "+faststart".to_string(),
+        ]),
+    }
+
+    // The muxer is named explicitly since partial outputs have no usable extension
+    args.extend(["-f".to_string(), settings.format.extension().to_string()]);
+    args.push("-y".to_string()); // Overwrite output file
+    args.push(output_path.to_string());
+    args
+}
+
+impl HoverPreviewFormat {
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            HoverPreviewFormat::Webp => "webp",
+            HoverPreviewFormat::Gif => "gif",
+            HoverPreviewFormat::Mp4 => "mp4",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(format: HoverPreviewFormat) -> HoverPreviewSettings {
+        HoverPreviewSettings {
+            format,
+            segment_count: 3,
+            segment_duration: 1.0,
+            width: 240,
+        }
+    }
+
+    #[test]
+    fn test_get_hover_preview_segments() {
+        assert_eq!(
+            get_hover_preview_segments(30.0, 3, 1.0),
+            vec![(4.5, 1.0), (14.5, 1.0), (24.5, 1.0)]
+        );
+
+        // Short clips are previewed whole
+        assert_eq!(get_hover_preview_segments(2.0, 5, 1.0), vec![(0.0, 2.0)]);
+        assert!(get_hover_preview_segments(0.0, 5, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_build_hover_preview_args() {
+        let segments = vec![(4.5, 1.0), (14.5, 1.0)];
+        let args = build_hover_preview_args("in.mov", "out.webp", &segments, &settings(HoverPreviewFormat::Webp));
+
+        assert_eq!(args.iter().filter(|arg| *arg == "-i").count(), 2);
+        assert!(args.windows(2).any(|w| w[0] == "-ss" && w[1] == "14.5"));
+        assert!(args.contains(
+            &"[0:v]fps=12,scale=240:-2,setsar=1[v0];[1:v]fps=12,scale=240:-2,setsar=1[v1];[v0][v1]concat=n=2:v=1:a=0[out]"
+                .to_string()
+        ));
+        assert!(args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "libwebp"));
+        assert!(args.windows(2).any(|w| w[0] == "-f" && w[1] == "webp"));
+        assert_eq!(args.last().unwrap(), "out.webp");
+
+        let args = build_hover_preview_args("in.mov", "out.gif", &segments, &settings(HoverPreviewFormat::Gif));
+        assert!(args.iter().any(|arg| arg.ends_with("concat=n=2:v=1:a=0,split[a][b];[a]palettegen[p];[b][p]paletteuse[out]")));
+    }
+
+    #[test]
+    fn test_build_hover_preview_cache_key() {
+        let source = SourceIdentity {
+            path: "/v.mp4".to_string(),
+            size_bytes: 100,
+            modified_secs: 1,
+        };
+        let key = build_hover_preview_cache_key(&source, &settings(HoverPreviewFormat::Webp));
+
+        assert_eq!(key, build_hover_preview_cache_key(&source, &settings(HoverPreviewFormat::Webp)));
+        assert_ne!(key, build_hover_preview_cache_key(&source, &settings(HoverPreviewFormat::Mp4)));
+    }
+
+    #[test]
+    fn test_validate_hover_preview_settings() {
+        assert!(validate_hover_preview_settings(&settings(HoverPreviewFormat::Gif)).is_ok());
+
+        let mut invalid = settings(HoverPreviewFormat::Gif);
+        invalid.segment_count = 0;
+        assert!(validate_hover_preview_settings(&invalid).is_err());
+
+        // Odd widths are fine for images but not for H.264
+        let mut odd_width = settings(HoverPreviewFormat::Webp);
+        odd_width.width = 241;
+        assert!(validate_hover_preview_settings(&odd_width).is_ok());
+        odd_width.format = HoverPreviewFormat::Mp4;
+        assert!(validate_hover_preview_settings(&odd_width).is_err());
+    }
+}
@@ -0,0 +1,564 @@
+// ============================================================================
+// FFMPEG INTEGRITY MODULE
+// ============================================================================
+// This module scans media files for damage (missing index, decoding errors,
+// truncation, audio gaps) and repairs what can be repaired by remuxing.
+// Recordings interrupted by a crash are the usual source of damaged files.
+
+use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::export::parse_ffmpeg_final_stats;
+use crate::ffmpeg::probe::{extract_video_metadata, ExtractMetadataRequest};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Request to scan a media file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanMediaIntegrityRequest {
+    pub file_path: String,
+    #[serde(default)]
+    pub full_decode: bool, // Decode the whole file instead of samples at both ends
+}
+
+/// Request to repair a media file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairMediaRequest {
+    pub file_path: String,
+    #[serde(default)]
+    pub output_path: Option<String>, // Defaults to "<name>.repaired.<ext>" next to the source
+}
+
+/// Response from a repair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairMediaResponse {
+    pub success: bool,
+    pub output_path: Option<String>,
+    pub error_message: Option<String>,
+    pub report: Option<MediaHealthReport>, // Scan of the repaired file
+}
+
+/// Result of an integrity scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaHealthReport {
+    pub file_path: String,
+    pub healthy: bool,    // True when there are no issues
+    pub repairable: bool, // True when every issue can be fixed by `repair_media_file`
+    pub full_decode: bool,
+    pub probed_duration: Option<f64>,  // Duration the container reports
+    pub decoded_duration: Option<f64>, // Duration actually decoded (full decode only)
+    pub issues: Vec<MediaHealthIssue>,
+}
+
+/// A problem found by an integrity scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaHealthIssue {
+    pub kind: String,     // "unreadable", "missing_index", "decode_errors", "truncated", "audio_gap"
+    pub severity: String, // "error" (unusable as-is) or "warning"
+    pub message: String,
+}
+
+/// Gap between consecutive audio packets
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioGap {
+    pub start: f64,    // End of the packet before the gap in seconds
+    pub duration: f64, // Gap length in seconds
+}
+
+/// Raw observations an integrity report is built from
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityObservations {
+    pub open_error: Option<String>, // FFmpeg output when the file could not be opened
+    pub probed_duration: Option<f64>,
+    pub decoded_duration: Option<f64>,
+    pub end_sample_frames: Option<u64>, // Frames decoded from the last seconds (sampled scans)
+    pub decode_errors: Vec<String>,
+    pub audio_gaps: Vec<AudioGap>,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Seconds decoded at each end of the file in a sampled scan
+const SAMPLE_SECONDS: u32 = 10;
+
+/// Minimum missing duration reported as truncation
+const TRUNCATION_MIN_SECONDS: f64 = 0.5;
+
+/// Missing share of the duration reported as truncation
+const TRUNCATION_MIN_RATIO: f64 = 0.02;
+
+/// Minimum silence between audio packets reported as a gap
+const AUDIO_GAP_MIN_SECONDS: f64 = 0.1;
+
+/// Decoder messages kept in a report
+const MAX_REPORTED_DECODE_ERRORS: usize = 5;
+
+// ============================================================================
+// INTERNAL FUNCTIONS
+// ============================================================================
+
+/// Scan a media file, decoding all of it or samples at both ends
+pub async fn scan_media_integrity_internal(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    full_decode: bool,
+) -> CommandResult<MediaHealthReport> {
+    let mut observations = IntegrityObservations::default();
+
+    // Probe the container first; a failure here usually means a missing index
+    let probe = extract_video_metadata(
+        app_handle.clone(),
+        ExtractMetadataRequest {
+            file_path: file_path.to_string(),
+        },
+    )
+    .await?;
+    let has_audio = probe.metadata.as_ref().map(|metadata| metadata.has_audio).unwrap_or(false);
+    observations.probed_duration = probe.metadata.as_ref().map(|metadata| metadata.duration);
+
+    if full_decode {
+        let (success, stderr) = run_decode_pass(app_handle, file_path, &[]).await?;
+        if !success && probe.metadata.is_none() {
+            observations.open_error = Some(stderr);
+        } else {
+            observations.decode_errors = collect_decode_errors(&stderr);
+            observations.decoded_duration = parse_ffmpeg_final_stats(&stderr).and_then(|stats| stats.time);
+        }
+    } else {
+        let sample = format!("{}", SAMPLE_SECONDS);
+        let (success, start_stderr) = run_decode_pass(app_handle, file_path, &["-t", &sample]).await?;
+        if !success && probe.metadata.is_none() {
+            observations.open_error = Some(start_stderr);
+        } else {
+            let end_offset = format!("-{}", SAMPLE_SECONDS);
+            let (_, end_stderr) = run_decode_pass(app_handle, file_path, &["-sseof", &end_offset]).await?;
+            observations.decode_errors = collect_decode_errors(&start_stderr);
+            observations.decode_errors.extend(collect_decode_errors(&end_stderr));
+            observations.end_sample_frames =
+                Some(parse_ffmpeg_final_stats(&end_stderr).map(|stats| stats.frames).unwrap_or(0));
+        }
+    }
+
+    // Audio gaps come from packet timing, which needs no decoding
+    if has_audio && observations.open_error.is_none() {
+        if let Some(packets) = probe_audio_packets(app_handle, file_path).await {
+            observations.audio_gaps = find_audio_gaps(&packets);
+        }
+    }
+
+    Ok(build_media_health_report(file_path, full_decode, &observations))
+}
+
+/// Repair a file into `output_path` and scan the result
+pub async fn repair_media_internal(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    output_path: &Path,
+) -> CommandResult<MediaHealthReport> {
+    let report = scan_media_integrity_internal(app_handle, file_path, false).await?;
+    if !report.repairable && !report.healthy {
+        let reasons: Vec<String> = report.issues.iter().map(|issue| issue.message.clone()).collect();
+        return Err(CommandError::validation_error(format!(
+            "File cannot be repaired: {}",
+            reasons.join("; ")
+        )));
+    }
+
+    let fix_audio_gaps = report.issues.iter().any(|issue| issue.kind == "audio_gap");
+    remux_media_file(app_handle, file_path, output_path, fix_audio_gaps).await?;
+
+    scan_media_integrity_internal(app_handle, &output_path.to_string_lossy(), false).await
+}
+
+/// Remux a file, regenerating timestamps and the index
+///
+/// Renders into a partial file and renames it, so the output path only ever
+/// holds a complete file.
+pub async fn remux_media_file(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    output_path: &Path,
+    fix_audio_gaps: bool,
+) -> CommandResult<()> {
+    let extension = output_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("mp4")
+        .to_string();
+    let partial_path = output_path.with_extension(format!("partial.{}", extension));
+    let args = build_repair_args(file_path, &partial_path.to_string_lossy(), fix_audio_gaps);
+
+    // Execute FFmpeg
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&partial_path);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg repair failed: {}",
+            stderr
+        )));
+    }
+
+    std::fs::rename(&partial_path, output_path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial_path);
+        CommandError::io_error(format!("Failed to move repaired file into place: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Decode a file to the null muxer, returning whether FFmpeg succeeded and its stderr
+async fn run_decode_pass(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    input_args: &[&str],
+) -> CommandResult<(bool, String)> {
+    let mut args = vec!["-v", "error", "-stats"];
+    args.extend_from_slice(input_args);
+    args.extend_from_slice(&["-i", file_path, "-f", "null", "-"]);
+
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    Ok((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    ))
+}
+
+/// Read audio packet timing (pts, duration) with ffprobe
+async fn probe_audio_packets(app_handle: &tauri::AppHandle, file_path: &str) -> Option<Vec<(f64, f64)>> {
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffprobe([
+            "-v",
+            "error",
+            "-select_streams",
+            "a:0",
+            "-show_entries",
+            "packet=pts_time,duration_time",
+            "-of",
+            "csv=p=0",
+            file_path,
+        ]))
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(parse_audio_packets(&String::from_utf8_lossy(&output.stdout)))
+}
+
+// ============================================================================
+// ANALYSIS FUNCTIONS
+// ============================================================================
+
+/// Collect decoder error messages from `-v error -stats` output
+///
+/// Everything except the progress lines is an error at this log level.
+pub fn collect_decode_errors(stderr: &str) -> Vec<String> {
+    stderr
+        .split(['\r', '\n'])
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .filter(|line| !line.starts_with("frame=") && !line.starts_with("size="))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Parse ffprobe audio packets ("pts_time,duration_time" per line)
+pub fn parse_audio_packets(csv: &str) -> Vec<(f64, f64)> {
+    csv.lines()
+        .filter_map(|line| {
+            let (pts, duration) = line.trim().split_once(',')?;
+            Some((pts.parse::<f64>().ok()?, duration.trim_end_matches(',').parse::<f64>().ok()?))
+        })
+        .collect()
+}
+
+/// Find gaps between consecutive audio packets
+pub fn find_audio_gaps(packets: &[(f64, f64)]) -> Vec<AudioGap> {
+    let mut packets = packets.to_vec();
+    packets.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    packets
+        .windows(2)
+        .filter_map(|pair| {
+            let packet_end = pair[0].0 + pair[0].1;
+            let gap = pair[1].0 - packet_end;
+            if gap >= AUDIO_GAP_MIN_SECONDS {
+                Some(AudioGap {
+                    start: packet_end,
+                    duration: gap,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Build a health report from scan observations
+pub fn build_media_health_report(
+    file_path: &str,
+    full_decode: bool,
+    observations: &IntegrityObservations,
+) -> MediaHealthReport {
+    let mut issues = Vec::new();
+
+    if let Some(open_error) = &observations.open_error {
+        if open_error.contains("moov atom not found") {
+            issues.push(MediaHealthIssue {
+                kind: "missing_index".to_string(),
+                severity: "error".to_string(),
+                message: "The moov atom (file index) is missing, usually because the recording was interrupted. It cannot be rebuilt without a reference recording from the same device".to_string(),
+            });
+        } else {
+            issues.push(MediaHealthIssue {
+                kind: "unreadable".to_string(),
+                severity: "error".to_string(),
+                message: format!(
+                    "The file could not be opened: {}",
+                    open_error.lines().last().unwrap_or("unknown error").trim()
+                ),
+            });
+        }
+    }
+
+    if !observations.decode_errors.is_empty() {
+        let mut examples: Vec<&str> = observations.decode_errors.iter().map(|e| e.as_str()).collect();
+        examples.truncate(MAX_REPORTED_DECODE_ERRORS);
+        issues.push(MediaHealthIssue {
+            kind: "decode_errors".to_string(),
+            severity: "warning".to_string(),
+            message: format!(
+                "{} decoding errors (e.g. {})",
+                observations.decode_errors.len(),
+                examples.join("; ")
+            ),
+        });
+    }
+
+    // Full decode: compare decoded and container durations
+    if let (Some(probed), Some(decoded)) = (observations.probed_duration, observations.decoded_duration) {
+        let missing = probed - decoded;
+        if missing > TRUNCATION_MIN_SECONDS && missing / probed > TRUNCATION_MIN_RATIO {
+            issues.push(MediaHealthIssue {
+                kind: "truncated".to_string(),
+                severity: "error".to_string(),
+                message: format!(
+                    "Only {:.2}s of {:.2}s could be decoded",
+                    decoded, probed
+                ),
+            });
+        }
+    }
+
+    // Sampled scan: nothing decodable at the end means the data stops early
+    if observations.end_sample_frames == Some(0) && observations.open_error.is_none() {
+        issues.push(MediaHealthIssue {
+            kind: "truncated".to_string(),
+            severity: "error".to_string(),
+            message: "No frames could be decoded near the end of the file".to_string(),
+        });
+    }
+
+    if !observations.audio_gaps.is_empty() {
+        let total_gap: f64 = observations.audio_gaps.iter().map(|gap| gap.duration).sum();
+        issues.push(MediaHealthIssue {
+            kind: "audio_gap".to_string(),
+            severity: "warning".to_string(),
+            message: format!(
+                "{} gaps in the audio ({:.2}s total, first at {:.2}s)",
+                observations.audio_gaps.len(),
+                total_gap,
+                observations.audio_gaps[0].start
+            ),
+        });
+    }
+
+    let repairable = issues
+        .iter()
+        .all(|issue| issue.kind != "missing_index" && issue.kind != "unreadable");
+
+    MediaHealthReport {
+        file_path: file_path.to_string(),
+        healthy: issues.is_empty(),
+        repairable: repairable && !issues.is_empty(),
+        full_decode,
+        probed_duration: observations.probed_duration,
+        decoded_duration: observations.decoded_duration,
+        issues,
+    }
+}
+
+/// Whether a report has issues that make the file unusable as-is
+pub fn has_blocking_issues(report: &MediaHealthReport) -> bool {
+    report.issues.iter().any(|issue| issue.severity == "error")
+}
+
+// ============================================================================
+// REPAIR FUNCTIONS
+// ============================================================================
+
+/// Get the default output path for a repaired file
+pub fn get_repaired_path(file_path: &Path) -> PathBuf {
+    let stem = file_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("video");
+    let extension = file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("mp4");
+
+    file_path.with_file_name(format!("{}.repaired.{}", stem, extension))
+}
+
+/// Build FFmpeg arguments for a repair remux
+///
+/// Corrupt packets are dropped and timestamps regenerated while streams are
+/// copied. Audio gaps need the audio re-encoded so silence can be inserted.
+pub fn build_repair_args(input_path: &str, output_path: &str, fix_audio_gaps: bool) -> Vec<String> {
+    let mut args = vec![
+        "-err_detect".to_string(),
+        "ignore_err".to_string(),
+        "-fflags".to_string(),
+        "+genpts+discardcorrupt".to_string(),
+        "-i".to_string(),
+        input_path.to_string(),
+        "-map".to_string(),
+        "0:v?".to_string(),
+        "-map".to_string(),
+        "0:a?".to_string(),
+        "-c:v".to_string(),
+        "copy".to_string(),
+    ];
+
+    if fix_audio_gaps {
+        args.extend(vec![
+            "-af".to_string(),
+            "aresample=async=1:first_pts=0".to_string(),
+            "-c:a".to_string(),
+            "aac".to_string(),
+            "-b:a".to_string(),
+            "192k".to_string(),
+        ]);
+    } else {
+        args.extend(vec!["-c:a".to_string(), "copy".to_string()]);
+    }
+
+    args.extend(vec![
+        "-movflags".to_string(),
+        "+faststart".to_string(), // Write the index at the front
+        "-y".to_string(),         // Overwrite output file
+        output_path.to_string(),
+    ]);
+
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_decode_errors() {
+        let stderr = "[h264 @ 0x7f] error while decoding MB 12 4, bytestream -5\n\
+                      frame=  120 fps=0.0 q=-0.0 size=N/A time=00:00:04.00 bitrate=N/A speed=8x\r\
+                      [h264 @ 0x7f] concealing 300 DC, 300 AC, 300 MV errors in P frame\n\
+                      frame=  300 fps=0.0 q=-0.0 Lsize=N/A time=00:00:10.00 bitrate=N/A speed=9x\n";
+
+        let errors = collect_decode_errors(stderr);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("error while decoding"));
+    }
+
+    #[test]
+    fn test_find_audio_gaps() {
+        let packets = parse_audio_packets("0.000000,0.021333\n0.021333,0.021333\n0.542667,0.021333\n0.564000,0.021333\n");
+        let gaps = find_audio_gaps(&packets);
+
+        assert_eq!(gaps.len(), 1);
+        assert!((gaps[0].start - 0.042666).abs() < 0.0001);
+        assert!((gaps[0].duration - 0.500001).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_health_report_healthy() {
+        let observations = IntegrityObservations {
+            probed_duration: Some(60.0),
+            decoded_duration: Some(59.9),
+            ..Default::default()
+        };
+
+        let report = build_media_health_report("/v.mp4", true, &observations);
+        assert!(report.healthy);
+        assert!(!report.repairable);
+        assert!(!has_blocking_issues(&report));
+    }
+
+    #[test]
+    fn test_health_report_missing_index() {
+        let observations = IntegrityObservations {
+            open_error: Some("[mov,mp4,m4a,3gp,3g2,mj2 @ 0x7f] moov atom not found\n/v.mp4: Invalid data found when processing input".to_string()),
+            ..Default::default()
+        };
+
+        let report = build_media_health_report("/v.mp4", false, &observations);
+        assert!(!report.healthy);
+        assert!(!report.repairable);
+        assert_eq!(report.issues[0].kind, "missing_index");
+        assert!(has_blocking_issues(&report));
+    }
+
+    #[test]
+    fn test_health_report_truncated_and_gaps() {
+        let observations = IntegrityObservations {
+            probed_duration: Some(60.0),
+            decoded_duration: Some(42.0),
+            decode_errors: vec!["error while decoding MB".to_string()],
+            audio_gaps: vec![AudioGap { start: 10.0, duration: 0.5 }],
+            ..Default::default()
+        };
+
+        let report = build_media_health_report("/v.mov", true, &observations);
+        let kinds: Vec<&str> = report.issues.iter().map(|issue| issue.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["decode_errors", "truncated", "audio_gap"]);
+        assert!(report.repairable);
+
+        // Sampled scans detect truncation from the end sample
+        let sampled = IntegrityObservations {
+            probed_duration: Some(60.0),
+            end_sample_frames: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(build_media_health_report("/v.mov", false, &sampled).issues[0].kind, "truncated");
+    }
+
+    #[test]
+    fn test_build_repair_args() {
+        let args = build_repair_args("in.mov", "out.partial.mov", false);
+        assert!(args.windows(2).any(|w| w[0] == "-c:a" && w[1] == "copy"));
+        assert!(args.windows(2).any(|w| w[0] == "-fflags" && w[1] == "+genpts+discardcorrupt"));
+
+        let args = build_repair_args("in.mov", "out.partial.mov", true);
+        assert!(args.windows(2).any(|w| w[0] == "-c:a" && w[1] == "aac"));
+        assert_eq!(args.last().unwrap(), "out.partial.mov");
+
+        assert_eq!(
+            get_repaired_path(Path::new("/rec/screen.mov")),
+            PathBuf::from("/rec/screen.repaired.mov")
+        );
+    }
+}
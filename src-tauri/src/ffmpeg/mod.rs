@@ -3,11 +3,35 @@
 // ============================================================================
 // This module contains FFmpeg integration functionality for video processing.
 
+pub mod capabilities;
+pub mod conform;
 pub mod export;
+pub mod hover_preview;
+pub mod integrity;
+pub mod packaging;
 pub mod probe;
+pub mod proxy;
+pub mod render_cache;
+pub mod runner;
+pub mod scene;
+pub mod scrub;
+pub mod silence;
 pub mod thumbnail;
+pub mod waveform;
 
 // Re-export commonly used types and functions
+pub use capabilities::*;
+pub use conform::*;
 pub use export::*;
+pub use hover_preview::*;
+pub use integrity::*;
+pub use packaging::*;
 pub use probe::*;
+pub use proxy::*;
+pub use render_cache::*;
+pub use runner::*;
+pub use scene::*;
+pub use scrub::*;
+pub use silence::*;
 pub use thumbnail::*;
+pub use waveform::*;
@@ -0,0 +1,814 @@
+// ============================================================================
+// FFMPEG STREAMING PACKAGE MODULE
+// ============================================================================
+// This module packages a timeline for adaptive streaming. It renders every
+// rendition of an ABR ladder in one FFmpeg pass and writes an HLS package
+// (optionally with a DASH manifest) into a staging directory that replaces
+// the output directory only once the package is complete.
+
+use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::export::{
+    generate_concat_file_with_tracking, get_export_encoders, sort_clips_by_track_and_timeline_position,
+    trim_clips_for_export_with_tracking, use_original_sources, validate_export_clips_trim_data,
+    validate_timeline_clips_for_export, ExportClip, ExportEncoders, TempFileTracker,
+    MAX_VIDEO_BITRATE_KBPS,
+};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// One rendition of an ABR ladder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbrRendition {
+    pub name: String, // Used for playlist and segment names, e.g. "720p"
+    pub width: u32,
+    pub height: u32,
+    pub video_bitrate_kbps: u32,
+    pub audio_bitrate_kbps: u32,
+}
+
+/// Request to package clips for adaptive streaming
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingPackageRequest {
+    pub clips: Vec<ExportClip>,
+    pub output_dir: String, // Package directory, replaced when complete
+    pub renditions: Vec<AbrRendition>,
+    #[serde(default)]
+    pub include_dash: bool, // Also write a DASH manifest (fMP4 segments)
+}
+
+/// Response from streaming package operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingPackageResponse {
+    pub success: bool,
+    pub output_dir: Option<String>,
+    pub master_playlist_path: Option<String>,
+    pub dash_manifest_path: Option<String>,
+    pub renditions: Vec<AbrRendition>, // Renditions actually rendered
+    pub error_message: Option<String>,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Target segment length (seconds)
+pub const STREAMING_SEGMENT_DURATION: u32 = 6;
+
+/// HLS master playlist written by both the HLS and DASH muxers
+pub const MASTER_PLAYLIST_NAME: &str = "master.m3u8";
+
+/// DASH manifest name
+pub const DASH_MANIFEST_NAME: &str = "manifest.mpd";
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Package clips as HLS (and optionally DASH) from an ABR ladder
+#[tauri::command]
+pub async fn package_streaming_export(
+    app_handle: tauri::AppHandle,
+    mut request: StreamingPackageRequest,
+) -> CommandResult<StreamingPackageResponse> {
+    // Package from full-quality originals
+    request.clips = use_original_sources(request.clips);
+
+    // Validate input
+    if request.clips.is_empty() {
+        return Err(CommandError::validation_error(
+            "No clips to export".to_string(),
+        ));
+    }
+    validate_export_clips_trim_data(&request.clips)?;
+    validate_timeline_clips_for_export(&request.clips)?;
+    validate_abr_ladder(&request.renditions)?;
+
+    // Validate output location
+    let parent_dir = std::path::Path::new(&request.output_dir)
+        .parent()
+        .ok_or_else(|| CommandError::validation_error("Invalid output directory".to_string()))?;
+    if !parent_dir.exists() {
+        return Err(CommandError::validation_error(
+            "Output directory does not exist".to_string(),
+        ));
+    }
+    check_package_output_dir(&request.output_dir)?;
+
+    match package_streaming_internal(&app_handle, &request).await {
+        Ok(renditions) => {
+            let output_dir = std::path::Path::new(&request.output_dir);
+            let dash_manifest_path = if request.include_dash {
+                Some(output_dir.join(DASH_MANIFEST_NAME).to_string_lossy().to_string())
+            } else {
+                None
+            };
+
+            Ok(StreamingPackageResponse {
+                success: true,
+                output_dir: Some(request.output_dir.clone()),
+                master_playlist_path: Some(
+                    output_dir.join(MASTER_PLAYLIST_NAME).to_string_lossy().to_string(),
+                ),
+                dash_manifest_path,
+                renditions,
+                error_message: None,
+            })
+        }
+        Err(error) => Ok(StreamingPackageResponse {
+            success: false,
+            output_dir: None,
+            master_playlist_path: None,
+            dash_manifest_path: None,
+            renditions: vec![],
+            error_message: Some(error.message),
+        }),
+    }
+}
+
+// ============================================================================
+// INTERNAL FUNCTIONS
+// ============================================================================
+
+/// Render and package the timeline, returning the renditions that were rendered
+async fn package_streaming_internal(
+    app_handle: &tauri::AppHandle,
+    request: &StreamingPackageRequest,
+) -> CommandResult<Vec<AbrRendition>> {
+    // Create temporary file tracker
+    let mut tracker = TempFileTracker::new();
+    let encoders = get_export_encoders(app_handle).await;
+
+    // Trim clips that need trimming with tracking
+    let trimmed_clips = match trim_clips_for_export_with_tracking(app_handle, &request.clips, &encoders, &mut tracker).await {
+        Ok(clips) => clips,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+
+    // Generate concat file using trimmed clips and track it
+    let concat_file_path = match generate_concat_file_with_tracking(&trimmed_clips, &mut tracker).await {
+        Ok(path) => path,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+
+    // Probe the first clip for its resolution and audio track
+    let first_clip = match sort_clips_by_track_and_timeline_position(&trimmed_clips).into_iter().next() {
+        Some(clip) => clip,
+        None => {
+            let _ = tracker.cleanup_all().await;
+            return Err(CommandError::validation_error("No clips to export".to_string()));
+        }
+    };
+    let probe = match crate::ffmpeg::probe::extract_video_metadata(
+        app_handle.clone(),
+        crate::ffmpeg::probe::ExtractMetadataRequest {
+            file_path: first_clip.file_path.clone(),
+        },
+    )
+    .await
+    {
+        Ok(probe) => probe,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+    let source = match probe.metadata {
+        Some(metadata) => metadata,
+        None => {
+            let _ = tracker.cleanup_all().await;
+            return Err(CommandError::ffmpeg_error(format!(
+                "Failed to probe source video: {}",
+                probe.error_message.unwrap_or_default()
+            )));
+        }
+    };
+
+    // Never upscale past the source
+    let renditions = select_renditions_for_source(&request.renditions, source.height);
+
+    // Render into a staging directory tracked until the package is complete
+    let staging_dir = match create_staging_package_dir(&request.output_dir) {
+        Ok(dir) => dir,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+    tracker.add_directory(staging_dir.clone());
+    if let Err(e) = std::fs::create_dir_all(&staging_dir) {
+        let _ = tracker.cleanup_all().await;
+        return Err(CommandError::io_error(format!("Failed to create package directory: {}", e)));
+    }
+
+    // Build FFmpeg command
+    let args = if request.include_dash {
+        build_dash_package_args(&concat_file_path, &staging_dir, &renditions, source.has_audio, &encoders)
+    } else {
+        build_hls_package_args(&concat_file_path, &staging_dir, &renditions, source.has_audio, &encoders)
+    };
+
+    // Execute FFmpeg
+    let output = match ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await
+    {
+        Ok(output) => output,
+        Err(error) => {
+            let _ = tracker.cleanup_all().await;
+            return Err(error);
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let _ = tracker.cleanup_all().await;
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg packaging failed: {}",
+            stderr
+        )));
+    }
+
+    // Make sure the package has its entry points before replacing anything
+    let staging_path = std::path::Path::new(&staging_dir);
+    let mut required_files = vec![MASTER_PLAYLIST_NAME];
+    if request.include_dash {
+        required_files.push(DASH_MANIFEST_NAME);
+    }
+    for required_file in required_files {
+        if !staging_path.join(required_file).exists() {
+            let _ = tracker.cleanup_all().await;
+            return Err(CommandError::ffmpeg_error(format!(
+                "Streaming package is incomplete: {} was not written",
+                required_file
+            )));
+        }
+    }
+
+    if let Err(error) = promote_staged_package(&staging_dir, &request.output_dir) {
+        let _ = tracker.cleanup_all().await;
+        return Err(error);
+    }
+
+    // Clean up all temporary files
+    tracker.cleanup_all().await?;
+
+    Ok(renditions)
+}
+
+// ============================================================================
+// ABR LADDER FUNCTIONS
+// ============================================================================
+
+/// Create a rendition entry
+fn rendition(name: &str, width: u32, height: u32, video_bitrate_kbps: u32, audio_bitrate_kbps: u32) -> AbrRendition {
+    AbrRendition {
+        name: name.to_string(),
+        width,
+        height,
+        video_bitrate_kbps,
+        audio_bitrate_kbps,
+    }
+}
+
+/// Get the ABR ladder for a streaming preset
+pub fn get_abr_ladder(preset: &str) -> CommandResult<Vec<AbrRendition>> {
+    match preset {
+        "standard" => Ok(vec![
+            rendition("1080p", 1920, 1080, 5000, 192),
+            rendition("720p", 1280, 720, 2800, 128),
+            rendition("480p", 854, 480, 1400, 128),
+            rendition("360p", 640, 360, 800, 96),
+        ]),
+        "mobile" => Ok(vec![
+            rendition("720p", 1280, 720, 2000, 128),
+            rendition("480p", 854, 480, 1000, 96),
+            rendition("360p", 640, 360, 600, 64),
+        ]),
+        "low_bandwidth" => Ok(vec![
+            rendition("480p", 854, 480, 800, 96),
+            rendition("360p", 640, 360, 500, 64),
+            rendition("240p", 426, 240, 300, 64),
+        ]),
+        _ => Err(CommandError::validation_error(format!(
+            "Invalid streaming preset '{}'. Must be 'standard', 'mobile', or 'low_bandwidth'",
+            preset
+        ))),
+    }
+}
+
+/// Validate an ABR ladder
+pub fn validate_abr_ladder(renditions: &[AbrRendition]) -> CommandResult<()> {
+    if renditions.is_empty() {
+        return Err(CommandError::validation_error(
+            "ABR ladder must contain at least one rendition".to_string(),
+        ));
+    }
+
+    for (index, rendition) in renditions.iter().enumerate() {
+        let valid_name = !rendition.name.is_empty()
+            && rendition
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            return Err(CommandError::validation_error(format!(
+                "Rendition {} has an invalid name '{}' (use letters, digits, '-' or '_')",
+                index + 1,
+                rendition.name
+            )));
+        }
+
+        if renditions[..index].iter().any(|other| other.name == rendition.name) {
+            return Err(CommandError::validation_error(format!(
+                "Duplicate rendition name '{}'",
+                rendition.name
+            )));
+        }
+
+        // H.264 with 4:2:0 chroma needs even dimensions
+        if rendition.width == 0 || rendition.height == 0 || rendition.width % 2 != 0 || rendition.height % 2 != 0 {
+            return Err(CommandError::validation_error(format!(
+                "Rendition '{}' has invalid dimensions {}x{} (must be even and non-zero)",
+                rendition.name, rendition.width, rendition.height
+            )));
+        }
+
+        if rendition.video_bitrate_kbps == 0 || rendition.audio_bitrate_kbps == 0 {
+            return Err(CommandError::validation_error(format!(
+                "Rendition '{}' must have non-zero bitrates",
+                rendition.name
+            )));
+        }
+
+        if rendition.video_bitrate_kbps > MAX_VIDEO_BITRATE_KBPS {
+            return Err(CommandError::validation_error(format!(
+                "Rendition '{}' exceeds the maximum video bitrate of {} kbps",
+                rendition.name, MAX_VIDEO_BITRATE_KBPS
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Drop renditions taller than the source
+///
+/// The smallest rendition is kept when the source is smaller than all of them.
+pub fn select_renditions_for_source(renditions: &[AbrRendition], source_height: u32) -> Vec<AbrRendition> {
+    let selected: Vec<AbrRendition> = renditions
+        .iter()
+        .filter(|rendition| rendition.height <= source_height)
+        .cloned()
+        .collect();
+
+    if !selected.is_empty() {
+        return selected;
+    }
+
+    renditions
+        .iter()
+        .min_by_key(|rendition| rendition.height)
+        .cloned()
+        .into_iter()
+        .collect()
+}
+
+// ============================================================================
+// FFMPEG ARGUMENT FUNCTIONS
+// ============================================================================
+
+/// Build the filter graph that splits the input and scales each rendition
+///
+/// Output labels are `[v0]`, `[v1]`, ... in ladder order.
+pub fn build_rendition_filter(renditions: &[AbrRendition]) -> String {
+    let split_outputs: String = (0..renditions.len()).map(|i| format!("[s{}]", i)).collect();
+    let mut filter = format!("[0:v]split={}{}", renditions.len(), split_outputs);
+
+    for (i, rendition) in renditions.iter().enumerate() {
+        filter.push_str(&format!(
+            ";[s{}]scale=w={}:h={}:force_original_aspect_ratio=decrease:force_divisible_by=2[v{}]",
+            i, rendition.width, rendition.height, i
+        ));
+    }
+
+    filter
+}
+
+/// Build input, filter and per-rendition video encoder arguments
+fn build_rendition_video_args(concat_file_path: &str, renditions: &[AbrRendition], encoders: &ExportEncoders) -> Vec<String> {
+    let mut args = vec![
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        concat_file_path.to_string(),
+        "-filter_complex".to_string(),
+        build_rendition_filter(renditions),
+    ];
+
+    for (i, rendition) in renditions.iter().enumerate() {
+        args.extend(vec![
+            "-map".to_string(),
+            format!("[v{}]", i),
+            format!("-c:v:{}", i),
+            encoders.video.clone(),
+            format!("-b:v:{}", i),
+            format!("{}k", rendition.video_bitrate_kbps),
+            format!("-maxrate:v:{}", i),
+            format!("{}k", rendition.video_bitrate_kbps as u64 * 107 / 100),
+            format!("-bufsize:v:{}", i),
+            format!("{}k", rendition.video_bitrate_kbps as u64 * 3 / 2),
+        ]);
+    }
+
+    args
+}
+
+/// Get encoder arguments that align keyframes with segment boundaries
+fn get_segment_keyframe_args(encoders: &ExportEncoders) -> Vec<String> {
+    let mut args = Vec::new();
+    if encoders.video == "libx264" {
+        args.extend(vec!["-preset".to_string(), "medium".to_string()]);
+    }
+    args.extend(vec![
+        "-sc_threshold".to_string(),
+        "0".to_string(), // No extra keyframes on scene cuts
+        "-force_key_frames".to_string(),
+        format!("expr:gte(t,n_forced*{})", STREAMING_SEGMENT_DURATION),
+    ]);
+    args
+}
+
+/// Build FFmpeg arguments for an HLS package with one playlist per rendition
+///
+/// Each rendition gets its own audio encode so every variant is self-contained.
+/// Playlists and segments are written flat into `package_dir` next to the
+/// master playlist.
+pub fn build_hls_package_args(
+    concat_file_path: &str,
+    package_dir: &str,
+    renditions: &[AbrRendition],
+    has_audio: bool,
+    encoders: &ExportEncoders,
+) -> Vec<String> {
+    let mut args = build_rendition_video_args(concat_file_path, renditions, encoders);
+
+    if has_audio {
+        for (i, rendition) in renditions.iter().enumerate() {
+            args.extend(vec![
+                "-map".to_string(),
+                "0:a:0".to_string(),
+                format!("-c:a:{}", i),
+                encoders.audio.clone(),
+                format!("-b:a:{}", i),
+                format!("{}k", rendition.audio_bitrate_kbps),
+            ]);
+        }
+    }
+
+    args.extend(get_segment_keyframe_args(encoders));
+
+    let var_stream_map = renditions
+        .iter()
+        .enumerate()
+        .map(|(i, rendition)| {
+            if has_audio {
+                format!("v:{},a:{},name:{}", i, i, rendition.name)
+            } else {
+                format!("v:{},name:{}", i, rendition.name)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let package_path = std::path::Path::new(package_dir);
+    args.extend(vec![
+        "-f".to_string(),
+        "hls".to_string(),
+        "-hls_time".to_string(),
+        STREAMING_SEGMENT_DURATION.to_string(),
+        "-hls_playlist_type".to_string(),
+        "vod".to_string(),
+        "-hls_flags".to_string(),
+        "independent_segments".to_string(),
+        "-hls_segment_filename".to_string(),
+        package_path.join("%v_%05d.ts").to_string_lossy().to_string(),
+        "-master_pl_name".to_string(),
+        MASTER_PLAYLIST_NAME.to_string(),
+        "-var_stream_map".to_string(),
+        var_stream_map,
+        "-y".to_string(),
+        package_path.join("%v.m3u8").to_string_lossy().to_string(),
+    ]);
+
+    args
+}
+
+/// Build FFmpeg arguments for a DASH package that also writes HLS playlists
+///
+/// The DASH muxer shares fMP4 segments between the MPD and the HLS master
+/// playlist, so audio is encoded once at the highest ladder bitrate.
+pub fn build_dash_package_args(
+    concat_file_path: &str,
+    package_dir: &str,
+    renditions: &[AbrRendition],
+    has_audio: bool,
+    encoders: &ExportEncoders,
+) -> Vec<String> {
+    let mut args = build_rendition_video_args(concat_file_path, renditions, encoders);
+
+    let adaptation_sets = if has_audio {
+        let audio_bitrate = renditions
+            .iter()
+            .map(|rendition| rendition.audio_bitrate_kbps)
+            .max()
+            .unwrap_or(128);
+        args.extend(vec![
+            "-map".to_string(),
+            "0:a:0".to_string(),
+            "-c:a".to_string(),
+            encoders.audio.clone(),
+            "-b:a".to_string(),
+            format!("{}k", audio_bitrate),
+        ]);
+        "id=0,streams=v id=1,streams=a"
+    } else {
+        "id=0,streams=v"
+    };
+
+    args.extend(get_segment_keyframe_args(encoders));
+
+    args.extend(vec![
+        "-f".to_string(),
+        "dash".to_string(),
+        "-seg_duration".to_string(),
+        STREAMING_SEGMENT_DURATION.to_string(),
+        "-use_template".to_string(),
+        "1".to_string(),
+        "-use_timeline".to_string(),
+        "1".to_string(),
+        "-adaptation_sets".to_string(),
+        adaptation_sets.to_string(),
+        "-hls_playlist".to_string(),
+        "1".to_string(), // Also write master.m3u8
+        "-y".to_string(),
+        std::path::Path::new(package_dir)
+            .join(DASH_MANIFEST_NAME)
+            .to_string_lossy()
+            .to_string(),
+    ]);
+
+    args
+}
+
+// ============================================================================
+// PACKAGE OUTPUT FUNCTIONS
+// ============================================================================
+
+/// Validate a package folder name; it must be a single plain path component
+pub fn validate_package_name(package_name: &str) -> CommandResult<()> {
+    let mut components = std::path::Path::new(package_name).components();
+    let is_plain_name = matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(_)), None)
+    );
+
+    if !is_plain_name || package_name.contains(['/', '\\']) {
+        return Err(CommandError::validation_error(format!(
+            "Invalid package name: {:?}",
+            package_name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Whether `path` is a directory holding a streaming package
+fn is_streaming_package_dir(path: &std::path::Path) -> bool {
+    path.is_dir()
+        && (path.join(MASTER_PLAYLIST_NAME).exists() || path.join(DASH_MANIFEST_NAME).exists())
+}
+
+/// Make sure packaging will only ever replace a previous package
+pub fn check_package_output_dir(output_dir: &str) -> CommandResult<()> {
+    let output_path = std::path::Path::new(output_dir);
+    if output_path.exists() && !is_streaming_package_dir(output_path) {
+        return Err(CommandError::validation_error(format!(
+            "{} already exists and is not a streaming package",
+            output_dir
+        )));
+    }
+
+    Ok(())
+}
+
+/// Create a hidden staging directory path next to the package directory
+pub fn create_staging_package_dir(output_dir: &str) -> CommandResult<String> {
+    let path = std::path::Path::new(output_dir);
+    let parent_dir = path
+        .parent()
+        .ok_or_else(|| CommandError::validation_error("Invalid output directory".to_string()))?;
+    let dir_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| CommandError::validation_error("Invalid output directory name".to_string()))?;
+
+    let nanoseconds = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0);
+    let staging_name = format!(".{}.{}.partial", dir_name, nanoseconds);
+
+    Ok(parent_dir.join(staging_name).to_string_lossy().to_string())
+}
+
+/// Move a complete staged package into place, replacing any previous package
+///
+/// Anything at `output_dir` that is not a package is left alone and reported
+/// as an error. The previous package is moved aside first and restored if the final
+/// rename fails.
+pub fn promote_staged_package(staging_dir: &str, output_dir: &str) -> CommandResult<()> {
+    let output_path = std::path::Path::new(output_dir);
+    check_package_output_dir(output_dir)?;
+
+    let backup_dir = if output_path.exists() {
+        let backup_dir = format!("{}.{}.old", staging_dir, std::process::id());
+        std::fs::rename(output_dir, &backup_dir).map_err(|e| {
+            CommandError::io_error(format!("Failed to move previous package aside: {}", e))
+        })?;
+        Some(backup_dir)
+    } else {
+        None
+    };
+
+    if let Err(e) = std::fs::rename(staging_dir, output_dir) {
+        if let Some(backup_dir) = &backup_dir {
+            let _ = std::fs::rename(backup_dir, output_dir);
+        }
+        return Err(CommandError::io_error(format!(
+            "Failed to move package into place: {}",
+            e
+        )));
+    }
+
+    if let Some(backup_dir) = backup_dir {
+        if let Err(e) = std::fs::remove_dir_all(&backup_dir) {
+            eprintln!("Warning: Failed to delete previous package {}: {}", backup_dir, e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_abr_ladder() {
+        let standard = get_abr_ladder("standard").unwrap();
+        assert_eq!(standard.len(), 4);
+        assert_eq!(standard[0].name, "1080p");
+        assert!(validate_abr_ladder(&standard).is_ok());
+
+        for preset in ["standard", "mobile", "low_bandwidth"] {
+            assert!(validate_abr_ladder(&get_abr_ladder(preset).unwrap()).is_ok());
+        }
+
+        assert!(get_abr_ladder("ultra").is_err());
+    }
+
+    #[test]
+    fn test_validate_abr_ladder_errors() {
+        assert!(validate_abr_ladder(&[]).is_err());
+        assert!(validate_abr_ladder(&[rendition("bad name", 1280, 720, 2000, 128)]).is_err());
+        assert!(validate_abr_ladder(&[rendition("odd", 1281, 720, 2000, 128)]).is_err());
+        assert!(validate_abr_ladder(&[rendition("silent", 1280, 720, 2000, 0)]).is_err());
+        assert!(validate_abr_ladder(&[rendition("huge", 1280, 720, MAX_VIDEO_BITRATE_KBPS + 1, 128)]).is_err());
+        assert!(validate_abr_ladder(&[
+            rendition("720p", 1280, 720, 2000, 128),
+            rendition("720p", 1280, 720, 1000, 128),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_select_renditions_for_source() {
+        let ladder = get_abr_ladder("standard").unwrap();
+
+        let selected = select_renditions_for_source(&ladder, 720);
+        let names: Vec<&str> = selected.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["720p", "480p", "360p"]);
+
+        // Tiny sources still get the smallest rendition
+        let selected = select_renditions_for_source(&ladder, 144);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].name, "360p");
+    }
+
+    #[test]
+    fn test_build_rendition_filter() {
+        let ladder = vec![rendition("720p", 1280, 720, 2800, 128), rendition("360p", 640, 360, 800, 96)];
+        assert_eq!(
+            build_rendition_filter(&ladder),
+            "[0:v]split=2[s0][s1];\
+             [s0]scale=w=1280:h=720:force_original_aspect_ratio=decrease:force_divisible_by=2[v0];\
+             [s1]scale=w=640:h=360:force_original_aspect_ratio=decrease:force_divisible_by=2[v1]"
+        );
+    }
+
+    #[test]
+    fn test_build_hls_package_args() {
+        let ladder = vec![rendition("720p", 1280, 720, 2800, 128), rendition("360p", 640, 360, 800, 96)];
+
+        let args = build_hls_package_args("list.txt", "/out/pkg", &ladder, true, &ExportEncoders::default());
+        assert!(args.windows(2).any(|w| w[0] == "-var_stream_map" && w[1] == "v:0,a:0,name:720p v:1,a:1,name:360p"));
+        assert!(args.windows(2).any(|w| w[0] == "-b:v:1" && w[1] == "800k"));
+        assert!(args.windows(2).any(|w| w[0] == "-b:a:0" && w[1] == "128k"));
+        assert!(args.windows(2).any(|w| w[0] == "-master_pl_name" && w[1] == "master.m3u8"));
+        assert_eq!(args.last().unwrap(), "/out/pkg/%v.m3u8");
+
+        let silent = build_hls_package_args("list.txt", "/out/pkg", &ladder, false, &ExportEncoders::default());
+        assert!(!silent.contains(&"0:a:0".to_string()));
+        assert!(silent.windows(2).any(|w| w[0] == "-var_stream_map" && w[1] == "v:0,name:720p v:1,name:360p"));
+    }
+
+    #[test]
+    fn test_build_dash_package_args() {
+        let ladder = vec![rendition("720p", 1280, 720, 2800, 128), rendition("360p", 640, 360, 800, 96)];
+
+        let args = build_dash_package_args("list.txt", "/out/pkg", &ladder, true, &ExportEncoders::default());
+        assert!(args.windows(2).any(|w| w[0] == "-f" && w[1] == "dash"));
+        assert!(args.windows(2).any(|w| w[0] == "-preset" && w[1] == "medium"));
+        assert!(args.windows(2).any(|w| w[0] == "-hls_playlist" && w[1] == "1"));
+        assert!(args.windows(2).any(|w| w[0] == "-b:a" && w[1] == "128k"));
+        assert_eq!(args.iter().filter(|arg| *arg == "0:a:0").count(), 1);
+        assert_eq!(args.last().unwrap(), "/out/pkg/manifest.mpd");
+
+        // Hardware encoders get no libx264 preset
+        let encoders = ExportEncoders {
+            video: "h264_videotoolbox".to_string(),
+            audio: "aac".to_string(),
+        };
+        let args = build_dash_package_args("list.txt", "/out/pkg", &ladder, true, &encoders);
+        assert!(args.windows(2).any(|w| w[0] == "-c:v:1" && w[1] == "h264_videotoolbox"));
+        assert!(!args.contains(&"-preset".to_string()));
+    }
+
+    #[test]
+    fn test_promote_staged_package_replaces_previous_package() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_dir = temp_dir.path().join("talk_hls");
+        let output_str = output_dir.to_str().unwrap();
+
+        std::fs::create_dir_all(&output_dir).unwrap();
+        std::fs::write(output_dir.join("master.m3u8"), b"old").unwrap();
+
+        let staging_dir = create_staging_package_dir(output_str).unwrap();
+        assert!(staging_dir.contains(".talk_hls."));
+        std::fs::create_dir_all(&staging_dir).unwrap();
+        std::fs::write(std::path::Path::new(&staging_dir).join("master.m3u8"), b"new").unwrap();
+
+        promote_staged_package(&staging_dir, output_str).unwrap();
+
+        assert_eq!(std::fs::read(output_dir.join("master.m3u8")).unwrap(), b"new");
+        assert!(!std::path::Path::new(&staging_dir).exists());
+        // Only the package directory is left behind
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_promote_staged_package_keeps_other_directories() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_dir = temp_dir.path().join("Documents");
+        let output_str = output_dir.to_str().unwrap();
+
+        std::fs::create_dir_all(&output_dir).unwrap();
+        std::fs::write(output_dir.join("notes.txt"), b"keep").unwrap();
+
+        let staging_dir = create_staging_package_dir(output_str).unwrap();
+        std::fs::create_dir_all(&staging_dir).unwrap();
+        std::fs::write(std::path::Path::new(&staging_dir).join("master.m3u8"), b"new").unwrap();
+
+        assert!(promote_staged_package(&staging_dir, output_str).is_err());
+        assert_eq!(std::fs::read(output_dir.join("notes.txt")).unwrap(), b"keep");
+    }
+
+    #[test]
+    fn test_validate_package_name() {
+        assert!(validate_package_name("talk_hls").is_ok());
+        assert!(validate_package_name("talk.v2").is_ok());
+
+        for name in ["", ".", "..", "a/b", "../talk", "/tmp", "a\\b"] {
+            assert!(validate_package_name(name).is_err(), "{:?} should be rejected", name);
+        }
+    }
+}
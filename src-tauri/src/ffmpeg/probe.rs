@@ -5,8 +5,9 @@
 // It extracts duration, resolution, fps, codec information, and other metadata.
 
 use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
 use serde::{Deserialize, Serialize};
-use tauri_plugin_shell::ShellExt;
+use std::collections::{BTreeMap, HashMap};
 
 // ============================================================================
 // DATA STRUCTURES
@@ -32,6 +33,8 @@ pub struct VideoMetadata {
     pub has_audio: bool,             // Whether the video has audio track
     pub audio_codec: Option<String>, // Audio codec if present
     pub audio_bitrate: Option<u64>,  // Audio bitrate if present
+    #[serde(default)]
+    pub is_vfr: bool,                // Variable frame rate (needs conforming for accurate edits)
 }
 
 /// Response from metadata extraction
@@ -40,6 +43,91 @@ pub struct ExtractMetadataResponse {
     pub success: bool,
     pub metadata: Option<VideoMetadata>,
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub details: Option<MediaDetails>, // Every stream and container tags (requires ffprobe)
+}
+
+/// Full description of a media file: container plus every stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaDetails {
+    pub container: ContainerInfo,
+    pub video_streams: Vec<VideoStreamInfo>,
+    pub audio_streams: Vec<AudioStreamInfo>,
+    pub subtitle_streams: Vec<SubtitleStreamInfo>,
+    pub data_streams: Vec<DataStreamInfo>,
+}
+
+/// Container-level information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerInfo {
+    pub format_name: Option<String>,      // Demuxer names (e.g., "mov,mp4,m4a,3gp,3g2,mj2")
+    pub format_long_name: Option<String>,
+    pub duration: Option<f64>,            // Duration in seconds
+    pub start_time: Option<f64>,          // First timestamp in seconds
+    pub bit_rate: Option<u64>,            // Bitrate in bits per second
+    pub creation_time: Option<String>,    // From the creation_time tag
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Video stream information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoStreamInfo {
+    pub index: u32,
+    pub codec: Option<String>,
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub level: Option<i64>,                 // Codec level, e.g. 40 for H.264 level 4.0
+    pub pix_fmt: Option<String>,
+    #[serde(default)]
+    pub time_base: Option<String>,          // Stream timebase, e.g. "1/15360"
+    pub width: u32,
+    pub height: u32,
+    pub fps: f64,                           // Average frame rate
+    pub color_space: Option<String>,
+    pub color_range: Option<String>,
+    pub color_primaries: Option<String>,
+    pub color_transfer: Option<String>,
+    pub hdr_format: Option<String>,         // "hdr10", "hlg", "dolby_vision"
+    pub rotation: u32,                      // Clockwise display rotation in degrees
+    pub sample_aspect_ratio: Option<String>,
+    pub display_aspect_ratio: Option<String>,
+    pub frame_count: Option<u64>,
+    pub bit_rate: Option<u64>,
+    pub language: Option<String>,
+}
+
+/// Audio stream information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioStreamInfo {
+    pub index: u32,
+    pub codec: Option<String>,
+    pub profile: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u32>,
+    pub channel_layout: Option<String>,
+    pub bit_rate: Option<u64>,
+    pub language: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Subtitle stream information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleStreamInfo {
+    pub index: u32,
+    pub codec: Option<String>,
+    pub language: Option<String>,
+    pub title: Option<String>,
+    pub is_default: bool,
+    pub is_forced: bool,
+}
+
+/// Data stream information (timecode tracks, camera telemetry)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataStreamInfo {
+    pub index: u32,
+    pub codec: Option<String>,
+    pub codec_tag: Option<String>,
+    pub handler_name: Option<String>,
 }
 
 // ============================================================================
@@ -59,15 +147,17 @@ pub async fn extract_video_metadata(
 
     // Extract metadata using FFmpeg probe
     match extract_metadata_internal(&app_handle, &file_path).await {
-        Ok(metadata) => Ok(ExtractMetadataResponse {
+        Ok((metadata, details)) => Ok(ExtractMetadataResponse {
             success: true,
             metadata: Some(metadata),
             error_message: None,
+            details,
         }),
         Err(error) => Ok(ExtractMetadataResponse {
             success: false,
             metadata: None,
             error_message: Some(error.message),
+            details: None,
         }),
     }
 }
@@ -76,39 +166,421 @@ pub async fn extract_video_metadata(
 // INTERNAL FUNCTIONS
 // ============================================================================
 
-/// Internal function to extract metadata using FFmpeg probe
+/// Internal function to extract metadata using ffprobe
+///
+/// Falls back to parsing FFmpeg's stream summary when ffprobe is not available,
+/// in which case no stream details are returned.
 async fn extract_metadata_internal(
     app_handle: &tauri::AppHandle,
     file_path: &str,
-) -> CommandResult<VideoMetadata> {
-    // Get FFmpeg sidecar
-    let sidecar = app_handle.shell().sidecar("ffmpeg").map_err(|e| {
-        CommandError::ffmpeg_error(format!("Failed to create FFmpeg sidecar: {}", e))
-    })?;
+) -> CommandResult<(VideoMetadata, Option<MediaDetails>)> {
+    extract_metadata_with_runner(&ProcessRunner::new(app_handle), file_path).await
+}
 
-    // Build FFmpeg probe command (using ffmpeg instead of ffprobe)
-    let output = sidecar
-        .args(&["-i", file_path, "-f", "null", "-"])
-        .output()
+/// Extract metadata and stream details through `runner`
+pub async fn extract_metadata_with_runner(
+    runner: &dyn FfmpegRunner,
+    file_path: &str,
+) -> CommandResult<(VideoMetadata, Option<MediaDetails>)> {
+    match run_ffprobe_json(runner, file_path).await? {
+        Some(probe_json) => {
+            let file_size = crate::commands::get_file_size(file_path)?;
+            let probe_data = parse_probe_data(&probe_json)?;
+            let mut metadata = build_video_metadata(&probe_data, file_path, file_size)?;
+
+            // Matching rates can still hide irregular timestamps, so check packet timing
+            if !metadata.is_vfr {
+                if let Some(packet_times) = probe_packet_times(runner, file_path).await {
+                    metadata.is_vfr = is_vfr_by_packet_times(&packet_times);
+                }
+            }
+
+            Ok((metadata, Some(build_media_details(&probe_data))))
+        }
+        None => Ok((extract_metadata_with_ffmpeg(runner, file_path).await?, None)),
+    }
+}
+
+/// Run ffprobe and return its JSON output
+///
+/// Returns `None` when the ffprobe binary is missing. Timeouts and other
+/// failures to run it are errors.
+async fn run_ffprobe_json(
+    runner: &dyn FfmpegRunner,
+    file_path: &str,
+) -> CommandResult<Option<String>> {
+    // Only the container is read, nothing is decoded
+    let output = match runner
+        .run(FfmpegInvocation::ffprobe([
+            "-v",
+            "error",
+            "-print_format",
+            "json",
+            "-show_streams",
+            "-show_format",
+            file_path,
+        ]).with_timeout(FFPROBE_TIMEOUT))
         .await
-        .map_err(|e| CommandError::ffmpeg_error(format!("Failed to execute ffmpeg: {}", e)))?;
+    {
+        Ok(output) => output,
+        Err(error) if error.error_type == "binary_not_found" => return Ok(None),
+        Err(error) => return Err(error),
+    };
 
     // Check if command succeeded
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(CommandError::ffmpeg_error(format!(
-            "FFmpeg probe failed: {}",
+            "ffprobe failed: {}",
             stderr
         )));
     }
 
-    // Parse stderr output (FFmpeg outputs metadata to stderr)
+    Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+/// Read video packet timestamps from the start of a file
+///
+/// Returns `None` when ffprobe is unavailable or fails; VFR detection then
+/// relies on the stream frame rates alone.
+async fn probe_packet_times(runner: &dyn FfmpegRunner, file_path: &str) -> Option<Vec<f64>> {
+    let read_interval = format!("%+{}", VFR_PACKET_PROBE_SECONDS);
+    let output = runner
+        .run(FfmpegInvocation::ffprobe([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-read_intervals",
+            &read_interval,
+            "-show_entries",
+            "packet=pts_time",
+            "-of",
+            "csv=p=0",
+            file_path,
+        ]).with_timeout(FFPROBE_TIMEOUT))
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(parse_packet_times(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Extract metadata from FFmpeg's input summary (used when ffprobe is missing)
+async fn extract_metadata_with_ffmpeg(
+    runner: &dyn FfmpegRunner,
+    file_path: &str,
+) -> CommandResult<VideoMetadata> {
+    // Without an output FFmpeg prints the input summary and exits without decoding
+    let output = runner
+        .run(FfmpegInvocation::ffmpeg(["-hide_banner", "-i", file_path]))
+        .await?;
+
+    // Parse stderr output (FFmpeg outputs metadata to stderr). The exit status is
+    // always an error here because no output file was given.
     let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.contains("Duration:") {
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg probe failed: {}",
+            stderr
+        )));
+    }
 
     // Extract metadata from FFmpeg stderr output
     extract_metadata_from_ffmpeg_output(&stderr, file_path).await
 }
 
+/// Deserialize ffprobe JSON output
+fn parse_probe_data(probe_json: &str) -> CommandResult<ProbeData> {
+    serde_json::from_str(probe_json).map_err(|e| {
+        CommandError::ffmpeg_error(format!("Failed to parse ffprobe output: {}", e))
+    })
+}
+
+/// Build the summary metadata from the first video and audio streams
+fn build_video_metadata(
+    probe_data: &ProbeData,
+    file_path: &str,
+    file_size: u64,
+) -> CommandResult<VideoMetadata> {
+    let video_stream = probe_data
+        .streams
+        .iter()
+        .find(|stream| stream.codec_type.as_deref() == Some("video"))
+        .ok_or_else(|| {
+            CommandError::ffmpeg_error("No video stream found in ffprobe output".to_string())
+        })?;
+    let audio_stream = probe_data
+        .streams
+        .iter()
+        .find(|stream| stream.codec_type.as_deref() == Some("audio"));
+
+    // Prefer the container duration, falling back to the video stream
+    let duration = parse_probe_number(probe_data.format.duration.as_deref())
+        .or_else(|| parse_probe_number(video_stream.duration.as_deref()))
+        .ok_or_else(|| {
+            CommandError::ffmpeg_error("Could not extract duration from ffprobe output".to_string())
+        })?;
+
+    // The average rate is exact for fractional rates (30000/1001) and stays
+    // meaningful for variable frame rate recordings, where r_frame_rate is the
+    // timebase-derived maximum
+    let fps = video_stream
+        .avg_frame_rate
+        .as_deref()
+        .map(parse_fps)
+        .filter(|fps| *fps > 0.0)
+        .or_else(|| video_stream.r_frame_rate.as_deref().map(parse_fps))
+        .unwrap_or(0.0);
+
+    // Extract format from file extension, falling back to the demuxer name
+    let format = std::path::Path::new(file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .or_else(|| {
+            probe_data
+                .format
+                .format_name
+                .as_deref()
+                .and_then(|name| name.split(',').next())
+                .map(|name| name.to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    Ok(VideoMetadata {
+        duration,
+        width: video_stream.width.unwrap_or(0),
+        height: video_stream.height.unwrap_or(0),
+        fps,
+        codec: video_stream.codec_name.clone().unwrap_or_else(|| "unknown".to_string()),
+        bitrate: parse_probe_number(probe_data.format.bit_rate.as_deref()).unwrap_or(0.0) as u64,
+        file_size,
+        format,
+        has_audio: audio_stream.is_some(),
+        audio_codec: audio_stream.and_then(|stream| stream.codec_name.clone()),
+        audio_bitrate: audio_stream
+            .and_then(|stream| parse_probe_number(stream.bit_rate.as_deref()))
+            .map(|bitrate| bitrate as u64),
+        is_vfr: match (video_stream.r_frame_rate.as_deref(), video_stream.avg_frame_rate.as_deref()) {
+            (Some(r_frame_rate), Some(avg_frame_rate)) => is_vfr_by_frame_rates(r_frame_rate, avg_frame_rate),
+            _ => false,
+        },
+    })
+}
+
+/// Build stream details from ffprobe data
+fn build_media_details(probe_data: &ProbeData) -> MediaDetails {
+    let format = &probe_data.format;
+    let container = ContainerInfo {
+        format_name: format.format_name.clone(),
+        format_long_name: format.format_long_name.clone(),
+        duration: parse_probe_number(format.duration.as_deref()),
+        start_time: parse_probe_number(format.start_time.as_deref()),
+        bit_rate: parse_probe_number(format.bit_rate.as_deref()).map(|rate| rate as u64),
+        creation_time: format.tags.get("creation_time").cloned(),
+        tags: format.tags.clone(),
+    };
+
+    let mut details = MediaDetails {
+        container,
+        video_streams: vec![],
+        audio_streams: vec![],
+        subtitle_streams: vec![],
+        data_streams: vec![],
+    };
+
+    for stream in &probe_data.streams {
+        let language = stream.tags.get("language").cloned();
+        let bit_rate = parse_probe_number(stream.bit_rate.as_deref()).map(|rate| rate as u64);
+
+        match stream.codec_type.as_deref() {
+            Some("video") => details.video_streams.push(VideoStreamInfo {
+                index: stream.index,
+                codec: stream.codec_name.clone(),
+                profile: stream.profile.clone(),
+                level: stream.level.filter(|level| *level > 0),
+                pix_fmt: stream.pix_fmt.clone(),
+                time_base: stream.time_base.clone(),
+                width: stream.width.unwrap_or(0),
+                height: stream.height.unwrap_or(0),
+                fps: stream
+                    .avg_frame_rate
+                    .as_deref()
+                    .map(parse_fps)
+                    .filter(|fps| *fps > 0.0)
+                    .or_else(|| stream.r_frame_rate.as_deref().map(parse_fps))
+                    .unwrap_or(0.0),
+                color_space: stream.color_space.clone(),
+                color_range: stream.color_range.clone(),
+                color_primaries: stream.color_primaries.clone(),
+                color_transfer: stream.color_transfer.clone(),
+                hdr_format: get_hdr_format(stream),
+                rotation: get_stream_rotation(stream),
+                sample_aspect_ratio: stream.sample_aspect_ratio.clone(),
+                display_aspect_ratio: stream.display_aspect_ratio.clone(),
+                frame_count: stream.nb_frames.as_deref().and_then(|count| count.parse().ok()),
+                bit_rate,
+                language,
+            }),
+            Some("audio") => details.audio_streams.push(AudioStreamInfo {
+                index: stream.index,
+                codec: stream.codec_name.clone(),
+                profile: stream.profile.clone(),
+                sample_rate: stream.sample_rate.as_deref().and_then(|rate| rate.parse().ok()),
+                channels: stream.channels,
+                channel_layout: stream.channel_layout.clone(),
+                bit_rate,
+                language,
+                title: stream.tags.get("title").cloned(),
+            }),
+            Some("subtitle") => details.subtitle_streams.push(SubtitleStreamInfo {
+                index: stream.index,
+                codec: stream.codec_name.clone(),
+                language,
+                title: stream.tags.get("title").cloned(),
+                is_default: stream.disposition.get("default").copied().unwrap_or(0) == 1,
+                is_forced: stream.disposition.get("forced").copied().unwrap_or(0) == 1,
+            }),
+            // Data and attachment streams
+            _ => details.data_streams.push(DataStreamInfo {
+                index: stream.index,
+                codec: stream.codec_name.clone(),
+                codec_tag: stream.codec_tag_string.clone(),
+                handler_name: stream.tags.get("handler_name").cloned(),
+            }),
+        }
+    }
+
+    details
+}
+
+/// Detect the HDR format of a video stream
+fn get_hdr_format(stream: &Stream) -> Option<String> {
+    let has_dolby_vision = stream.side_data_list.iter().any(|side_data| {
+        side_data.side_data_type.as_deref() == Some("DOVI configuration record")
+    });
+    if has_dolby_vision {
+        return Some("dolby_vision".to_string());
+    }
+
+    match stream.color_transfer.as_deref() {
+        Some("smpte2084") => Some("hdr10".to_string()), // PQ transfer
+        Some("arib-std-b67") => Some("hlg".to_string()),
+        _ => None,
+    }
+}
+
+/// Get the clockwise display rotation of a video stream (0, 90, 180, 270)
+///
+/// The display matrix reports counter-clockwise degrees; older files use a
+/// clockwise "rotate" tag instead.
+fn get_stream_rotation(stream: &Stream) -> u32 {
+    let counter_clockwise = stream
+        .side_data_list
+        .iter()
+        .find_map(|side_data| side_data.rotation);
+    let degrees = match counter_clockwise {
+        Some(rotation) => -rotation.round() as i64,
+        None => stream
+            .tags
+            .get("rotate")
+            .and_then(|rotate| rotate.parse::<i64>().ok())
+            .unwrap_or(0),
+    };
+
+    degrees.rem_euclid(360) as u32
+}
+
+// ============================================================================
+// VARIABLE FRAME RATE DETECTION
+// ============================================================================
+
+/// Seconds of packets read to check frame timing
+const VFR_PACKET_PROBE_SECONDS: u32 = 10;
+
+/// Longest an ffprobe run may take before it is killed (stalled network mounts)
+const FFPROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Relative difference between r_frame_rate and avg_frame_rate treated as VFR
+const VFR_RATE_TOLERANCE: f64 = 0.01;
+
+/// Relative deviation from the typical frame interval treated as irregular
+const VFR_INTERVAL_TOLERANCE: f64 = 0.2;
+
+/// Share of irregular frame intervals treated as VFR
+const VFR_IRREGULAR_SHARE: f64 = 0.05;
+
+/// Whether the stream's base and average frame rates disagree
+pub fn is_vfr_by_frame_rates(r_frame_rate: &str, avg_frame_rate: &str) -> bool {
+    let base_fps = parse_fps(r_frame_rate);
+    let average_fps = parse_fps(avg_frame_rate);
+    if base_fps <= 0.0 || average_fps <= 0.0 {
+        return false;
+    }
+
+    (base_fps - average_fps).abs() / base_fps > VFR_RATE_TOLERANCE
+}
+
+/// Parse ffprobe packet timestamps (one `pts_time` per line)
+pub fn parse_packet_times(csv: &str) -> Vec<f64> {
+    csv.lines()
+        .filter_map(|line| line.trim().trim_end_matches(',').parse::<f64>().ok())
+        .collect()
+}
+
+/// Whether packet timestamps have irregular frame intervals
+///
+/// Timestamps are sorted first since packets with B-frames arrive out of
+/// presentation order.
+pub fn is_vfr_by_packet_times(packet_times: &[f64]) -> bool {
+    let mut times = packet_times.to_vec();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut intervals: Vec<f64> = times
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|interval| *interval > 0.0)
+        .collect();
+    if intervals.len() < 2 {
+        return false;
+    }
+
+    intervals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let typical_interval = intervals[intervals.len() / 2];
+    let irregular_count = intervals
+        .iter()
+        .filter(|interval| (*interval - typical_interval).abs() / typical_interval > VFR_INTERVAL_TOLERANCE)
+        .count();
+
+    irregular_count as f64 / intervals.len() as f64 > VFR_IRREGULAR_SHARE
+}
+
+/// Parse a numeric ffprobe field ("120.5", "N/A")
+fn parse_probe_number(value: Option<&str>) -> Option<f64> {
+    value.and_then(|value| value.parse::<f64>().ok())
+}
+
+/// Parse an ffprobe frame rate ("30000/1001") into frames per second
+pub fn parse_fps(fps_str: &str) -> f64 {
+    if let Some((num, den)) = fps_str.split_once('/') {
+        if let (Ok(n), Ok(d)) = (num.parse::<f64>(), den.parse::<f64>()) {
+            if d != 0.0 {
+                n / d
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        }
+    } else {
+        0.0
+    }
+}
+
 /// Extract metadata from FFmpeg stderr output
 async fn extract_metadata_from_ffmpeg_output(
     stderr: &str,
@@ -220,6 +692,7 @@ async fn extract_metadata_from_ffmpeg_output(
         has_audio,
         audio_codec,
         audio_bitrate,
+        is_vfr: false, // Not detectable from the input summary
     })
 }
 
@@ -236,52 +709,94 @@ struct ProbeData {
 
 #[derive(Debug, Deserialize)]
 struct Stream {
+    #[serde(default)]
+    index: u32,
     codec_type: Option<String>,
     codec_name: Option<String>,
     width: Option<u32>,
     height: Option<u32>,
     r_frame_rate: Option<String>,
+    #[serde(default)]
+    avg_frame_rate: Option<String>,
+    #[serde(default)]
+    duration: Option<String>,
     bit_rate: Option<String>,
+    #[serde(default)]
+    profile: Option<String>,
+    #[serde(default)]
+    codec_tag_string: Option<String>,
+    #[serde(default)]
+    level: Option<i64>,
+    #[serde(default)]
+    pix_fmt: Option<String>,
+    #[serde(default)]
+    time_base: Option<String>,
+    #[serde(default)]
+    color_space: Option<String>,
+    #[serde(default)]
+    color_range: Option<String>,
+    #[serde(default)]
+    color_primaries: Option<String>,
+    #[serde(default)]
+    color_transfer: Option<String>,
+    #[serde(default)]
+    sample_aspect_ratio: Option<String>,
+    #[serde(default)]
+    display_aspect_ratio: Option<String>,
+    #[serde(default)]
+    nb_frames: Option<String>,
+    #[serde(default)]
+    sample_rate: Option<String>,
+    #[serde(default)]
+    channels: Option<u32>,
+    #[serde(default)]
+    channel_layout: Option<String>,
+    #[serde(default)]
+    disposition: HashMap<String, i64>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    #[serde(default)]
+    side_data_list: Vec<SideData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SideData {
+    side_data_type: Option<String>,
+    #[serde(default)]
+    rotation: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
 struct Format {
     format_name: Option<String>,
+    #[serde(default)]
+    format_long_name: Option<String>,
     duration: Option<String>,
+    #[serde(default)]
+    start_time: Option<String>,
     bit_rate: Option<String>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
 }
 
-// ============================================================================
-// FFMPEG PROBE DATA STRUCTURES
-// ============================================================================
-
 // ============================================================================
 // UTILITY FUNCTIONS
 // ============================================================================
 
 /// Check if FFmpeg probe is available
 pub async fn check_ffmpeg_probe_availability(app_handle: &tauri::AppHandle) -> CommandResult<bool> {
-    let sidecar = app_handle.shell().sidecar("ffprobe").map_err(|e| {
-        CommandError::ffmpeg_error(format!("Failed to create FFmpeg sidecar: {}", e))
-    })?;
-
-    let output = sidecar.args(&["-version"]).output().await.map_err(|e| {
-        CommandError::ffmpeg_error(format!("Failed to check FFmpeg version: {}", e))
-    })?;
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffprobe(["-version"]))
+        .await?;
 
     Ok(output.status.success())
 }
 
 /// Get FFmpeg version information
 pub async fn get_ffmpeg_version(app_handle: &tauri::AppHandle) -> CommandResult<String> {
-    let sidecar = app_handle.shell().sidecar("ffprobe").map_err(|e| {
-        CommandError::ffmpeg_error(format!("Failed to create FFmpeg sidecar: {}", e))
-    })?;
-
-    let output =
-        sidecar.args(&["-version"]).output().await.map_err(|e| {
-            CommandError::ffmpeg_error(format!("Failed to get FFmpeg version: {}", e))
-        })?;
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffprobe(["-version"]))
+        .await?;
 
     if !output.status.success() {
         return Err(CommandError::ffmpeg_error(
@@ -300,24 +815,16 @@ pub async fn validate_video_format(
     app_handle: &tauri::AppHandle,
     file_path: &str,
 ) -> CommandResult<bool> {
-    let sidecar = app_handle.shell().sidecar("ffprobe").map_err(|e| {
-        CommandError::ffmpeg_error(format!("Failed to create FFmpeg sidecar: {}", e))
-    })?;
-
-    let output = sidecar
-        .args(&[
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffprobe([
             "-v",
             "quiet",
             "-print_format",
             "json",
             "-show_format",
             file_path,
-        ])
-        .output()
-        .await
-        .map_err(|e| {
-            CommandError::ffmpeg_error(format!("Failed to validate video format: {}", e))
-        })?;
+        ]))
+        .await?;
 
     Ok(output.status.success())
 }
@@ -356,12 +863,14 @@ mod tests {
             has_audio: true,
             audio_codec: Some("aac".to_string()),
             audio_bitrate: Some(128000),
+            is_vfr: false,
         };
 
         let response = ExtractMetadataResponse {
             success: true,
             metadata: Some(metadata.clone()),
             error_message: None,
+            details: None,
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -399,6 +908,7 @@ mod tests {
             has_audio: true,
             audio_codec: Some("aac".to_string()),
             audio_bitrate: Some(128000),
+            is_vfr: false,
         };
 
         let json = serde_json::to_string(&metadata).unwrap();
@@ -473,19 +983,278 @@ mod tests {
         assert_eq!(parse_fps(""), 0.0);
     }
 
-    fn parse_fps(fps_str: &str) -> f64 {
-        if let Some((num, den)) = fps_str.split_once('/') {
-            if let (Ok(n), Ok(d)) = (num.parse::<f64>(), den.parse::<f64>()) {
-                if d != 0.0 {
-                    n / d
-                } else {
-                    0.0
+    #[test]
+    fn test_build_video_metadata() {
+        let json_data = r#"{
+            "streams": [
+                {
+                    "codec_type": "video",
+                    "codec_name": "h264",
+                    "width": 1920,
+                    "height": 1080,
+                    "r_frame_rate": "30000/1001",
+                    "avg_frame_rate": "30000/1001",
+                    "duration": "60.060000",
+                    "bit_rate": "4800000"
+                },
+                {
+                    "codec_type": "audio",
+                    "codec_name": "aac",
+                    "bit_rate": "128000"
+                }
+            ],
+            "format": {
+                "format_name": "mov,mp4,m4a,3gp,3g2,mj2",
+                "duration": "60.095000",
+                "bit_rate": "4930000"
+            }
+        }"#;
+
+        let metadata = build_video_metadata(&parse_probe_data(json_data).unwrap(), "/videos/clip.MOV", 1000).unwrap();
+
+        assert!((metadata.fps - 29.97).abs() < 0.001);
+        assert_eq!(metadata.duration, 60.095);
+        assert_eq!(metadata.width, 1920);
+        assert_eq!(metadata.codec, "h264");
+        assert_eq!(metadata.bitrate, 4930000);
+        assert_eq!(metadata.file_size, 1000);
+        assert_eq!(metadata.format, "mov");
+        assert!(metadata.has_audio);
+        assert_eq!(metadata.audio_codec, Some("aac".to_string()));
+        assert_eq!(metadata.audio_bitrate, Some(128000));
+    }
+
+    #[test]
+    fn test_build_video_metadata_fallbacks() {
+        // Variable frame rate recording: r_frame_rate is the timebase maximum
+        let json_data = r#"{
+            "streams": [
+                {
+                    "codec_type": "video",
+                    "codec_name": "hevc",
+                    "width": 2880,
+                    "height": 1800,
+                    "r_frame_rate": "600/1",
+                    "avg_frame_rate": "14400/241",
+                    "duration": "12.5",
+                    "bit_rate": null
                 }
-            } else {
-                0.0
+            ],
+            "format": {
+                "format_name": "matroska,webm",
+                "duration": "N/A",
+                "bit_rate": null
             }
-        } else {
-            0.0
-        }
+        }"#;
+
+        let metadata = build_video_metadata(&parse_probe_data(json_data).unwrap(), "/videos/recording", 0).unwrap();
+
+        assert!((metadata.fps - 59.75).abs() < 0.01);
+        assert_eq!(metadata.duration, 12.5);
+        assert_eq!(metadata.bitrate, 0);
+        assert_eq!(metadata.format, "matroska");
+        assert!(!metadata.has_audio);
+
+        // Audio-only files are rejected
+        let audio_only = r#"{"streams": [{"codec_type": "audio", "codec_name": "aac"}], "format": {"duration": "3.0"}}"#;
+        assert!(build_video_metadata(&parse_probe_data(audio_only).unwrap(), "/a.m4a", 0).is_err());
+    }
+
+    #[test]
+    fn test_build_media_details() {
+        let json_data = r#"{
+            "streams": [
+                {
+                    "index": 0,
+                    "codec_type": "video",
+                    "codec_name": "hevc",
+                    "profile": "Main 10",
+                    "width": 3840,
+                    "height": 2160,
+                    "pix_fmt": "yuv420p10le",
+                    "color_space": "bt2020nc",
+                    "color_range": "tv",
+                    "color_primaries": "bt2020",
+                    "color_transfer": "arib-std-b67",
+                    "sample_aspect_ratio": "1:1",
+                    "display_aspect_ratio": "16:9",
+                    "r_frame_rate": "30000/1001",
+                    "avg_frame_rate": "30000/1001",
+                    "nb_frames": "1798",
+                    "tags": { "language": "und", "handler_name": "Core Media Video" },
+                    "side_data_list": [
+                        { "side_data_type": "Display Matrix", "displaymatrix": "...", "rotation": -90 }
+                    ]
+                },
+                {
+                    "index": 1,
+                    "codec_type": "audio",
+                    "codec_name": "aac",
+                    "profile": "LC",
+                    "sample_rate": "48000",
+                    "channels": 2,
+                    "channel_layout": "stereo",
+                    "bit_rate": "192000",
+                    "tags": { "language": "eng" }
+                },
+                {
+                    "index": 2,
+                    "codec_type": "subtitle",
+                    "codec_name": "mov_text",
+                    "disposition": { "default": 0, "forced": 1 },
+                    "tags": { "language": "fra", "title": "French" }
+                },
+                {
+                    "index": 3,
+                    "codec_type": "data",
+                    "codec_tag_string": "mebx",
+                    "tags": { "handler_name": "Core Media Metadata" }
+                }
+            ],
+            "format": {
+                "format_name": "mov,mp4,m4a,3gp,3g2,mj2",
+                "format_long_name": "QuickTime / MOV",
+                "start_time": "0.000000",
+                "duration": "60.000000",
+                "bit_rate": "50000000",
+                "tags": {
+                    "creation_time": "2024-05-01T10:00:00.000000Z",
+                    "com.apple.quicktime.make": "Apple"
+                }
+            }
+        }"#;
+
+        let details = build_media_details(&parse_probe_data(json_data).unwrap());
+
+        assert_eq!(details.container.creation_time, Some("2024-05-01T10:00:00.000000Z".to_string()));
+        assert_eq!(details.container.tags.get("com.apple.quicktime.make"), Some(&"Apple".to_string()));
+        assert_eq!(details.container.bit_rate, Some(50000000));
+
+        let video = &details.video_streams[0];
+        assert_eq!(video.profile, Some("Main 10".to_string()));
+        assert_eq!(video.pix_fmt, Some("yuv420p10le".to_string()));
+        assert_eq!(video.hdr_format, Some("hlg".to_string()));
+        assert_eq!(video.rotation, 90);
+        assert_eq!(video.frame_count, Some(1798));
+        assert!((video.fps - 29.97).abs() < 0.001);
+
+        let audio = &details.audio_streams[0];
+        assert_eq!(audio.sample_rate, Some(48000));
+        assert_eq!(audio.channels, Some(2));
+        assert_eq!(audio.channel_layout, Some("stereo".to_string()));
+        assert_eq!(audio.language, Some("eng".to_string()));
+
+        let subtitle = &details.subtitle_streams[0];
+        assert_eq!(subtitle.index, 2);
+        assert!(subtitle.is_forced);
+        assert!(!subtitle.is_default);
+
+        assert_eq!(details.data_streams[0].codec_tag, Some("mebx".to_string()));
+        assert_eq!(details.data_streams[0].handler_name, Some("Core Media Metadata".to_string()));
+    }
+
+    #[test]
+    fn test_stream_rotation_from_tag() {
+        let json_data = r#"{
+            "streams": [
+                { "codec_type": "video", "codec_name": "h264", "color_transfer": "smpte2084", "tags": { "rotate": "270" } }
+            ],
+            "format": {}
+        }"#;
+
+        let details = build_media_details(&parse_probe_data(json_data).unwrap());
+        assert_eq!(details.video_streams[0].rotation, 270);
+        assert_eq!(details.video_streams[0].hdr_format, Some("hdr10".to_string()));
+        assert!(details.container.creation_time.is_none());
+    }
+
+    #[test]
+    fn test_is_vfr_by_frame_rates() {
+        assert!(!is_vfr_by_frame_rates("30000/1001", "30000/1001"));
+        assert!(!is_vfr_by_frame_rates("30/1", "2997/100")); // Within tolerance
+        assert!(is_vfr_by_frame_rates("600/1", "14400/241"));
+        assert!(!is_vfr_by_frame_rates("0/0", "30/1"));
+    }
+
+    #[test]
+    fn test_is_vfr_by_packet_times() {
+        // Constant 30 fps, out of order like B-frame packets, with ms rounding
+        let constant = parse_packet_times("0.000000\n0.067000\n0.033000\n0.100000\n0.133000\n0.167000\n0.200000\n");
+        assert_eq!(constant.len(), 7);
+        assert!(!is_vfr_by_packet_times(&constant));
+
+        // Screen recording that drops to a few frames per second when idle
+        let variable = vec![0.0, 0.033, 0.066, 0.1, 0.5, 0.533, 1.2, 1.233, 1.266, 1.3];
+        assert!(is_vfr_by_packet_times(&variable));
+
+        assert!(!is_vfr_by_packet_times(&[0.0]));
+    }
+
+    #[test]
+    fn test_extract_metadata_with_runner_uses_ffprobe() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("clip.mp4");
+        std::fs::write(&file_path, vec![0u8; 2048]).unwrap();
+
+        let probe_json = r#"{
+            "streams": [{"codec_type": "video", "codec_name": "h264", "width": 1280, "height": 720,
+                         "r_frame_rate": "30/1", "avg_frame_rate": "30/1"}],
+            "format": {"format_name": "mov,mp4", "duration": "2.0", "bit_rate": "800000"}
+        }"#;
+        let runner = crate::ffmpeg::runner::MockFfmpegRunner::new()
+            .with_output(0, probe_json, "")
+            .with_output(0, "0.0\n0.033333\n0.066667\n0.1\n", "");
+
+        let (metadata, details) = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(extract_metadata_with_runner(&runner, file_path.to_str().unwrap()))
+            .unwrap();
+
+        assert_eq!(metadata.width, 1280);
+        assert_eq!(metadata.file_size, 2048);
+        assert!(!metadata.is_vfr);
+        assert!(details.is_some());
+
+        let invocations = runner.invocations();
+        assert_eq!(invocations.len(), 2);
+        assert!(invocations.iter().all(|(tool, _)| *tool == crate::ffmpeg::runner::FfmpegTool::Ffprobe));
+        assert!(invocations[1].1.contains(&"packet=pts_time".to_string()));
+    }
+
+    #[test]
+    fn test_extract_metadata_with_runner_falls_back_to_ffmpeg() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("clip.mov");
+        std::fs::write(&file_path, b"data").unwrap();
+
+        let summary = "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'clip.mov':\n  Duration: 00:01:05.50, start: 0.000000, bitrate: 1500 kb/s\n    Stream #0:0(und): Video: h264 (High), yuv420p, 1920x1080, 1400 kb/s, 25 fps, 25 tbr\nAt least one output file must be specified\n";
+        let runner = crate::ffmpeg::runner::MockFfmpegRunner::new()
+            .with_missing_binary("ffprobe binary not found")
+            .with_output(1, "", summary);
+
+        let (metadata, details) = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(extract_metadata_with_runner(&runner, file_path.to_str().unwrap()))
+            .unwrap();
+
+        assert_eq!(metadata.duration, 65.5);
+        assert_eq!((metadata.width, metadata.height), (1920, 1080));
+        assert!(details.is_none());
+        assert_eq!(runner.invocations()[1].0, crate::ffmpeg::runner::FfmpegTool::Ffmpeg);
+    }
+
+    #[test]
+    fn test_extract_metadata_with_runner_reports_ffprobe_timeout() {
+        let runner = crate::ffmpeg::runner::MockFfmpegRunner::new()
+            .with_error("ffprobe timed out after 30.0 seconds");
+
+        let error = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(extract_metadata_with_runner(&runner, "clip.mov"))
+            .unwrap_err();
+
+        // A hung ffprobe is not mistaken for a missing one
+        assert!(error.message.contains("timed out"));
+        assert_eq!(runner.invocations().len(), 1);
     }
 }
@@ -0,0 +1,508 @@
+// ============================================================================
+// FFMPEG PROXY MODULE
+// ============================================================================
+// This module generates low-resolution proxy files for large project assets.
+// Proxies live in the project's "proxies" directory next to "assets", named
+// after the asset they stand in for, so either path can be derived from the
+// other. Each proxy's path and state is recorded in the project's asset index
+// (assets.json). Previews and thumbnails read proxies; export always uses
+// originals.
+
+use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::export::{get_export_encoders, ExportEncoders};
+use crate::ffmpeg::probe::VideoMetadata;
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Emitter;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Event emitted when a background proxy job finishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyGeneratedEvent {
+    pub asset_path: String,
+    pub proxy_path: String,
+    pub success: bool,
+    pub error_message: Option<String>,
+}
+
+/// Proxy recorded for a project asset in the project's asset index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetProxyRecord {
+    pub proxy_path: String,
+    pub status: String, // "generating", "ready" or "failed"
+    #[serde(default)]
+    pub error_message: Option<String>,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Project subdirectory holding proxy files
+pub const PROXY_DIR_NAME: &str = "proxies";
+
+/// Project subdirectory holding original assets
+const ASSETS_DIR_NAME: &str = "assets";
+
+/// Suffix appended to the asset filename to name its proxy
+pub const PROXY_SUFFIX: &str = ".proxy.mp4";
+
+/// Proxy frame height in pixels
+pub const PROXY_HEIGHT: u32 = 540;
+
+/// Proxy video bitrate (kbps) for encoders without CRF support
+const PROXY_FALLBACK_VIDEO_BITRATE_KBPS: u32 = 1500;
+
+/// Sources larger than this in either dimension get a proxy
+const PROXY_SOURCE_MAX_WIDTH: u32 = 1920;
+const PROXY_SOURCE_MAX_HEIGHT: u32 = 1080;
+
+/// Event name for finished proxy jobs
+pub const PROXY_GENERATED_EVENT: &str = "proxy-generated";
+
+/// Project file recording the proxy of each asset, keyed by asset filename
+pub const ASSET_INDEX_FILE_NAME: &str = "assets.json";
+
+/// Proxy states recorded in the asset index
+pub const PROXY_STATUS_GENERATING: &str = "generating";
+pub const PROXY_STATUS_READY: &str = "ready";
+pub const PROXY_STATUS_FAILED: &str = "failed";
+
+lazy_static::lazy_static! {
+    /// Asset paths with a proxy job running in this session
+    static ref PROXY_JOBS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+
+    /// Serializes read-modify-write updates of asset indexes
+    static ref ASSET_INDEX_LOCK: Mutex<()> = Mutex::new(());
+}
+
+// ============================================================================
+// PATH FUNCTIONS
+// ============================================================================
+
+/// Get the proxy path for a project asset
+///
+/// Returns `None` when the file is not inside a project "assets" directory.
+pub fn get_proxy_path_for_asset(asset_path: &str) -> Option<PathBuf> {
+    let path = Path::new(asset_path);
+    let assets_dir = path.parent()?;
+    if assets_dir.file_name()?.to_str()? != ASSETS_DIR_NAME {
+        return None;
+    }
+
+    let filename = path.file_name()?.to_str()?;
+    Some(
+        assets_dir
+            .parent()?
+            .join(PROXY_DIR_NAME)
+            .join(format!("{}{}", filename, PROXY_SUFFIX)),
+    )
+}
+
+/// Get the original asset path for a proxy file
+///
+/// Returns `None` when the file is not a proxy.
+pub fn get_asset_path_for_proxy(proxy_path: &str) -> Option<PathBuf> {
+    let path = Path::new(proxy_path);
+    let proxies_dir = path.parent()?;
+    if proxies_dir.file_name()?.to_str()? != PROXY_DIR_NAME {
+        return None;
+    }
+
+    let filename = path.file_name()?.to_str()?;
+    let asset_filename = filename.strip_suffix(PROXY_SUFFIX)?;
+    if asset_filename.is_empty() {
+        return None;
+    }
+
+    Some(proxies_dir.parent()?.join(ASSETS_DIR_NAME).join(asset_filename))
+}
+
+/// Get the file previews and thumbnails should read for a source
+///
+/// Uses the recorded proxy once it is ready, otherwise the source itself.
+/// Assets imported before proxies were recorded fall back to the derived
+/// proxy path.
+pub fn resolve_preview_source(file_path: &str) -> String {
+    match get_asset_proxy_record(file_path) {
+        Some(record) if record.status == PROXY_STATUS_READY && Path::new(&record.proxy_path).exists() => {
+            record.proxy_path
+        }
+        Some(_) => file_path.to_string(),
+        None => match get_proxy_path_for_asset(file_path) {
+            Some(proxy_path) if proxy_path.exists() => proxy_path.to_string_lossy().to_string(),
+            _ => file_path.to_string(),
+        },
+    }
+}
+
+/// Get the full-quality file for a source that may be a proxy
+pub fn resolve_original_source(file_path: &str) -> String {
+    match get_asset_path_for_proxy(file_path) {
+        Some(asset_path) if asset_path.exists() => asset_path.to_string_lossy().to_string(),
+        _ => file_path.to_string(),
+    }
+}
+
+/// Whether a source is large enough to need a proxy
+pub fn needs_proxy(metadata: &VideoMetadata) -> bool {
+    metadata.width > PROXY_SOURCE_MAX_WIDTH || metadata.height > PROXY_SOURCE_MAX_HEIGHT
+}
+
+// ============================================================================
+// ASSET INDEX FUNCTIONS
+// ============================================================================
+
+/// Get the asset index of the project an asset belongs to
+///
+/// Returns `None` when the file is not inside a project "assets" directory.
+fn get_asset_index_path(asset_path: &Path) -> Option<PathBuf> {
+    let assets_dir = asset_path.parent()?;
+    if assets_dir.file_name()?.to_str()? != ASSETS_DIR_NAME {
+        return None;
+    }
+
+    Some(assets_dir.parent()?.join(ASSET_INDEX_FILE_NAME))
+}
+
+/// Read an asset index, returning an empty index if none is saved
+pub fn read_asset_index(index_path: &Path) -> CommandResult<BTreeMap<String, AssetProxyRecord>> {
+    if !index_path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let index_json = std::fs::read_to_string(index_path)
+        .map_err(|e| CommandError::file_error(format!("Failed to read asset index: {}", e)))?;
+    serde_json::from_str(&index_json).map_err(|e| {
+        CommandError::serialization_error(format!("Failed to parse asset index: {}", e))
+    })
+}
+
+/// Write an asset index, replacing the previous file atomically
+fn write_asset_index(index_path: &Path, index: &BTreeMap<String, AssetProxyRecord>) -> CommandResult<()> {
+    let index_json = serde_json::to_string_pretty(index).map_err(|e| {
+        CommandError::serialization_error(format!("Failed to serialize asset index: {}", e))
+    })?;
+
+    let partial_path = index_path.with_extension("json.partial");
+    std::fs::write(&partial_path, index_json)
+        .map_err(|e| CommandError::file_error(format!("Failed to write asset index: {}", e)))?;
+    std::fs::rename(&partial_path, index_path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial_path);
+        CommandError::file_error(format!("Failed to replace asset index: {}", e))
+    })
+}
+
+/// Get the proxy recorded for a project asset
+///
+/// A "generating" record without a running job was interrupted, for example
+/// by the app quitting, and is reported as failed.
+pub fn get_asset_proxy_record(asset_path: &str) -> Option<AssetProxyRecord> {
+    let path = Path::new(asset_path);
+    let index_path = get_asset_index_path(path)?;
+    let filename = path.file_name()?.to_str()?;
+
+    let mut record = match read_asset_index(&index_path) {
+        Ok(mut index) => index.remove(filename)?,
+        Err(error) => {
+            eprintln!("Warning: Failed to read asset index: {}", error.message);
+            return None;
+        }
+    };
+
+    let is_running = PROXY_JOBS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(asset_path);
+    if record.status == PROXY_STATUS_GENERATING && !is_running {
+        record.status = PROXY_STATUS_FAILED.to_string();
+        record.error_message = Some("Proxy generation was interrupted".to_string());
+    }
+
+    Some(record)
+}
+
+/// Record (or with `None`, forget) the proxy of a project asset
+pub fn update_asset_proxy_record(asset_path: &str, record: Option<AssetProxyRecord>) -> CommandResult<()> {
+    let path = Path::new(asset_path);
+    let (Some(index_path), Some(filename)) = (
+        get_asset_index_path(path),
+        path.file_name().and_then(|name| name.to_str()),
+    ) else {
+        return Err(CommandError::validation_error(format!(
+            "Not a project asset: {}",
+            asset_path
+        )));
+    };
+
+    let _guard = ASSET_INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index = read_asset_index(&index_path)?;
+    match record {
+        Some(record) => {
+            index.insert(filename.to_string(), record);
+        }
+        None => {
+            if index.remove(filename).is_none() {
+                return Ok(());
+            }
+        }
+    }
+    write_asset_index(&index_path, &index)
+}
+
+// ============================================================================
+// PROXY GENERATION FUNCTIONS
+// ============================================================================
+
+/// Build FFmpeg arguments for a proxy encode
+///
+/// Short GOPs keep scrubbing responsive; quality only needs to be good enough
+/// for editing. Encoders other than libx264 get a fixed bitrate instead of a CRF.
+pub fn build_proxy_args(source_path: &str, output_path: &str, encoders: &ExportEncoders) -> Vec<String> {
+    let mut args = vec![
+        "-i".to_string(),
+        source_path.to_string(),
+        "-vf".to_string(),
+        format!("scale=-2:{}", PROXY_HEIGHT),
+        "-c:v".to_string(),
+        encoders.video.clone(),
+    ];
+    if encoders.video == "libx264" {
+        args.extend(vec![
+            "-preset".to_string(),
+            "veryfast".to_string(),
+            "-crf".to_string(),
+            "28".to_string(),
+        ]);
+    } else {
+        args.extend(vec!["-b:v".to_string(), format!("{}k", PROXY_FALLBACK_VIDEO_BITRATE_KBPS)]);
+    }
+    args.extend(vec![
+        "-g".to_string(),
+        "30".to_string(), // Keyframe every 30 frames for fast seeking
+        "-c:a".to_string(),
+        encoders.audio.clone(),
+        "-b:a".to_string(),
+        "96k".to_string(),
+        "-movflags".to_string(),
+        "+faststart".to_string(),
+        "-y".to_string(), // Overwrite output file
+        output_path.to_string(),
+    ]);
+    args
+}
+
+/// Generate a proxy file for a source video
+///
+/// Renders into a partial file and renames it, so an existing proxy path
+/// always refers to a complete proxy.
+pub async fn generate_proxy(
+    app_handle: &tauri::AppHandle,
+    source_path: &str,
+    proxy_path: &Path,
+) -> CommandResult<()> {
+    // Create proxies directory if it doesn't exist
+    if let Some(proxy_dir) = proxy_path.parent() {
+        std::fs::create_dir_all(proxy_dir).map_err(|e| {
+            CommandError::file_error(format!("Failed to create proxies directory: {}", e))
+        })?;
+    }
+
+    let partial_path = proxy_path.with_extension("partial.mp4");
+    let encoders = get_export_encoders(app_handle).await;
+    let args = build_proxy_args(source_path, &partial_path.to_string_lossy(), &encoders);
+
+    // Execute FFmpeg
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&partial_path);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg proxy generation failed: {}",
+            stderr
+        )));
+    }
+
+    std::fs::rename(&partial_path, proxy_path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial_path);
+        CommandError::io_error(format!("Failed to move proxy into place: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Record a proxy's state in the asset index, warning if it cannot be saved
+fn record_proxy_status(asset_path: &str, proxy_path: &Path, status: &str, error_message: Option<String>) {
+    let record = AssetProxyRecord {
+        proxy_path: proxy_path.to_string_lossy().to_string(),
+        status: status.to_string(),
+        error_message,
+    };
+    if let Err(error) = update_asset_proxy_record(asset_path, Some(record)) {
+        eprintln!("Warning: Failed to record proxy state: {}", error.message);
+    }
+}
+
+/// Generate a proxy in the background and emit an event when done
+///
+/// The asset's record is "generating" until the job finishes, then "ready"
+/// or "failed".
+pub fn start_proxy_generation(app_handle: tauri::AppHandle, asset_path: String, proxy_path: PathBuf) {
+    PROXY_JOBS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(asset_path.clone());
+    record_proxy_status(&asset_path, &proxy_path, PROXY_STATUS_GENERATING, None);
+
+    tauri::async_runtime::spawn(async move {
+        let result = generate_proxy(&app_handle, &asset_path, &proxy_path).await;
+        let error_message = result.err().map(|error| error.message);
+
+        let status = if error_message.is_none() { PROXY_STATUS_READY } else { PROXY_STATUS_FAILED };
+        record_proxy_status(&asset_path, &proxy_path, status, error_message.clone());
+        PROXY_JOBS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&asset_path);
+
+        let event = ProxyGeneratedEvent {
+            asset_path,
+            proxy_path: proxy_path.to_string_lossy().to_string(),
+            success: error_message.is_none(),
+            error_message,
+        };
+        if let Err(e) = app_handle.emit(PROXY_GENERATED_EVENT, &event) {
+            eprintln!("Warning: Failed to emit proxy event: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_metadata(width: u32, height: u32) -> VideoMetadata {
+        VideoMetadata {
+            duration: 10.0,
+            width,
+            height,
+            fps: 30.0,
+            codec: "h264".to_string(),
+            bitrate: 0,
+            file_size: 0,
+            format: "mp4".to_string(),
+            has_audio: true,
+            audio_codec: Some("aac".to_string()),
+            audio_bitrate: None,
+            is_vfr: false,
+        }
+    }
+
+    #[test]
+    fn test_proxy_path_round_trip() {
+        let asset_path = "/projects/p1/assets/screen_p1_123.mov";
+
+        let proxy_path = get_proxy_path_for_asset(asset_path).unwrap();
+        assert_eq!(proxy_path, PathBuf::from("/projects/p1/proxies/screen_p1_123.mov.proxy.mp4"));
+
+        let original = get_asset_path_for_proxy(&proxy_path.to_string_lossy()).unwrap();
+        assert_eq!(original, PathBuf::from(asset_path));
+    }
+
+    #[test]
+    fn test_proxy_paths_outside_project() {
+        assert!(get_proxy_path_for_asset("/Users/me/Movies/clip.mp4").is_none());
+        assert!(get_asset_path_for_proxy("/Users/me/Movies/clip.mp4").is_none());
+        assert!(get_asset_path_for_proxy("/projects/p1/proxies/clip.mp4").is_none());
+    }
+
+    #[test]
+    fn test_resolve_sources() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let assets_dir = temp_dir.path().join("assets");
+        std::fs::create_dir_all(&assets_dir).unwrap();
+        let asset_path = assets_dir.join("clip.mp4");
+        std::fs::write(&asset_path, b"original").unwrap();
+        let asset_str = asset_path.to_string_lossy().to_string();
+
+        // No proxy yet: previews use the original
+        assert_eq!(resolve_preview_source(&asset_str), asset_str);
+
+        let proxy_path = get_proxy_path_for_asset(&asset_str).unwrap();
+        std::fs::create_dir_all(proxy_path.parent().unwrap()).unwrap();
+        std::fs::write(&proxy_path, b"proxy").unwrap();
+        let proxy_str = proxy_path.to_string_lossy().to_string();
+
+        assert_eq!(resolve_preview_source(&asset_str), proxy_str);
+        assert_eq!(resolve_original_source(&proxy_str), asset_str);
+        assert_eq!(resolve_original_source(&asset_str), asset_str);
+    }
+
+    #[test]
+    fn test_resolve_preview_source_reads_record() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let assets_dir = temp_dir.path().join("assets");
+        std::fs::create_dir_all(&assets_dir).unwrap();
+        let asset_str = assets_dir.join("clip.mp4").to_string_lossy().to_string();
+        let proxy_path = get_proxy_path_for_asset(&asset_str).unwrap();
+        std::fs::create_dir_all(proxy_path.parent().unwrap()).unwrap();
+        std::fs::write(&proxy_path, b"proxy").unwrap();
+        let proxy_str = proxy_path.to_string_lossy().to_string();
+
+        let record = |status: &str| AssetProxyRecord {
+            proxy_path: proxy_str.clone(),
+            status: status.to_string(),
+            error_message: None,
+        };
+
+        // A failed proxy is never previewed, even if a file is left behind
+        update_asset_proxy_record(&asset_str, Some(record(PROXY_STATUS_FAILED))).unwrap();
+        assert_eq!(resolve_preview_source(&asset_str), asset_str);
+
+        update_asset_proxy_record(&asset_str, Some(record(PROXY_STATUS_READY))).unwrap();
+        assert_eq!(resolve_preview_source(&asset_str), proxy_str);
+        assert_eq!(get_asset_proxy_record(&asset_str), Some(record(PROXY_STATUS_READY)));
+
+        // A generating record with no running job was interrupted
+        update_asset_proxy_record(&asset_str, Some(record(PROXY_STATUS_GENERATING))).unwrap();
+        assert_eq!(get_asset_proxy_record(&asset_str).unwrap().status, PROXY_STATUS_FAILED);
+
+        update_asset_proxy_record(&asset_str, None).unwrap();
+        assert_eq!(get_asset_proxy_record(&asset_str), None);
+        assert!(temp_dir.path().join(ASSET_INDEX_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_needs_proxy() {
+        assert!(needs_proxy(&create_metadata(3840, 2160)));
+        assert!(needs_proxy(&create_metadata(2560, 1080)));
+        assert!(!needs_proxy(&create_metadata(1920, 1080)));
+        assert!(!needs_proxy(&create_metadata(1280, 720)));
+    }
+
+    #[test]
+    fn test_build_proxy_args() {
+        let args = build_proxy_args("in.mov", "out.partial.mp4", &ExportEncoders::default());
+        assert!(args.windows(2).any(|w| w[0] == "-vf" && w[1] == "scale=-2:540"));
+        assert!(args.windows(2).any(|w| w[0] == "-crf" && w[1] == "28"));
+        assert_eq!(args.last().unwrap(), "out.partial.mp4");
+
+        let encoders = ExportEncoders {
+            video: "h264_videotoolbox".to_string(),
+            audio: "aac".to_string(),
+        };
+        let args = build_proxy_args("in.mov", "out.partial.mp4", &encoders);
+        assert!(args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "h264_videotoolbox"));
+        assert!(args.windows(2).any(|w| w[0] == "-b:v" && w[1] == "1500k"));
+        assert!(!args.contains(&"-crf".to_string()));
+    }
+}
@@ -0,0 +1,572 @@
+// ============================================================================
+// FFMPEG RENDER CACHE MODULE
+// ============================================================================
+// This module caches intermediate export segments (trimmed clips) in the
+// project directory so unchanged clips are not re-trimmed on every export.
+// Entries are keyed by a hash of the source file identity, trim range,
+// transform and encode settings, and evicted least-recently-used first once
+// the cache exceeds its size limit. Segments a running export is using are
+// pinned and never evicted until that export finishes.
+
+use crate::commands::{stable_hash, CommandError, CommandResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// A cached intermediate segment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderCacheEntry {
+    pub key: String,
+    pub file_name: String,   // File inside the cache directory
+    pub source_path: String, // Source the segment was rendered from
+    pub trim_start: f64,
+    pub trim_end: f64,
+    pub size_bytes: u64,
+    pub created_at: String,   // RFC 3339 timestamp
+    pub last_used_at: String, // RFC 3339 timestamp, used for LRU eviction
+}
+
+/// On-disk index of a render cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderCacheIndex {
+    pub version: u32,
+    pub entries: Vec<RenderCacheEntry>,
+}
+
+/// Summary of a render cache for inspection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderCacheStats {
+    pub cache_dir: String,
+    pub entry_count: usize,
+    pub total_size_bytes: u64,
+    pub max_size_bytes: u64,
+    pub entries: Vec<RenderCacheEntry>,
+}
+
+/// Identity of a source file; changes whenever the file is replaced or edited
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct SourceIdentity {
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified_secs: u64,
+}
+
+/// Segment keys in use by one export
+///
+/// Pinned segments are skipped by eviction and purges; the pins are released
+/// when this is dropped.
+#[derive(Debug, Default)]
+pub struct RenderCachePins {
+    keys: Vec<String>,
+}
+
+/// Render cache stored in a project directory
+#[derive(Debug, Clone)]
+pub struct RenderCache {
+    pub cache_dir: PathBuf,
+    pub max_size_bytes: u64,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Render cache directory name inside a project
+pub const RENDER_CACHE_DIR_NAME: &str = "render_cache";
+
+/// Index file name inside the cache directory
+const RENDER_CACHE_INDEX_FILE: &str = "index.json";
+
+/// Current index format version
+const RENDER_CACHE_INDEX_VERSION: u32 = 1;
+
+/// Default cache size limit (2 GB)
+pub const DEFAULT_RENDER_CACHE_MAX_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Transform applied to cached segments (clips have no effects yet)
+pub const NO_TRANSFORM: &str = "none";
+
+lazy_static::lazy_static! {
+    /// Serializes index updates across concurrent exports
+    static ref RENDER_CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Pin counts of segments in use by running exports
+    static ref PINNED_SEGMENTS: Mutex<HashMap<String, usize>> = Mutex::new(HashMap::new());
+}
+
+// ============================================================================
+// KEY FUNCTIONS
+// ============================================================================
+
+/// Read the identity of a source file from the filesystem
+pub fn get_source_identity(source_path: &str) -> CommandResult<SourceIdentity> {
+    let metadata = std::fs::metadata(source_path)
+        .map_err(|e| CommandError::file_error(format!("Failed to read source metadata: {}", e)))?;
+    let modified_secs = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    Ok(SourceIdentity {
+        path: source_path.to_string(),
+        size_bytes: metadata.len(),
+        modified_secs,
+    })
+}
+
+/// Build the cache key for a segment
+///
+/// Trim points are rounded to milliseconds so float noise from the frontend
+/// does not cause misses. Keys name files on disk, so they use `stable_hash`
+/// rather than a hasher that may change between Rust versions.
+pub fn build_segment_cache_key(
+    source: &SourceIdentity,
+    trim_start: f64,
+    trim_end: f64,
+    transform: &str,
+    encode_settings: &str,
+) -> String {
+    let trim_start_ms = (trim_start * 1000.0).round() as i64;
+    let trim_end_ms = (trim_end * 1000.0).round() as i64;
+    let hash = stable_hash(&[
+        source.path.as_bytes(),
+        &source.size_bytes.to_le_bytes(),
+        &source.modified_secs.to_le_bytes(),
+        &trim_start_ms.to_le_bytes(),
+        &trim_end_ms.to_le_bytes(),
+        transform.as_bytes(),
+        encode_settings.as_bytes(),
+    ]);
+    format!("{:016x}", hash)
+}
+
+// ============================================================================
+// PINNING FUNCTIONS
+// ============================================================================
+
+impl RenderCachePins {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Protect a segment from eviction until these pins are dropped
+    pub fn pin(&mut self, key: &str) {
+        let mut pinned = PINNED_SEGMENTS.lock().unwrap_or_else(|e| e.into_inner());
+        *pinned.entry(key.to_string()).or_insert(0) += 1;
+        self.keys.push(key.to_string());
+    }
+}
+
+impl Clone for RenderCachePins {
+    fn clone(&self) -> Self {
+        let mut pins = Self::new();
+        for key in &self.keys {
+            pins.pin(key);
+        }
+        pins
+    }
+}
+
+impl Drop for RenderCachePins {
+    fn drop(&mut self) {
+        let mut pinned = PINNED_SEGMENTS.lock().unwrap_or_else(|e| e.into_inner());
+        for key in self.keys.drain(..) {
+            if let Some(count) = pinned.get_mut(&key) {
+                *count -= 1;
+                if *count == 0 {
+                    pinned.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// Keys of all segments pinned by running exports
+fn get_pinned_segment_keys() -> HashSet<String> {
+    let pinned = PINNED_SEGMENTS.lock().unwrap_or_else(|e| e.into_inner());
+    pinned.keys().cloned().collect()
+}
+
+// ============================================================================
+// RENDER CACHE IMPLEMENTATION
+// ============================================================================
+
+impl RenderCache {
+    /// Create a cache rooted at a directory
+    pub fn new(cache_dir: PathBuf, max_size_bytes: u64) -> Self {
+        Self {
+            cache_dir,
+            max_size_bytes,
+        }
+    }
+
+    /// Open the render cache of a project
+    pub fn for_project(project_id: &str) -> CommandResult<Self> {
+        let project_dir = crate::commands::project::get_project_dir(project_id)?;
+        if !project_dir.exists() {
+            return Err(CommandError::validation_error(format!(
+                "Project not found: {}",
+                project_id
+            )));
+        }
+
+        Ok(Self::new(
+            project_dir.join(RENDER_CACHE_DIR_NAME),
+            DEFAULT_RENDER_CACHE_MAX_SIZE,
+        ))
+    }
+
+    /// Get the path a new segment with this key should be rendered to
+    pub fn get_segment_path(&self, key: &str, extension: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.{}", key, extension))
+    }
+
+    /// Look up a segment, marking it as recently used on a hit
+    pub fn lookup(&self, key: &str) -> CommandResult<Option<PathBuf>> {
+        let _guard = RENDER_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.read_index()?;
+
+        let position = match index.entries.iter().position(|entry| entry.key == key) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+
+        let segment_path = self.cache_dir.join(&index.entries[position].file_name);
+        if !segment_path.exists() {
+            // Drop entries whose file disappeared
+            index.entries.remove(position);
+            self.write_index(&index)?;
+            return Ok(None);
+        }
+
+        index.entries[position].last_used_at = chrono::Utc::now().to_rfc3339();
+        self.write_index(&index)?;
+        Ok(Some(segment_path))
+    }
+
+    /// Record a rendered segment, evicting old entries to keep within the size limit
+    pub fn insert(
+        &self,
+        key: &str,
+        segment_path: &Path,
+        source_path: &str,
+        trim_start: f64,
+        trim_end: f64,
+    ) -> CommandResult<()> {
+        let _guard = RENDER_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.read_index()?;
+
+        let file_name = segment_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| CommandError::validation_error("Invalid segment path".to_string()))?
+            .to_string();
+        let size_bytes = std::fs::metadata(segment_path)
+            .map_err(|e| CommandError::io_error(format!("Failed to read segment metadata: {}", e)))?
+            .len();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        // Make room first so the new segment itself is never evicted
+        index.entries.retain(|entry| entry.key != key);
+        self.evict_to_size(&mut index, self.max_size_bytes.saturating_sub(size_bytes));
+        index.entries.push(RenderCacheEntry {
+            key: key.to_string(),
+            file_name,
+            source_path: source_path.to_string(),
+            trim_start,
+            trim_end,
+            size_bytes,
+            created_at: now.clone(),
+            last_used_at: now,
+        });
+
+        self.write_index(&index)
+    }
+
+    /// Get cache statistics
+    pub fn stats(&self) -> CommandResult<RenderCacheStats> {
+        let _guard = RENDER_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let index = self.read_index()?;
+        Ok(self.build_stats(index))
+    }
+
+    /// Evict least-recently-used entries until the cache fits `max_size_bytes`
+    ///
+    /// Segment files that are not in the index (left by a reset index or an
+    /// interrupted trim) are removed too. A limit of 0 empties the cache, except
+    /// for segments pinned by a running export.
+    pub fn purge(&self, max_size_bytes: u64) -> CommandResult<RenderCacheStats> {
+        let _guard = RENDER_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.read_index()?;
+        self.evict_to_size(&mut index, max_size_bytes);
+        self.remove_unindexed_segments(&index)?;
+        self.write_index(&index)?;
+        Ok(self.build_stats(index))
+    }
+
+    /// Remove oldest unpinned entries and their files until the total fits the limit
+    fn evict_to_size(&self, index: &mut RenderCacheIndex, max_size_bytes: u64) {
+        // RFC 3339 timestamps in UTC sort chronologically as strings
+        index
+            .entries
+            .sort_by(|a, b| a.last_used_at.cmp(&b.last_used_at));
+
+        let pinned_keys = get_pinned_segment_keys();
+        let mut total_size: u64 = index.entries.iter().map(|entry| entry.size_bytes).sum();
+        let mut kept_entries = Vec::with_capacity(index.entries.len());
+        for entry in std::mem::take(&mut index.entries) {
+            if total_size <= max_size_bytes || pinned_keys.contains(&entry.key) {
+                kept_entries.push(entry);
+                continue;
+            }
+
+            let segment_path = self.cache_dir.join(&entry.file_name);
+            if segment_path.exists() {
+                if let Err(e) = std::fs::remove_file(&segment_path) {
+                    eprintln!("Warning: Failed to delete cached segment {}: {}", entry.file_name, e);
+                }
+            }
+            total_size -= entry.size_bytes;
+        }
+        index.entries = kept_entries;
+    }
+
+    /// Delete segment files in the cache directory that the index does not know about
+    fn remove_unindexed_segments(&self, index: &RenderCacheIndex) -> CommandResult<()> {
+        if !self.cache_dir.exists() {
+            return Ok(());
+        }
+
+        let entries = std::fs::read_dir(&self.cache_dir).map_err(|e| {
+            CommandError::io_error(format!("Failed to read render cache directory: {}", e))
+        })?;
+
+        let pinned_keys = get_pinned_segment_keys();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !path.is_file() || file_name.starts_with(RENDER_CACHE_INDEX_FILE) {
+                continue;
+            }
+
+            // Segments and their "<key>.<pid>.<nanos>.partial.<ext>" trims start
+            // with the key; keep the ones a running export has pinned
+            let key = file_name.split('.').next().unwrap_or_default();
+            let is_indexed = index.entries.iter().any(|e| e.file_name == file_name);
+            if is_indexed || pinned_keys.contains(key) {
+                continue;
+            }
+
+            if let Err(e) = std::fs::remove_file(&path) {
+                eprintln!("Warning: Failed to delete stale segment {}: {}", file_name, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build statistics from an index
+    fn build_stats(&self, index: RenderCacheIndex) -> RenderCacheStats {
+        RenderCacheStats {
+            cache_dir: self.cache_dir.to_string_lossy().to_string(),
+            entry_count: index.entries.len(),
+            total_size_bytes: index.entries.iter().map(|entry| entry.size_bytes).sum(),
+            max_size_bytes: self.max_size_bytes,
+            entries: index.entries,
+        }
+    }
+
+    /// Read the cache index, starting fresh if it is missing or unreadable
+    fn read_index(&self) -> CommandResult<RenderCacheIndex> {
+        let index_path = self.cache_dir.join(RENDER_CACHE_INDEX_FILE);
+        let empty_index = RenderCacheIndex {
+            version: RENDER_CACHE_INDEX_VERSION,
+            entries: vec![],
+        };
+
+        if !index_path.exists() {
+            return Ok(empty_index);
+        }
+
+        let index_json = std::fs::read_to_string(&index_path)
+            .map_err(|e| CommandError::file_error(format!("Failed to read render cache index: {}", e)))?;
+        match serde_json::from_str::<RenderCacheIndex>(&index_json) {
+            Ok(index) if index.version == RENDER_CACHE_INDEX_VERSION => Ok(index),
+            _ => Ok(empty_index), // Unindexed segments are removed by the next purge
+        }
+    }
+
+    /// Write the cache index
+    fn write_index(&self, index: &RenderCacheIndex) -> CommandResult<()> {
+        std::fs::create_dir_all(&self.cache_dir).map_err(|e| {
+            CommandError::file_error(format!("Failed to create render cache directory: {}", e))
+        })?;
+
+        let index_json = serde_json::to_string_pretty(index).map_err(|e| {
+            CommandError::serialization_error(format!("Failed to serialize render cache index: {}", e))
+        })?;
+
+        // Write next to the index and rename so a crash never leaves a torn file
+        let index_path = self.cache_dir.join(RENDER_CACHE_INDEX_FILE);
+        let partial_path = self.cache_dir.join(format!("{}.partial", RENDER_CACHE_INDEX_FILE));
+        std::fs::write(&partial_path, index_json)
+            .map_err(|e| CommandError::file_error(format!("Failed to write render cache index: {}", e)))?;
+        std::fs::rename(&partial_path, &index_path).map_err(|e| {
+            let _ = std::fs::remove_file(&partial_path);
+            CommandError::file_error(format!("Failed to replace render cache index: {}", e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(path: &str, size_bytes: u64, modified_secs: u64) -> SourceIdentity {
+        SourceIdentity {
+            path: path.to_string(),
+            size_bytes,
+            modified_secs,
+        }
+    }
+
+    fn add_segment(cache: &RenderCache, key: &str, size: usize) -> PathBuf {
+        std::fs::create_dir_all(&cache.cache_dir).unwrap();
+        let segment_path = cache.get_segment_path(key, "mp4");
+        std::fs::write(&segment_path, vec![0u8; size]).unwrap();
+        cache.insert(key, &segment_path, "source.mp4", 0.0, 1.0).unwrap();
+        segment_path
+    }
+
+    #[test]
+    fn test_build_segment_cache_key() {
+        let source = identity("/p/assets/a.mp4", 1000, 42);
+        let key = build_segment_cache_key(&source, 1.0, 5.0, NO_TRANSFORM, "copy");
+
+        // Stable for the same inputs, ignoring sub-millisecond noise
+        assert_eq!(key, build_segment_cache_key(&source, 1.0000001, 5.0, NO_TRANSFORM, "copy"));
+        assert_eq!(key.len(), 16);
+
+        // Keys name files on disk and must not change between builds
+        assert_eq!(key, "ec82724c3d37fda6");
+
+        // Any change to the inputs changes the key
+        assert_ne!(key, build_segment_cache_key(&source, 1.5, 5.0, NO_TRANSFORM, "copy"));
+        assert_ne!(key, build_segment_cache_key(&source, 1.0, 5.0, NO_TRANSFORM, "libx264"));
+        assert_ne!(key, build_segment_cache_key(&source, 1.0, 5.0, "crop", "copy"));
+        assert_ne!(key, build_segment_cache_key(&identity("/p/assets/a.mp4", 1000, 43), 1.0, 5.0, NO_TRANSFORM, "copy"));
+    }
+
+    #[test]
+    fn test_lookup_and_insert() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = RenderCache::new(temp_dir.path().join(RENDER_CACHE_DIR_NAME), 1024 * 1024);
+
+        assert!(cache.lookup("missing").unwrap().is_none());
+
+        let segment_path = add_segment(&cache, "abc", 100);
+        assert_eq!(cache.lookup("abc").unwrap(), Some(segment_path.clone()));
+
+        // Entries whose file was removed are dropped
+        std::fs::remove_file(&segment_path).unwrap();
+        assert!(cache.lookup("abc").unwrap().is_none());
+        assert_eq!(cache.stats().unwrap().entry_count, 0);
+    }
+
+    #[test]
+    fn test_insert_evicts_least_recently_used() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = RenderCache::new(temp_dir.path().join(RENDER_CACHE_DIR_NAME), 250);
+
+        let first = add_segment(&cache, "first", 100);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        add_segment(&cache, "second", 100);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        // Touch the first entry so the second becomes least recently used
+        cache.lookup("first").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        add_segment(&cache, "third", 100);
+
+        let stats = cache.stats().unwrap();
+        let keys: Vec<&str> = stats.entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, vec!["first", "third"]);
+        assert_eq!(stats.total_size_bytes, 200);
+        assert!(first.exists());
+        assert!(!cache.get_segment_path("second", "mp4").exists());
+    }
+
+    #[test]
+    fn test_purge() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = RenderCache::new(temp_dir.path().join(RENDER_CACHE_DIR_NAME), 1024 * 1024);
+        let segment_path = add_segment(&cache, "a", 100);
+        add_segment(&cache, "b", 100);
+
+        let stats = cache.purge(150).unwrap();
+        assert_eq!(stats.entry_count, 1);
+
+        let stats = cache.purge(0).unwrap();
+        assert_eq!(stats.entry_count, 0);
+        assert_eq!(stats.total_size_bytes, 0);
+        assert!(!segment_path.exists());
+    }
+
+    #[test]
+    fn test_pinned_segments_survive_eviction() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = RenderCache::new(temp_dir.path().join(RENDER_CACHE_DIR_NAME), 150);
+
+        let mut pins = RenderCachePins::new();
+        pins.pin("pinned-old");
+        let pinned_path = add_segment(&cache, "pinned-old", 100);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        // Over the limit, but the least recently used entry is pinned
+        add_segment(&cache, "newer", 100);
+        assert!(pinned_path.exists());
+
+        let stats = cache.purge(0).unwrap();
+        assert_eq!(stats.entry_count, 1);
+        assert_eq!(stats.entries[0].key, "pinned-old");
+
+        drop(pins);
+        assert_eq!(cache.purge(0).unwrap().entry_count, 0);
+        assert!(!pinned_path.exists());
+    }
+
+    #[test]
+    fn test_purge_removes_unindexed_segments() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = RenderCache::new(temp_dir.path().join(RENDER_CACHE_DIR_NAME), 1024 * 1024);
+        let indexed_path = add_segment(&cache, "indexed", 100);
+
+        // Left behind by a reset index and an interrupted trim
+        let orphan_path = cache.get_segment_path("orphan", "mp4");
+        std::fs::write(&orphan_path, vec![0u8; 100]).unwrap();
+        let stale_partial_path = cache.get_segment_path("stale", "1.2.partial.mp4");
+        std::fs::write(&stale_partial_path, vec![0u8; 100]).unwrap();
+
+        // A trim a running export is still writing
+        let mut pins = RenderCachePins::new();
+        pins.pin("running");
+        let running_partial_path = cache.get_segment_path("running", "1.2.partial.mp4");
+        std::fs::write(&running_partial_path, vec![0u8; 100]).unwrap();
+
+        let stats = cache.purge(1024 * 1024).unwrap();
+        assert_eq!(stats.entry_count, 1);
+        assert!(indexed_path.exists());
+        assert!(!orphan_path.exists());
+        assert!(!stale_partial_path.exists());
+        assert!(running_partial_path.exists());
+        assert!(cache.cache_dir.join(RENDER_CACHE_INDEX_FILE).exists());
+        drop(pins);
+    }
+}
@@ -0,0 +1,679 @@
+// ============================================================================
+// FFMPEG RUNNER MODULE
+// ============================================================================
+// This module is the single place FFmpeg and ffprobe are started from.
+// It resolves which binary to run (environment override, sidecar, bundled
+// copy or system install), streams stderr lines to callers for progress,
+// enforces timeouts and cancellation, and hides process spawning behind a
+// trait so export, probe and thumbnail logic can be tested with a mock.
+
+use crate::commands::{CommandError, CommandResult};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Manager;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::Notify;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Binary from the FFmpeg suite to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FfmpegTool {
+    Ffmpeg,
+    Ffprobe,
+}
+
+impl FfmpegTool {
+    /// Executable name without platform suffix
+    pub fn binary_name(&self) -> &'static str {
+        match self {
+            FfmpegTool::Ffmpeg => "ffmpeg",
+            FfmpegTool::Ffprobe => "ffprobe",
+        }
+    }
+
+    /// Environment variable that points at a binary to use instead of the bundled one
+    pub fn env_override_var(&self) -> &'static str {
+        match self {
+            FfmpegTool::Ffmpeg => "CLIPFORGE_FFMPEG_PATH",
+            FfmpegTool::Ffprobe => "CLIPFORGE_FFPROBE_PATH",
+        }
+    }
+}
+
+/// Where a resolved binary was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinarySource {
+    EnvOverride, // CLIPFORGE_FFMPEG_PATH / CLIPFORGE_FFPROBE_PATH
+    Sidecar,     // Next to the app executable (Tauri externalBin)
+    Bundled,     // bin/ in the source tree (dev) or resource directory (release)
+    System,      // Found on PATH
+}
+
+/// A location a binary may be found at
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryCandidate {
+    pub path: PathBuf,
+    pub source: BinarySource,
+}
+
+/// Binary chosen to run
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedBinary {
+    pub path: PathBuf,
+    pub source: BinarySource,
+}
+
+/// Called with every stderr line as FFmpeg prints it
+pub type StderrLineHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// A single FFmpeg or ffprobe run
+#[derive(Clone)]
+pub struct FfmpegInvocation {
+    pub tool: FfmpegTool,
+    pub args: Vec<String>,
+    pub timeout: Option<Duration>,
+    pub cancellation: Option<CancellationToken>,
+    pub on_stderr_line: Option<StderrLineHandler>,
+}
+
+/// Exit status of a finished run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FfmpegExitStatus {
+    code: Option<i32>,
+}
+
+/// Everything a finished run printed
+#[derive(Debug, Clone)]
+pub struct FfmpegOutput {
+    pub status: FfmpegExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Shared flag used to stop a running invocation
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+/// Future returned by `FfmpegRunner::run`
+pub type FfmpegFuture<'a> = Pin<Box<dyn Future<Output = CommandResult<FfmpegOutput>> + Send + 'a>>;
+
+/// Runs FFmpeg suite binaries
+///
+/// Returns Ok for any run that exited, successful or not, so callers can
+/// read stderr. Errors mean the binary could not be started, timed out or
+/// was cancelled.
+pub trait FfmpegRunner: Send + Sync {
+    fn run(&self, invocation: FfmpegInvocation) -> FfmpegFuture<'_>;
+}
+
+/// Runner that spawns real FFmpeg processes
+#[derive(Clone)]
+pub struct ProcessRunner {
+    app_handle: tauri::AppHandle,
+}
+
+// ============================================================================
+// INVOCATION AND OUTPUT
+// ============================================================================
+
+impl FfmpegInvocation {
+    pub fn new<I, S>(tool: FfmpegTool, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            tool,
+            args: args.into_iter().map(|arg| arg.as_ref().to_string()).collect(),
+            timeout: None,
+            cancellation: None,
+            on_stderr_line: None,
+        }
+    }
+
+    /// Run ffmpeg with the given arguments
+    pub fn ffmpeg<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::new(FfmpegTool::Ffmpeg, args)
+    }
+
+    /// Run ffprobe with the given arguments
+    pub fn ffprobe<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::new(FfmpegTool::Ffprobe, args)
+    }
+
+    /// Kill the process if it has not exited after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Kill the process when `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Receive stderr lines while the process runs (progress is printed there)
+    pub fn on_stderr_line<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.on_stderr_line = Some(Arc::new(handler));
+        self
+    }
+}
+
+impl std::fmt::Debug for FfmpegInvocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FfmpegInvocation")
+            .field("tool", &self.tool)
+            .field("args", &self.args)
+            .field("timeout", &self.timeout)
+            .field("cancellable", &self.cancellation.is_some())
+            .field("streams_stderr", &self.on_stderr_line.is_some())
+            .finish()
+    }
+}
+
+impl FfmpegExitStatus {
+    pub fn from_code(code: Option<i32>) -> Self {
+        Self { code }
+    }
+
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    /// Exit code, or None when the process was killed by a signal
+    #[cfg(test)]
+    pub fn code(&self) -> Option<i32> {
+        self.code
+    }
+}
+
+#[cfg(test)]
+impl FfmpegOutput {
+    /// Build an output from text for mocked runs
+    pub fn new(code: i32, stdout: &str, stderr: &str) -> Self {
+        Self {
+            status: FfmpegExitStatus::from_code(Some(code)),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every invocation holding this token
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            // Register before checking so a cancel in between is not missed
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+// ============================================================================
+// BINARY RESOLUTION
+// ============================================================================
+
+/// List the places a binary is looked for, in priority order
+///
+/// The environment override comes first so a developer or packager can point
+/// at a specific build, then the Tauri sidecar next to the executable, then
+/// the copy under `bin/`, and finally whatever is installed on PATH.
+pub fn get_binary_candidates(tool: FfmpegTool, app_handle: Option<&tauri::AppHandle>) -> Vec<BinaryCandidate> {
+    let mut candidates = Vec::new();
+    let name = tool.binary_name();
+
+    if let Some(path) = std::env::var_os(tool.env_override_var()).filter(|value| !value.is_empty()) {
+        candidates.push(BinaryCandidate { path: PathBuf::from(path), source: BinarySource::EnvOverride });
+    }
+
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        candidates.push(BinaryCandidate {
+            path: exe_dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)),
+            source: BinarySource::Sidecar,
+        });
+    }
+
+    if cfg!(debug_assertions) {
+        // Development mode - use source tree path
+        if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+            candidates.push(BinaryCandidate {
+                path: PathBuf::from(manifest_dir).join("bin").join(name),
+                source: BinarySource::Bundled,
+            });
+        }
+    } else if let Some(app_handle) = app_handle {
+        // Production mode - use bundled resource
+        if let Ok(path) = app_handle
+            .path()
+            .resolve(format!("bin/{}", name), tauri::path::BaseDirectory::Resource)
+        {
+            candidates.push(BinaryCandidate { path, source: BinarySource::Bundled });
+        }
+    }
+
+    if let Ok(path) = which::which(name) {
+        candidates.push(BinaryCandidate { path, source: BinarySource::System });
+    }
+
+    candidates
+}
+
+/// Pick the first candidate that exists
+///
+/// An environment override that points at a missing file is an error rather
+/// than silently falling back, since it was set on purpose.
+pub fn choose_binary(
+    tool: FfmpegTool,
+    candidates: &[BinaryCandidate],
+    exists: impl Fn(&Path) -> bool,
+) -> CommandResult<ResolvedBinary> {
+    for candidate in candidates {
+        if exists(&candidate.path) {
+            return Ok(ResolvedBinary { path: candidate.path.clone(), source: candidate.source });
+        }
+        if candidate.source == BinarySource::EnvOverride {
+            return Err(CommandError::validation_error(format!(
+                "{} points to a missing file: {}",
+                tool.env_override_var(),
+                candidate.path.display()
+            )));
+        }
+    }
+
+    let searched: Vec<String> = candidates
+        .iter()
+        .map(|candidate| candidate.path.display().to_string())
+        .collect();
+    Err(CommandError::binary_not_found(format!(
+        "{} binary not found (searched: {})",
+        tool.binary_name(),
+        if searched.is_empty() { "nothing".to_string() } else { searched.join(", ") }
+    )))
+}
+
+/// Resolve the binary to run for `tool`
+pub fn resolve_ffmpeg_binary(tool: FfmpegTool, app_handle: Option<&tauri::AppHandle>) -> CommandResult<ResolvedBinary> {
+    choose_binary(tool, &get_binary_candidates(tool, app_handle), |path| path.is_file())
+}
+
+// ============================================================================
+// PROCESS RUNNER
+// ============================================================================
+
+impl ProcessRunner {
+    pub fn new(app_handle: &tauri::AppHandle) -> Self {
+        Self { app_handle: app_handle.clone() }
+    }
+
+    /// Resolve the binary this runner would use for `tool`
+    pub fn resolve(&self, tool: FfmpegTool) -> CommandResult<ResolvedBinary> {
+        resolve_ffmpeg_binary(tool, Some(&self.app_handle))
+    }
+}
+
+impl FfmpegRunner for ProcessRunner {
+    fn run(&self, invocation: FfmpegInvocation) -> FfmpegFuture<'_> {
+        Box::pin(async move {
+            let binary = self.resolve(invocation.tool)?;
+            run_process(&binary.path, invocation).await
+        })
+    }
+}
+
+/// Spawn `program` for an invocation and wait for it to exit
+pub async fn run_process(program: &Path, invocation: FfmpegInvocation) -> CommandResult<FfmpegOutput> {
+    let name = invocation.tool.binary_name();
+
+    let mut child = tokio::process::Command::new(program)
+        .args(&invocation.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            let message = format!("Failed to execute {}: {}", name, e);
+            if e.kind() == std::io::ErrorKind::NotFound {
+                CommandError::binary_not_found(message)
+            } else {
+                CommandError::ffmpeg_error(message)
+            }
+        })?;
+
+    // Drain both pipes concurrently so a full stdout never blocks stderr
+    let stdout_task = tokio::spawn(read_all(child.stdout.take()));
+    let stderr_task = tokio::spawn(read_stderr_lines(child.stderr.take(), invocation.on_stderr_line.clone()));
+
+    let status = tokio::select! {
+        status = child.wait() => status
+            .map_err(|e| CommandError::ffmpeg_error(format!("Failed to wait for {}: {}", name, e)))?,
+        _ = wait_for_timeout(invocation.timeout) => {
+            let _ = child.kill().await;
+            return Err(CommandError::ffmpeg_error(format!(
+                "{} timed out after {:.1} seconds",
+                name,
+                invocation.timeout.unwrap_or_default().as_secs_f64()
+            )));
+        }
+        _ = wait_for_cancellation(invocation.cancellation.as_ref()) => {
+            let _ = child.kill().await;
+            return Err(CommandError::ffmpeg_error(format!("{} was cancelled", name)));
+        }
+    };
+
+    let stdout = stdout_task.await.unwrap_or_default();
+    let stderr = stderr_task.await.unwrap_or_default();
+
+    Ok(FfmpegOutput {
+        status: FfmpegExitStatus::from_code(status.code()),
+        stdout,
+        stderr,
+    })
+}
+
+async fn wait_for_timeout(timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn wait_for_cancellation(token: Option<&CancellationToken>) {
+    match token {
+        Some(token) => token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn read_all<R: AsyncRead + Unpin>(reader: Option<R>) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    if let Some(mut reader) = reader {
+        let _ = reader.read_to_end(&mut buffer).await;
+    }
+    buffer
+}
+
+/// Collect stderr while handing each completed line to `handler`
+async fn read_stderr_lines<R: AsyncRead + Unpin>(reader: Option<R>, handler: Option<StderrLineHandler>) -> Vec<u8> {
+    let mut collected = Vec::new();
+    let Some(mut reader) = reader else {
+        return collected;
+    };
+
+    let mut pending = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let read = match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        collected.extend_from_slice(&chunk[..read]);
+        for line in split_stderr_lines(&mut pending, &chunk[..read]) {
+            if let Some(handler) = &handler {
+                handler(&line);
+            }
+        }
+    }
+
+    // Whatever is left had no line ending
+    if !pending.is_empty() {
+        if let Some(handler) = &handler {
+            handler(&String::from_utf8_lossy(&pending));
+        }
+    }
+
+    collected
+}
+
+/// Split a chunk of stderr into complete lines, keeping the unfinished tail in `pending`
+///
+/// FFmpeg rewrites its progress line with carriage returns, so both `\r` and
+/// `\n` end a line.
+pub fn split_stderr_lines(pending: &mut Vec<u8>, chunk: &[u8]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for &byte in chunk {
+        if byte == b'\r' || byte == b'\n' {
+            if !pending.is_empty() {
+                lines.push(String::from_utf8_lossy(pending).to_string());
+                pending.clear();
+            }
+        } else {
+            pending.push(byte);
+        }
+    }
+    lines
+}
+
+// ============================================================================
+// MOCK RUNNER
+// ============================================================================
+
+/// Runner that replays canned outputs instead of spawning FFmpeg
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockFfmpegRunner {
+    responses: std::sync::Mutex<std::collections::VecDeque<CommandResult<FfmpegOutput>>>,
+    invocations: std::sync::Mutex<Vec<(FfmpegTool, Vec<String>)>>,
+}
+
+#[cfg(test)]
+impl MockFfmpegRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue the output of the next run
+    pub fn with_output(self, code: i32, stdout: &str, stderr: &str) -> Self {
+        self.responses.lock().unwrap().push_back(Ok(FfmpegOutput::new(code, stdout, stderr)));
+        self
+    }
+
+    /// Queue a failure to start the next run
+    pub fn with_error(self, message: &str) -> Self {
+        self.responses.lock().unwrap().push_back(Err(CommandError::ffmpeg_error(message.to_string())));
+        self
+    }
+
+    /// Queue a missing binary for the next run
+    pub fn with_missing_binary(self, message: &str) -> Self {
+        self.responses.lock().unwrap().push_back(Err(CommandError::binary_not_found(message.to_string())));
+        self
+    }
+
+    /// Tool and arguments of every run so far
+    pub fn invocations(&self) -> Vec<(FfmpegTool, Vec<String>)> {
+        self.invocations.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl FfmpegRunner for MockFfmpegRunner {
+    fn run(&self, invocation: FfmpegInvocation) -> FfmpegFuture<'_> {
+        self.invocations.lock().unwrap().push((invocation.tool, invocation.args.clone()));
+        let response = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Err(CommandError::ffmpeg_error("No mocked FFmpeg output left".to_string())));
+
+        // Stream stderr like a real process so progress handlers run
+        if let (Ok(output), Some(handler)) = (&response, &invocation.on_stderr_line) {
+            let mut pending = Vec::new();
+            for line in split_stderr_lines(&mut pending, &output.stderr) {
+                handler(&line);
+            }
+            if !pending.is_empty() {
+                handler(&String::from_utf8_lossy(&pending));
+            }
+        }
+
+        Box::pin(async move { response })
+    }
+}
+
+// ============================================================================
+// UNIT TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(path: &str, source: BinarySource) -> BinaryCandidate {
+        BinaryCandidate { path: PathBuf::from(path), source }
+    }
+
+    #[test]
+    fn test_choose_binary_takes_first_existing_candidate() {
+        let candidates = vec![
+            candidate("/app/ffmpeg", BinarySource::Sidecar),
+            candidate("/src/bin/ffmpeg", BinarySource::Bundled),
+            candidate("/usr/bin/ffmpeg", BinarySource::System),
+        ];
+
+        let resolved = choose_binary(FfmpegTool::Ffmpeg, &candidates, |path| path != Path::new("/app/ffmpeg")).unwrap();
+        assert_eq!(resolved.path, PathBuf::from("/src/bin/ffmpeg"));
+        assert_eq!(resolved.source, BinarySource::Bundled);
+    }
+
+    #[test]
+    fn test_choose_binary_rejects_missing_env_override() {
+        let candidates = vec![
+            candidate("/missing/ffprobe", BinarySource::EnvOverride),
+            candidate("/usr/bin/ffprobe", BinarySource::System),
+        ];
+
+        let error = choose_binary(FfmpegTool::Ffprobe, &candidates, |path| path == Path::new("/usr/bin/ffprobe")).unwrap_err();
+        assert!(error.message.contains("CLIPFORGE_FFPROBE_PATH"));
+    }
+
+    #[test]
+    fn test_choose_binary_reports_searched_paths() {
+        let candidates = vec![candidate("/app/ffmpeg", BinarySource::Sidecar)];
+
+        let error = choose_binary(FfmpegTool::Ffmpeg, &candidates, |_| false).unwrap_err();
+        assert!(error.message.contains("/app/ffmpeg"));
+        assert_eq!(error.error_type, "binary_not_found");
+    }
+
+    #[test]
+    fn test_split_stderr_lines_handles_carriage_returns_across_chunks() {
+        let mut pending = Vec::new();
+
+        let first = split_stderr_lines(&mut pending, b"Input #0\nframe=  10 time=00:00");
+        assert_eq!(first, vec!["Input #0".to_string()]);
+
+        let second = split_stderr_lines(&mut pending, b":01.00\rframe=  20\r\n");
+        assert_eq!(second, vec!["frame=  10 time=00:00:01.00".to_string(), "frame=  20".to_string()]);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_exit_status() {
+        assert!(FfmpegExitStatus::from_code(Some(0)).success());
+        assert!(!FfmpegExitStatus::from_code(Some(1)).success());
+        assert!(!FfmpegExitStatus::from_code(None).success());
+    }
+
+    #[test]
+    fn test_mock_runner_records_invocations_and_streams_stderr() {
+        let runner = MockFfmpegRunner::new().with_output(0, "", "frame=1\rframe=2\n");
+        let lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = lines.clone();
+
+        let invocation = FfmpegInvocation::ffmpeg(["-i", "in.mp4"])
+            .on_stderr_line(move |line| sink.lock().unwrap().push(line.to_string()));
+        let output = tokio::runtime::Runtime::new().unwrap().block_on(runner.run(invocation)).unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(*lines.lock().unwrap(), vec!["frame=1".to_string(), "frame=2".to_string()]);
+        assert_eq!(runner.invocations(), vec![(FfmpegTool::Ffmpeg, vec!["-i".to_string(), "in.mp4".to_string()])]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_process_times_out() {
+        let invocation = FfmpegInvocation::ffmpeg(["-c", "sleep 5"]).with_timeout(Duration::from_millis(100));
+
+        let result = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(run_process(Path::new("/bin/sh"), invocation));
+        assert!(result.unwrap_err().message.contains("timed out"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_process_cancels() {
+        let token = CancellationToken::new();
+        let invocation = FfmpegInvocation::ffmpeg(["-c", "sleep 5"]).with_cancellation(token.clone());
+
+        let result = tokio::runtime::Runtime::new().unwrap().block_on(async move {
+            let run = tokio::spawn(run_process(Path::new("/bin/sh"), invocation));
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            token.cancel();
+            run.await.unwrap()
+        });
+        assert!(result.unwrap_err().message.contains("cancelled"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_process_collects_output() {
+        let invocation = FfmpegInvocation::ffmpeg(["-c", "printf out; printf 'a\\rb\\n' >&2; exit 3"]);
+
+        let output = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(run_process(Path::new("/bin/sh"), invocation))
+            .unwrap();
+        assert_eq!(output.stdout, b"out");
+        assert_eq!(output.stderr, b"a\rb\n");
+        assert_eq!(output.status.code(), Some(3));
+    }
+}
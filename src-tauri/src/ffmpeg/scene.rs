@@ -0,0 +1,299 @@
+// ============================================================================
+// FFMPEG SCENE DETECTION MODULE
+// ============================================================================
+// This module finds scene/shot changes with FFmpeg's scene score
+// (`select='gt(scene,T)'`) and splits long recordings into sub-clips at the
+// detected cuts.
+
+use crate::commands::export::TimelineExportClip;
+use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Request to detect scene changes in a video
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectScenesRequest {
+    pub file_path: String,
+    #[serde(default)]
+    pub threshold: Option<f64>, // Scene score (0-1) above which a frame is a cut
+    #[serde(default)]
+    pub min_scene_duration: Option<f64>, // Minimum seconds between cuts
+}
+
+/// A detected scene change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneCut {
+    pub timestamp: f64, // Source time of the first frame of the new scene
+    pub score: f64,     // Scene change score (0-1)
+}
+
+/// Response from scene detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectScenesResponse {
+    pub success: bool,
+    pub cuts: Vec<SceneCut>,
+    pub duration: f64, // Source duration in seconds
+    pub error_message: Option<String>,
+}
+
+/// Request to split a source into sub-clips at scene cuts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitAtScenesRequest {
+    pub file_path: String,
+    pub cuts: Vec<SceneCut>,
+    pub original_duration: f64,
+    #[serde(default)]
+    pub trim_start: Option<f64>, // Only split this part of the source
+    #[serde(default)]
+    pub trim_end: Option<f64>,
+    #[serde(default)]
+    pub start_time: f64, // Timeline position of the first sub-clip
+    #[serde(default = "default_scene_track_id")]
+    pub track_id: String,
+}
+
+/// Default track for sub-clips
+fn default_scene_track_id() -> String {
+    "video-1".to_string()
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default scene score threshold
+pub const DEFAULT_SCENE_THRESHOLD: f64 = 0.4;
+
+/// Default minimum scene length in seconds
+pub const DEFAULT_MIN_SCENE_DURATION: f64 = 1.0;
+
+/// Frame width scenes are scored at (smaller is faster, scores barely change)
+const SCENE_ANALYSIS_WIDTH: u32 = 320;
+
+// ============================================================================
+// INTERNAL FUNCTIONS
+// ============================================================================
+
+/// Detect scene changes in a video
+pub async fn detect_scenes_internal(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    threshold: f64,
+    min_scene_duration: f64,
+    duration: f64,
+) -> CommandResult<Vec<SceneCut>> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(CommandError::validation_error(format!(
+            "Scene threshold must be between 0 and 1, got {}",
+            threshold
+        )));
+    }
+
+    let args = build_scene_detection_args(file_path, threshold);
+
+    // Execute FFmpeg
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg scene detection failed: {}",
+            stderr
+        )));
+    }
+
+    Ok(filter_scene_cuts(parse_scene_cuts(&stderr), min_scene_duration, duration))
+}
+
+/// Build FFmpeg arguments for scene detection
+///
+/// Only frames scoring above the threshold pass the select filter, and the
+/// metadata filter logs their time and score.
+pub fn build_scene_detection_args(file_path: &str, threshold: f64) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-i".to_string(),
+        file_path.to_string(),
+        "-an".to_string(),
+        "-vf".to_string(),
+        format!(
+            "scale={}:-2,select='gt(scene,{})',metadata=print",
+            SCENE_ANALYSIS_WIDTH, threshold
+        ),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Parse scene cuts from metadata filter output
+///
+/// Each selected frame logs a "pts_time:" line followed by its
+/// "lavfi.scene_score=" line.
+pub fn parse_scene_cuts(stderr: &str) -> Vec<SceneCut> {
+    let mut cuts = Vec::new();
+    let mut pending_timestamp = None;
+
+    for line in stderr.lines() {
+        if let Some(index) = line.find("pts_time:") {
+            pending_timestamp = line[index + "pts_time:".len()..]
+                .split_whitespace()
+                .next()
+                .and_then(|value| value.parse::<f64>().ok());
+        } else if let Some(index) = line.find("lavfi.scene_score=") {
+            let score = line[index + "lavfi.scene_score=".len()..].trim().parse::<f64>().ok();
+            if let (Some(timestamp), Some(score)) = (pending_timestamp.take(), score) {
+                cuts.push(SceneCut { timestamp, score });
+            }
+        }
+    }
+
+    cuts
+}
+
+/// Drop cuts that would leave scenes shorter than `min_scene_duration`
+///
+/// Within a cluster of close cuts the first one is kept, and cuts too close
+/// to either end of the source are dropped.
+pub fn filter_scene_cuts(cuts: Vec<SceneCut>, min_scene_duration: f64, duration: f64) -> Vec<SceneCut> {
+    let mut cuts = cuts;
+    cuts.sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut filtered: Vec<SceneCut> = Vec::new();
+    let mut previous_cut = 0.0;
+    for cut in cuts {
+        if cut.timestamp - previous_cut < min_scene_duration {
+            continue;
+        }
+        if duration > 0.0 && duration - cut.timestamp < min_scene_duration {
+            break;
+        }
+        previous_cut = cut.timestamp;
+        filtered.push(cut);
+    }
+
+    filtered
+}
+
+/// Split a source into consecutive sub-clips at scene cuts
+///
+/// Cuts outside the trim range are ignored; sub-clips are laid end to end on
+/// the timeline starting at `start_time`.
+pub fn build_scene_sub_clips(request: &SplitAtScenesRequest) -> CommandResult<Vec<TimelineExportClip>> {
+    let range_start = request.trim_start.unwrap_or(0.0);
+    let range_end = request.trim_end.unwrap_or(request.original_duration);
+    if range_start < 0.0 || range_end <= range_start || range_end > request.original_duration + 0.001 {
+        return Err(CommandError::validation_error(format!(
+            "Invalid split range: {} to {} (source duration {})",
+            range_start, range_end, request.original_duration
+        )));
+    }
+
+    let mut boundaries = vec![range_start];
+    let mut cut_times: Vec<f64> = request
+        .cuts
+        .iter()
+        .map(|cut| cut.timestamp)
+        .filter(|timestamp| *timestamp > range_start && *timestamp < range_end)
+        .collect();
+    cut_times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    boundaries.extend(cut_times);
+    boundaries.push(range_end);
+
+    let mut timeline_position = request.start_time;
+    let sub_clips = boundaries
+        .windows(2)
+        .map(|pair| {
+            let duration = pair[1] - pair[0];
+            let clip = TimelineExportClip {
+                file_path: request.file_path.clone(),
+                start_time: timeline_position,
+                duration,
+                trim_start: pair[0],
+                trim_end: pair[1],
+                track_id: request.track_id.clone(),
+                original_duration: request.original_duration,
+            };
+            timeline_position += duration;
+            clip
+        })
+        .collect();
+
+    Ok(sub_clips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cut(timestamp: f64, score: f64) -> SceneCut {
+        SceneCut { timestamp, score }
+    }
+
+    #[test]
+    fn test_parse_scene_cuts() {
+        let stderr = "[Parsed_metadata_2 @ 0x600] frame:0    pts:61440   pts_time:4.8\n\
+                      [Parsed_metadata_2 @ 0x600] lavfi.scene_score=0.612345\n\
+                      frame=  120 fps=0.0 q=-0.0 size=N/A time=00:00:04.00 bitrate=N/A\n\
+                      [Parsed_metadata_2 @ 0x600] frame:1    pts:157696  pts_time:12.32\n\
+                      [Parsed_metadata_2 @ 0x600] lavfi.scene_score=0.998\n";
+
+        assert_eq!(parse_scene_cuts(stderr), vec![cut(4.8, 0.612345), cut(12.32, 0.998)]);
+        assert!(parse_scene_cuts("").is_empty());
+    }
+
+    #[test]
+    fn test_filter_scene_cuts() {
+        let cuts = vec![cut(0.2, 0.9), cut(5.0, 0.5), cut(5.4, 0.8), cut(12.0, 0.6), cut(29.5, 0.7)];
+        let filtered = filter_scene_cuts(cuts, 1.0, 30.0);
+
+        assert_eq!(filtered, vec![cut(5.0, 0.5), cut(12.0, 0.6)]);
+    }
+
+    #[test]
+    fn test_build_scene_sub_clips() {
+        let request = SplitAtScenesRequest {
+            file_path: "/rec.mov".to_string(),
+            cuts: vec![cut(12.0, 0.6), cut(5.0, 0.5), cut(40.0, 0.9)],
+            original_duration: 60.0,
+            trim_start: Some(2.0),
+            trim_end: Some(30.0),
+            start_time: 10.0,
+            track_id: "video-1".to_string(),
+        };
+
+        let clips = build_scene_sub_clips(&request).unwrap();
+        let ranges: Vec<(f64, f64, f64)> = clips.iter().map(|c| (c.start_time, c.trim_start, c.trim_end)).collect();
+        assert_eq!(ranges, vec![(10.0, 2.0, 5.0), (13.0, 5.0, 12.0), (20.0, 12.0, 30.0)]);
+        assert_eq!(clips[2].duration, 18.0);
+        assert!(clips.iter().all(|c| c.original_duration == 60.0));
+    }
+
+    #[test]
+    fn test_build_scene_sub_clips_invalid_range() {
+        let request = SplitAtScenesRequest {
+            file_path: "/rec.mov".to_string(),
+            cuts: vec![],
+            original_duration: 10.0,
+            trim_start: Some(4.0),
+            trim_end: Some(12.0),
+            start_time: 0.0,
+            track_id: "video-1".to_string(),
+        };
+
+        assert!(build_scene_sub_clips(&request).is_err());
+    }
+
+    #[test]
+    fn test_build_scene_detection_args() {
+        let args = build_scene_detection_args("in.mp4", 0.3);
+        assert!(args.contains(&"scale=320:-2,select='gt(scene,0.3)',metadata=print".to_string()));
+        assert_eq!(args.last().unwrap(), "-");
+    }
+}
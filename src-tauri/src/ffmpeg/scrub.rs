@@ -0,0 +1,412 @@
+// ============================================================================
+// FFMPEG SCRUB STRIP MODULE
+// ============================================================================
+// This module generates timeline scrub strips: frames sampled at a fixed
+// interval and packed into sprite sheets in a single FFmpeg pass (`fps` +
+// `tile`), with a JSON and WebVTT index mapping timestamps to tile positions.
+// Strips are cached per source and interval in the thumbnails directory.
+
+use crate::commands::{get_thumbnails_dir, hash_file_path, CommandError, CommandResult};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Request to generate a scrub strip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateScrubStripRequest {
+    pub file_path: String,
+    #[serde(default)]
+    pub interval: Option<f64>, // Seconds between frames, derived from the duration if omitted
+    #[serde(default)]
+    pub tile_width: Option<u32>,
+    #[serde(default)]
+    pub tile_height: Option<u32>,
+}
+
+/// Response from scrub strip generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateScrubStripResponse {
+    pub success: bool,
+    pub index: Option<ScrubStripIndex>,
+    pub index_path: Option<String>, // JSON index
+    pub vtt_path: Option<String>,   // WebVTT index for players
+    pub error_message: Option<String>,
+}
+
+/// Index of a generated scrub strip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrubStripIndex {
+    pub version: u32,
+    pub interval: f64,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub sheets: Vec<String>, // Absolute sprite sheet paths, in order
+    pub tiles: Vec<ScrubTile>,
+}
+
+/// A single frame in a sprite sheet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrubTile {
+    pub start: f64, // Source time the tile represents, in seconds
+    pub end: f64,
+    pub sheet: usize, // Index into `sheets`
+    pub x: u32,       // Pixel offset within the sheet
+    pub y: u32,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Scrub strip index format version
+const SCRUB_INDEX_VERSION: u32 = 1;
+
+/// Default tile size (16:9, letterboxed for other aspect ratios)
+pub const DEFAULT_SCRUB_TILE_WIDTH: u32 = 160;
+pub const DEFAULT_SCRUB_TILE_HEIGHT: u32 = 90;
+
+/// Sprite sheet grid; long sources produce several sheets
+const SCRUB_SHEET_COLUMNS: u32 = 10;
+const SCRUB_SHEET_ROWS: u32 = 10;
+
+/// Tile count the default interval aims for
+const DEFAULT_SCRUB_TILE_TARGET: f64 = 300.0;
+
+/// Smallest interval between frames in seconds
+const MIN_SCRUB_INTERVAL: f64 = 0.5;
+
+/// Cache file names inside a strip directory
+const SCRUB_SHEET_PATTERN: &str = "sheet_%03d.jpg";
+const SCRUB_INDEX_FILE_NAME: &str = "index.json";
+const SCRUB_VTT_FILE_NAME: &str = "index.vtt";
+
+// ============================================================================
+// INTERNAL FUNCTIONS
+// ============================================================================
+
+/// Generate (or load from cache) the scrub strip for a source
+pub async fn generate_scrub_strip_internal(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    duration: f64,
+    interval: f64,
+    tile_width: u32,
+    tile_height: u32,
+) -> CommandResult<ScrubStripIndex> {
+    if duration <= 0.0 {
+        return Err(CommandError::validation_error(format!(
+            "Cannot build a scrub strip for a source with duration {}",
+            duration
+        )));
+    }
+    if interval < MIN_SCRUB_INTERVAL {
+        return Err(CommandError::validation_error(format!(
+            "Scrub interval must be at least {}s, got {}",
+            MIN_SCRUB_INTERVAL, interval
+        )));
+    }
+    if tile_width == 0 || tile_height == 0 {
+        return Err(CommandError::validation_error(
+            "Tile dimensions must be greater than 0".to_string(),
+        ));
+    }
+
+    let strip_dir = get_scrub_strip_dir(file_path, interval, tile_width, tile_height)?;
+    if let Some(index) = read_cached_scrub_index(&strip_dir, Path::new(file_path)) {
+        return Ok(index);
+    }
+
+    // Render into a partial directory and rename, so a strip is never half written
+    let partial_dir = strip_dir.with_extension("partial");
+    let _ = std::fs::remove_dir_all(&partial_dir);
+    std::fs::create_dir_all(&partial_dir)
+        .map_err(|e| CommandError::io_error(format!("Failed to create scrub strip directory: {}", e)))?;
+
+    let source = crate::ffmpeg::proxy::resolve_preview_source(file_path);
+    let output_pattern = partial_dir.join(SCRUB_SHEET_PATTERN);
+    let args = build_scrub_strip_args(
+        &source,
+        &output_pattern.to_string_lossy(),
+        interval,
+        tile_width,
+        tile_height,
+    );
+
+    // Execute FFmpeg
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_dir_all(&partial_dir);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg scrub strip generation failed: {}",
+            stderr
+        )));
+    }
+
+    // Count the sheets FFmpeg actually wrote; the duration is only an estimate
+    let sheet_count = (1..)
+        .take_while(|number| partial_dir.join(get_scrub_sheet_name(*number)).exists())
+        .count();
+    if sheet_count == 0 {
+        let _ = std::fs::remove_dir_all(&partial_dir);
+        return Err(CommandError::ffmpeg_error(
+            "Scrub strip sheets were not created".to_string(),
+        ));
+    }
+
+    let sheets = (1..=sheet_count)
+        .map(|number| strip_dir.join(get_scrub_sheet_name(number)).to_string_lossy().to_string())
+        .collect();
+    let index = build_scrub_strip_index(duration, interval, tile_width, tile_height, sheets);
+
+    write_scrub_index_files(&partial_dir, &index)?;
+
+    let _ = std::fs::remove_dir_all(&strip_dir);
+    std::fs::rename(&partial_dir, &strip_dir).map_err(|e| {
+        let _ = std::fs::remove_dir_all(&partial_dir);
+        CommandError::io_error(format!("Failed to move scrub strip into place: {}", e))
+    })?;
+
+    Ok(index)
+}
+
+/// Build FFmpeg arguments that render every sprite sheet in one pass
+///
+/// Frames are letterboxed into the tile size so every source tiles the same
+/// way, and the tile filter flushes a partly filled last sheet at the end.
+pub fn build_scrub_strip_args(
+    input_path: &str,
+    output_pattern: &str,
+    interval: f64,
+    tile_width: u32,
+    tile_height: u32,
+) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-i".to_string(),
+        input_path.to_string(),
+        "-an".to_string(),
+        "-vf".to_string(),
+        format!(
+            "fps=1/{interval},scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,tile={cols}x{rows}",
+            interval = interval,
+            w = tile_width,
+            h = tile_height,
+            cols = SCRUB_SHEET_COLUMNS,
+            rows = SCRUB_SHEET_ROWS
+        ),
+        "-q:v".to_string(),
+        "4".to_string(),
+        "-y".to_string(), // Overwrite output files
+        output_pattern.to_string(),
+    ]
+}
+
+/// Get the default interval for a source, aiming for a few hundred tiles
+pub fn get_default_scrub_interval(duration: f64) -> f64 {
+    (duration / DEFAULT_SCRUB_TILE_TARGET).max(1.0)
+}
+
+/// Get the cache directory for a source's strip at an interval and tile size
+fn get_scrub_strip_dir(file_path: &str, interval: f64, tile_width: u32, tile_height: u32) -> CommandResult<PathBuf> {
+    let thumbnails_dir = get_thumbnails_dir()?;
+    Ok(thumbnails_dir.join("scrub").join(format!(
+        "{}_{}ms_{}x{}",
+        hash_file_path(file_path),
+        (interval * 1000.0).round() as u64,
+        tile_width,
+        tile_height
+    )))
+}
+
+/// Get the file name FFmpeg gives a sheet (numbered from 1)
+fn get_scrub_sheet_name(number: usize) -> String {
+    format!("sheet_{:03}.jpg", number)
+}
+
+/// Read a cached index if it exists and is newer than its source
+fn read_cached_scrub_index(strip_dir: &Path, source_path: &Path) -> Option<ScrubStripIndex> {
+    let index_path = strip_dir.join(SCRUB_INDEX_FILE_NAME);
+    let index_time = std::fs::metadata(&index_path).and_then(|m| m.modified()).ok()?;
+    let source_time = std::fs::metadata(source_path).and_then(|m| m.modified()).ok()?;
+    if index_time < source_time {
+        return None;
+    }
+
+    let contents = std::fs::read_to_string(&index_path).ok()?;
+    let index: ScrubStripIndex = serde_json::from_str(&contents).ok()?;
+    if index.version != SCRUB_INDEX_VERSION || !index.sheets.iter().all(|sheet| Path::new(sheet).exists()) {
+        return None;
+    }
+
+    Some(index)
+}
+
+/// Write the JSON and WebVTT indexes into a strip directory
+fn write_scrub_index_files(dir: &Path, index: &ScrubStripIndex) -> CommandResult<()> {
+    let json = serde_json::to_string_pretty(index)
+        .map_err(|e| CommandError::serialization_error(format!("Failed to serialize scrub index: {}", e)))?;
+    std::fs::write(dir.join(SCRUB_INDEX_FILE_NAME), json)
+        .map_err(|e| CommandError::file_error(format!("Failed to write scrub index: {}", e)))?;
+    std::fs::write(dir.join(SCRUB_VTT_FILE_NAME), build_scrub_vtt(index))
+        .map_err(|e| CommandError::file_error(format!("Failed to write scrub WebVTT index: {}", e)))?;
+    Ok(())
+}
+
+/// Get the JSON and WebVTT index paths for a strip's sheets
+pub fn get_scrub_index_paths(index: &ScrubStripIndex) -> Option<(PathBuf, PathBuf)> {
+    let strip_dir = Path::new(index.sheets.first()?).parent()?;
+    Some((strip_dir.join(SCRUB_INDEX_FILE_NAME), strip_dir.join(SCRUB_VTT_FILE_NAME)))
+}
+
+// ============================================================================
+// INDEX FUNCTIONS
+// ============================================================================
+
+/// Build the tile index for a strip
+///
+/// Tile `i` shows the frame at `i * interval` and covers until the next tile.
+/// Tiles past the sheets that were actually written are left out.
+pub fn build_scrub_strip_index(
+    duration: f64,
+    interval: f64,
+    tile_width: u32,
+    tile_height: u32,
+    sheets: Vec<String>,
+) -> ScrubStripIndex {
+    let tiles_per_sheet = (SCRUB_SHEET_COLUMNS * SCRUB_SHEET_ROWS) as usize;
+    let tile_count = ((duration / interval).ceil() as usize).min(sheets.len() * tiles_per_sheet);
+
+    let tiles = (0..tile_count)
+        .map(|i| {
+            let position = (i % tiles_per_sheet) as u32;
+            ScrubTile {
+                start: i as f64 * interval,
+                end: ((i + 1) as f64 * interval).min(duration),
+                sheet: i / tiles_per_sheet,
+                x: (position % SCRUB_SHEET_COLUMNS) * tile_width,
+                y: (position / SCRUB_SHEET_COLUMNS) * tile_height,
+            }
+        })
+        .collect();
+
+    ScrubStripIndex {
+        version: SCRUB_INDEX_VERSION,
+        interval,
+        tile_width,
+        tile_height,
+        columns: SCRUB_SHEET_COLUMNS,
+        rows: SCRUB_SHEET_ROWS,
+        sheets,
+        tiles,
+    }
+}
+
+/// Build a WebVTT thumbnail track (`sheet#xywh=x,y,w,h` cues) for a strip
+///
+/// Sheets are referenced by file name, relative to the .vtt file.
+pub fn build_scrub_vtt(index: &ScrubStripIndex) -> String {
+    let mut vtt = String::from("WEBVTT\n");
+
+    for tile in &index.tiles {
+        let sheet_name = index
+            .sheets
+            .get(tile.sheet)
+            .and_then(|sheet| Path::new(sheet).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        vtt.push_str(&format!(
+            "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+            format_vtt_timestamp(tile.start),
+            format_vtt_timestamp(tile.end),
+            sheet_name,
+            tile.x,
+            tile.y,
+            index.tile_width,
+            index.tile_height
+        ));
+    }
+
+    vtt
+}
+
+/// Format seconds as a WebVTT timestamp (HH:MM:SS.mmm)
+pub fn format_vtt_timestamp(seconds: f64) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        total_ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_scrub_strip_index() {
+        let sheets = vec!["/t/sheet_001.jpg".to_string(), "/t/sheet_002.jpg".to_string()];
+        let index = build_scrub_strip_index(125.5, 1.0, 160, 90, sheets);
+
+        assert_eq!(index.tiles.len(), 126);
+        assert_eq!(index.tiles[0], ScrubTile { start: 0.0, end: 1.0, sheet: 0, x: 0, y: 0 });
+        assert_eq!(index.tiles[23], ScrubTile { start: 23.0, end: 24.0, sheet: 0, x: 480, y: 180 });
+        assert_eq!(index.tiles[100].sheet, 1);
+        assert_eq!((index.tiles[100].x, index.tiles[100].y), (0, 0));
+
+        // The last tile ends at the source duration
+        assert_eq!(index.tiles[125].end, 125.5);
+    }
+
+    #[test]
+    fn test_build_scrub_strip_index_limited_by_sheets() {
+        let index = build_scrub_strip_index(250.0, 1.0, 160, 90, vec!["/t/sheet_001.jpg".to_string()]);
+        assert_eq!(index.tiles.len(), 100);
+    }
+
+    #[test]
+    fn test_build_scrub_vtt() {
+        let index = build_scrub_strip_index(1.5, 1.0, 160, 90, vec!["/t/sheet_001.jpg".to_string()]);
+        let vtt = build_scrub_vtt(&index);
+
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.000\nsheet_001.jpg#xywh=0,0,160,90\n\
+             \n00:00:01.000 --> 00:00:01.500\nsheet_001.jpg#xywh=160,0,160,90\n"
+        );
+    }
+
+    #[test]
+    fn test_format_vtt_timestamp() {
+        assert_eq!(format_vtt_timestamp(0.0), "00:00:00.000");
+        assert_eq!(format_vtt_timestamp(3725.25), "01:02:05.250");
+    }
+
+    #[test]
+    fn test_build_scrub_strip_args() {
+        let args = build_scrub_strip_args("in.mov", "/t/sheet_%03d.jpg", 2.0, 160, 90);
+
+        assert!(args.contains(
+            &"fps=1/2,scale=160:90:force_original_aspect_ratio=decrease,pad=160:90:(ow-iw)/2:(oh-ih)/2,tile=10x10".to_string()
+        ));
+        assert_eq!(args.last().unwrap(), "/t/sheet_%03d.jpg");
+    }
+
+    #[test]
+    fn test_get_default_scrub_interval() {
+        assert_eq!(get_default_scrub_interval(60.0), 1.0);
+        assert_eq!(get_default_scrub_interval(3000.0), 10.0);
+    }
+}
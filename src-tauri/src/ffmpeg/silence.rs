@@ -0,0 +1,346 @@
+// ============================================================================
+// FFMPEG SILENCE DETECTION MODULE
+// ============================================================================
+// This module finds silent intervals with FFmpeg's `silencedetect` filter and
+// turns them into "jump cuts": a clip list with the silence removed that can
+// be passed straight to timeline export.
+
+use crate::commands::export::TimelineExportClip;
+use crate::commands::{CommandError, CommandResult};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Request to detect silence in a clip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectSilenceRequest {
+    pub file_path: String,
+    #[serde(default)]
+    pub threshold_db: Option<f64>, // Noise level below which audio counts as silence
+    #[serde(default)]
+    pub min_silence_duration: Option<f64>, // Shortest silence reported, in seconds
+}
+
+/// A silent interval in source time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SilentInterval {
+    pub start: f64,
+    pub end: f64,
+    pub duration: f64,
+}
+
+/// Response from silence detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectSilenceResponse {
+    pub success: bool,
+    pub intervals: Vec<SilentInterval>,
+    pub duration: f64, // Source duration in seconds
+    pub error_message: Option<String>,
+}
+
+/// Request to remove silent intervals from a clip list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveSilenceRequest {
+    pub clips: Vec<TimelineExportClip>,
+    pub silences: HashMap<String, Vec<SilentInterval>>, // Silent intervals by source file path
+    #[serde(default)]
+    pub padding: Option<f64>, // Seconds of silence kept on each side of a cut
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default silence threshold in dB
+pub const DEFAULT_SILENCE_THRESHOLD_DB: f64 = -35.0;
+
+/// Default minimum silence duration in seconds
+pub const DEFAULT_MIN_SILENCE_DURATION: f64 = 0.5;
+
+/// Default padding kept around cuts in seconds
+pub const DEFAULT_SILENCE_PADDING: f64 = 0.1;
+
+/// Kept segments shorter than this are dropped
+const MIN_KEPT_SEGMENT_DURATION: f64 = 0.1;
+
+// ============================================================================
+// INTERNAL FUNCTIONS
+// ============================================================================
+
+/// Detect silent intervals in a clip's audio
+pub async fn detect_silence_internal(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    threshold_db: f64,
+    min_silence_duration: f64,
+    duration: f64,
+) -> CommandResult<Vec<SilentInterval>> {
+    if threshold_db > 0.0 {
+        return Err(CommandError::validation_error(format!(
+            "Silence threshold must be at most 0 dB, got {}",
+            threshold_db
+        )));
+    }
+    if min_silence_duration <= 0.0 {
+        return Err(CommandError::validation_error(format!(
+            "Minimum silence duration must be greater than 0, got {}",
+            min_silence_duration
+        )));
+    }
+
+    let args = build_silence_detection_args(file_path, threshold_db, min_silence_duration);
+
+    // Execute FFmpeg
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg silence detection failed: {}",
+            stderr
+        )));
+    }
+
+    Ok(parse_silence_intervals(&stderr, duration))
+}
+
+/// Build FFmpeg arguments for silence detection
+pub fn build_silence_detection_args(file_path: &str, threshold_db: f64, min_silence_duration: f64) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-i".to_string(),
+        file_path.to_string(),
+        "-vn".to_string(),
+        "-af".to_string(),
+        format!("silencedetect=noise={}dB:d={}", threshold_db, min_silence_duration),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Parse silencedetect output into intervals
+///
+/// Silence still running at the end of the file has no "silence_end" line and
+/// is closed at `duration`.
+pub fn parse_silence_intervals(stderr: &str, duration: f64) -> Vec<SilentInterval> {
+    let mut intervals = Vec::new();
+    let mut pending_start: Option<f64> = None;
+
+    let parse_value = |line: &str, key: &str| -> Option<f64> {
+        let index = line.find(key)?;
+        line[index + key.len()..]
+            .split(|c: char| c.is_whitespace() || c == '|')
+            .find(|value| !value.is_empty())
+            .and_then(|value| value.parse::<f64>().ok())
+    };
+
+    for line in stderr.lines() {
+        if let Some(start) = parse_value(line, "silence_start:") {
+            pending_start = Some(start.max(0.0));
+        } else if let Some(end) = parse_value(line, "silence_end:") {
+            if let Some(start) = pending_start.take() {
+                intervals.push(SilentInterval {
+                    start,
+                    end,
+                    duration: end - start,
+                });
+            }
+        }
+    }
+
+    if let Some(start) = pending_start {
+        if duration > start {
+            intervals.push(SilentInterval {
+                start,
+                end: duration,
+                duration: duration - start,
+            });
+        }
+    }
+
+    intervals
+}
+
+/// Remove silent intervals from a clip list
+///
+/// Each clip is split into the non-silent parts of its trim range, keeping
+/// `padding` seconds of silence next to each cut. Each track is laid end to end
+/// from its own first clip's timeline position, closing the gaps left by the cuts.
+pub fn remove_silence_from_clips(
+    clips: &[TimelineExportClip],
+    silences: &HashMap<String, Vec<SilentInterval>>,
+    padding: f64,
+) -> CommandResult<Vec<TimelineExportClip>> {
+    if padding < 0.0 {
+        return Err(CommandError::validation_error(format!(
+            "Padding must not be negative, got {}",
+            padding
+        )));
+    }
+
+    let mut ordered: Vec<&TimelineExportClip> = clips.iter().collect();
+    ordered.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Next free position on each track, starting at the track's first clip
+    let mut track_positions: HashMap<&str, f64> = HashMap::new();
+    let mut result = Vec::new();
+
+    for clip in ordered {
+        let timeline_position = track_positions.entry(clip.track_id.as_str()).or_insert(clip.start_time);
+        let intervals = silences.get(&clip.file_path).map(|v| v.as_slice()).unwrap_or(&[]);
+        for (segment_start, segment_end) in get_kept_segments(clip.trim_start, clip.trim_end, intervals, padding) {
+            let duration = segment_end - segment_start;
+            result.push(TimelineExportClip {
+                file_path: clip.file_path.clone(),
+                start_time: *timeline_position,
+                duration,
+                trim_start: segment_start,
+                trim_end: segment_end,
+                track_id: clip.track_id.clone(),
+                original_duration: clip.original_duration,
+            });
+            *timeline_position += duration;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Get the parts of a trim range outside the (padded) silent intervals
+fn get_kept_segments(
+    trim_start: f64,
+    trim_end: f64,
+    intervals: &[SilentInterval],
+    padding: f64,
+) -> Vec<(f64, f64)> {
+    let mut cuts: Vec<(f64, f64)> = intervals
+        .iter()
+        .map(|interval| (interval.start + padding, interval.end - padding))
+        .filter(|(start, end)| end > start)
+        .collect();
+    cuts.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut segments = Vec::new();
+    let mut segment_start = trim_start;
+    for (cut_start, cut_end) in cuts {
+        if cut_end <= segment_start || cut_start >= trim_end {
+            continue;
+        }
+        if cut_start > segment_start {
+            segments.push((segment_start, cut_start));
+        }
+        segment_start = cut_end;
+    }
+    if trim_end > segment_start {
+        segments.push((segment_start, trim_end));
+    }
+
+    segments
+        .into_iter()
+        .filter(|(start, end)| end - start >= MIN_KEPT_SEGMENT_DURATION)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(start: f64, end: f64) -> SilentInterval {
+        SilentInterval {
+            start,
+            end,
+            duration: end - start,
+        }
+    }
+
+    fn clip(file_path: &str, start_time: f64, trim_start: f64, trim_end: f64) -> TimelineExportClip {
+        TimelineExportClip {
+            file_path: file_path.to_string(),
+            start_time,
+            duration: trim_end - trim_start,
+            trim_start,
+            trim_end,
+            track_id: "video-1".to_string(),
+            original_duration: 60.0,
+        }
+    }
+
+    #[test]
+    fn test_parse_silence_intervals() {
+        let stderr = "[silencedetect @ 0x600] silence_start: 2.5\n\
+                      [silencedetect @ 0x600] silence_end: 4.25 | silence_duration: 1.75\n\
+                      size=N/A time=00:00:09.00 bitrate=N/A speed= 200x\n\
+                      [silencedetect @ 0x600] silence_start: -0.01\n\
+                      [silencedetect @ 0x600] silence_end: 0.8 | silence_duration: 0.81\n\
+                      [silencedetect @ 0x600] silence_start: 9.2\n";
+
+        let intervals = parse_silence_intervals(stderr, 10.0);
+        assert_eq!(intervals[0], interval(2.5, 4.25));
+        assert_eq!(intervals[1], interval(0.0, 0.8));
+        assert_eq!(intervals[2].start, 9.2);
+        assert_eq!(intervals[2].end, 10.0);
+    }
+
+    #[test]
+    fn test_remove_silence_from_clips() {
+        let mut silences = HashMap::new();
+        silences.insert("/a.mov".to_string(), vec![interval(2.0, 4.0), interval(8.0, 12.0)]);
+
+        let clips = vec![clip("/b.mov", 10.0, 0.0, 5.0), clip("/a.mov", 0.0, 1.0, 10.0)];
+        let result = remove_silence_from_clips(&clips, &silences, 0.1).unwrap();
+
+        let ranges: Vec<(&str, f64, f64)> = result
+            .iter()
+            .map(|c| (c.file_path.as_str(), c.trim_start, c.trim_end))
+            .collect();
+        assert_eq!(ranges, vec![("/a.mov", 1.0, 2.1), ("/a.mov", 3.9, 8.1), ("/b.mov", 0.0, 5.0)]);
+
+        // Clips are laid end to end from the first clip's position
+        assert_eq!(result[0].start_time, 0.0);
+        assert!((result[1].start_time - 1.1).abs() < 1e-9);
+        assert!((result[2].start_time - 5.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_remove_silence_from_clips_per_track() {
+        let mut silences = HashMap::new();
+        silences.insert("/a.mov".to_string(), vec![interval(2.0, 4.0)]);
+
+        let mut overlay = clip("/c.mov", 3.0, 0.0, 2.0);
+        overlay.track_id = "video-2".to_string();
+        let clips = vec![clip("/a.mov", 0.0, 0.0, 6.0), overlay, clip("/b.mov", 6.0, 0.0, 1.0)];
+        let result = remove_silence_from_clips(&clips, &silences, 0.0).unwrap();
+
+        let positions: Vec<(&str, &str, f64)> = result
+            .iter()
+            .map(|c| (c.track_id.as_str(), c.file_path.as_str(), c.start_time))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                ("video-1", "/a.mov", 0.0),
+                ("video-1", "/a.mov", 2.0),
+                ("video-2", "/c.mov", 3.0), // Other tracks keep their own start
+                ("video-1", "/b.mov", 4.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_kept_segments() {
+        // Padding wider than the silence keeps it
+        assert_eq!(get_kept_segments(0.0, 5.0, &[interval(2.0, 2.3)], 0.2), vec![(0.0, 5.0)]);
+
+        // Slivers shorter than the minimum are dropped
+        assert_eq!(get_kept_segments(0.0, 5.0, &[interval(0.05, 5.0)], 0.0), vec![]);
+
+        assert!(remove_silence_from_clips(&[], &HashMap::new(), -1.0).is_err());
+    }
+}
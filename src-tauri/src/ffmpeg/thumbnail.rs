@@ -3,10 +3,16 @@
 // ============================================================================
 // This module handles thumbnail generation using FFmpeg.
 // It extracts frames from videos at specific timestamps and saves them as images.
+// Thumbnails are cached under a stable key of the source identity and output
+// settings, with an index used to evict least-recently-used thumbnails once
+// the cache exceeds its size limit.
 
-use crate::commands::{get_thumbnails_dir, hash_file_path, CommandError, CommandResult};
+use crate::commands::{get_thumbnails_dir, stable_hash, CommandError, CommandResult};
+use crate::ffmpeg::render_cache::{get_source_identity, SourceIdentity};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
 use serde::{Deserialize, Serialize};
-use tauri::Manager;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 // ============================================================================
 // DATA STRUCTURES
@@ -19,6 +25,12 @@ pub struct GenerateThumbnailRequest {
     pub timestamp: Option<f64>, // Time in seconds, defaults to 1.0
     pub width: Option<u32>,     // Thumbnail width, defaults to 320
     pub height: Option<u32>,    // Thumbnail height, defaults to 180
+    #[serde(default)]
+    pub best_frame_window: Option<f64>, // Pick the best frame in the first N seconds instead of `timestamp`
+    #[serde(default)]
+    pub format: Option<ThumbnailFormat>, // Image format, defaults to JPEG
+    #[serde(default)]
+    pub fit: Option<ThumbnailFit>, // How the frame fits the box, defaults to fit
 }
 
 /// Response from thumbnail generation
@@ -26,9 +38,39 @@ pub struct GenerateThumbnailRequest {
 pub struct GenerateThumbnailResponse {
     pub success: bool,
     pub thumbnail_path: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>, // Actual output dimensions
+    #[serde(default)]
+    pub height: Option<u32>,
     pub error_message: Option<String>,
 }
 
+/// Thumbnail image format
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+/// How a frame is fitted into the requested width and height
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailFit {
+    Fit,  // Scale to fit inside the box, keeping aspect ratio (output may be smaller)
+    Fill, // Scale to cover the box and crop the overflow
+    Pad,  // Scale to fit inside the box and letterbox to its exact size
+}
+
+/// A generated (or cached) thumbnail
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedThumbnail {
+    pub path: String,
+    pub width: Option<u32>, // None if the output size could not be determined
+    pub height: Option<u32>,
+}
+
 /// Request to generate multiple thumbnails
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenerateMultipleThumbnailsRequest {
@@ -36,6 +78,10 @@ pub struct GenerateMultipleThumbnailsRequest {
     pub timestamps: Vec<f64>, // Multiple timestamps
     pub width: Option<u32>,
     pub height: Option<u32>,
+    #[serde(default)]
+    pub format: Option<ThumbnailFormat>,
+    #[serde(default)]
+    pub fit: Option<ThumbnailFit>,
 }
 
 /// Response from multiple thumbnail generation
@@ -46,6 +92,88 @@ pub struct GenerateMultipleThumbnailsResponse {
     pub error_message: Option<String>,
 }
 
+/// A cached thumbnail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailCacheEntry {
+    pub key: String,
+    pub file_name: String,   // File inside the thumbnails directory
+    pub source_path: String, // Video the thumbnail was taken from
+    pub timestamp: f64,
+    pub width: u32, // Requested box
+    pub height: u32,
+    #[serde(default)]
+    pub output_width: Option<u32>, // Actual image size
+    #[serde(default)]
+    pub output_height: Option<u32>,
+    pub size_bytes: u64,
+    pub created_at: String,   // RFC 3339 timestamp
+    pub last_used_at: String, // RFC 3339 timestamp, used for LRU eviction
+}
+
+/// On-disk index of the thumbnail cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailCacheIndex {
+    pub version: u32,
+    pub entries: Vec<ThumbnailCacheEntry>,
+}
+
+/// Summary of the thumbnail cache for inspection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailCacheStats {
+    pub cache_dir: String,
+    pub entry_count: usize,
+    pub total_size_bytes: u64,
+    pub max_size_bytes: u64,
+}
+
+/// A frame considered for a best-frame thumbnail
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameCandidate {
+    pub timestamp: f64,
+    pub brightness: f64, // Average luma (0-255)
+    pub entropy: f64,    // Normalized luma entropy (0-1), higher means more detail
+}
+
+/// Thumbnail cache stored in the app thumbnails directory
+#[derive(Debug, Clone)]
+pub struct ThumbnailCache {
+    pub cache_dir: PathBuf,
+    pub max_size_bytes: u64,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Index file name inside the thumbnails directory
+const THUMBNAIL_CACHE_INDEX_FILE: &str = "thumbnail_index.json";
+
+/// Current index format version
+const THUMBNAIL_CACHE_INDEX_VERSION: u32 = 1;
+
+/// Default cache size limit (256 MB)
+pub const DEFAULT_THUMBNAIL_CACHE_MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Default number of seconds searched for a best-frame thumbnail
+pub const DEFAULT_BEST_FRAME_WINDOW: f64 = 10.0;
+
+/// Candidate frames sampled per second during best-frame analysis
+const BEST_FRAME_SAMPLE_RATE: u32 = 2;
+
+/// Frames darker than this average luma are treated as black
+const BLACK_FRAME_MAX_BRIGHTNESS: f64 = 30.0;
+
+/// Frames brighter than this average luma are treated as blown out or blank white
+const WHITE_FRAME_MIN_BRIGHTNESS: f64 = 235.0;
+
+/// Extensions purged when they are not in the index (older cache layouts)
+const THUMBNAIL_IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif", "mp4"];
+
+lazy_static::lazy_static! {
+    /// Serializes index updates across concurrent thumbnail generation
+    static ref THUMBNAIL_CACHE_LOCK: Mutex<()> = Mutex::new(());
+}
+
 // ============================================================================
 // TAURI COMMANDS
 // ============================================================================
@@ -62,20 +190,36 @@ pub async fn generate_thumbnail(
     crate::commands::validate_file_path(&file_path)?;
 
     // Set defaults
-    let timestamp = request.timestamp.unwrap_or(1.0);
+    let mut timestamp = request.timestamp.unwrap_or(1.0);
     let width = request.width.unwrap_or(320);
     let height = request.height.unwrap_or(180);
 
+    // Pick a representative frame, keeping the requested timestamp if analysis fails
+    if let Some(window) = request.best_frame_window {
+        match find_best_frame_timestamp(&app_handle, &file_path, window).await {
+            Ok(Some(best_timestamp)) => timestamp = best_timestamp,
+            Ok(None) => {}
+            Err(error) => eprintln!("Warning: Best frame analysis failed: {}", error.message),
+        }
+    }
+
+    let format = request.format.unwrap_or(ThumbnailFormat::Jpeg);
+    let fit = request.fit.unwrap_or(ThumbnailFit::Fit);
+
     // Generate thumbnail
-    match generate_thumbnail_internal(&app_handle, &file_path, timestamp, width, height).await {
-        Ok(thumbnail_path) => Ok(GenerateThumbnailResponse {
+    match generate_thumbnail_with_options(&app_handle, &file_path, timestamp, width, height, format, fit).await {
+        Ok(thumbnail) => Ok(GenerateThumbnailResponse {
             success: true,
-            thumbnail_path: Some(thumbnail_path),
+            thumbnail_path: Some(thumbnail.path),
+            width: thumbnail.width,
+            height: thumbnail.height,
             error_message: None,
         }),
         Err(error) => Ok(GenerateThumbnailResponse {
             success: false,
             thumbnail_path: None,
+            width: None,
+            height: None,
             error_message: Some(error.message),
         }),
     }
@@ -95,14 +239,16 @@ pub async fn generate_multiple_thumbnails(
     // Set defaults
     let width = request.width.unwrap_or(320);
     let height = request.height.unwrap_or(180);
+    let format = request.format.unwrap_or(ThumbnailFormat::Jpeg);
+    let fit = request.fit.unwrap_or(ThumbnailFit::Fit);
 
     let mut thumbnail_paths = Vec::new();
     let mut errors = Vec::new();
 
     // Generate thumbnails for each timestamp
     for timestamp in request.timestamps {
-        match generate_thumbnail_internal(&app_handle, &file_path, timestamp, width, height).await {
-            Ok(thumbnail_path) => thumbnail_paths.push(thumbnail_path),
+        match generate_thumbnail_with_options(&app_handle, &file_path, timestamp, width, height, format, fit).await {
+            Ok(thumbnail) => thumbnail_paths.push(thumbnail.path),
             Err(error) => errors.push(error.message),
         }
     }
@@ -130,9 +276,20 @@ pub async fn generate_multiple_thumbnails(
 pub async fn thumbnail_exists(request: GenerateThumbnailRequest) -> CommandResult<bool> {
     let file_path = request.file_path;
     let timestamp = request.timestamp.unwrap_or(1.0);
+    let width = request.width.unwrap_or(320);
+    let height = request.height.unwrap_or(180);
+    let format = request.format.unwrap_or(ThumbnailFormat::Jpeg);
+    let fit = request.fit.unwrap_or(ThumbnailFit::Fit);
+
+    // A missing source has no thumbnails
+    let source = match get_source_identity(&file_path) {
+        Ok(source) => source,
+        Err(_) => return Ok(false),
+    };
 
     // Generate thumbnail path
-    let thumbnail_path = get_thumbnail_path(&file_path, timestamp)?;
+    let key = build_thumbnail_cache_key(&source, timestamp, width, height, format, fit);
+    let thumbnail_path = ThumbnailCache::open()?.get_thumbnail_path(&key, format.extension());
 
     Ok(thumbnail_path.exists())
 }
@@ -142,24 +299,25 @@ pub async fn thumbnail_exists(request: GenerateThumbnailRequest) -> CommandResul
 pub async fn delete_thumbnail(request: GenerateThumbnailRequest) -> CommandResult<bool> {
     let file_path = request.file_path;
     let timestamp = request.timestamp.unwrap_or(1.0);
+    let width = request.width.unwrap_or(320);
+    let height = request.height.unwrap_or(180);
+    let format = request.format.unwrap_or(ThumbnailFormat::Jpeg);
+    let fit = request.fit.unwrap_or(ThumbnailFit::Fit);
 
-    // Generate thumbnail path
-    let thumbnail_path = get_thumbnail_path(&file_path, timestamp)?;
+    let source = match get_source_identity(&file_path) {
+        Ok(source) => source,
+        Err(_) => return Ok(false),
+    };
 
-    if thumbnail_path.exists() {
-        std::fs::remove_file(&thumbnail_path)
-            .map_err(|e| CommandError::io_error(format!("Failed to delete thumbnail: {}", e)))?;
-        Ok(true)
-    } else {
-        Ok(false)
-    }
+    let key = build_thumbnail_cache_key(&source, timestamp, width, height, format, fit);
+    ThumbnailCache::open()?.remove(&key)
 }
 
 // ============================================================================
 // INTERNAL FUNCTIONS
 // ============================================================================
 
-/// Internal function to generate a thumbnail
+/// Internal function to generate a JPEG thumbnail fitted into a box
 async fn generate_thumbnail_internal(
     app_handle: &tauri::AppHandle,
     file_path: &str,
@@ -167,70 +325,67 @@ async fn generate_thumbnail_internal(
     width: u32,
     height: u32,
 ) -> CommandResult<String> {
-    // Check if thumbnail already exists
-    let thumbnail_path = get_thumbnail_path(file_path, timestamp)?;
-    if thumbnail_path.exists() {
-        return Ok(thumbnail_path.to_string_lossy().to_string());
-    }
-
-    // Resolve FFmpeg path (same logic as in recording)
-    let ffmpeg_path = if cfg!(debug_assertions) {
-        // Development mode - use source tree path
-        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
-            .map_err(|e| CommandError::validation_error(format!("Failed to get manifest dir: {}", e)))?;
-        let dev_path = std::path::PathBuf::from(manifest_dir).join("bin/ffmpeg");
-        dev_path.to_string_lossy().to_string()
-    } else {
-        // Production mode - use bundled sidecar
-        app_handle.path().resolve("bin/ffmpeg", tauri::path::BaseDirectory::Resource)
-            .map_err(|e| {
-                CommandError::validation_error(format!("Failed to find FFmpeg: {}", e))
-            })?
-            .to_string_lossy()
-            .to_string()
-    };
-
-    // Verify FFmpeg binary exists
-    if !std::path::Path::new(&ffmpeg_path).exists() {
-        return Err(CommandError::ffmpeg_error(format!("FFmpeg binary not found at: {}", ffmpeg_path)));
-    }
-
-    // Build FFmpeg command for thumbnail generation
-    use std::process::Command;
-    
-    // Clone values for the blocking task
-    let ffmpeg_path_clone = ffmpeg_path.clone();
-    let file_path_clone = file_path.to_string();
-    let thumbnail_path_clone = thumbnail_path.clone();
-    let timestamp_str = timestamp.to_string();
-    let scale_str = format!("scale={}:{}", width, height);
-    
-    // Run FFmpeg in a blocking task to avoid blocking the async runtime
-    let output = tokio::task::spawn_blocking(move || {
-        Command::new(&ffmpeg_path_clone)
-            .args(&[
-                "-ss",
-                &timestamp_str, // Seek to timestamp
-                "-i",
-                &file_path_clone, // Input file
-                "-vframes",
-                "1", // Extract only 1 frame
-                "-vf",
-                &scale_str, // Scale to desired size
-                "-q:v",
-                "2",  // High quality
-                "-y", // Overwrite output file
-                thumbnail_path_clone.to_string_lossy().as_ref(),
-            ])
-            .output()
-    })
+    generate_thumbnail_with_options(
+        app_handle,
+        file_path,
+        timestamp,
+        width,
+        height,
+        ThumbnailFormat::Jpeg,
+        ThumbnailFit::Fit,
+    )
     .await
-    .map_err(|e| CommandError::ffmpeg_error(format!("Failed to spawn ffmpeg task: {}", e)))?
-    .map_err(|e| CommandError::ffmpeg_error(format!("Failed to execute ffmpeg: {}", e)))?;
+    .map(|thumbnail| thumbnail.path)
+}
+
+/// Generate a thumbnail in a given format and fit mode
+pub async fn generate_thumbnail_with_options(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    timestamp: f64,
+    width: u32,
+    height: u32,
+    format: ThumbnailFormat,
+    fit: ThumbnailFit,
+) -> CommandResult<GeneratedThumbnail> {
+    if width == 0 || height == 0 {
+        return Err(CommandError::validation_error(
+            "Thumbnail dimensions must be greater than 0".to_string(),
+        ));
+    }
+
+    // Check if thumbnail is already cached for this exact source and settings
+    let source = get_source_identity(file_path)?;
+    let key = build_thumbnail_cache_key(&source, timestamp, width, height, format, fit);
+    let cache = ThumbnailCache::open()?;
+    if let Some(entry) = cache.lookup(&key)? {
+        return Ok(GeneratedThumbnail {
+            path: cache.get_entry_path(&entry).to_string_lossy().to_string(),
+            width: entry.output_width,
+            height: entry.output_height,
+        });
+    }
+    let thumbnail_path = cache.get_thumbnail_path(&key, format.extension());
+
+    // Build FFmpeg command for thumbnail generation, reading the proxy when one exists
+    let args = build_thumbnail_args(
+        &crate::ffmpeg::proxy::resolve_preview_source(file_path),
+        &thumbnail_path.to_string_lossy(),
+        timestamp,
+        width,
+        height,
+        format,
+        fit,
+    );
+
+    // Execute FFmpeg
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
 
     // Check if command succeeded
+    let stderr = String::from_utf8_lossy(&output.stderr);
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(CommandError::ffmpeg_error(format!(
             "FFmpeg thumbnail generation failed: {}",
             stderr
@@ -244,19 +399,506 @@ async fn generate_thumbnail_internal(
         ));
     }
 
-    Ok(thumbnail_path.to_string_lossy().to_string())
+    // Record it in the cache index, evicting old thumbnails if over the limit
+    let output_size = get_thumbnail_output_size(&stderr, width, height, fit);
+    cache.insert(&key, &thumbnail_path, file_path, timestamp, (width, height), output_size)?;
+
+    Ok(GeneratedThumbnail {
+        path: thumbnail_path.to_string_lossy().to_string(),
+        width: output_size.map(|size| size.0),
+        height: output_size.map(|size| size.1),
+    })
+}
+
+/// Build FFmpeg arguments to extract one frame as a thumbnail
+pub fn build_thumbnail_args(
+    input_path: &str,
+    output_path: &str,
+    timestamp: f64,
+    width: u32,
+    height: u32,
+    format: ThumbnailFormat,
+    fit: ThumbnailFit,
+) -> Vec<String> {
+    let mut args = vec![
+        "-ss".to_string(),
+        timestamp.to_string(), // Seek to timestamp
+        "-i".to_string(),
+        input_path.to_string(),
+        "-vframes".to_string(),
+        "1".to_string(), // Extract only 1 frame
+        "-vf".to_string(),
+        build_thumbnail_scale_filter(width, height, fit),
+    ];
+
+    match format {
+        ThumbnailFormat::Jpeg => args.extend(["-q:v".to_string(), "2".to_string()]), // High quality
+        ThumbnailFormat::Png => args.extend(["-c:v".to_string(), "png".to_string()]),
+        ThumbnailFormat::Webp => args.extend([
+            "-c:v".to_string(),
+            "libwebp".to_string(),
+            "-quality".to_string(),
+            "80".to_string(),
+        ]),
+    }
+
+    args.push("-y".to_string()); // Overwrite output file
+    args.push(output_path.to_string());
+    args
+}
+
+/// Build the scale filter for a fit mode, always preserving the aspect ratio
+pub fn build_thumbnail_scale_filter(width: u32, height: u32, fit: ThumbnailFit) -> String {
+    match fit {
+        ThumbnailFit::Fit => format!("scale={}:{}:force_original_aspect_ratio=decrease", width, height),
+        ThumbnailFit::Fill => format!(
+            "scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h}",
+            w = width,
+            h = height
+        ),
+        ThumbnailFit::Pad => format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+            w = width,
+            h = height
+        ),
+    }
+}
+
+/// Get the size of a written thumbnail
+///
+/// Fill and pad always produce the requested box; fit depends on the source
+/// aspect ratio, so it is read from FFmpeg's output stream description.
+pub fn get_thumbnail_output_size(stderr: &str, width: u32, height: u32, fit: ThumbnailFit) -> Option<(u32, u32)> {
+    match fit {
+        ThumbnailFit::Fill | ThumbnailFit::Pad => Some((width, height)),
+        ThumbnailFit::Fit => parse_output_video_size(stderr),
+    }
+}
+
+/// Parse the frame size of the first output video stream from FFmpeg stderr
+pub fn parse_output_video_size(stderr: &str) -> Option<(u32, u32)> {
+    let output_section = &stderr[stderr.find("Output #0")?..];
+    let stream_line = output_section.lines().find(|line| line.contains("Video:"))?;
+
+    stream_line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .find_map(|token| {
+            let (w, h) = token.split_once('x')?;
+            Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?))
+        })
+}
+
+/// Find the most representative frame in the first `window` seconds of a video
+///
+/// Candidates are sampled a few times per second and scored by luma entropy,
+/// since black, blank and blurry frames carry little detail. Returns None when
+/// every candidate is black or blown out.
+pub async fn find_best_frame_timestamp(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    window: f64,
+) -> CommandResult<Option<f64>> {
+    find_best_frame_timestamp_with_runner(&ProcessRunner::new(app_handle), file_path, window).await
+}
+
+/// Find the most representative frame through `runner`
+pub async fn find_best_frame_timestamp_with_runner(
+    runner: &dyn FfmpegRunner,
+    file_path: &str,
+    window: f64,
+) -> CommandResult<Option<f64>> {
+    if window <= 0.0 {
+        return Err(CommandError::validation_error(format!(
+            "Best frame window must be greater than 0, got {}",
+            window
+        )));
+    }
+
+    let source = crate::ffmpeg::proxy::resolve_preview_source(file_path);
+    let args = build_best_frame_analysis_args(&source, window);
+
+    // Execute FFmpeg
+    let output = runner.run(FfmpegInvocation::ffmpeg(&args)).await?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg frame analysis failed: {}",
+            stderr
+        )));
+    }
+
+    Ok(select_best_frame(&parse_frame_candidates(&stderr)).map(|candidate| candidate.timestamp))
+}
+
+/// Build FFmpeg arguments that log brightness and entropy for sampled frames
+pub fn build_best_frame_analysis_args(file_path: &str, window: f64) -> Vec<String> {
+    vec![
+        "-hide_banner".to_string(),
+        "-i".to_string(),
+        file_path.to_string(),
+        "-t".to_string(),
+        window.to_string(),
+        "-an".to_string(),
+        "-vf".to_string(),
+        format!(
+            "fps={},scale=160:-2,signalstats,entropy,metadata=print",
+            BEST_FRAME_SAMPLE_RATE
+        ),
+        "-f".to_string(),
+        "null".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Parse per-frame statistics from metadata filter output
+///
+/// Each frame logs a "pts_time:" line followed by its metadata keys.
+pub fn parse_frame_candidates(stderr: &str) -> Vec<FrameCandidate> {
+    let mut candidates: Vec<FrameCandidate> = Vec::new();
+
+    let parse_value = |line: &str, key: &str| -> Option<f64> {
+        let index = line.find(key)?;
+        line[index + key.len()..].trim().parse::<f64>().ok()
+    };
+
+    for line in stderr.lines() {
+        if let Some(index) = line.find("pts_time:") {
+            if let Some(timestamp) = line[index + "pts_time:".len()..]
+                .split_whitespace()
+                .next()
+                .and_then(|value| value.parse::<f64>().ok())
+            {
+                candidates.push(FrameCandidate {
+                    timestamp,
+                    brightness: 0.0,
+                    entropy: 0.0,
+                });
+            }
+        } else if let Some(candidate) = candidates.last_mut() {
+            if let Some(brightness) = parse_value(line, "lavfi.signalstats.YAVG=") {
+                candidate.brightness = brightness;
+            } else if let Some(entropy) = parse_value(line, "lavfi.entropy.normalized_entropy.normal.Y=") {
+                candidate.entropy = entropy;
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Pick the candidate with the most detail, skipping black and blank white frames
+///
+/// Ties go to the earliest frame.
+pub fn select_best_frame(candidates: &[FrameCandidate]) -> Option<&FrameCandidate> {
+    candidates
+        .iter()
+        .filter(|c| c.brightness > BLACK_FRAME_MAX_BRIGHTNESS && c.brightness < WHITE_FRAME_MIN_BRIGHTNESS)
+        .fold(None, |best: Option<&FrameCandidate>, candidate| match best {
+            Some(best) if best.entropy >= candidate.entropy => Some(best),
+            _ => Some(candidate),
+        })
+}
+
+/// Build the cache key for a thumbnail
+///
+/// The key covers the source identity (so a re-recorded file at the same path
+/// misses) and every output setting. Timestamps are rounded to milliseconds so
+/// float noise from the frontend does not cause misses.
+pub fn build_thumbnail_cache_key(
+    source: &SourceIdentity,
+    timestamp: f64,
+    width: u32,
+    height: u32,
+    format: ThumbnailFormat,
+    fit: ThumbnailFit,
+) -> String {
+    let timestamp_ms = (timestamp * 1000.0).round() as i64;
+    let hash = stable_hash(&[
+        source.path.as_bytes(),
+        &source.size_bytes.to_le_bytes(),
+        &source.modified_secs.to_le_bytes(),
+        &timestamp_ms.to_le_bytes(),
+        &width.to_le_bytes(),
+        &height.to_le_bytes(),
+        format.extension().as_bytes(),
+        fit.as_str().as_bytes(),
+    ]);
+    format!("{:016x}", hash)
+}
+
+// ============================================================================
+// THUMBNAIL OPTION IMPLEMENTATIONS
+// ============================================================================
+
+impl ThumbnailFormat {
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "jpg",
+            ThumbnailFormat::Png => "png",
+            ThumbnailFormat::Webp => "webp",
+        }
+    }
+}
+
+impl ThumbnailFit {
+    /// Name of the fit mode, as used in cache keys
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThumbnailFit::Fit => "fit",
+            ThumbnailFit::Fill => "fill",
+            ThumbnailFit::Pad => "pad",
+        }
+    }
 }
 
-/// Get the thumbnail path for a file and timestamp
-fn get_thumbnail_path(file_path: &str, timestamp: f64) -> CommandResult<std::path::PathBuf> {
-    let thumbnails_dir = get_thumbnails_dir()?;
+// ============================================================================
+// THUMBNAIL CACHE IMPLEMENTATION
+// ============================================================================
+
+impl ThumbnailCache {
+    /// Create a cache rooted at a directory
+    pub fn new(cache_dir: PathBuf, max_size_bytes: u64) -> Self {
+        Self {
+            cache_dir,
+            max_size_bytes,
+        }
+    }
+
+    /// Open the app thumbnail cache
+    pub fn open() -> CommandResult<Self> {
+        Ok(Self::new(get_thumbnails_dir()?, DEFAULT_THUMBNAIL_CACHE_MAX_SIZE))
+    }
+
+    /// Get the path a new thumbnail with this key should be written to
+    pub fn get_thumbnail_path(&self, key: &str, extension: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.{}", key, extension))
+    }
+
+    /// Get the path of a cached thumbnail
+    pub fn get_entry_path(&self, entry: &ThumbnailCacheEntry) -> PathBuf {
+        self.cache_dir.join(&entry.file_name)
+    }
+
+    /// Look up a thumbnail, marking it as recently used on a hit
+    pub fn lookup(&self, key: &str) -> CommandResult<Option<ThumbnailCacheEntry>> {
+        let _guard = THUMBNAIL_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.read_index()?;
+
+        let position = match index.entries.iter().position(|entry| entry.key == key) {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+
+        let thumbnail_path = self.cache_dir.join(&index.entries[position].file_name);
+        if !thumbnail_path.exists() {
+            // Drop entries whose file disappeared
+            index.entries.remove(position);
+            self.write_index(&index)?;
+            return Ok(None);
+        }
+
+        index.entries[position].last_used_at = chrono::Utc::now().to_rfc3339();
+        self.write_index(&index)?;
+        Ok(Some(index.entries[position].clone()))
+    }
+
+    /// Record a generated thumbnail, evicting old entries to keep within the size limit
+    pub fn insert(
+        &self,
+        key: &str,
+        thumbnail_path: &Path,
+        source_path: &str,
+        timestamp: f64,
+        requested_size: (u32, u32),
+        output_size: Option<(u32, u32)>,
+    ) -> CommandResult<()> {
+        let _guard = THUMBNAIL_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.read_index()?;
+
+        let file_name = thumbnail_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| CommandError::validation_error("Invalid thumbnail path".to_string()))?
+            .to_string();
+        let size_bytes = std::fs::metadata(thumbnail_path)
+            .map_err(|e| CommandError::io_error(format!("Failed to read thumbnail metadata: {}", e)))?
+            .len();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        // Make room first so the new thumbnail itself is never evicted
+        index.entries.retain(|entry| entry.key != key);
+        self.evict_to_size(&mut index, self.max_size_bytes.saturating_sub(size_bytes));
+        index.entries.push(ThumbnailCacheEntry {
+            key: key.to_string(),
+            file_name,
+            source_path: source_path.to_string(),
+            timestamp,
+            width: requested_size.0,
+            height: requested_size.1,
+            output_width: output_size.map(|size| size.0),
+            output_height: output_size.map(|size| size.1),
+            size_bytes,
+            created_at: now.clone(),
+            last_used_at: now,
+        });
+
+        self.write_index(&index)
+    }
+
+    /// Remove a single thumbnail, returning whether it was cached
+    pub fn remove(&self, key: &str) -> CommandResult<bool> {
+        let _guard = THUMBNAIL_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.read_index()?;
+
+        let position = match index.entries.iter().position(|entry| entry.key == key) {
+            Some(position) => position,
+            None => return Ok(false),
+        };
+
+        let entry = index.entries.remove(position);
+        let thumbnail_path = self.cache_dir.join(&entry.file_name);
+        if thumbnail_path.exists() {
+            std::fs::remove_file(&thumbnail_path)
+                .map_err(|e| CommandError::io_error(format!("Failed to delete thumbnail: {}", e)))?;
+        }
+
+        self.write_index(&index)?;
+        Ok(true)
+    }
+
+    /// Get cache statistics
+    pub fn stats(&self) -> CommandResult<ThumbnailCacheStats> {
+        let _guard = THUMBNAIL_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let index = self.read_index()?;
+        Ok(self.build_stats(&index))
+    }
+
+    /// Evict least-recently-used thumbnails until the cache fits `max_size_bytes`
+    ///
+    /// Image files that are not in the index (left by older cache layouts or
+    /// interrupted hover previews) are removed too. A limit of 0 empties the cache.
+    pub fn purge(&self, max_size_bytes: u64) -> CommandResult<ThumbnailCacheStats> {
+        let _guard = THUMBNAIL_CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.read_index()?;
+        self.evict_to_size(&mut index, max_size_bytes);
+        self.remove_unindexed_images(&index)?;
+        self.write_index(&index)?;
+        Ok(self.build_stats(&index))
+    }
+
+    /// Remove oldest entries and their files until the total fits the limit
+    fn evict_to_size(&self, index: &mut ThumbnailCacheIndex, max_size_bytes: u64) {
+        // RFC 3339 timestamps in UTC sort chronologically as strings
+        index
+            .entries
+            .sort_by(|a, b| a.last_used_at.cmp(&b.last_used_at));
+
+        let mut total_size: u64 = index.entries.iter().map(|entry| entry.size_bytes).sum();
+        while total_size > max_size_bytes && !index.entries.is_empty() {
+            let entry = index.entries.remove(0);
+            let thumbnail_path = self.cache_dir.join(&entry.file_name);
+            if thumbnail_path.exists() {
+                if let Err(e) = std::fs::remove_file(&thumbnail_path) {
+                    eprintln!("Warning: Failed to delete cached thumbnail {}: {}", entry.file_name, e);
+                }
+            }
+            total_size -= entry.size_bytes;
+        }
+    }
+
+    /// Delete image files in the cache directory that the index does not know about
+    fn remove_unindexed_images(&self, index: &ThumbnailCacheIndex) -> CommandResult<()> {
+        if !self.cache_dir.exists() {
+            return Ok(());
+        }
+
+        let entries = std::fs::read_dir(&self.cache_dir).map_err(|e| {
+            CommandError::io_error(format!("Failed to read thumbnails directory: {}", e))
+        })?;
 
-    // Generate a unique filename based on file path hash and timestamp
-    let file_hash = hash_file_path(file_path);
-    let timestamp_str = format!("{:.1}", timestamp);
-    let filename = format!("{}_{}.jpg", file_hash, timestamp_str.replace('.', "_"));
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().to_string();
+
+            // Interrupted hover previews leave "<key>.<ext>.partial" files; keep
+            // the ones a running job is still writing
+            let (image_name, is_partial) = match file_name.strip_suffix(".partial") {
+                Some(name) => (name, true),
+                None => (file_name.as_str(), false),
+            };
+            let is_image = Path::new(image_name)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| THUMBNAIL_IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false);
+            let is_unused = if is_partial {
+                let key = image_name.split('.').next().unwrap_or_default();
+                !crate::ffmpeg::hover_preview::is_hover_preview_running(key)
+            } else {
+                !index.entries.iter().any(|e| e.file_name == file_name)
+            };
+            if path.is_file() && is_image && is_unused {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    eprintln!("Warning: Failed to delete stale thumbnail {}: {}", file_name, e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build statistics from an index
+    fn build_stats(&self, index: &ThumbnailCacheIndex) -> ThumbnailCacheStats {
+        ThumbnailCacheStats {
+            cache_dir: self.cache_dir.to_string_lossy().to_string(),
+            entry_count: index.entries.len(),
+            total_size_bytes: index.entries.iter().map(|entry| entry.size_bytes).sum(),
+            max_size_bytes: self.max_size_bytes,
+        }
+    }
 
-    Ok(thumbnails_dir.join(filename))
+    /// Read the cache index, starting fresh if it is missing or unreadable
+    fn read_index(&self) -> CommandResult<ThumbnailCacheIndex> {
+        let index_path = self.cache_dir.join(THUMBNAIL_CACHE_INDEX_FILE);
+        let empty_index = ThumbnailCacheIndex {
+            version: THUMBNAIL_CACHE_INDEX_VERSION,
+            entries: vec![],
+        };
+
+        if !index_path.exists() {
+            return Ok(empty_index);
+        }
+
+        let index_json = std::fs::read_to_string(&index_path)
+            .map_err(|e| CommandError::file_error(format!("Failed to read thumbnail cache index: {}", e)))?;
+        match serde_json::from_str::<ThumbnailCacheIndex>(&index_json) {
+            Ok(index) if index.version == THUMBNAIL_CACHE_INDEX_VERSION => Ok(index),
+            _ => Ok(empty_index), // Unindexed thumbnails are removed by the next purge
+        }
+    }
+
+    /// Write the cache index
+    fn write_index(&self, index: &ThumbnailCacheIndex) -> CommandResult<()> {
+        std::fs::create_dir_all(&self.cache_dir).map_err(|e| {
+            CommandError::file_error(format!("Failed to create thumbnails directory: {}", e))
+        })?;
+
+        let index_json = serde_json::to_string_pretty(index).map_err(|e| {
+            CommandError::serialization_error(format!("Failed to serialize thumbnail cache index: {}", e))
+        })?;
+
+        // Write next to the index and rename so a crash never leaves a torn file
+        let index_path = self.cache_dir.join(THUMBNAIL_CACHE_INDEX_FILE);
+        let partial_path = self.cache_dir.join(format!("{}.partial", THUMBNAIL_CACHE_INDEX_FILE));
+        std::fs::write(&partial_path, index_json)
+            .map_err(|e| CommandError::file_error(format!("Failed to write thumbnail cache index: {}", e)))?;
+        std::fs::rename(&partial_path, &index_path).map_err(|e| {
+            let _ = std::fs::remove_file(&partial_path);
+            CommandError::file_error(format!("Failed to replace thumbnail cache index: {}", e))
+        })
+    }
 }
 
 // ============================================================================
@@ -271,6 +913,25 @@ pub async fn generate_default_thumbnail(
     generate_thumbnail_internal(app_handle, file_path, 1.0, 320, 180).await
 }
 
+/// Generate a thumbnail from the best frame in the first seconds (320x180)
+///
+/// Falls back to the default 1 second frame when analysis fails or finds
+/// nothing but black frames.
+pub async fn generate_best_frame_thumbnail(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+) -> CommandResult<String> {
+    let timestamp = match find_best_frame_timestamp(app_handle, file_path, DEFAULT_BEST_FRAME_WINDOW).await {
+        Ok(Some(timestamp)) => timestamp,
+        Ok(None) => 1.0,
+        Err(error) => {
+            eprintln!("Warning: Best frame analysis failed: {}", error.message);
+            1.0
+        }
+    };
+    generate_thumbnail_internal(app_handle, file_path, timestamp, 320, 180).await
+}
+
 /// Generate a high-quality thumbnail (1 second, 640x360)
 pub async fn generate_hq_thumbnail(
     app_handle: &tauri::AppHandle,
@@ -305,48 +966,6 @@ pub async fn generate_timeline_thumbnails(
     Ok(thumbnail_paths)
 }
 
-/// Clean up old thumbnails (older than specified days)
-pub async fn cleanup_old_thumbnails(days_old: u64) -> CommandResult<usize> {
-    let thumbnails_dir = get_thumbnails_dir()?;
-    let mut deleted_count = 0;
-
-    if thumbnails_dir.exists() {
-        let cutoff_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            - (days_old * 24 * 60 * 60);
-
-        let entries = std::fs::read_dir(&thumbnails_dir).map_err(|e| {
-            CommandError::io_error(format!("Failed to read thumbnails directory: {}", e))
-        })?;
-
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let metadata = entry.metadata().map_err(|e| {
-                    CommandError::io_error(format!("Failed to get file metadata: {}", e))
-                })?;
-
-                if let Ok(modified_time) = metadata.modified() {
-                    let modified_secs = modified_time
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs();
-
-                    if modified_secs < cutoff_time {
-                        std::fs::remove_file(entry.path()).map_err(|e| {
-                            CommandError::io_error(format!("Failed to delete old thumbnail: {}", e))
-                        })?;
-                        deleted_count += 1;
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(deleted_count)
-}
-
 /// Get thumbnail file size
 pub async fn get_thumbnail_size(thumbnail_path: &str) -> CommandResult<u64> {
     let metadata = std::fs::metadata(thumbnail_path)
@@ -393,6 +1012,9 @@ mod tests {
             timestamp: Some(5.0),
             width: Some(640),
             height: Some(360),
+            best_frame_window: None,
+            format: Some(ThumbnailFormat::Webp),
+            fit: Some(ThumbnailFit::Pad),
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -402,6 +1024,17 @@ mod tests {
         assert_eq!(request.timestamp, deserialized.timestamp);
         assert_eq!(request.width, deserialized.width);
         assert_eq!(request.height, deserialized.height);
+        assert_eq!(deserialized.format, Some(ThumbnailFormat::Webp));
+        assert_eq!(deserialized.fit, Some(ThumbnailFit::Pad));
+        assert!(json.contains("\"format\":\"webp\""));
+
+        // Older frontends omit the new fields
+        let legacy: GenerateThumbnailRequest = serde_json::from_str(
+            r#"{"file_path":"/v.mp4","timestamp":null,"width":null,"height":null}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.format, None);
+        assert_eq!(legacy.fit, None);
     }
 
     #[test]
@@ -409,6 +1042,8 @@ mod tests {
         let response = GenerateThumbnailResponse {
             success: true,
             thumbnail_path: Some("/path/to/thumbnail.jpg".to_string()),
+            width: Some(101),
+            height: Some(180),
             error_message: None,
         };
 
@@ -417,6 +1052,8 @@ mod tests {
 
         assert_eq!(response.success, deserialized.success);
         assert_eq!(response.thumbnail_path, deserialized.thumbnail_path);
+        assert_eq!(response.width, deserialized.width);
+        assert_eq!(response.height, deserialized.height);
         assert_eq!(response.error_message, deserialized.error_message);
     }
 
@@ -427,6 +1064,8 @@ mod tests {
             timestamps: vec![1.0, 5.0, 10.0],
             width: Some(320),
             height: Some(180),
+            format: None,
+            fit: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -457,26 +1096,205 @@ mod tests {
         assert_eq!(response.error_message, deserialized.error_message);
     }
 
+    fn identity(path: &str, size_bytes: u64, modified_secs: u64) -> SourceIdentity {
+        SourceIdentity {
+            path: path.to_string(),
+            size_bytes,
+            modified_secs,
+        }
+    }
+
+    fn add_thumbnail(cache: &ThumbnailCache, key: &str, size: usize) -> PathBuf {
+        std::fs::create_dir_all(&cache.cache_dir).unwrap();
+        let thumbnail_path = cache.get_thumbnail_path(key, "jpg");
+        std::fs::write(&thumbnail_path, vec![0u8; size]).unwrap();
+        cache.insert(key, &thumbnail_path, "/path/to/video.mp4", 1.0, (320, 180), Some((101, 180))).unwrap();
+        thumbnail_path
+    }
+
+    #[test]
+    fn test_build_thumbnail_cache_key() {
+        let source = identity("/path/to/video.mp4", 1000, 42);
+        let jpeg = ThumbnailFormat::Jpeg;
+        let fit = ThumbnailFit::Fit;
+        let key = build_thumbnail_cache_key(&source, 5.0, 320, 180, jpeg, fit);
+
+        // Stable for the same inputs, ignoring sub-millisecond noise
+        assert_eq!(key, build_thumbnail_cache_key(&source, 5.0000001, 320, 180, jpeg, fit));
+        assert_eq!(key.len(), 16);
+
+        // A re-recorded file at the same path, or different output settings, misses
+        assert_ne!(key, build_thumbnail_cache_key(&identity("/path/to/video.mp4", 2000, 42), 5.0, 320, 180, jpeg, fit));
+        assert_ne!(key, build_thumbnail_cache_key(&identity("/path/to/video.mp4", 1000, 43), 5.0, 320, 180, jpeg, fit));
+        assert_ne!(key, build_thumbnail_cache_key(&source, 5.0, 640, 360, jpeg, fit));
+        assert_ne!(key, build_thumbnail_cache_key(&source, 5.0, 320, 180, ThumbnailFormat::Png, fit));
+        assert_ne!(key, build_thumbnail_cache_key(&source, 5.0, 320, 180, jpeg, ThumbnailFit::Pad));
+    }
+
+    #[test]
+    fn test_build_thumbnail_scale_filter() {
+        assert_eq!(
+            build_thumbnail_scale_filter(320, 180, ThumbnailFit::Fit),
+            "scale=320:180:force_original_aspect_ratio=decrease"
+        );
+        assert_eq!(
+            build_thumbnail_scale_filter(320, 180, ThumbnailFit::Fill),
+            "scale=320:180:force_original_aspect_ratio=increase,crop=320:180"
+        );
+        assert_eq!(
+            build_thumbnail_scale_filter(320, 180, ThumbnailFit::Pad),
+            "scale=320:180:force_original_aspect_ratio=decrease,pad=320:180:(ow-iw)/2:(oh-ih)/2"
+        );
+    }
+
+    #[test]
+    fn test_build_thumbnail_args() {
+        let args = build_thumbnail_args("in.mov", "out.webp", 2.5, 320, 180, ThumbnailFormat::Webp, ThumbnailFit::Fit);
+        assert!(args.windows(2).any(|w| w[0] == "-ss" && w[1] == "2.5"));
+        assert!(args.windows(2).any(|w| w[0] == "-c:v" && w[1] == "libwebp"));
+        assert_eq!(args.last().unwrap(), "out.webp");
+
+        let args = build_thumbnail_args("in.mov", "out.jpg", 1.0, 320, 180, ThumbnailFormat::Jpeg, ThumbnailFit::Fit);
+        assert!(args.windows(2).any(|w| w[0] == "-q:v" && w[1] == "2"));
+    }
+
+    #[test]
+    fn test_get_thumbnail_output_size() {
+        let stderr = "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'phone.mov':\n\
+                      \x20 Stream #0:0[0x1](und): Video: hevc (Main) (hvc1 / 0x31637668), yuv420p(tv), 1080x1920, 8000 kb/s\n\
+                      Output #0, image2, to 'out.jpg':\n\
+                      \x20 Stream #0:0(und): Video: mjpeg, yuvj420p(pc, progressive), 101x180 [SAR 1:1 DAR 101:180], q=2-31\n";
+
+        // Vertical video fitted into a 16:9 box keeps its aspect ratio
+        assert_eq!(get_thumbnail_output_size(stderr, 320, 180, ThumbnailFit::Fit), Some((101, 180)));
+        assert_eq!(get_thumbnail_output_size(stderr, 320, 180, ThumbnailFit::Pad), Some((320, 180)));
+        assert_eq!(get_thumbnail_output_size("", 320, 180, ThumbnailFit::Fit), None);
+    }
+
+    fn candidate(timestamp: f64, brightness: f64, entropy: f64) -> FrameCandidate {
+        FrameCandidate {
+            timestamp,
+            brightness,
+            entropy,
+        }
+    }
+
+    #[test]
+    fn test_parse_frame_candidates() {
+        let stderr = "[Parsed_metadata_4 @ 0x600] frame:0    pts:0       pts_time:0\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.signalstats.YMIN=16\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.signalstats.YAVG=16.2\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.entropy.entropy.normal.Y=0.4\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.entropy.normalized_entropy.normal.Y=0.05\n\
+                      frame=    1 fps=0.0 q=-0.0 size=N/A time=00:00:00.50 bitrate=N/A\n\
+                      [Parsed_metadata_4 @ 0x600] frame:1    pts:1       pts_time:0.5\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.signalstats.YAVG=112.7\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.entropy.normalized_entropy.normal.Y=0.83\n";
+
+        assert_eq!(
+            parse_frame_candidates(stderr),
+            vec![candidate(0.0, 16.2, 0.05), candidate(0.5, 112.7, 0.83)]
+        );
+    }
+
+    #[test]
+    fn test_select_best_frame() {
+        let candidates = vec![
+            candidate(0.0, 16.0, 0.9),  // Black, even if noisy
+            candidate(0.5, 120.0, 0.4), // Flat desktop
+            candidate(1.0, 250.0, 0.9), // Blank white
+            candidate(1.5, 110.0, 0.8),
+            candidate(2.0, 100.0, 0.8),
+        ];
+        assert_eq!(select_best_frame(&candidates).unwrap().timestamp, 1.5);
+
+        // Nothing usable
+        assert!(select_best_frame(&[candidate(0.0, 16.0, 0.9)]).is_none());
+        assert!(select_best_frame(&[]).is_none());
+    }
+
+    #[test]
+    fn test_build_best_frame_analysis_args() {
+        let args = build_best_frame_analysis_args("in.mov", 10.0);
+        assert!(args.windows(2).any(|w| w[0] == "-t" && w[1] == "10"));
+        assert!(args.contains(&"fps=2,scale=160:-2,signalstats,entropy,metadata=print".to_string()));
+    }
+
+    #[test]
+    fn test_find_best_frame_timestamp_with_runner() {
+        let stderr = "[Parsed_metadata_4 @ 0x600] frame:0    pts:0       pts_time:0\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.signalstats.YAVG=16.2\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.entropy.normalized_entropy.normal.Y=0.05\n\
+                      [Parsed_metadata_4 @ 0x600] frame:1    pts:1       pts_time:0.5\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.signalstats.YAVG=112.7\n\
+                      [Parsed_metadata_4 @ 0x600] lavfi.entropy.normalized_entropy.normal.Y=0.83\n";
+        let runner = crate::ffmpeg::runner::MockFfmpegRunner::new()
+            .with_output(0, "", stderr)
+            .with_output(1, "", "/missing.mov: No such file or directory");
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let timestamp = runtime
+            .block_on(find_best_frame_timestamp_with_runner(&runner, "/missing.mov", 10.0))
+            .unwrap();
+        assert_eq!(timestamp, Some(0.5));
+
+        let error = runtime
+            .block_on(find_best_frame_timestamp_with_runner(&runner, "/missing.mov", 10.0))
+            .unwrap_err();
+        assert!(error.message.contains("No such file"));
+    }
+
+    #[test]
+    fn test_thumbnail_cache_evicts_least_recently_used() {
+        let temp_dir = tempdir().unwrap();
+        let cache = ThumbnailCache::new(temp_dir.path().to_path_buf(), 250);
+
+        let first = add_thumbnail(&cache, "first", 100);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = add_thumbnail(&cache, "second", 100);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        // Touch the first entry so the second becomes least recently used
+        let entry = cache.lookup("first").unwrap().unwrap();
+        assert_eq!(cache.get_entry_path(&entry), first);
+        assert_eq!((entry.output_width, entry.output_height), (Some(101), Some(180)));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        add_thumbnail(&cache, "third", 100);
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.entry_count, 2);
+        assert_eq!(stats.total_size_bytes, 200);
+        assert!(first.exists());
+        assert!(!second.exists());
+        assert!(cache.lookup("second").unwrap().is_none());
+    }
+
     #[test]
-    fn test_get_thumbnail_path() {
-        // Mock the get_thumbnails_dir function for testing
+    fn test_thumbnail_cache_purge_and_remove() {
         let temp_dir = tempdir().unwrap();
-        let thumbnails_dir = temp_dir.path();
-
-        // Test thumbnail path generation
-        let file_path = "/path/to/video.mp4";
-        let timestamp = 5.0;
-
-        // Generate expected path
-        let file_hash = crate::commands::hash_file_path(file_path);
-        let timestamp_str = format!("{:.1}", timestamp).replace('.', "_");
-        let expected_filename = format!("{}_{}.jpg", file_hash, timestamp_str);
-        let expected_path = thumbnails_dir.join(expected_filename);
-
-        // This test verifies the path generation logic
-        assert!(expected_path.to_string_lossy().contains(&file_hash));
-        assert!(expected_path.to_string_lossy().contains("5_0"));
-        assert!(expected_path.to_string_lossy().ends_with(".jpg"));
+        let cache = ThumbnailCache::new(temp_dir.path().to_path_buf(), 1024 * 1024);
+        add_thumbnail(&cache, "a", 100);
+        let kept = add_thumbnail(&cache, "b", 100);
+
+        // Thumbnails from the old path+timestamp layout are not indexed
+        let legacy = temp_dir.path().join("1a2b3c_1_0.jpg");
+        std::fs::write(&legacy, b"old").unwrap();
+        let waveform = temp_dir.path().join("1a2b3c.waveform");
+        std::fs::write(&waveform, b"peaks").unwrap();
+        let interrupted = temp_dir.path().join("4d5e6f.webp.partial");
+        std::fs::write(&interrupted, b"torn").unwrap();
+
+        let stats = cache.purge(150).unwrap();
+        assert_eq!(stats.entry_count, 1);
+        assert!(kept.exists());
+        assert!(!legacy.exists());
+        assert!(waveform.exists());
+        assert!(!interrupted.exists());
+
+        assert!(cache.remove("b").unwrap());
+        assert!(!cache.remove("b").unwrap());
+        assert!(!kept.exists());
+        assert_eq!(cache.stats().unwrap().entry_count, 0);
     }
 
     #[test]
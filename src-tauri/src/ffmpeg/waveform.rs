@@ -0,0 +1,414 @@
+// ============================================================================
+// FFMPEG WAVEFORM MODULE
+// ============================================================================
+// This module generates audio waveform peaks for the timeline. Audio is
+// decoded to mono PCM with FFmpeg, reduced to min/max peak buckets at several
+// zoom levels, and cached as a compact binary file in the thumbnails
+// directory so the timeline can request any range without decoding again.
+
+use crate::commands::{get_thumbnails_dir, hash_file_path, CommandError, CommandResult};
+use crate::ffmpeg::runner::{FfmpegInvocation, FfmpegRunner, ProcessRunner};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// Request for waveform peaks in a time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetWaveformPeaksRequest {
+    pub file_path: String,
+    pub start_time: f64, // Range start in source seconds
+    pub end_time: f64,   // Range end in source seconds
+    #[serde(default)]
+    pub peak_count: Option<u32>, // Peaks wanted for the range (usually its width in pixels)
+}
+
+/// Waveform peaks for a time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaveformPeaksResponse {
+    pub success: bool,
+    pub peaks_per_second: f64, // Resolution of the zoom level used
+    pub start_time: f64,       // Time of the first peak
+    pub min_peaks: Vec<i8>,    // Minimum of each bucket (-128 to 127)
+    pub max_peaks: Vec<i8>,    // Maximum of each bucket (-128 to 127)
+    pub error_message: Option<String>,
+}
+
+/// Peaks at one zoom level
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveformLevel {
+    pub samples_per_peak: u32,
+    pub peaks: Vec<(i8, i8)>, // (min, max) per bucket
+}
+
+/// All zoom levels for a source
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveformData {
+    pub sample_rate: u32,
+    pub levels: Vec<WaveformLevel>, // Finest level first
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Sample rate audio is decoded at for peak analysis
+const WAVEFORM_SAMPLE_RATE: u32 = 8000;
+
+/// Samples per peak at the finest zoom level (250 peaks per second)
+const WAVEFORM_BASE_SAMPLES_PER_PEAK: u32 = 32;
+
+/// Number of zoom levels; each is 4x coarser than the previous one
+const WAVEFORM_LEVEL_COUNT: usize = 4;
+
+/// Factor between zoom levels
+const WAVEFORM_LEVEL_FACTOR: u32 = 4;
+
+/// Default number of peaks returned for a range
+pub const DEFAULT_PEAK_COUNT: u32 = 1000;
+
+/// Cache file magic and version
+const WAVEFORM_MAGIC: &[u8; 4] = b"CFWF";
+const WAVEFORM_FORMAT_VERSION: u8 = 1;
+
+// ============================================================================
+// INTERNAL FUNCTIONS
+// ============================================================================
+
+/// Get peaks for a time range, generating the waveform cache if needed
+pub async fn get_waveform_peaks_internal(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+    start_time: f64,
+    end_time: f64,
+    peak_count: u32,
+) -> CommandResult<WaveformPeaksResponse> {
+    if start_time < 0.0 || end_time <= start_time {
+        return Err(CommandError::validation_error(format!(
+            "Invalid waveform range: {} to {}",
+            start_time, end_time
+        )));
+    }
+
+    let data = load_or_generate_waveform(app_handle, file_path).await?;
+    let level = select_waveform_level(&data, end_time - start_time, peak_count.max(1));
+    let (first_peak, peaks) = slice_waveform_level(level, data.sample_rate, start_time, end_time);
+    let peaks_per_second = data.sample_rate as f64 / level.samples_per_peak as f64;
+
+    Ok(WaveformPeaksResponse {
+        success: true,
+        peaks_per_second,
+        start_time: first_peak as f64 / peaks_per_second,
+        min_peaks: peaks.iter().map(|peak| peak.0).collect(),
+        max_peaks: peaks.iter().map(|peak| peak.1).collect(),
+        error_message: None,
+    })
+}
+
+/// Load the cached waveform for a source, generating it if missing or stale
+pub async fn load_or_generate_waveform(
+    app_handle: &tauri::AppHandle,
+    file_path: &str,
+) -> CommandResult<WaveformData> {
+    let cache_path = get_waveform_cache_path(file_path)?;
+
+    if is_waveform_cache_fresh(&cache_path, Path::new(file_path)) {
+        let bytes = std::fs::read(&cache_path)
+            .map_err(|e| CommandError::file_error(format!("Failed to read waveform cache: {}", e)))?;
+        match decode_waveform(&bytes) {
+            Ok(data) => return Ok(data),
+            Err(error) => eprintln!("Warning: Regenerating unreadable waveform cache: {}", error.message),
+        }
+    }
+
+    let samples = decode_audio_samples(app_handle, file_path).await?;
+    let data = build_waveform_data(&samples, WAVEFORM_SAMPLE_RATE);
+
+    // Write a partial file and rename so readers never see a torn cache
+    let partial_path = cache_path.with_extension("waveform.partial");
+    std::fs::write(&partial_path, encode_waveform(&data))
+        .map_err(|e| CommandError::file_error(format!("Failed to write waveform cache: {}", e)))?;
+    std::fs::rename(&partial_path, &cache_path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial_path);
+        CommandError::file_error(format!("Failed to move waveform cache into place: {}", e))
+    })?;
+
+    Ok(data)
+}
+
+/// Decode a source's audio to mono 16-bit PCM samples
+async fn decode_audio_samples(app_handle: &tauri::AppHandle, file_path: &str) -> CommandResult<Vec<i16>> {
+    let args = build_waveform_decode_args(file_path);
+
+    // Execute FFmpeg; PCM is written to stdout
+    let output = ProcessRunner::new(app_handle)
+        .run(FfmpegInvocation::ffmpeg(&args))
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("does not contain any stream") || stderr.contains("matches no streams") {
+            return Err(CommandError::validation_error(
+                "Source has no audio stream".to_string(),
+            ));
+        }
+        return Err(CommandError::ffmpeg_error(format!(
+            "FFmpeg audio decode failed: {}",
+            stderr
+        )));
+    }
+
+    Ok(pcm_bytes_to_samples(&output.stdout))
+}
+
+/// Build FFmpeg arguments to decode audio to raw mono PCM on stdout
+pub fn build_waveform_decode_args(file_path: &str) -> Vec<String> {
+    vec![
+        "-v".to_string(),
+        "error".to_string(),
+        "-i".to_string(),
+        file_path.to_string(),
+        "-map".to_string(),
+        "0:a:0".to_string(),
+        "-ac".to_string(),
+        "1".to_string(),
+        "-ar".to_string(),
+        WAVEFORM_SAMPLE_RATE.to_string(),
+        "-f".to_string(),
+        "s16le".to_string(),
+        "-".to_string(),
+    ]
+}
+
+/// Get the waveform cache path for a source
+pub fn get_waveform_cache_path(file_path: &str) -> CommandResult<PathBuf> {
+    let thumbnails_dir = get_thumbnails_dir()?;
+    Ok(thumbnails_dir.join(format!("{}.waveform", hash_file_path(file_path))))
+}
+
+/// Whether a cache file exists and is newer than its source
+fn is_waveform_cache_fresh(cache_path: &Path, source_path: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    match (modified(cache_path), modified(source_path)) {
+        (Some(cache_time), Some(source_time)) => cache_time >= source_time,
+        _ => false,
+    }
+}
+
+// ============================================================================
+// PEAK FUNCTIONS
+// ============================================================================
+
+/// Convert little-endian 16-bit PCM bytes to samples
+pub fn pcm_bytes_to_samples(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+/// Reduce samples to (min, max) buckets scaled to 8 bits
+pub fn compute_peaks(samples: &[i16], samples_per_peak: u32) -> Vec<(i8, i8)> {
+    samples
+        .chunks(samples_per_peak.max(1) as usize)
+        .map(|chunk| {
+            let min = chunk.iter().copied().min().unwrap_or(0);
+            let max = chunk.iter().copied().max().unwrap_or(0);
+            ((min >> 8) as i8, (max >> 8) as i8)
+        })
+        .collect()
+}
+
+/// Merge every `factor` buckets into one
+pub fn downsample_peaks(peaks: &[(i8, i8)], factor: u32) -> Vec<(i8, i8)> {
+    peaks
+        .chunks(factor.max(1) as usize)
+        .map(|chunk| {
+            let min = chunk.iter().map(|peak| peak.0).min().unwrap_or(0);
+            let max = chunk.iter().map(|peak| peak.1).max().unwrap_or(0);
+            (min, max)
+        })
+        .collect()
+}
+
+/// Build every zoom level from decoded samples
+pub fn build_waveform_data(samples: &[i16], sample_rate: u32) -> WaveformData {
+    let mut levels = vec![WaveformLevel {
+        samples_per_peak: WAVEFORM_BASE_SAMPLES_PER_PEAK,
+        peaks: compute_peaks(samples, WAVEFORM_BASE_SAMPLES_PER_PEAK),
+    }];
+
+    // Coarser levels are derived from the previous level rather than the samples
+    while levels.len() < WAVEFORM_LEVEL_COUNT {
+        let previous = &levels[levels.len() - 1];
+        levels.push(WaveformLevel {
+            samples_per_peak: previous.samples_per_peak * WAVEFORM_LEVEL_FACTOR,
+            peaks: downsample_peaks(&previous.peaks, WAVEFORM_LEVEL_FACTOR),
+        });
+    }
+
+    WaveformData { sample_rate, levels }
+}
+
+/// Pick the coarsest level that still has `peak_count` peaks over the range
+pub fn select_waveform_level(data: &WaveformData, range_duration: f64, peak_count: u32) -> &WaveformLevel {
+    let wanted_peaks_per_second = peak_count as f64 / range_duration;
+
+    data.levels
+        .iter()
+        .rev()
+        .find(|level| data.sample_rate as f64 / level.samples_per_peak as f64 >= wanted_peaks_per_second)
+        .unwrap_or(&data.levels[0])
+}
+
+/// Get the peaks of a level covering a time range, with the index of the first one
+pub fn slice_waveform_level(
+    level: &WaveformLevel,
+    sample_rate: u32,
+    start_time: f64,
+    end_time: f64,
+) -> (usize, &[(i8, i8)]) {
+    let peaks_per_second = sample_rate as f64 / level.samples_per_peak as f64;
+    let first = ((start_time * peaks_per_second).floor() as usize).min(level.peaks.len());
+    let last = ((end_time * peaks_per_second).ceil() as usize).clamp(first, level.peaks.len());
+
+    (first, &level.peaks[first..last])
+}
+
+// ============================================================================
+// CACHE FILE FUNCTIONS
+// ============================================================================
+
+/// Encode waveform data in the cache file format
+///
+/// Layout (little endian): magic "CFWF", version u8, level count u8, sample
+/// rate u32, then per level samples_per_peak u32 and peak count u32 followed by
+/// (min, max) byte pairs.
+pub fn encode_waveform(data: &WaveformData) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(WAVEFORM_MAGIC);
+    bytes.push(WAVEFORM_FORMAT_VERSION);
+    bytes.push(data.levels.len() as u8);
+    bytes.extend_from_slice(&data.sample_rate.to_le_bytes());
+
+    for level in &data.levels {
+        bytes.extend_from_slice(&level.samples_per_peak.to_le_bytes());
+        bytes.extend_from_slice(&(level.peaks.len() as u32).to_le_bytes());
+        for (min, max) in &level.peaks {
+            bytes.push(*min as u8);
+            bytes.push(*max as u8);
+        }
+    }
+
+    bytes
+}
+
+/// Decode waveform data from the cache file format
+pub fn decode_waveform(bytes: &[u8]) -> CommandResult<WaveformData> {
+    let invalid = |reason: &str| CommandError::serialization_error(format!("Invalid waveform cache: {}", reason));
+    let read_u32 = |offset: usize| -> CommandResult<u32> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|slice| u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]]))
+            .ok_or_else(|| invalid("truncated header"))
+    };
+
+    if bytes.len() < 10 || &bytes[0..4] != WAVEFORM_MAGIC {
+        return Err(invalid("bad magic"));
+    }
+    if bytes[4] != WAVEFORM_FORMAT_VERSION {
+        return Err(invalid("unsupported version"));
+    }
+
+    let level_count = bytes[5] as usize;
+    let sample_rate = read_u32(6)?;
+    let mut offset = 10;
+    let mut levels = Vec::with_capacity(level_count);
+
+    for _ in 0..level_count {
+        let samples_per_peak = read_u32(offset)?;
+        let peak_count = read_u32(offset + 4)? as usize;
+        offset += 8;
+
+        let peak_bytes = bytes
+            .get(offset..offset + peak_count * 2)
+            .ok_or_else(|| invalid("truncated peaks"))?;
+        let peaks = peak_bytes
+            .chunks_exact(2)
+            .map(|pair| (pair[0] as i8, pair[1] as i8))
+            .collect();
+        offset += peak_count * 2;
+
+        levels.push(WaveformLevel {
+            samples_per_peak,
+            peaks,
+        });
+    }
+
+    if levels.is_empty() {
+        return Err(invalid("no levels"));
+    }
+
+    Ok(WaveformData { sample_rate, levels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_and_downsample_peaks() {
+        let samples: Vec<i16> = vec![0, 1024, -2048, 512, 32767, -32768, 256, -256, 100];
+        let peaks = compute_peaks(&samples, 4);
+
+        assert_eq!(peaks, vec![(-8, 4), (-128, 127), (0, 0)]);
+        assert_eq!(downsample_peaks(&peaks, 2), vec![(-128, 127), (0, 0)]);
+    }
+
+    #[test]
+    fn test_pcm_bytes_to_samples() {
+        assert_eq!(pcm_bytes_to_samples(&[0x00, 0x80, 0xff, 0x7f, 0x01]), vec![-32768, 32767]);
+    }
+
+    #[test]
+    fn test_build_waveform_data_levels() {
+        let samples = vec![1000i16; 8000 * 2]; // Two seconds
+        let data = build_waveform_data(&samples, 8000);
+
+        let peak_counts: Vec<usize> = data.levels.iter().map(|level| level.peaks.len()).collect();
+        assert_eq!(peak_counts, vec![500, 125, 32, 8]);
+        assert_eq!(data.levels[3].samples_per_peak, 2048);
+    }
+
+    #[test]
+    fn test_waveform_round_trip() {
+        let data = build_waveform_data(&[0, -5000, 12000, 300, -32768, 32767], 8000);
+        let bytes = encode_waveform(&data);
+
+        assert_eq!(decode_waveform(&bytes).unwrap(), data);
+        assert!(decode_waveform(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_waveform(b"nope").is_err());
+    }
+
+    #[test]
+    fn test_select_and_slice_level() {
+        let data = build_waveform_data(&vec![0i16; 8000 * 60], 8000);
+
+        // 1000 peaks over 60s needs ~17 per second: level 2 (15.6/s) is too coarse
+        let level = select_waveform_level(&data, 60.0, 1000);
+        assert_eq!(level.samples_per_peak, 128);
+
+        // Zoomed far in falls back to the finest level
+        assert_eq!(select_waveform_level(&data, 1.0, 1000).samples_per_peak, 32);
+
+        let (first, peaks) = slice_waveform_level(level, 8000, 10.0, 20.0);
+        assert_eq!(first, 625);
+        assert_eq!(peaks.len(), 625);
+
+        // Ranges past the end are clamped
+        let (_, peaks) = slice_waveform_level(level, 8000, 59.0, 90.0);
+        assert_eq!(peaks.len(), 63);
+    }
+}
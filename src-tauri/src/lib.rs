@@ -15,15 +15,18 @@ use commands::file_ops::{
 };
 
 use commands::metadata::{
-    batch_import_videos, check_ffmpeg_availability, check_thumbnail_exists, delete_video_thumbnail,
+    batch_import_videos, check_ffmpeg_availability, get_ffmpeg_capabilities, check_thumbnail_exists, delete_video_thumbnail,
     generate_video_thumbnail, generate_video_thumbnails, get_ffmpeg_version, get_video_metadata,
-    import_video_with_metadata,
+    import_video_with_metadata, repair_media_file, scan_media_integrity, detect_scenes,
+    split_clip_at_scenes, detect_silence, remove_silence, get_waveform_peaks,
+    generate_scrub_strip, get_thumbnail_cache_info, purge_thumbnail_cache, generate_hover_preview,
 };
 
 use commands::export::{
     cleanup_export_files, estimate_export_info, export_timeline, export_timeline_with_progress,
-    get_export_file_path, validate_export_path, validate_export_settings_command,
-    cancel_export, get_export_status,
+    export_timeline_streaming_package, get_export_file_path, validate_export_path,
+    validate_export_settings_command, cancel_export, get_export_status, get_render_cache_info,
+    purge_render_cache,
 };
 
 use commands::project::{
@@ -31,8 +34,11 @@ use commands::project::{
     open_project,
 };
 
+use commands::timeline::{export_project, load_project_timeline, save_project_timeline};
+
 use commands::project_import::{
-    delete_project_asset, get_project_asset_path, import_video_to_project, list_project_assets,
+    delete_project_asset, get_preview_source, get_project_asset_path, import_video_to_project,
+    list_project_assets,
 };
 
 use commands::recording::{
@@ -75,17 +81,36 @@ pub fn run() {
             generate_video_thumbnails,
             check_thumbnail_exists,
             delete_video_thumbnail,
+            generate_scrub_strip,
+            generate_hover_preview,
+            get_thumbnail_cache_info,
+            purge_thumbnail_cache,
             batch_import_videos,
             get_ffmpeg_version,
             check_ffmpeg_availability,
+            get_ffmpeg_capabilities,
+            // Media integrity operations
+            scan_media_integrity,
+            repair_media_file,
+            // Scene detection operations
+            detect_scenes,
+            split_clip_at_scenes,
+            // Silence detection operations
+            detect_silence,
+            remove_silence,
+            // Waveform operations
+            get_waveform_peaks,
             // Export operations
             export_timeline,
             export_timeline_with_progress,
+            export_timeline_streaming_package,
             estimate_export_info,
             validate_export_settings_command,
             get_export_file_path,
             validate_export_path,
             cleanup_export_files,
+            get_render_cache_info,
+            purge_render_cache,
             cancel_export,
             get_export_status,
             // Project operations
@@ -95,11 +120,16 @@ pub fn run() {
             list_projects,
             load_project_settings,
             load_project_metadata,
+            // Project timeline operations
+            save_project_timeline,
+            load_project_timeline,
+            export_project,
             // Project import operations
             import_video_to_project,
             get_project_asset_path,
             list_project_assets,
             delete_project_asset,
+            get_preview_source,
             // Recording operations
             get_available_screens,
             get_available_cameras,
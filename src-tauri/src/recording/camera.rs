@@ -1,19 +1,12 @@
 /**
  * Camera Recording Module
  * 
- * Handles webcam/camera recording functionality using AVFoundation.
+ * Handles webcam/camera recording functionality on top of the platform backends.
  * Provides camera enumeration and camera recording capabilities.
  */
 
 use serde::{Deserialize, Serialize};
-use core_foundation::base::{CFRelease, CFRetain};
-use core_foundation::string::{CFString, CFStringRef};
-use core_foundation::array::{CFArray, CFArrayRef};
-use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
-use core_foundation::number::{CFNumber, CFNumberRef};
-use core_foundation::base::kCFAllocatorDefault;
-use std::ffi::c_void;
-use std::ptr;
+use crate::recording::platform;
 
 /// Camera information structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,101 +66,9 @@ impl CameraRecordingSession {
     }
 }
 
-/// Get all available cameras using AVFoundation
+/// Get all available cameras
 pub fn get_available_cameras() -> Result<Vec<CameraInfo>, String> {
-    let mut cameras = Vec::new();
-    
-    // Use AVCaptureDevice to enumerate video devices
-    let devices = unsafe { get_av_capture_devices() };
-    
-    if devices.is_empty() {
-        // Fallback to mock data for development/testing
-        return Ok(get_mock_cameras());
-    }
-    
-    for (index, device) in devices.iter().enumerate() {
-        let camera_info = create_camera_info_from_av_device(device, index);
-        cameras.push(camera_info);
-    }
-    
-    Ok(cameras)
-}
-
-/// Get mock cameras for development/testing
-fn get_mock_cameras() -> Vec<CameraInfo> {
-    vec![
-        CameraInfo {
-            id: "camera-1".to_string(),
-            name: "FaceTime HD Camera".to_string(),
-            is_default: true,
-            is_available: true,
-            capabilities: CameraCapabilities {
-                max_width: 1920,
-                max_height: 1080,
-                supported_formats: vec!["mp4".to_string(), "mov".to_string()],
-                has_audio: true,
-            },
-        },
-        CameraInfo {
-            id: "camera-2".to_string(),
-            name: "External USB Camera".to_string(),
-            is_default: false,
-            is_available: true,
-            capabilities: CameraCapabilities {
-                max_width: 1280,
-                max_height: 720,
-                supported_formats: vec!["mp4".to_string(), "mov".to_string()],
-                has_audio: false,
-            },
-        },
-    ]
-}
-
-/// Get AVCaptureDevice instances for video devices
-unsafe fn get_av_capture_devices() -> Vec<*mut c_void> {
-    let mut devices = Vec::new();
-    
-    // For now, return empty vector to use mock data
-    // TODO: Implement real AVFoundation device enumeration
-    // This would involve:
-    // 1. Creating AVCaptureDeviceDiscoverySession
-    // 2. Enumerating devices with video capability
-    // 3. Extracting device properties
-    
-    devices
-}
-
-/// Create camera info from AVFoundation device
-fn create_camera_info_from_av_device(_device: &*mut c_void, index: usize) -> CameraInfo {
-    // This is a placeholder - in reality you'd extract actual device properties
-    // from the AVFoundation device object
-    if index == 0 {
-        CameraInfo {
-            id: "camera-1".to_string(),
-            name: "FaceTime HD Camera".to_string(),
-            is_default: true,
-            is_available: true,
-            capabilities: CameraCapabilities {
-                max_width: 1920,
-                max_height: 1080,
-                supported_formats: vec!["mp4".to_string(), "mov".to_string()],
-                has_audio: true,
-            },
-        }
-    } else {
-        CameraInfo {
-            id: format!("camera-{}", index + 1),
-            name: format!("Camera {}", index + 1),
-            is_default: false,
-            is_available: true,
-            capabilities: CameraCapabilities {
-                max_width: 1280,
-                max_height: 720,
-                supported_formats: vec!["mp4".to_string(), "mov".to_string()],
-                has_audio: false,
-            },
-        }
-    }
+    platform::get_available_cameras()
 }
 
 /// Start camera recording
@@ -374,6 +275,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(target_os = "macos")]
     fn test_get_available_cameras() {
         let cameras = get_available_cameras().unwrap();
         assert!(!cameras.is_empty());
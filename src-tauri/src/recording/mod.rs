@@ -2,7 +2,8 @@
  * Recording Module
  * 
  * This module handles all recording functionality including screen recording,
- * webcam recording, and Picture-in-Picture recording using AVFoundation.
+ * webcam recording, and Picture-in-Picture recording. Capture devices come from
+ * the platform backends: AVFoundation on macOS, x11grab/v4l2/PulseAudio on Linux.
  */
 
 pub mod screen;
@@ -10,6 +11,7 @@ pub mod camera;
 pub mod pip;
 pub mod session;
 pub mod permissions;
+pub mod platform;
 pub mod process_manager;
 
 // Re-export the main functionality
@@ -2,10 +2,11 @@
  * Picture-in-Picture Recording Module
  * 
  * Handles simultaneous screen and camera recording with Picture-in-Picture overlay.
- * Combines screen capture and camera recording using AVFoundation.
+ * Combines screen capture and camera recording through the platform backends.
  */
 
 use serde::{Deserialize, Serialize};
+use crate::recording::platform;
 use crate::recording::screen::CaptureArea;
 
 /// PiP recording settings
@@ -97,7 +98,7 @@ fn start_pip_recording_with_id(
     let output_path = output_dir.join(&filename);
     
     // Start FFmpeg PiP recording
-    start_avfoundation_pip_recording(&session_id, &settings, &output_path, ffmpeg_path)?;
+    start_ffmpeg_pip_recording(&session_id, &settings, &output_path, ffmpeg_path)?;
     
     Ok(output_path.to_string_lossy().to_string())
 }
@@ -121,8 +122,8 @@ fn get_project_assets_directory(project_id: &str) -> Result<std::path::PathBuf,
     Ok(assets_dir)
 }
 
-/// Start AVFoundation PiP recording using FFmpeg with overlay filter
-fn start_avfoundation_pip_recording(
+/// Start PiP recording using FFmpeg with overlay filter
+fn start_ffmpeg_pip_recording(
     session_id: &str,
     settings: &PiPRecordingSettings,
     output_path: &std::path::PathBuf,
@@ -130,42 +131,28 @@ fn start_avfoundation_pip_recording(
 ) -> Result<(), String> {
     use crate::recording::process_manager::PROCESS_MANAGER;
     
-    // Parse display ID from screen_id
-    let display_id = settings.screen_id
-        .strip_prefix("screen-")
-        .ok_or("Invalid screen ID format")?
-        .parse::<u32>()
-        .map_err(|_| "Invalid screen ID")?;
-    
-    let screen_index = if display_id > 0 { display_id - 1 } else { 0 };
+    // Screen input (video only, no audio from screen)
+    let mut inputs = platform::screen_capture_inputs(
+        &settings.screen_id,
+        settings.frame_rate,
+        settings.capture_area.as_ref(),
+        false,
+    )?;
     
-    // Parse camera device ID
-    let camera_device_id = settings.camera_id.parse::<u32>().unwrap_or(0);
+    // Camera input, followed by the microphone when audio is enabled
+    inputs.extend(platform::camera_capture_inputs(
+        &settings.camera_id,
+        settings.frame_rate,
+        settings.audio_enabled,
+    )?);
     
     // Build FFmpeg command for PiP recording
-    let mut args = vec![
-        // Screen input (AVFoundation format - video only, no audio from screen)
-        "-f".to_string(),
-        "avfoundation".to_string(),
-        "-r".to_string(),
-        settings.frame_rate.to_string(),
-        "-i".to_string(),
-        format!("Capture screen {}:", screen_index),
-        
-        // Camera input (AVFoundation format - video:audio)
-        // Format is "video_device:audio_device" for AVFoundation
-        // Using "N:0" captures video from device N and audio from default mic
-        "-f".to_string(),
-        "avfoundation".to_string(),
-        "-r".to_string(),
-        settings.frame_rate.to_string(),
-        "-i".to_string(),
-        format!("{}:0", camera_device_id),  // Capture both video AND audio from camera
-        
+    let mut args = platform::capture_input_args(&inputs);
+    args.extend([
         // Use wallclock as timestamps to ensure sync between inputs
         "-use_wallclock_as_timestamps".to_string(),
         "1".to_string(),
-    ];
+    ]);
     
     // Calculate PiP overlay dimensions and position
     // We'll use a fixed screen resolution for now (will be scaled by FFmpeg)
@@ -187,10 +174,10 @@ fn start_avfoundation_pip_recording(
     args.push("-map".to_string());
     args.push("[vout]".to_string());
     
-    // Audio handling (if enabled, use audio from camera input [1:a])
+    // Audio handling (if enabled, use audio from the last input)
     if settings.audio_enabled {
         args.push("-map".to_string());
-        args.push("1:a?".to_string()); // Map audio from camera input if available
+        args.push(format!("{}:a?", inputs.len() - 1)); // Map microphone audio if available
         args.push("-c:a".to_string());
         args.push("aac".to_string());
         args.push("-b:a".to_string());
@@ -0,0 +1,480 @@
+// ============================================================================
+// LINUX RECORDING BACKEND
+// ============================================================================
+// Captures screens with x11grab, cameras with v4l2 and audio with
+// PulseAudio, falling back to ALSA. Displays come from `xrandr --query`
+// (or the DRM connectors in sysfs when xrandr is missing) and cameras
+// from the `/dev/video*` capture nodes.
+
+use super::CaptureInput;
+use crate::ffmpeg::capabilities::{get_cached_ffmpeg_capabilities, FfmpegCapabilities};
+use crate::recording::camera::{CameraCapabilities, CameraInfo};
+use crate::recording::screen::{CaptureArea, ScreenInfo};
+use std::path::Path;
+use std::process::Command;
+
+/// Directory holding the `videoN` device nodes
+const DEV_DIR: &str = "/dev";
+
+/// sysfs directory with a `name` and `index` file per video node
+const VIDEO4LINUX_SYS_DIR: &str = "/sys/class/video4linux";
+
+/// sysfs directory with a `status` and `modes` file per display connector
+const DRM_SYS_DIR: &str = "/sys/class/drm";
+
+/// Frame size reported for cameras; sysfs does not list supported sizes
+const DEFAULT_CAMERA_WIDTH: u32 = 1280;
+const DEFAULT_CAMERA_HEIGHT: u32 = 720;
+
+/// Queue size for live inputs, so a slow device does not block the others
+const LIVE_INPUT_QUEUE_SIZE: u32 = 512;
+
+// ============================================================================
+// SCREENS
+// ============================================================================
+
+/// Get all active displays, preferring xrandr's layout over sysfs
+pub fn get_available_screens() -> Result<Vec<ScreenInfo>, String> {
+    if let Ok(output) = Command::new("xrandr").arg("--query").output() {
+        if output.status.success() {
+            return Ok(parse_xrandr_query(&String::from_utf8_lossy(&output.stdout)));
+        }
+    }
+
+    read_drm_screens(Path::new(DRM_SYS_DIR))
+}
+
+/// Parse `xrandr --query` output into the active outputs
+///
+/// Outputs look like `HDMI-1 connected primary 1920x1080+1920+0 (normal ...)`;
+/// connected outputs without a geometry are switched off and skipped.
+pub fn parse_xrandr_query(output: &str) -> Vec<ScreenInfo> {
+    let mut screens: Vec<ScreenInfo> = output
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(parse_xrandr_output_line)
+        .collect();
+
+    mark_primary_screen(&mut screens);
+    screens
+}
+
+fn parse_xrandr_output_line(line: &str) -> Option<ScreenInfo> {
+    let mut tokens = line.split_whitespace();
+    let name = tokens.next()?;
+    if tokens.next()? != "connected" {
+        return None;
+    }
+
+    let mut token = tokens.next()?;
+    let is_primary = token == "primary";
+    if is_primary {
+        token = tokens.next()?;
+    }
+
+    let (width, height, x, y) = parse_geometry(token)?;
+    Some(ScreenInfo {
+        id: format!("screen-{}", name),
+        name: name.to_string(),
+        width,
+        height,
+        x,
+        y,
+        is_primary,
+        scale_factor: 1.0,
+    })
+}
+
+/// Parse an X11 geometry such as `1920x1080+1920+0`
+fn parse_geometry(geometry: &str) -> Option<(u32, u32, i32, i32)> {
+    let (size, offset) = geometry.split_once('+')?;
+    let (width, height) = parse_mode(size)?;
+    let (x, y) = offset.split_once('+')?;
+    Some((width, height, x.parse().ok()?, y.parse().ok()?))
+}
+
+/// Parse a mode such as `1920x1080`; interlaced modes end in `i`
+fn parse_mode(mode: &str) -> Option<(u32, u32)> {
+    let (width, height) = mode.trim().split_once('x')?;
+    let height = height.trim_end_matches(|c: char| !c.is_ascii_digit());
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Read connected displays from the DRM connectors in sysfs
+///
+/// sysfs has no layout, so displays are assumed to sit side by side in
+/// connector order, each at its preferred (first listed) mode.
+pub fn read_drm_screens(drm_dir: &Path) -> Result<Vec<ScreenInfo>, String> {
+    let entries = std::fs::read_dir(drm_dir)
+        .map_err(|e| format!("Failed to read {}: {}", drm_dir.display(), e))?;
+
+    // Connectors are named card<N>-<connector>, e.g. card0-HDMI-A-1
+    let mut connectors: Vec<(String, std::path::PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let (card, connector) = file_name.split_once('-')?;
+            card.starts_with("card").then(|| (connector.to_string(), entry.path()))
+        })
+        .collect();
+    connectors.sort();
+
+    let mut screens = Vec::new();
+    let mut next_x = 0;
+    for (connector, path) in connectors {
+        if read_trimmed(&path.join("status")).as_deref() != Some("connected") {
+            continue;
+        }
+        let Some((width, height)) = read_trimmed(&path.join("modes"))
+            .and_then(|modes| modes.lines().next().and_then(parse_mode))
+        else {
+            continue;
+        };
+
+        screens.push(ScreenInfo {
+            id: format!("screen-{}", connector),
+            name: connector,
+            width,
+            height,
+            x: next_x,
+            y: 0,
+            is_primary: false,
+            scale_factor: 1.0,
+        });
+        next_x += width as i32;
+    }
+
+    mark_primary_screen(&mut screens);
+    Ok(screens)
+}
+
+/// Treat the first screen as primary when none is marked
+fn mark_primary_screen(screens: &mut [ScreenInfo]) {
+    if !screens.iter().any(|screen| screen.is_primary) {
+        if let Some(first) = screens.first_mut() {
+            first.is_primary = true;
+        }
+    }
+}
+
+/// x11grab input for a screen of the current X display
+pub fn screen_capture_inputs(
+    screen_id: &str,
+    frame_rate: u32,
+    capture_area: Option<&CaptureArea>,
+    capture_audio: bool,
+) -> Result<Vec<CaptureInput>, String> {
+    let screen = get_available_screens()?
+        .into_iter()
+        .find(|screen| screen.id == screen_id)
+        .ok_or_else(|| format!("Screen not found: {}", screen_id))?;
+
+    // Under Wayland this is XWayland, which only sees X11 windows
+    let display = std::env::var("DISPLAY")
+        .map_err(|_| "DISPLAY is not set; screen recording needs an X11 display".to_string())?;
+
+    let mut inputs = vec![x11grab_input(&display, &screen, capture_area, frame_rate)];
+    if capture_audio {
+        inputs.push(audio_capture_input());
+    }
+    Ok(inputs)
+}
+
+/// x11grab input for `screen`, or for `capture_area` relative to it
+///
+/// The size is rounded down to even numbers, which yuv420p encoding needs.
+pub fn x11grab_input(
+    display: &str,
+    screen: &ScreenInfo,
+    capture_area: Option<&CaptureArea>,
+    frame_rate: u32,
+) -> CaptureInput {
+    let (x, y, width, height) = match capture_area {
+        Some(area) => (screen.x + area.x, screen.y + area.y, area.width, area.height),
+        None => (screen.x, screen.y, screen.width, screen.height),
+    };
+
+    CaptureInput::new("x11grab", format!("{}+{},{}", display, x, y))
+        .option("-framerate", frame_rate)
+        .option("-video_size", format!("{}x{}", width & !1, height & !1))
+        .option("-draw_mouse", 1)
+}
+
+// ============================================================================
+// CAMERAS
+// ============================================================================
+
+/// Get all V4L2 capture devices
+pub fn get_available_cameras() -> Result<Vec<CameraInfo>, String> {
+    read_video_devices(Path::new(DEV_DIR), Path::new(VIDEO4LINUX_SYS_DIR))
+}
+
+/// Read the `videoN` nodes in `dev_dir`, naming them from `sys_dir`
+///
+/// Drivers expose metadata nodes next to each capture node; only the node
+/// with index 0 delivers frames, so the others are skipped.
+pub fn read_video_devices(dev_dir: &Path, sys_dir: &Path) -> Result<Vec<CameraInfo>, String> {
+    let entries = std::fs::read_dir(dev_dir)
+        .map_err(|e| format!("Failed to read {}: {}", dev_dir.display(), e))?;
+
+    let mut numbers: Vec<u32> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| parse_video_node(&entry.file_name().to_string_lossy()))
+        .collect();
+    numbers.sort_unstable();
+
+    let mut cameras = Vec::new();
+    for number in numbers {
+        let sys_node = sys_dir.join(format!("video{}", number));
+        if read_trimmed(&sys_node.join("index")).is_some_and(|index| index != "0") {
+            continue;
+        }
+
+        let name = read_trimmed(&sys_node.join("name"))
+            .unwrap_or_else(|| format!("Camera {}", number));
+        let is_default = cameras.is_empty();
+
+        cameras.push(CameraInfo {
+            id: format!("camera-{}", number),
+            name,
+            is_default,
+            // Fails without access to the node (e.g. not in the video group)
+            is_available: std::fs::File::open(dev_dir.join(format!("video{}", number))).is_ok(),
+            capabilities: CameraCapabilities {
+                max_width: DEFAULT_CAMERA_WIDTH,
+                max_height: DEFAULT_CAMERA_HEIGHT,
+                supported_formats: vec!["mp4".to_string(), "mov".to_string()],
+                has_audio: false,
+            },
+        });
+    }
+
+    Ok(cameras)
+}
+
+/// Node number of a `videoN` device name
+fn parse_video_node(file_name: &str) -> Option<u32> {
+    file_name.strip_prefix("video")?.parse().ok()
+}
+
+/// Device node for a camera ID (`camera-N` or `N`)
+pub fn camera_device_path(camera_id: &str) -> Option<String> {
+    let number: u32 = camera_id.strip_prefix("camera-").unwrap_or(camera_id).parse().ok()?;
+    Some(format!("{}/video{}", DEV_DIR, number))
+}
+
+/// v4l2 input for a camera, plus an audio input when requested
+pub fn camera_capture_inputs(
+    camera_id: &str,
+    frame_rate: u32,
+    capture_audio: bool,
+) -> Result<Vec<CaptureInput>, String> {
+    // IDs that are not V4L2 nodes (e.g. browser device IDs) fall back to the default camera
+    let device = match camera_device_path(camera_id) {
+        Some(device) => device,
+        None => get_available_cameras()?
+            .into_iter()
+            .find(|camera| camera.is_default)
+            .and_then(|camera| camera_device_path(&camera.id))
+            .ok_or("No camera found")?,
+    };
+
+    let mut inputs = vec![v4l2_input(&device, frame_rate)];
+    if capture_audio {
+        inputs.push(audio_capture_input());
+    }
+    Ok(inputs)
+}
+
+/// v4l2 input for a device node
+pub fn v4l2_input(device: &str, frame_rate: u32) -> CaptureInput {
+    CaptureInput::new("v4l2", device)
+        .option("-thread_queue_size", LIVE_INPUT_QUEUE_SIZE)
+        .option("-framerate", frame_rate)
+}
+
+// ============================================================================
+// AUDIO
+// ============================================================================
+
+/// Sound server FFmpeg records the microphone through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioBackend {
+    Pulse,
+    Alsa,
+}
+
+impl AudioBackend {
+    /// FFmpeg input format name
+    pub fn format(&self) -> &'static str {
+        match self {
+            AudioBackend::Pulse => "pulse",
+            AudioBackend::Alsa => "alsa",
+        }
+    }
+}
+
+/// Use PulseAudio when a server is running and FFmpeg was built with it
+///
+/// Without probed capabilities the build is assumed to support it.
+pub fn choose_audio_backend(
+    pulse_running: bool,
+    capabilities: Option<&FfmpegCapabilities>,
+) -> AudioBackend {
+    let ffmpeg_has_pulse = capabilities.is_none_or(|caps| caps.has_demuxer("pulse"));
+    if pulse_running && ffmpeg_has_pulse {
+        AudioBackend::Pulse
+    } else {
+        AudioBackend::Alsa
+    }
+}
+
+/// Whether a PulseAudio (or PipeWire pulse) server is reachable
+fn is_pulse_running() -> bool {
+    std::env::var_os("PULSE_SERVER").is_some()
+        || std::env::var_os("XDG_RUNTIME_DIR")
+            .is_some_and(|dir| Path::new(&dir).join("pulse").join("native").exists())
+}
+
+/// Default microphone input for the detected backend
+///
+/// Frontend audio device IDs come from the browser and do not name
+/// PulseAudio sources or ALSA devices, so the default input is used.
+fn audio_capture_input() -> CaptureInput {
+    let capabilities = get_cached_ffmpeg_capabilities();
+    let backend = choose_audio_backend(is_pulse_running(), capabilities.as_ref());
+    audio_input(backend)
+}
+
+/// Input for the default device of `backend`
+pub fn audio_input(backend: AudioBackend) -> CaptureInput {
+    CaptureInput::new(backend.format(), "default")
+        .option("-thread_queue_size", LIVE_INPUT_QUEUE_SIZE)
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Read a sysfs attribute without its trailing newline
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok().map(|value| value.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XRANDR_QUERY: &str = "Screen 0: minimum 320 x 200, current 3840 x 1080, maximum 16384 x 16384\n\
+        eDP-1 connected 1920x1080+0+0 (normal left inverted right x axis y axis) 344mm x 194mm\n   \
+        1920x1080     60.02*+  59.93\n\
+        HDMI-1 connected primary 1920x1080+1920+0 (normal left inverted right x axis y axis) 527mm x 296mm\n   \
+        1920x1080     60.00*+\n\
+        DP-1 disconnected (normal left inverted right x axis y axis)\n\
+        DP-2 connected (normal left inverted right x axis y axis)\n";
+
+    #[test]
+    fn test_parse_xrandr_query() {
+        let screens = parse_xrandr_query(XRANDR_QUERY);
+        assert_eq!(screens.len(), 2);
+
+        assert_eq!(screens[0].id, "screen-eDP-1");
+        assert_eq!((screens[0].width, screens[0].height), (1920, 1080));
+        assert!(!screens[0].is_primary);
+
+        assert_eq!(screens[1].name, "HDMI-1");
+        assert_eq!((screens[1].x, screens[1].y), (1920, 0));
+        assert!(screens[1].is_primary);
+    }
+
+    #[test]
+    fn test_parse_xrandr_query_marks_first_screen_primary() {
+        let screens = parse_xrandr_query("VGA-1 connected 1280x1024+0+0 (normal) 0mm x 0mm\n");
+        assert_eq!(screens.len(), 1);
+        assert!(screens[0].is_primary);
+    }
+
+    #[test]
+    fn test_read_drm_screens() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, status, modes) in [
+            ("card0-HDMI-A-1", "connected", "2560x1440\n1920x1080\n"),
+            ("card0-DP-1", "disconnected", ""),
+            ("card0-eDP-1", "connected", "1920x1080i\n"),
+        ] {
+            let connector = dir.path().join(name);
+            std::fs::create_dir(&connector).unwrap();
+            std::fs::write(connector.join("status"), format!("{}\n", status)).unwrap();
+            std::fs::write(connector.join("modes"), modes).unwrap();
+        }
+        std::fs::create_dir(dir.path().join("card0")).unwrap();
+
+        let screens = read_drm_screens(dir.path()).unwrap();
+        assert_eq!(screens.len(), 2);
+        assert_eq!(screens[0].id, "screen-HDMI-A-1");
+        assert_eq!((screens[0].width, screens[0].x), (2560, 0));
+        assert!(screens[0].is_primary);
+        assert_eq!(screens[1].id, "screen-eDP-1");
+        assert_eq!((screens[1].height, screens[1].x), (1080, 2560));
+    }
+
+    #[test]
+    fn test_x11grab_input() {
+        let screen = parse_xrandr_query(XRANDR_QUERY).remove(1);
+
+        let input = x11grab_input(":0", &screen, None, 30);
+        assert_eq!(
+            input.to_args(),
+            vec!["-f", "x11grab", "-framerate", "30", "-video_size", "1920x1080", "-draw_mouse", "1", "-i", ":0+1920,0"]
+        );
+
+        let area = CaptureArea { x: 100, y: 50, width: 801, height: 601 };
+        let input = x11grab_input(":1.0", &screen, Some(&area), 30);
+        assert_eq!(input.device, ":1.0+2020,50");
+        assert!(input.options.contains(&"800x600".to_string()));
+    }
+
+    #[test]
+    fn test_read_video_devices() {
+        let dev = tempfile::tempdir().unwrap();
+        let sys = tempfile::tempdir().unwrap();
+        for (node, name, index) in [(0, "Integrated Camera", "0"), (1, "Integrated Camera", "1"), (2, "USB Webcam", "0")] {
+            std::fs::write(dev.path().join(format!("video{}", node)), "").unwrap();
+            let sys_node = sys.path().join(format!("video{}", node));
+            std::fs::create_dir(&sys_node).unwrap();
+            std::fs::write(sys_node.join("name"), format!("{}\n", name)).unwrap();
+            std::fs::write(sys_node.join("index"), format!("{}\n", index)).unwrap();
+        }
+        std::fs::write(dev.path().join("video10"), "").unwrap();
+        std::fs::write(dev.path().join("vhci"), "").unwrap();
+
+        let cameras = read_video_devices(dev.path(), sys.path()).unwrap();
+        let ids: Vec<&str> = cameras.iter().map(|camera| camera.id.as_str()).collect();
+        assert_eq!(ids, vec!["camera-0", "camera-2", "camera-10"]);
+        assert_eq!(cameras[0].name, "Integrated Camera");
+        assert!(cameras[0].is_default && cameras[0].is_available);
+        assert_eq!(cameras[2].name, "Camera 10");
+        assert!(!cameras[2].is_default);
+    }
+
+    #[test]
+    fn test_camera_device_path() {
+        assert_eq!(camera_device_path("camera-2"), Some("/dev/video2".to_string()));
+        assert_eq!(camera_device_path("0"), Some("/dev/video0".to_string()));
+        assert_eq!(camera_device_path("3f2a9c"), None);
+    }
+
+    #[test]
+    fn test_choose_audio_backend() {
+        let mut capabilities = FfmpegCapabilities::default();
+        capabilities.demuxers.insert("alsa".to_string());
+
+        assert_eq!(choose_audio_backend(true, None), AudioBackend::Pulse);
+        assert_eq!(choose_audio_backend(false, None), AudioBackend::Alsa);
+        assert_eq!(choose_audio_backend(true, Some(&capabilities)), AudioBackend::Alsa);
+
+        capabilities.demuxers.insert("pulse".to_string());
+        assert_eq!(choose_audio_backend(true, Some(&capabilities)), AudioBackend::Pulse);
+        assert_eq!(audio_input(AudioBackend::Alsa).to_args(), vec!["-f", "alsa", "-thread_queue_size", "512", "-i", "default"]);
+    }
+}
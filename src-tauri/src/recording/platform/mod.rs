@@ -0,0 +1,88 @@
+// ============================================================================
+// RECORDING PLATFORM BACKENDS
+// ============================================================================
+// Each platform describes its capture devices as FFmpeg inputs:
+// AVFoundation on macOS, and x11grab, v4l2 and PulseAudio/ALSA on Linux.
+// The recording modules only build the encoding side of the command.
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "macos")]
+use macos as backend;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+use linux as backend;
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+mod unsupported;
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+use unsupported as backend;
+
+/// Screen and camera enumeration for the current platform
+pub use backend::{get_available_cameras, get_available_screens};
+
+/// Capture inputs for the current platform
+///
+/// `screen_capture_inputs(screen_id, frame_rate, capture_area, capture_audio)`
+/// and `camera_capture_inputs(camera_id, frame_rate, capture_audio)` return the
+/// video input first; when audio is requested it comes from the last input.
+pub use backend::{camera_capture_inputs, screen_capture_inputs};
+
+/// A single FFmpeg capture input (`-f <format> [options] -i <device>`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureInput {
+    pub format: String,
+    pub options: Vec<String>,
+    pub device: String,
+}
+
+impl CaptureInput {
+    pub fn new(format: &str, device: impl Into<String>) -> Self {
+        Self {
+            format: format.to_string(),
+            options: Vec::new(),
+            device: device.into(),
+        }
+    }
+
+    /// Add an input option, placed before `-i`
+    pub fn option(mut self, name: &str, value: impl ToString) -> Self {
+        self.options.push(name.to_string());
+        self.options.push(value.to_string());
+        self
+    }
+
+    /// FFmpeg arguments for this input
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["-f".to_string(), self.format.clone()];
+        args.extend(self.options.iter().cloned());
+        args.push("-i".to_string());
+        args.push(self.device.clone());
+        args
+    }
+}
+
+/// FFmpeg arguments for a list of inputs, in order
+pub fn capture_input_args(inputs: &[CaptureInput]) -> Vec<String> {
+    inputs.iter().flat_map(|input| input.to_args()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_input_args() {
+        let inputs = vec![
+            CaptureInput::new("x11grab", ":0+0,0").option("-framerate", 30),
+            CaptureInput::new("pulse", "default"),
+        ];
+
+        assert_eq!(
+            capture_input_args(&inputs),
+            vec!["-f", "x11grab", "-framerate", "30", "-i", ":0+0,0", "-f", "pulse", "-i", "default"]
+        );
+    }
+}
@@ -0,0 +1,38 @@
+// ============================================================================
+// UNSUPPORTED PLATFORM BACKEND
+// ============================================================================
+// Recording is only implemented for macOS and Linux; everything here
+// reports that so the frontend can show a clear message.
+
+use super::CaptureInput;
+use crate::recording::camera::CameraInfo;
+use crate::recording::screen::{CaptureArea, ScreenInfo};
+
+fn unsupported() -> String {
+    format!("Recording is not supported on {}", std::env::consts::OS)
+}
+
+pub fn get_available_screens() -> Result<Vec<ScreenInfo>, String> {
+    Err(unsupported())
+}
+
+pub fn get_available_cameras() -> Result<Vec<CameraInfo>, String> {
+    Err(unsupported())
+}
+
+pub fn screen_capture_inputs(
+    _screen_id: &str,
+    _frame_rate: u32,
+    _capture_area: Option<&CaptureArea>,
+    _capture_audio: bool,
+) -> Result<Vec<CaptureInput>, String> {
+    Err(unsupported())
+}
+
+pub fn camera_capture_inputs(
+    _camera_id: &str,
+    _frame_rate: u32,
+    _capture_audio: bool,
+) -> Result<Vec<CaptureInput>, String> {
+    Err(unsupported())
+}
@@ -1,12 +1,12 @@
 /**
  * Screen Recording Module
  * 
- * Handles screen capture functionality using CoreGraphics and AVFoundation.
+ * Handles screen capture functionality on top of the platform backends.
  * Provides screen enumeration and screen recording capabilities.
  */
 
 use serde::{Deserialize, Serialize};
-use core_graphics::display::{CGDisplay, CGDisplayBounds, CGGetActiveDisplayList};
+use crate::recording::platform;
 use std::sync::{Arc, Mutex};
 use std::path::PathBuf;
 
@@ -66,74 +66,7 @@ impl ScreenRecordingSession {
 
 /// Get all available screens
 pub fn get_available_screens() -> Result<Vec<ScreenInfo>, String> {
-    let mut screens = Vec::new();
-    
-    // Get the number of active displays
-    let mut display_count: u32 = 0;
-    let result = unsafe {
-        CGGetActiveDisplayList(0, std::ptr::null_mut(), &mut display_count)
-    };
-    
-    if result != 0 {
-        return Err("Failed to get display count".to_string());
-    }
-    
-    if display_count == 0 {
-        return Ok(screens);
-    }
-    
-    // Allocate array for display IDs
-    let mut display_ids = vec![0u32; display_count as usize];
-    let result = unsafe {
-        CGGetActiveDisplayList(display_count, display_ids.as_mut_ptr(), &mut display_count)
-    };
-    
-    if result != 0 {
-        return Err("Failed to get display list".to_string());
-    }
-    
-    // Get the main display ID
-    let main_display_id = CGDisplay::main().id;
-    
-    // Process each display
-    for (index, &display_id) in display_ids.iter().enumerate() {
-        let _display = CGDisplay::new(display_id);
-        
-        // Get display bounds
-        let bounds = unsafe { CGDisplayBounds(display_id) };
-        
-        // Get display name (try to get a meaningful name)
-        let name = get_display_name(display_id, index);
-        
-        // Calculate scale factor (simplified - in reality this is more complex)
-        let scale_factor = if display_id == main_display_id { 2.0 } else { 1.0 };
-        
-        let screen_info = ScreenInfo {
-            id: format!("screen-{}", display_id),
-            name,
-            width: bounds.size.width as u32,
-            height: bounds.size.height as u32,
-            x: bounds.origin.x as i32,
-            y: bounds.origin.y as i32,
-            is_primary: display_id == main_display_id,
-            scale_factor,
-        };
-        
-        screens.push(screen_info);
-    }
-    
-    Ok(screens)
-}
-
-/// Get display name (simplified implementation)
-fn get_display_name(display_id: u32, index: usize) -> String {
-    // Try to get display name from system
-    // This is a simplified implementation - in reality you'd use more CoreGraphics APIs
-    if display_id == CGDisplay::main().id {
-        "Built-in Retina Display".to_string()
-    } else {
-        format!("Display {}", index + 1)
-    }
+    platform::get_available_screens()
 }
 
 /// Start screen recording with provided session ID and project ID
@@ -152,8 +85,8 @@ pub fn start_screen_recording_with_id(
     let file_path = output_dir.join(filename);
     session.file_path = Some(file_path.to_string_lossy().to_string());
     
-    // Start actual screen recording using FFmpeg + AVFoundation
-    match start_avfoundation_recording(&session_id, &settings, &file_path, &ffmpeg_path) {
+    // Start actual screen recording using FFmpeg and the platform capture inputs
+    match start_ffmpeg_screen_recording(&session_id, &settings, &file_path, &ffmpeg_path) {
         Ok(_) => {
             session.is_recording = true;
             session.start_time = Some(std::time::Instant::now());
@@ -183,23 +116,23 @@ fn get_project_assets_directory(project_id: &str) -> Result<std::path::PathBuf,
 }
 
 /// Start screen recording (generates own session ID)
-/// Note: This function requires FFmpeg to be in system PATH and uses a default project
+/// Note: This function resolves FFmpeg without an app handle and uses a default project
 /// This is primarily for testing - production code should use start_screen_recording_with_id
 pub fn start_screen_recording(
     settings: ScreenRecordingSettings,
     project_id: String,
 ) -> Result<ScreenRecordingSession, String> {
-    // Try to find FFmpeg in system PATH
-    let ffmpeg_path = which::which("ffmpeg")
-        .map(|p| p.to_string_lossy().to_string())
-        .map_err(|_| "FFmpeg not found in system PATH. Please use start_screen_recording_with_id instead.".to_string())?;
+    // Resolve FFmpeg without an app handle (env override, sidecar, source tree or PATH)
+    let ffmpeg_path = crate::ffmpeg::runner::resolve_ffmpeg_binary(crate::ffmpeg::runner::FfmpegTool::Ffmpeg, None)
+        .map(|binary| binary.path.to_string_lossy().to_string())
+        .map_err(|e| format!("{}. Please use start_screen_recording_with_id instead.", e.message))?;
     
     let session_id = format!("screen_recording_{}", chrono::Utc::now().timestamp_millis());
     start_screen_recording_with_id(session_id, settings, ffmpeg_path, project_id)
 }
 
-/// Start AVFoundation screen recording using FFmpeg
-fn start_avfoundation_recording(
+/// Start screen recording using FFmpeg
+fn start_ffmpeg_screen_recording(
     session_id: &str,
     settings: &ScreenRecordingSettings,
     output_path: &PathBuf,
@@ -207,37 +140,26 @@ fn start_avfoundation_recording(
 ) -> Result<(), String> {
     use crate::recording::process_manager::PROCESS_MANAGER;
     
-    // Get the display ID from settings  
-    let display_id = settings.screen_id
-        .strip_prefix("screen-")
-        .ok_or("Invalid screen ID format")?
-        .parse::<u32>()
-        .map_err(|_| "Invalid screen ID")?;
-    
-    // Build FFmpeg command for screen recording
-    // Using AVFoundation input on macOS
-    let mut args = vec![
-        "-f".to_string(),
-        "avfoundation".to_string(),
-        "-capture_cursor".to_string(),
-        "1".to_string(),
-        "-capture_mouse_clicks".to_string(),
-        "1".to_string(),
-    ];
-    
-    // Frame rate
-    args.push("-r".to_string());
-    args.push(settings.frame_rate.to_string());
-    
-    // Input device (screen capture)
-    // For AVFoundation screen capture, we need to use "Capture screen N" format
-    // where N is 0 for the first screen, 1 for the second, etc.
-    // The format is: -i "Capture screen N:"
-    let screen_index = if display_id > 0 { display_id - 1 } else { 0 };
-    let input_device = format!("Capture screen {}:", screen_index);
-    
-    args.push("-i".to_string());
-    args.push(input_device);
+    // Screen (and microphone) inputs for this platform
+    let inputs = platform::screen_capture_inputs(
+        &settings.screen_id,
+        settings.frame_rate,
+        settings.capture_area.as_ref(),
+        settings.audio_enabled,
+    )?;
+    let mut args = platform::capture_input_args(&inputs);
+    
+    // Video from the screen input, audio from the last input
+    args.push("-map".to_string());
+    args.push("0:v".to_string());
+    if settings.audio_enabled {
+        args.push("-map".to_string());
+        args.push(format!("{}:a", inputs.len() - 1));
+        args.push("-c:a".to_string());
+        args.push("aac".to_string());
+        args.push("-b:a".to_string());
+        args.push("128k".to_string());
+    }
     
     // Video codec and quality settings
     args.push("-c:v".to_string());
@@ -384,6 +306,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(target_os = "macos")]
     fn test_get_available_screens() {
         let screens = get_available_screens().unwrap();
         assert!(!screens.is_empty());